@@ -8,7 +8,7 @@
 //! Settings are resolved in the following priority order (highest to lowest):
 //! 
 //! 1. Command-line arguments (e.g., `--port 9090`)
-//! 2. Environment variables (e.g., `EMBED_TOOL_SERVER_PORT=9090`)
+//! 2. Environment variables (e.g., `EMBED_TOOL_SERVER_DEFAULT_PORT=9090`)
 //! 3. Configuration file (`~/.config/static-embedding-tool/config.toml`)
 //! 4. Built-in defaults
 //! 
@@ -17,6 +17,7 @@
 //! - **Server**: Port, bind address, default model
 //! - **Models**: Model paths, cache directory, auto-download settings
 //! - **Logging**: Log levels, output format, file rotation
+//! - **CLI**: Timeouts for commands that talk to a running server
 //! 
 //! ## Examples
 //! 
@@ -32,6 +33,9 @@
 //! 
 //! # Show config file location
 //! static-embedding-tool config path
+//!
+//! # Upgrade an older config file (a backup is written first)
+//! static-embedding-tool config migrate
 //! ```
 //! 
 //! ## Environment Variables
@@ -39,28 +43,194 @@
 //! All config keys can be overridden via environment variables with the prefix
 //! `EMBED_TOOL_` and uppercase section.key format:
 //! 
-//! - `EMBED_TOOL_SERVER_PORT=9090`
-//! - `EMBED_TOOL_MODELS_CACHE_DIR=/custom/path`
+//! - `EMBED_TOOL_SERVER_DEFAULT_PORT=9090`
+//! - `EMBED_TOOL_MODELS_MODELS_DIR=/custom/path`
+//! - `EMBED_TOOL_SERVER_CORS_ALLOWED_ORIGINS=https://a.example,https://b.example`
+//!
+//! Overrides apply to the loaded configuration only; `config set` still edits
+//! the file. A variable naming an unknown key in a config section, or with a
+//! value the key cannot hold, is reported and ignored. `config get` marks each
+//! value with the [`ValueSource`] it came from.
 
-use crate::cli::{BatchArgs, ConfigAction, EmbedArgs, SetConfigArgs};
+use crate::cli::{BatchArgs, ConfigAction, EmbedArgs, GetConfigArgs, SetConfigArgs, UnsetConfigArgs};
+use crate::embed::Embedder;
+use crate::utils::OutputDtype;
+use crate::utils::logging::{LogFile, LogSettings};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fs;
-use std::path::PathBuf;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Current config file format version, stamped by `config migrate`.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Upper bound for `server.encode_retries`, so a broken backend cannot stall requests.
+const MAX_ENCODE_RETRIES: u32 = 10;
 
 /// Top-level configuration structure.
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct Config {
+    /// Format version of the file; files written before versioning read as 0
+    #[serde(default)]
+    pub version: u32,
+    /// HTTP server settings
     pub server: ServerConfig,
+    /// Model storage and download settings
     pub models: ModelConfig,
+    /// Log output settings
     pub logging: LoggingConfig,
+    /// Settings for CLI commands that call a running server
+    #[serde(default)]
+    pub cli: CliConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            server: ServerConfig::default(),
+            models: ModelConfig::default(),
+            logging: LoggingConfig::default(),
+            cli: CliConfig::default(),
+        }
+    }
 }
 
 /// Server-specific configuration.
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct ServerConfig {
+    /// Port `server start` listens on
     pub default_port: u16,
+    /// Address `server start` binds to
     pub default_bind: String,
+    /// Model used when a request does not name one
     pub default_model: String,
+    /// When `/ready` reports ready: "loaded" (models constructed) or "warmed"
+    /// (probe encode succeeded). `loaded` starts serving sooner; `warmed` never
+    /// routes traffic to a server that cannot encode yet.
+    #[serde(default = "default_ready_policy")]
+    pub ready_policy: String,
+    /// Browser origins allowed to call the API ("*" for any). CORS is off when empty.
+    #[serde(default)]
+    pub cors_allowed_origins: Vec<String>,
+    /// Answer requests from disallowed origins with 403 instead of omitting CORS headers
+    #[serde(default)]
+    pub cors_reject_disallowed: bool,
+    /// Default precision of returned embeddings: "f32" or "f16". `f16` halves
+    /// payload size at roughly three significant digits of precision.
+    #[serde(default = "default_output_dtype")]
+    pub output_dtype: String,
+    /// Refuse to start unless every requested model loads
+    #[serde(default)]
+    pub strict_models: bool,
+    /// Retries (with short backoff) for an encode call that fails. Static
+    /// models never fail transiently, so this only matters for other backends.
+    #[serde(default)]
+    pub encode_retries: u32,
+    /// Embeddings kept in the in-memory LRU cache so repeated texts skip
+    /// encoding; 0 disables the cache
+    #[serde(default)]
+    pub embedding_cache_size: usize,
+    /// Serve the HTTP API over HTTPS using `tls_cert_path` and `tls_key_path`
+    #[serde(default)]
+    pub enable_tls: bool,
+    /// PEM certificate chain presented when TLS is enabled
+    #[serde(default)]
+    pub tls_cert_path: Option<String>,
+    /// PEM private key for `tls_cert_path`
+    #[serde(default)]
+    pub tls_key_path: Option<String>,
+    /// Oldest TLS version the server accepts: "1.2" or "1.3"
+    #[serde(default = "default_min_tls_version")]
+    pub min_tls_version: String,
+    /// TLS cipher suites offered, such as "TLS13_AES_256_GCM_SHA384"; the
+    /// provider's defaults when empty
+    #[serde(default)]
+    pub tls_cipher_suites: Vec<String>,
+    /// Serve the HTTP API on this Unix socket instead of a TCP port. CLI
+    /// commands that call the server connect through it too.
+    #[serde(default)]
+    pub socket_path: Option<String>,
+    /// Octal permissions of the socket file; the default "0600" allows only the owner
+    #[serde(default = "default_socket_mode")]
+    pub socket_mode: String,
+    /// Seconds an MCP session over HTTP may stay idle before it is logged and
+    /// dropped; 0 keeps idle sessions open
+    #[serde(default = "default_mcp_session_idle_timeout_secs")]
+    pub mcp_session_idle_timeout_secs: u64,
+    /// Largest request body the HTTP API accepts, in bytes; larger requests
+    /// are answered with 413
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Seconds an HTTP API request may take before it is answered with 408;
+    /// 0 never times out
+    #[serde(default = "default_server_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// Most inputs in one embedding request, over HTTP or MCP; 0 uses the
+    /// default of 100
+    #[serde(default)]
+    pub max_batch_inputs: usize,
+    /// Longest embedding input, in characters, over HTTP or MCP; 0 uses the
+    /// default of 65536
+    #[serde(default)]
+    pub max_input_chars: usize,
+    /// Seconds in-flight requests get to finish after SIGTERM or Ctrl+C
+    /// before the server exits anyway
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+    /// Seconds `server stop` waits after SIGTERM before killing the server; it
+    /// always waits at least `shutdown_timeout_secs` plus 5
+    #[serde(default = "default_stop_grace_period_secs")]
+    pub stop_grace_period_secs: u64,
+}
+
+fn default_ready_policy() -> String {
+    "warmed".to_string()
+}
+
+fn default_output_dtype() -> String {
+    "f32".to_string()
+}
+
+fn default_min_tls_version() -> String {
+    "1.2".to_string()
+}
+
+fn default_socket_mode() -> String {
+    "0600".to_string()
+}
+
+fn default_mcp_session_idle_timeout_secs() -> u64 {
+    1800
+}
+
+fn default_max_body_bytes() -> usize {
+    4 * 1024 * 1024
+}
+
+fn default_server_request_timeout_secs() -> u64 {
+    60
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+fn default_stop_grace_period_secs() -> u64 {
+    5
+}
+
+/// Parse octal socket permissions such as "0600", "660" or "0o600".
+pub fn parse_socket_mode(value: &str) -> Result<u32, String> {
+    let digits = value.strip_prefix("0o").unwrap_or(value);
+    match u32::from_str_radix(digits, 8) {
+        Ok(mode) if !digits.is_empty() && mode <= 0o777 => Ok(mode),
+        _ => Err(format!("Invalid socket mode '{}'. Use octal permissions such as 0600", value)),
+    }
 }
 
 impl Default for ServerConfig {
@@ -69,14 +239,39 @@ impl Default for ServerConfig {
             default_port: 8084,
             default_bind: "127.0.0.1".to_string(),
             default_model: "potion-32M".to_string(),
+            ready_policy: default_ready_policy(),
+            cors_allowed_origins: Vec::new(),
+            cors_reject_disallowed: false,
+            output_dtype: default_output_dtype(),
+            strict_models: false,
+            encode_retries: 0,
+            embedding_cache_size: 0,
+            enable_tls: false,
+            tls_cert_path: None,
+            tls_key_path: None,
+            min_tls_version: default_min_tls_version(),
+            tls_cipher_suites: Vec::new(),
+            socket_path: None,
+            socket_mode: default_socket_mode(),
+            mcp_session_idle_timeout_secs: default_mcp_session_idle_timeout_secs(),
+            max_body_bytes: default_max_body_bytes(),
+            request_timeout_secs: default_server_request_timeout_secs(),
+            max_batch_inputs: 0,
+            max_input_chars: 0,
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            stop_grace_period_secs: default_stop_grace_period_secs(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Model storage configuration.
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct ModelConfig {
+    /// Directory models are stored in
     pub models_dir: Option<String>,
+    /// Download missing built-in models on first use
     pub auto_download: bool,
+    /// Dimensions used by `model distill` when `--dims` is not given
     pub default_distill_dims: Option<usize>,
 }
 
@@ -90,13 +285,54 @@ impl Default for ModelConfig {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Log output configuration.
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct LoggingConfig {
+    /// Minimum level logged: "trace", "debug", "info", "warn" or "error"
     pub level: String,
+    /// File logs are written to
     pub file: Option<String>,
+    /// Write logs as JSON lines instead of text
     pub json_format: bool,
+    /// Size in bytes at which the log file is rotated; unset never rotates
     pub max_file_size: Option<u64>,
+    /// Number of rotated log files kept (5 when unset)
     pub max_files: Option<u32>,
+    /// Fraction (0.0–1.0) of successful requests logged at INFO; errors are always logged
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: f64,
+    /// Write logs to stderr; turn off to log only to `file`
+    #[serde(default = "default_console")]
+    pub console: bool,
+}
+
+impl LoggingConfig {
+    /// The log file to write, with its rotation settings.
+    pub fn log_file(&self) -> Option<LogFile> {
+        self.file.as_ref().map(|path| LogFile {
+            path: PathBuf::from(path),
+            max_file_size: self.max_file_size,
+            max_files: self.max_files,
+        })
+    }
+
+    /// Log output for this config; `verbose` raises the level to debug.
+    pub fn log_settings(&self, verbose: bool) -> LogSettings {
+        LogSettings {
+            level: if verbose { "debug".to_string() } else { self.level.clone() },
+            json: self.json_format,
+            file: self.log_file(),
+            console: self.console,
+        }
+    }
+}
+
+fn default_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_console() -> bool {
+    true
 }
 
 impl Default for LoggingConfig {
@@ -107,8 +343,116 @@ impl Default for LoggingConfig {
             json_format: false,
             max_file_size: None,
             max_files: None,
+            sample_rate: default_sample_rate(),
+            console: default_console(),
+        }
+    }
+}
+
+/// Settings for CLI commands that call a running server.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CliConfig {
+    /// Connect and read timeout for requests to the server, in seconds
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+impl Default for CliConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout_secs: default_request_timeout_secs(),
+        }
+    }
+}
+
+/// Build the HTTP client used by CLI commands, so a wedged server cannot hang
+/// them indefinitely.
+pub fn http_client(config: &CliConfig) -> reqwest::Result<reqwest::Client> {
+    let timeout = std::time::Duration::from_secs(config.request_timeout_secs);
+    reqwest::Client::builder()
+        .connect_timeout(timeout)
+        .read_timeout(timeout)
+        .build()
+}
+
+/// How CLI commands reach a running server: over `server.socket_path` when it
+/// is set, otherwise TCP on localhost.
+pub struct ServerEndpoint {
+    client: reqwest::Client,
+    port: u16,
+    socket_path: Option<PathBuf>,
+}
+
+impl ServerEndpoint {
+    pub fn new(cli: &CliConfig, port: u16, socket_path: Option<PathBuf>) -> reqwest::Result<Self> {
+        let client = match &socket_path {
+            #[cfg(unix)]
+            Some(path) => {
+                let timeout = std::time::Duration::from_secs(cli.request_timeout_secs);
+                reqwest::Client::builder()
+                    .unix_socket(path.clone())
+                    .read_timeout(timeout)
+                    .build()?
+            }
+            _ => http_client(cli)?,
+        };
+        Ok(Self { client, port, socket_path })
+    }
+
+    /// The endpoint configured in `config`, with the TCP port overridden by `port`.
+    pub fn from_config(config: &Config, port: u16) -> reqwest::Result<Self> {
+        Self::new(&config.cli, port, config.server.socket_path.as_ref().map(PathBuf::from))
+    }
+
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn socket_path(&self) -> Option<&Path> {
+        self.socket_path.as_deref()
+    }
+
+    /// Base URL requests are sent to; the host is ignored over a socket.
+    pub fn base_url(&self) -> String {
+        match &self.socket_path {
+            Some(_) => "http://localhost".to_string(),
+            None => format!("http://localhost:{}", self.port),
         }
     }
+
+    /// URL of `path` (starting with `/`) on the server.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url(), path)
+    }
+}
+
+impl std::fmt::Display for ServerEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.socket_path {
+            Some(path) => write!(f, "unix:{}", path.display()),
+            None => write!(f, "{}", self.base_url()),
+        }
+    }
+}
+
+/// Describe a failed request to the server, calling out timeouts explicitly.
+pub fn describe_request_error(error: &reqwest::Error, config: &CliConfig) -> String {
+    if error.is_timeout() {
+        format!(
+            "Server did not respond in time ({}s). Check it with 'static-embedding-tool server status' or raise cli.request_timeout_secs.",
+            config.request_timeout_secs
+        )
+    } else {
+        format!("Request to server failed: {}", error)
+    }
 }
 
 pub async fn handle_config_command(
@@ -116,10 +460,21 @@ pub async fn handle_config_command(
     config_path: Option<PathBuf>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     match action {
-        ConfigAction::Get => show_config(config_path).await,
-        ConfigAction::Set(args) => set_config(args, config_path).await,
+        ConfigAction::Get(GetConfigArgs { key: None }) => show_config(config_path).await,
+        ConfigAction::Get(GetConfigArgs { key: Some(key) }) => {
+            let config = load_config(config_path)?;
+            print!("{}", config_value_text(&config, &key)?);
+            Ok(())
+        }
+        ConfigAction::Set(args) => Ok(set_config(args, config_path).await?),
+        ConfigAction::Unset(args) => Ok(unset_config(args, config_path).await?),
         ConfigAction::Reset => reset_config(config_path).await,
         ConfigAction::Path => show_config_path(config_path).await,
+        ConfigAction::Migrate => migrate_config(config_path).await,
+        ConfigAction::Schema => {
+            println!("{}", serde_json::to_string_pretty(&config_schema())?);
+            Ok(())
+        }
     }
 }
 
@@ -127,114 +482,184 @@ pub async fn handle_embed_command(
     args: EmbedArgs,
     config_path: Option<PathBuf>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use reqwest::Client;
-    use serde_json::{Value, json};
+    use std::io::Write;
 
     let config = load_config(config_path)?;
     let port = config.server.default_port;
-    let client = Client::new();
-    let url = format!("http://localhost:{}/v1/embeddings", port);
+    let verbose = config.logging.level == "debug" || config.logging.level == "trace";
+
+    if !matches!(args.format.as_str(), "json" | "csv" | "raw") {
+        return Err(format!("Unsupported output format '{}' (expected json, csv or raw)", args.format).into());
+    }
+
+    let texts = if args.stdin {
+        read_embed_inputs(std::io::stdin().lock(), args.lines)?
+    } else {
+        args.texts.clone()
+    };
+    if texts.is_empty() {
+        return Err("No text to embed on stdin".into());
+    }
 
     let model_name = args.model.as_deref().unwrap_or("potion-32M");
 
-    let request_body = json!({
-        "input": [args.text],
-        "model": model_name,
-        "encoding_format": if args.format == "json" { "float" } else { &args.format }
-    });
+    if verbose {
+        eprintln!("🔍 Embedding {} text(s) using model '{}'...", texts.len(), model_name);
+        for text in &texts {
+            eprintln!("  Text: \"{}\"", text);
+        }
+    }
 
-    if config.logging.level == "debug" || config.logging.level == "trace" {
-        eprintln!("🔍 Embedding text using model '{}'...", model_name);
-        eprintln!("  Text: \"{}\"", args.text);
+    // A configured models directory that does not exist is a setup mistake, not a fallback case
+    let models_dir = config.models.models_dir.as_deref().map(PathBuf::from);
+    if let Some(dir) = &models_dir
+        && !dir.is_dir()
+    {
+        return Err(format!(
+            "Models directory '{}' (models.models_dir) does not exist; create it or run 'static-embedding-tool config set models.models_dir <dir>'",
+            dir.display()
+        )
+        .into());
     }
 
-    // Try to use the server first
-    match client.post(&url).json(&request_body).send().await {
-        Ok(response) => {
-            let status = response.status();
-            if status.is_success() {
-                let result: Value = response.json().await?;
-                display_embedding_result(&result, &args.format)?;
-                if config.logging.level == "debug" || config.logging.level == "trace" {
-                    eprintln!("✓ Embedding completed successfully (via server)");
-                }
-                return Ok(());
-            } else {
-                let error_text = response.text().await?;
-                eprintln!("⚠️  Server error ({}): {}", status, error_text);
+    // Embed locally first, falling back to a running server
+    let local = {
+        let model_name = model_name.to_string();
+        let texts = texts.clone();
+        tokio::task::spawn_blocking(move || {
+            match &models_dir {
+                Some(dir) => Embedder::with_models_dir(&model_name, dir),
+                None => Embedder::new(&model_name),
+            }
+            .map(|embedder| embedder.embed_batch(&texts))
+        })
+        .await?
+    };
+    let embeddings = match local {
+        Ok(embeddings) => {
+            if verbose {
+                eprintln!("✓ Embedding completed successfully (local)");
             }
+            embeddings
         }
-        Err(_) => {
-            if config.logging.level == "debug" || config.logging.level == "trace" {
-                eprintln!("ℹ️  Server not reachable on http://localhost:{}, attempting local embedding...", port);
+        Err(local_error) => {
+            let endpoint = ServerEndpoint::from_config(&config, port)?;
+            if verbose {
+                eprintln!("ℹ️  {}; trying the server on {}...", local_error, endpoint);
+            }
+            let url = endpoint.url("/v1/embeddings");
+            let server_result = match embed_via_server(endpoint.client(), &url, model_name, &texts).await {
+                Ok(embeddings) if embeddings.len() == texts.len() => Ok(embeddings),
+                Ok(embeddings) => Err(format!(
+                    "server returned {} embeddings for {} texts",
+                    embeddings.len(),
+                    texts.len()
+                )),
+                Err(ServerCallError::Rejected { status, message }) => Err(format!("server error ({}): {}", status, message)),
+                Err(ServerCallError::Request(e)) if e.is_timeout() => Err(describe_request_error(&e, &config.cli)),
+                Err(ServerCallError::Request(_)) => Err(format!("server not reachable on {}", endpoint)),
+            };
+            match server_result {
+                Ok(embeddings) => {
+                    if verbose {
+                        eprintln!("✓ Embedding completed successfully (via server)");
+                    }
+                    embeddings
+                }
+                Err(server_error) => {
+                    eprintln!("❌ Local embedding failed: {}", local_error);
+                    eprintln!("❌ Server fallback failed: {}", server_error);
+                    eprintln!("\nMake sure the model is downloaded or the server is running:");
+                    eprintln!("  static-embedding-tool model download {}", model_name);
+                    eprintln!("  static-embedding-tool server start");
+                    return Err(format!("Failed to embed text with model '{}'", model_name).into());
+                }
             }
         }
+    };
+
+    // A closed pipe (e.g. `| head -c 100`) is not an error for the user
+    let mut stdout = std::io::stdout().lock();
+    match writeln!(stdout, "{}", format_embeddings(&embeddings, &texts, model_name, &args.format)?) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
     }
+}
 
-    // Fallback to local embedding
-    match run_local_embedding(&[args.text], model_name).await {
-        Ok(embeddings) => {
-            let prompt_tokens = embeddings.len().div_ceil(4);
-            let result = json!({
-                "object": "list",
-                "data": embeddings.into_iter().enumerate().map(|(i, e)| {
-                    json!({
+/// Read the texts for `embed --stdin`: all of `input` as one text, with the
+/// trailing newline removed, or with `lines` each non-empty line as a text.
+fn read_embed_inputs(mut input: impl std::io::Read, lines: bool) -> std::io::Result<Vec<String>> {
+    let mut content = String::new();
+    input.read_to_string(&mut content)?;
+    Ok(if lines {
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect()
+    } else {
+        let text = content.strip_suffix('\n').unwrap_or(&content);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        if text.trim().is_empty() {
+            Vec::new()
+        } else {
+            vec![text.to_string()]
+        }
+    })
+}
+
+/// Render `embed` results: `json` is an OpenAI-style response, `csv` a header
+/// plus one row per text, and `raw` the bare values separated by spaces, one
+/// line per text.
+fn format_embeddings(
+    embeddings: &[Vec<f32>],
+    texts: &[String],
+    model_name: &str,
+    format: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(match format {
+        "json" => {
+            let prompt_tokens: usize = texts.iter().map(|text| text.len().div_ceil(4)).sum();
+            let data: Vec<_> = embeddings
+                .iter()
+                .enumerate()
+                .map(|(index, embedding)| {
+                    serde_json::json!({
                         "object": "embedding",
-                        "embedding": e,
-                        "index": i
+                        "embedding": embedding,
+                        "index": index
                     })
-                }).collect::<Vec<_>>(),
+                })
+                .collect();
+            serde_json::to_string_pretty(&serde_json::json!({
+                "object": "list",
+                "data": data,
                 "model": model_name,
                 "usage": {
                     "prompt_tokens": prompt_tokens,
                     "total_tokens": prompt_tokens
                 }
-            });
-            display_embedding_result(&result, &args.format)?;
-            if config.logging.level == "debug" || config.logging.level == "trace" {
-                eprintln!("✓ Embedding completed successfully (local)");
-            }
-        }
-        Err(e) => {
-            eprintln!("❌ Local embedding failed: {}", e);
-            eprintln!("\nMake sure the model is downloaded or the server is running:");
-            eprintln!("  static-embedding-tool model download {}", model_name);
-            eprintln!("  static-embedding-tool server start");
-        }
-    }
-
-    Ok(())
-}
-
-fn display_embedding_result(result: &serde_json::Value, format: &str) -> Result<(), Box<dyn std::error::Error>> {
-    match format {
-        "json" => {
-            println!("{}", serde_json::to_string_pretty(&result)?);
+            }))?
         }
         "csv" => {
-            if let Some(data) = result
-                .get("data")
-                .and_then(|d| d.as_array())
-                .and_then(|arr| arr.first())
-                && let Some(embedding) = data.get("embedding").and_then(|e| e.as_array())
-            {
-                println!("embedding");
-                for (i, value) in embedding.iter().enumerate() {
-                    if let Some(num) = value.as_f64() {
-                        print!("{:.6}{}", num, if i < embedding.len() - 1 { "," } else { "" });
-                    }
-                }
-                println!();
+            let mut out = String::from("embedding");
+            for embedding in embeddings {
+                let values: Vec<String> = embedding.iter().map(|v| format!("{:.6}", v)).collect();
+                out.push('\n');
+                out.push_str(&values.join(","));
             }
+            out
         }
-        _ => {
-            println!("{}", serde_json::to_string_pretty(&result)?);
-        }
-    }
-    Ok(())
+        "raw" => embeddings
+            .iter()
+            .map(|embedding| embedding.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => return Err(format!("Unsupported output format '{}'", other).into()),
+    })
 }
 
-async fn run_local_embedding(inputs: &[String], model_name: &str) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error>> {
+pub(crate) async fn load_local_model(model_name: &str) -> Result<model2vec_rs::model::StaticModel, Box<dyn std::error::Error>> {
     use model2vec_rs::model::StaticModel;
     
     // Determine model path
@@ -259,580 +684,3614 @@ async fn run_local_embedding(inputs: &[String], model_name: &str) -> Result<Vec<
         let model = tokio::task::spawn_blocking(move || {
             StaticModel::from_pretrained(hf_id, None, None, None)
         }).await??;
-        return Ok(model.encode(inputs));
+        return Ok(model);
     }
 
     let model = tokio::task::spawn_blocking(move || {
         StaticModel::from_pretrained(&model_path, None, None, None)
     }).await??;
     
-    Ok(model.encode(inputs))
+    Ok(model)
 }
 
-pub async fn handle_batch_command(
-    args: BatchArgs,
-    config_path: Option<PathBuf>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    use reqwest::Client;
-    use serde_json::{Value, json};
-    use std::fs;
-    use std::io::Write;
+/// An input `batch --skip-errors` could not embed.
+#[derive(Serialize, Deserialize, Debug)]
+struct BatchFailure {
+    index: usize,
+    input_excerpt: String,
+    error: String,
+}
 
-    let config = load_config(config_path)?;
-    let port = config.server.default_port;
+impl BatchFailure {
+    /// Longest input prefix (in characters) kept in the errors file.
+    const EXCERPT_CHARS: usize = 80;
 
-    // Check if input file exists
-    if !args.input.exists() {
-        eprintln!(
-            "❌ Error: Input file '{}' does not exist",
-            args.input.display()
-        );
-        return Ok(());
+    fn new(index: usize, input: &str, error: impl Into<String>) -> Self {
+        let mut input_excerpt: String = input.chars().take(Self::EXCERPT_CHARS).collect();
+        if input.chars().count() > Self::EXCERPT_CHARS {
+            input_excerpt.push('…');
+        }
+        Self {
+            index,
+            input_excerpt,
+            error: error.into(),
+        }
     }
+}
 
-    // Read input file
-    let input_content = fs::read_to_string(&args.input)?;
-    let input_data: Vec<String> = if args.input.extension().and_then(|s| s.to_str()) == Some("json")
-    {
-        serde_json::from_str(&input_content)?
+/// Reject empty inputs, so they can be skipped up front. Length limits are
+/// left to the server's `server.max_input_chars` and token limit, or the local
+/// model, which report them per input.
+fn validate_batch_input(text: &str) -> Result<(), String> {
+    if text.trim().is_empty() {
+        Err("input is empty".to_string())
     } else {
-        // Assume text file with one item per line
-        input_content.lines().map(|s| s.to_string()).collect()
-    };
-
-    if input_data.is_empty() {
-        eprintln!("❌ Error: Input file is empty or contains no valid data");
-        return Ok(());
+        Ok(())
     }
+}
 
-        let client = Client::new();
-        let url = format!("http://localhost:{}/v1/embeddings", port);
-        let model_name = args.model.as_deref().unwrap_or("potion-32M");
-    
-        if config.logging.level == "debug" || config.logging.level == "trace" {
-            eprintln!(
-                "🔍 Processing {} texts in batches of {} using model '{}'...",
-                input_data.len(),
-                args.batch_size,
-                model_name
-            );
-            eprintln!("  Input: {}", args.input.display());
-            if let Some(output) = &args.output {
-                eprintln!("  Output: {}", output.display());
+/// Write the row index for an npy output next to it, as
+/// `<name>.texts.json`: a JSON array with each row's `row`, input `text` and,
+/// when ids were extracted, `id`. Returns the index file's path.
+fn write_npy_texts_index(npy_path: &Path, texts: &[String], ids: Option<&[String]>) -> std::io::Result<PathBuf> {
+    let index_path = npy_path.with_extension("texts.json");
+    let rows: Vec<serde_json::Value> = texts
+        .iter()
+        .enumerate()
+        .map(|(row, text)| {
+            let mut entry = serde_json::json!({ "row": row, "text": text });
+            if let Some(ids) = ids {
+                entry["id"] = serde_json::json!(ids[row]);
             }
+            entry
+        })
+        .collect();
+    fs::write(&index_path, serde_json::to_string_pretty(&rows)?)?;
+    Ok(index_path)
+}
+
+fn write_errors_file(path: &Path, failures: &[BatchFailure]) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(path, serde_json::to_string_pretty(failures)?)?;
+    Ok(())
+}
+
+/// Why a request to the embeddings endpoint did not produce embeddings.
+enum ServerCallError {
+    /// The server answered with an error status
+    Rejected { status: reqwest::StatusCode, message: String },
+    /// The request could not be completed (unreachable, timed out, bad body)
+    Request(reqwest::Error),
+}
+
+/// Embed `texts` via the server's `/v1/embeddings` endpoint.
+async fn embed_via_server(
+    client: &reqwest::Client,
+    url: &str,
+    model_name: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, ServerCallError> {
+    let request_body = serde_json::json!({
+        "input": texts,
+        "model": model_name,
+        "encoding_format": "float"
+    });
+    let response = client
+        .post(url)
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(ServerCallError::Request)?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.map_err(ServerCallError::Request)?;
+        // Prefer the OpenAI-style error message over the raw body
+        let message = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+            .unwrap_or(body);
+        return Err(ServerCallError::Rejected { status, message });
+    }
+
+    let result: serde_json::Value = response.json().await.map_err(ServerCallError::Request)?;
+    Ok(result
+        .get("data")
+        .and_then(|d| d.as_array())
+        .map(|data| {
+            data.iter()
+                .filter_map(|item| item.get("embedding").and_then(|e| e.as_array()))
+                .map(|embedding| {
+                    embedding
+                        .iter()
+                        .filter_map(|v| v.as_f64())
+                        .map(|v| v as f32)
+                        .collect()
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Build the JSON document written by `batch` for the json format and stdout.
+fn batch_output_json(
+    model_name: &str,
+    embeddings: &[Vec<f32>],
+    input_count: usize,
+    ids: Option<&[String]>,
+) -> serde_json::Value {
+    let mut output = serde_json::json!({
+        "model": model_name,
+        "embeddings": embeddings,
+        "input_count": input_count,
+        "dimensions": embeddings.first().map(|e| e.len()).unwrap_or(0)
+    });
+    if let Some(ids) = ids {
+        output["ids"] = serde_json::json!(ids);
+    }
+    output
+}
+
+/// Evaluate a JSONPath expression and collect its matches as strings.
+///
+/// Texts must be JSON strings; ids (`allow_numbers`) may also be numbers.
+fn extract_jsonpath_strings(
+    document: &serde_json::Value,
+    path: &str,
+    allow_numbers: bool,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let json_path = serde_json_path::JsonPath::parse(path)
+        .map_err(|e| format!("Invalid JSONPath '{}': {}", path, e))?;
+    let matches = json_path.query(document).all();
+    if matches.is_empty() {
+        return Err(format!("JSONPath '{}' matched no values", path).into());
+    }
+    matches
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| match value {
+            serde_json::Value::String(s) => Ok(s.clone()),
+            serde_json::Value::Number(n) if allow_numbers => Ok(n.to_string()),
+            other => Err(format!(
+                "JSONPath '{}' matched a non-string value at position {}: {}",
+                path, i, other
+            )
+            .into()),
+        })
+        .collect()
+}
+
+/// How often `batch --checkpoint` rewrites the checkpoint file.
+const CHECKPOINT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Progress of a `batch --checkpoint` run.
+#[derive(Serialize, Deserialize)]
+struct BatchCheckpoint<'a> {
+    /// Input file the checkpoint belongs to
+    input: Cow<'a, Path>,
+    /// Model the completed embeddings were produced with
+    model: Cow<'a, str>,
+    /// Number of leading inputs already embedded
+    completed: usize,
+    /// Embeddings for the completed inputs, in input order
+    embeddings: Cow<'a, [Vec<f32>]>,
+}
+
+impl BatchCheckpoint<'_> {
+    /// Load a checkpoint, returning `None` if none has been written yet.
+    ///
+    /// Fails if the checkpoint was written for a different input file or model,
+    /// or records more inputs than the input file now holds.
+    fn load(
+        path: &Path,
+        input: &Path,
+        model: &str,
+        total: usize,
+    ) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(None);
         }
-    
-        let mut all_embeddings = Vec::new();
-        
-        // Try server first
-        let mut use_local = false;
-        for chunk in input_data.chunks(args.batch_size) {
-            let request_body = json!({
-                "input": chunk,
-                "model": model_name,
-                "encoding_format": "float"
-            });
-    
-            match client.post(&url).json(&request_body).send().await {
-                Ok(response) => {
-                    let status = response.status();
-                    if status.is_success() {
-                        let result: Value = response.json().await?;
-                        if let Some(data) = result.get("data").and_then(|d| d.as_array()) {
-                            for item in data {
-                                if let Some(embedding) =
-                                    item.get("embedding").and_then(|e| e.as_array())
-                                {
-                                    let embedding_vec: Vec<f32> = embedding
-                                        .iter()
-                                        .filter_map(|v| v.as_f64())
-                                        .map(|v| v as f32)
-                                        .collect();
-                                    all_embeddings.push(embedding_vec);
-                                }
-                            }
-                            if config.logging.level == "debug" || config.logging.level == "trace" {
-                                eprintln!("  ✓ Processed {}/{} texts (via server)", all_embeddings.len(), input_data.len());
-                            }
-                        }
-                    } else {
-                        let error_text = response.text().await?;
-                        eprintln!("⚠️  Server error ({}): {}", status, error_text);
-                        use_local = true;
-                        break;
-                    }
-                }
-                Err(_) => {
-                    if config.logging.level == "debug" || config.logging.level == "trace" {
-                        eprintln!("ℹ️  Server not reachable, falling back to local processing...");
-                    }
-                    use_local = true;
-                    break;
-                }
-            }
+        let checkpoint: BatchCheckpoint = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| format!("Invalid checkpoint file '{}': {}", path.display(), e))?;
+        if checkpoint.input != input || checkpoint.model != model {
+            return Err(format!(
+                "Checkpoint '{}' belongs to input '{}' with model '{}'; remove it to start over",
+                path.display(),
+                checkpoint.input.display(),
+                checkpoint.model
+            )
+            .into());
         }
-    
-        if use_local {
-            all_embeddings.clear();
-            match run_local_embedding(&input_data, model_name).await {
-                Ok(embeddings) => {
-                    all_embeddings = embeddings;
-                    if config.logging.level == "debug" || config.logging.level == "trace" {
-                        eprintln!("  ✓ Processed {} texts (local)", all_embeddings.len());
-                    }
+        if checkpoint.completed != checkpoint.embeddings.len() || checkpoint.completed > total {
+            return Err(format!(
+                "Checkpoint '{}' does not match the input file ({} of {} texts recorded)",
+                path.display(),
+                checkpoint.completed,
+                total
+            )
+            .into());
+        }
+        Ok(Some(checkpoint))
+    }
+
+    /// Write the checkpoint via a temporary file so an interruption mid-write
+    /// leaves the previous checkpoint intact.
+    fn save(
+        path: &Path,
+        input: &Path,
+        model: &str,
+        embeddings: &[Vec<f32>],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let checkpoint = BatchCheckpoint {
+            input: Cow::Borrowed(input),
+            model: Cow::Borrowed(model),
+            completed: embeddings.len(),
+            embeddings: Cow::Borrowed(embeddings),
+        };
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_string(&checkpoint)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// The error a `batch` job fails with when the chunk of `len` inputs at
+/// `offset` could not be embedded after `retries` retries.
+fn chunk_failed(offset: usize, len: usize, retries: u32, error: Box<dyn std::error::Error>) -> Box<dyn std::error::Error> {
+    format!(
+        "Inputs {}..{} could not be embedded after {} retries: {}",
+        offset,
+        offset + len,
+        retries,
+        error
+    )
+    .into()
+}
+
+/// Wait before the first retry of a failed `batch` chunk; doubles per attempt.
+const BATCH_RETRY_BACKOFF: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Embeds `batch` chunks via the server, switching to a local model for the
+/// rest of the run once the server is unavailable.
+struct BatchEmbedder<'a> {
+    endpoint: &'a ServerEndpoint,
+    model_name: &'a str,
+    config: &'a Config,
+    skip_errors: bool,
+    /// Times a failed server call or local model load is retried
+    retries: u32,
+    /// Whether `endpoint` was checked for a running server yet
+    server_checked: bool,
+    use_local: bool,
+    local_model: Option<Arc<Embedder>>,
+}
+
+impl<'a> BatchEmbedder<'a> {
+    fn new(endpoint: &'a ServerEndpoint, model_name: &'a str, config: &'a Config, skip_errors: bool) -> Self {
+        Self {
+            endpoint,
+            model_name,
+            config,
+            skip_errors,
+            retries: 0,
+            server_checked: false,
+            use_local: false,
+            local_model: None,
+        }
+    }
+
+    /// Retry failed chunks `retries` times, backing off between attempts.
+    fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Embed locally from now on unless a server is running at the endpoint.
+    async fn detect_server(&mut self) {
+        self.server_checked = true;
+        #[cfg(feature = "mcp")]
+        let running = crate::cli::server::server_running(self.endpoint).await.unwrap_or(false);
+        // Without the server commands only a healthy `/health` tells
+        #[cfg(not(feature = "mcp"))]
+        let running = self
+            .endpoint
+            .client()
+            .get(self.endpoint.url("/health"))
+            .send()
+            .await
+            .is_ok_and(|response| response.status().is_success());
+        if !running {
+            eprintln!("ℹ️  No server running on {}; embedding locally", self.endpoint);
+            self.use_local = true;
+        }
+    }
+
+    /// Sleep before retry `attempt` (counting from 1).
+    async fn backoff(attempt: u32) {
+        tokio::time::sleep(BATCH_RETRY_BACKOFF * 2u32.saturating_pow(attempt - 1)).await;
+    }
+
+    /// Embed `texts` via the server, retrying timeouts, unreachable servers
+    /// and server-side errors; client errors are returned at once.
+    async fn embed_via_server(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, ServerCallError> {
+        let url = self.endpoint.url("/v1/embeddings");
+        let mut attempt = 0;
+        loop {
+            let result = match embed_via_server(self.endpoint.client(), &url, self.model_name, texts).await {
+                // A short or long answer cannot be matched back to its inputs
+                Ok(embeddings) if embeddings.len() != texts.len() => Err(ServerCallError::Rejected {
+                    status: reqwest::StatusCode::BAD_GATEWAY,
+                    message: format!("server returned {} embeddings for {} texts", embeddings.len(), texts.len()),
+                }),
+                result => result,
+            };
+            match result {
+                Err(ServerCallError::Rejected { status, message }) if status.is_client_error() => {
+                    return Err(ServerCallError::Rejected { status, message });
                 }
-                Err(e) => {
-                    eprintln!("❌ Local batch processing failed: {}", e);
-                    return Ok(());
+                Err(e) if attempt < self.retries => {
+                    attempt += 1;
+                    let reason = match &e {
+                        ServerCallError::Rejected { status, message } => format!("{} {}", status, message),
+                        ServerCallError::Request(e) => describe_request_error(e, &self.config.cli),
+                    };
+                    eprintln!("⚠️  {}; retrying ({}/{})", reason, attempt, self.retries);
+                    Self::backoff(attempt).await;
                 }
+                result => return result,
             }
         }
-    
-        // Output results
-        if let Some(output_path) = &args.output {
-            match args.format.as_str() {
-                "json" => {
-                    let output_data = json!({
-                        "model": model_name,
-                        "embeddings": all_embeddings,
-                        "input_count": input_data.len(),
-                        "dimensions": all_embeddings.first().map(|e| e.len()).unwrap_or(0)
-                    });
-                    fs::write(output_path, serde_json::to_string_pretty(&output_data)?)?;
+    }
+
+    /// The local model, loaded on first use and retried like server calls.
+    async fn local_model(&mut self) -> Result<Arc<Embedder>, Box<dyn std::error::Error>> {
+        if let Some(model) = &self.local_model {
+            return Ok(Arc::clone(model));
+        }
+        let mut attempt = 0;
+        let model = loop {
+            let model_name = self.model_name.to_string();
+            // Found where `embed` finds it
+            let models_dir = self.config.models.models_dir.as_deref().map(PathBuf::from);
+            let load = move || match &models_dir {
+                Some(dir) => Embedder::with_models_dir(&model_name, dir),
+                None => Embedder::new(&model_name),
+            };
+            match tokio::task::spawn_blocking(load).await? {
+                Ok(model) => break Arc::new(model),
+                Err(e) if attempt < self.retries => {
+                    attempt += 1;
+                    eprintln!("⚠️  Loading model '{}' failed: {}; retrying ({}/{})", self.model_name, e, attempt, self.retries);
+                    Self::backoff(attempt).await;
                 }
-                "csv" => {
-                    let mut file = fs::File::create(output_path)?;
-                    // Write header
-                    writeln!(file, "index,embedding")?;
-                    for (i, embedding) in all_embeddings.iter().enumerate() {
-                        write!(file, "{}", i)?;
-                        for value in embedding {
-                            write!(file, ",{:.6}", value)?;
-                        }
-                        writeln!(file)?;
+                Err(e) => return Err(e.into()),
+            }
+        };
+        self.local_model = Some(Arc::clone(&model));
+        Ok(model)
+    }
+
+    /// Width of the model's embeddings, found by embedding a probe text.
+    async fn dimensions(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        let probe = ["dimensions".to_string()];
+        match self.embed_chunk(&probe, 0, &mut Vec::new(), "probe").await?.pop().flatten() {
+            Some(embedding) if !embedding.is_empty() => Ok(embedding.len()),
+            _ => Err(format!("Could not determine the dimensions of model '{}'", self.model_name).into()),
+        }
+    }
+
+    /// Embed one chunk whose first input is at `offset`, returning an
+    /// embedding per input and reporting `progress` on stderr.
+    ///
+    /// With `skip_errors`, inputs that cannot be embedded are recorded in
+    /// `failures` and come back as `None`. Fails only if the local model is
+    /// needed and cannot be loaded after `retries` attempts.
+    async fn embed_chunk(
+        &mut self,
+        chunk: &[String],
+        offset: usize,
+        failures: &mut Vec<BatchFailure>,
+        progress: &str,
+    ) -> Result<Vec<Option<Vec<f32>>>, Box<dyn std::error::Error>> {
+        let verbose = self.config.logging.level == "debug" || self.config.logging.level == "trace";
+        let mut results: Vec<Option<Vec<f32>>> = vec![None; chunk.len()];
+
+        // Positions in this chunk that still need an embedding
+        let mut pending: Vec<usize> = Vec::new();
+        for (i, text) in chunk.iter().enumerate() {
+            match validate_batch_input(text) {
+                Err(e) if self.skip_errors => failures.push(BatchFailure::new(offset + i, text, e)),
+                _ => pending.push(i),
+            }
+        }
+
+        if !self.server_checked && !pending.is_empty() {
+            self.detect_server().await;
+        }
+        if !self.use_local && !pending.is_empty() {
+            let texts: Vec<String> = pending.iter().map(|&i| chunk[i].clone()).collect();
+            match self.embed_via_server(&texts).await {
+                Ok(embeddings) => {
+                    for (&i, embedding) in pending.iter().zip(embeddings) {
+                        results[i] = Some(embedding);
                     }
+                    pending.clear();
+                    eprintln!("  ✓ Processed {} texts (via server)", progress);
                 }
-                "npy" => {
-                    // For NPY format, we'd need the npy crate, but for now just save as JSON
-                    eprintln!("⚠️  NPY format not yet supported, saving as JSON instead");
-                    let output_data = json!({
-                        "model": model_name,
-                        "embeddings": all_embeddings,
-                        "input_count": input_data.len(),
-                        "dimensions": all_embeddings.first().map(|e| e.len()).unwrap_or(0)
-                    });
-                    let npy_path = output_path.with_extension("json");
-                    fs::write(&npy_path, serde_json::to_string_pretty(&output_data)?)?;
-                    if config.logging.level == "debug" || config.logging.level == "trace" {
-                        eprintln!(
-                            "✓ Results saved to {} (NPY format not implemented)",
-                            npy_path.display()
-                        );
+                Err(ServerCallError::Rejected { status, message })
+                    if self.skip_errors && status.is_client_error() =>
+                {
+                    // Retry one at a time so a single bad input does not sink the chunk
+                    for &i in &pending {
+                        match self.embed_via_server(std::slice::from_ref(&chunk[i])).await {
+                            Ok(mut embeddings) => results[i] = embeddings.pop(),
+                            Err(ServerCallError::Rejected { message, .. }) => {
+                                failures.push(BatchFailure::new(offset + i, &chunk[i], message));
+                            }
+                            Err(ServerCallError::Request(e)) => {
+                                failures.push(BatchFailure::new(offset + i, &chunk[i], e.to_string()));
+                            }
+                        }
                     }
+                    pending.clear();
+                    if verbose {
+                        eprintln!("  ⚠️  Chunk rejected ({}): {}; retried inputs individually", status, message);
+                    }
+                    eprintln!("  ✓ Processed {} texts (via server)", progress);
                 }
-                _ => {
-                    eprintln!("❌ Unsupported output format: {}", args.format);
-                    return Ok(());
+                Err(ServerCallError::Rejected { status, message }) => {
+                    eprintln!("⚠️  Server error ({}): {}", status, message);
+                    eprintln!("ℹ️  Falling back to local processing...");
+                    self.use_local = true;
+                }
+                Err(ServerCallError::Request(e)) => {
+                    eprintln!("⚠️  {}", describe_request_error(&e, &self.config.cli));
+                    eprintln!("ℹ️  Falling back to local processing...");
+                    self.use_local = true;
                 }
             }
-            if config.logging.level == "debug" || config.logging.level == "trace" {
-                eprintln!("✓ Results saved to {}", output_path.display());
+        }
+
+        if self.use_local && !pending.is_empty() {
+            let model = self.local_model().await?;
+            let texts: Vec<String> = pending.iter().map(|&i| chunk[i].clone()).collect();
+            let embeddings = tokio::task::spawn_blocking(move || model.embed_batch(&texts)).await?;
+            for (&i, embedding) in pending.iter().zip(embeddings) {
+                results[i] = Some(embedding);
             }
-        } else {
-            // Print to stdout
-            let output_data = json!({
-                "model": model_name,
-                "embeddings": all_embeddings,
-                "input_count": input_data.len(),
-                "dimensions": all_embeddings.first().map(|e| e.len()).unwrap_or(0)
-            });
-            println!("{}", serde_json::to_string_pretty(&output_data)?);
+            eprintln!("  ✓ Processed {} texts (local)", progress);
         }
-    
-        if config.logging.level == "debug" || config.logging.level == "trace" {
-            eprintln!("✓ Batch processing completed successfully");
+
+        Ok(results)
+    }
+}
+
+/// Layout of a `batch` input file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BatchInputFormat {
+    /// A JSON array of strings
+    Json,
+    /// One JSON string or `{"text": ...}` object per line
+    JsonLines,
+    /// CSV with a header row; texts come from one column
+    Csv,
+    /// One text per line
+    Text,
+}
+
+impl BatchInputFormat {
+    /// Use `format` when given, otherwise go by the extension of `path`;
+    /// unknown extensions read as plain text.
+    fn resolve(format: Option<&str>, path: &Path) -> Result<Self, String> {
+        let extension = path.extension().and_then(|s| s.to_str()).map(str::to_ascii_lowercase);
+        match format.map(str::to_ascii_lowercase).or(extension).as_deref() {
+            Some("json") => Ok(Self::Json),
+            Some("jsonl") | Some("ndjson") => Ok(Self::JsonLines),
+            Some("csv") => Ok(Self::Csv),
+            Some("parquet") => Err(
+                "Parquet input is not supported by this build; convert the file to CSV or JSONL".to_string(),
+            ),
+            Some("text") | Some("txt") => Ok(Self::Text),
+            Some(other) if format.is_some() => Err(format!(
+                "Unsupported input format '{}' (expected json, jsonl, csv or text)",
+                other
+            )),
+            _ => Ok(Self::Text),
         }
-    
-        Ok(())
     }
-async fn show_config(config_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
-    let config = load_config(config_path)?;
-    let config_file_path = get_config_path(None)?;
+}
+
+/// Reads `batch` inputs one at a time instead of loading the whole file: the
+/// strings of a top-level JSON array, one column of a CSV file, or one input
+/// per line.
+enum BatchInputReader<R> {
+    JsonArray { reader: R, started: bool, done: bool },
+    JsonLines(std::io::Lines<R>),
+    Csv {
+        records: crate::utils::csv::CsvReader<R>,
+        text_column: Option<String>,
+        /// Index of the text column, known once the header is read
+        column: Option<usize>,
+    },
+    Lines(std::io::Lines<R>),
+}
 
-    println!("Configuration ({})", config_file_path.display());
-    println!("{}", "-".repeat(50));
+impl<R: std::io::BufRead> BatchInputReader<R> {
+    fn json_array(reader: R) -> Self {
+        Self::JsonArray { reader, started: false, done: false }
+    }
 
-    println!("\n[server]");
-    println!("default_port = {}", config.server.default_port);
-    println!("default_bind = \"{}\"", config.server.default_bind);
-    println!("default_model = \"{}\"", config.server.default_model);
+    fn json_lines(reader: R) -> Self {
+        Self::JsonLines(reader.lines())
+    }
 
-    println!("\n[models]");
-    if let Some(models_dir) = &config.models.models_dir {
-        println!("models_dir = \"{}\"", models_dir);
+    fn csv(reader: R, text_column: Option<&str>) -> Self {
+        Self::Csv {
+            records: crate::utils::csv::CsvReader::new(reader),
+            text_column: text_column.map(str::to_string),
+            column: None,
+        }
     }
-    println!("auto_download = {}", config.models.auto_download);
-    println!(
-        "default_distill_dims = {}",
-        config.models.default_distill_dims.map(|d| d.to_string()).unwrap_or_else(|| "default".to_string())
-    );
 
-    println!("\n[logging]");
-    println!("level = \"{}\"", config.logging.level);
-    if let Some(file) = &config.logging.file {
-        println!("file = \"{}\"", file);
+    fn lines(reader: R) -> Self {
+        Self::Lines(reader.lines())
     }
-    println!("json_format = {}", config.logging.json_format);
-    if let Some(max_file_size) = config.logging.max_file_size {
-        println!("max_file_size = {}", max_file_size);
+
+    /// Pick the reader for `format`; `text_column` applies to CSV only.
+    fn new(format: BatchInputFormat, reader: R, text_column: Option<&str>) -> Self {
+        match format {
+            BatchInputFormat::Json => Self::json_array(reader),
+            BatchInputFormat::JsonLines => Self::json_lines(reader),
+            BatchInputFormat::Csv => Self::csv(reader, text_column),
+            BatchInputFormat::Text => Self::lines(reader),
+        }
     }
-    if let Some(max_files) = config.logging.max_files {
-        println!("max_files = {}", max_files);
+}
+
+/// Find `name` in a CSV header, or failing that take it as a zero-based
+/// column index.
+fn csv_column(header: &[String], name: &str) -> Result<usize, String> {
+    header
+        .iter()
+        .position(|column| column.trim() == name)
+        .or_else(|| name.parse().ok().filter(|&index| index < header.len()))
+        .ok_or_else(|| format!("CSV header (row 1) has no column '{}' (columns: {})", name, header.join(", ")))
+}
+
+/// Find the column texts are read from in a CSV header: `text_column` when
+/// given, otherwise a `text` column, or the only column.
+fn csv_text_column(header: &[String], text_column: Option<&str>) -> Result<usize, String> {
+    let names = || header.join(", ");
+    match text_column {
+        Some(name) => csv_column(header, name),
+        None if header.len() == 1 => Ok(0),
+        None => header.iter().position(|column| column.trim() == "text").ok_or_else(|| {
+            format!(
+                "CSV header (row 1) has no 'text' column; choose one with --text-column (columns: {})",
+                names()
+            )
+        }),
     }
+}
 
-    Ok(())
+/// Read the values of `columns` from every record of a CSV document, in the
+/// order the records appear.
+fn read_csv_columns(content: &str, columns: &[String]) -> Result<Vec<Vec<String>>, String> {
+    let mut records = crate::utils::csv::CsvReader::new(content.as_bytes());
+    let header = match records.next_record() {
+        Ok(Some(header)) => header,
+        Ok(None) => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Invalid CSV: {}", e)),
+    };
+    let indices = columns
+        .iter()
+        .map(|name| csv_column(&header, name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut rows = Vec::new();
+    while let Some(record) = records.next_record().map_err(|e| format!("Invalid CSV: {}", e))? {
+        let row = indices
+            .iter()
+            .map(|&index| record.get(index).cloned().unwrap_or_default())
+            .collect();
+        rows.push(row);
+    }
+    Ok(rows)
 }
 
-async fn set_config(
-    args: SetConfigArgs,
-    config_path: Option<PathBuf>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut config = load_config(config_path.clone()).unwrap_or_default();
+/// Parse one JSONL input line: a JSON string, or an object with a `text` field.
+fn parse_jsonl_input(line: &str) -> Result<String, String> {
+    match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(serde_json::Value::String(text)) => Ok(text),
+        Ok(serde_json::Value::Object(mut record)) => match record.remove("text") {
+            Some(serde_json::Value::String(text)) => Ok(text),
+            _ => Err("JSONL object has no string 'text' field".to_string()),
+        },
+        Ok(_) => Err("JSONL line is neither a string nor an object with 'text'".to_string()),
+        Err(e) => Err(format!("Invalid JSONL line: {}", e)),
+    }
+}
 
-    // Parse the key path (e.g., "server.default_port" or "models.auto_download")
-    let parts: Vec<&str> = args.key.split('.').collect();
-    let value = args.value.clone(); // Clone to avoid move issues
+/// UTF-8 byte order mark that Windows tools often prepend to text files.
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
 
-    match parts.as_slice() {
-        ["server", "default_port"] => {
-            config.server.default_port = value.parse()?;
-        }
-        ["server", "default_bind"] => {
-            config.server.default_bind = value;
-        }
-        ["server", "default_model"] => {
-            config.server.default_model = value;
-        }
-        ["models", "models_dir"] => {
-            config.models.models_dir = Some(value);
-        }
-        ["models", "auto_download"] => {
-            config.models.auto_download = value.parse()?;
+/// Consume a leading UTF-8 byte order mark, if there is one.
+fn skip_bom(reader: &mut impl std::io::BufRead) -> std::io::Result<()> {
+    if reader.fill_buf()?.starts_with(UTF8_BOM) {
+        reader.consume(UTF8_BOM.len());
+    }
+    Ok(())
+}
+
+/// Skip whitespace and return the next byte without consuming it.
+fn peek_non_whitespace(reader: &mut impl std::io::BufRead) -> std::io::Result<Option<u8>> {
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(None);
         }
-        ["models", "default_distill_dims"] => {
-            config.models.default_distill_dims = Some(value.parse()?);
+        match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(pos) => {
+                let byte = buf[pos];
+                reader.consume(pos);
+                return Ok(Some(byte));
+            }
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+            }
         }
-        ["logging", "level"] => {
-            if ["trace", "debug", "info", "warn", "error"].contains(&value.as_str()) {
-                config.logging.level = value;
-            } else {
-                eprintln!("Invalid log level. Use: trace, debug, info, warn, error");
-                return Ok(());
+    }
+}
+
+impl<R: std::io::BufRead> Iterator for BatchInputReader<R> {
+    type Item = Result<String, Box<dyn std::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (reader, started, done) = match self {
+            Self::Lines(lines) => return lines.next().map(|line| line.map_err(Into::into)),
+            Self::Csv { records, text_column, column } => {
+                let column = match column {
+                    Some(column) => *column,
+                    None => {
+                        let header = match records.next_record() {
+                            Ok(Some(header)) => header,
+                            Ok(None) => return None,
+                            Err(e) => return Some(Err(format!("Invalid CSV: {}", e).into())),
+                        };
+                        match csv_text_column(&header, text_column.as_deref()) {
+                            Ok(index) => *column.insert(index),
+                            Err(e) => return Some(Err(e.into())),
+                        }
+                    }
+                };
+                return match records.next_record() {
+                    Ok(Some(mut record)) if column < record.len() => Some(Ok(record.swap_remove(column))),
+                    Ok(Some(record)) => Some(Err(format!(
+                        "CSV row {} has {} field(s) but the text column is field {}",
+                        records.row(),
+                        record.len(),
+                        column + 1
+                    )
+                    .into())),
+                    Ok(None) => None,
+                    Err(e) => Some(Err(format!("Invalid CSV: {}", e).into())),
+                };
             }
+            Self::JsonLines(lines) => {
+                // Blank lines (e.g. a trailing newline) are not records
+                return lines
+                    .find(|line| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+                    .map(|line| Ok(parse_jsonl_input(&line?)?));
+            }
+            Self::JsonArray { reader, started, done } => (reader, started, done),
+        };
+        if *done {
+            return None;
         }
-        ["logging", "file"] => {
-            config.logging.file = Some(value);
+        // Any error ends the stream, since the reader is no longer at an element
+        *done = true;
+
+        let expected = if *started { "',' or ']'" } else { "'['" };
+        let open = match peek_non_whitespace(reader) {
+            Ok(byte) => byte,
+            Err(e) => return Some(Err(e.into())),
+        };
+        match (open, *started) {
+            (Some(b'['), false) | (Some(b','), true) => reader.consume(1),
+            (Some(b']'), true) => {
+                reader.consume(1);
+                return None;
+            }
+            _ => return Some(Err(format!("Input is not a JSON array of strings: expected {}", expected).into())),
         }
-        ["logging", "json_format"] => {
-            config.logging.json_format = value.parse()?;
+        if !*started {
+            *started = true;
+            match peek_non_whitespace(reader) {
+                Ok(Some(b']')) => {
+                    reader.consume(1);
+                    return None;
+                }
+                Ok(_) => {}
+                Err(e) => return Some(Err(e.into())),
+            }
         }
-        _ => {
-            eprintln!("Unknown configuration key: {}", args.key);
-            eprintln!("Available keys:");
-            eprintln!("  server.default_port, server.default_bind, server.default_model");
-            eprintln!("  models.models_dir, models.auto_download, models.default_distill_dims");
-            eprintln!("  logging.level, logging.file, logging.json_format");
-            return Ok(());
+
+        // Strings end at their closing quote, so the parser never reads past the element
+        match String::deserialize(&mut serde_json::Deserializer::from_reader(&mut *reader)) {
+            Ok(text) => {
+                *done = false;
+                Some(Ok(text))
+            }
+            Err(e) => Some(Err(format!("Input is not a JSON array of strings: {}", e).into())),
         }
     }
+}
 
-    save_config(&config, config_path)?;
-    println!("✓ Configuration updated: {} = {}", args.key, args.value);
+/// Hex SHA-256 of an input, identifying it in `batch --format ndjson` output.
+fn text_hash(text: &str) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
 
-    Ok(())
+/// Write one `batch --format ndjson` record.
+fn write_ndjson_record(
+    out: &mut impl std::io::Write,
+    index: usize,
+    text: &str,
+    embedding: &[f32],
+) -> std::io::Result<()> {
+    let record = serde_json::json!({
+        "index": index,
+        "text_hash": text_hash(text),
+        "embedding": embedding,
+    });
+    writeln!(out, "{}", record)
 }
 
-async fn reset_config(config_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
-    let config_file_path = get_config_path(config_path)?;
+/// `batch --format ndjson`: read the input incrementally and write each
+/// embedding as soon as its chunk is encoded, so memory use is bounded by the
+/// batch size rather than the input size.
+///
+/// Stops quietly when the reader of stdout goes away (e.g. `| head`).
+async fn stream_batch_ndjson(
+    args: &BatchArgs,
+    config: &Config,
+    input_format: BatchInputFormat,
+    output_dtype: OutputDtype,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufReader, BufWriter, ErrorKind, Write};
 
-    if config_file_path.exists() {
-        print!("Reset configuration to defaults? [y/N]: ");
-        use std::io::{self, Write};
-        io::stdout().flush()?;
+    if args.input_jsonpath.is_some() {
+        return Err("--format ndjson cannot be combined with --input-jsonpath, which reads the whole document".into());
+    }
+    if args.checkpoint.is_some() {
+        return Err("--format ndjson does not support --checkpoint".into());
+    }
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+    let mut reader = BufReader::new(fs::File::open(&args.input)?);
+    if !args.keep_bom {
+        skip_bom(&mut reader)?;
+    }
+    let mut inputs = BatchInputReader::new(input_format, reader, args.text_column.as_deref());
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(fs::File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
 
-        if input.trim().to_lowercase().starts_with('y') {
-            fs::remove_file(&config_file_path)?;
-            println!("✓ Configuration reset to defaults");
-        } else {
-            println!("Cancelled.");
+    let endpoint = ServerEndpoint::from_config(config, config.server.default_port)?;
+    let model_name = args.model.as_deref().unwrap_or("potion-32M");
+    let mut embedder =
+        BatchEmbedder::new(&endpoint, model_name, config, args.skip_errors).with_retries(args.retries);
+    let mut failures: Vec<BatchFailure> = Vec::new();
+    let mut offset = 0;
+    loop {
+        let mut chunk = Vec::with_capacity(args.batch_size);
+        for input in inputs.by_ref().take(args.batch_size) {
+            match input {
+                Ok(text) => chunk.push(text),
+                Err(e) => {
+                    if let Some(errors_path) = &args.errors_file {
+                        write_errors_file(errors_path, &failures)?;
+                    }
+                    return Err(format!("Failed to read input {}: {}", offset + chunk.len(), e).into());
+                }
+            }
         }
-    } else {
-        println!("Configuration file does not exist (already at defaults)");
+        if chunk.is_empty() {
+            break;
+        }
+
+        let progress = (offset + chunk.len()).to_string();
+        let results = match embedder.embed_chunk(&chunk, offset, &mut failures, &progress).await {
+            Ok(results) => results,
+            Err(e) => {
+                if let Some(errors_path) = &args.errors_file {
+                    write_errors_file(errors_path, &failures)?;
+                }
+                return Err(chunk_failed(offset, chunk.len(), args.retries, e));
+            }
+        };
+
+        // Skipped inputs have no record; the errors file lists them
+        let written = chunk
+            .iter()
+            .zip(results)
+            .enumerate()
+            .filter_map(|(i, (text, embedding))| embedding.map(|e| (offset + i, text, e)))
+            .try_for_each(|(index, text, mut embedding)| {
+                output_dtype.round_embedding(&mut embedding);
+                write_ndjson_record(&mut out, index, text, &embedding)
+            })
+            .and_then(|()| out.flush());
+        match written {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+        offset += chunk.len();
     }
 
+    if offset == 0 {
+        eprintln!("❌ Error: Input file is empty or contains no valid data");
+        return Ok(());
+    }
+    if let Some(errors_path) = &args.errors_file {
+        write_errors_file(errors_path, &failures)?;
+    }
+    if !failures.is_empty() {
+        eprintln!(
+            "⚠️  Skipped {} of {} inputs{}",
+            failures.len(),
+            offset,
+            args.errors_file
+                .as_ref()
+                .map(|p| format!("; details in {}", p.display()))
+                .unwrap_or_default()
+        );
+    }
+    if let Some(output_path) = &args.output
+        && (config.logging.level == "debug" || config.logging.level == "trace")
+    {
+        eprintln!("✓ Results saved to {}", output_path.display());
+    }
     Ok(())
 }
 
-async fn show_config_path(config_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
-    let config_file_path = get_config_path(config_path)?;
-    println!("{}", config_file_path.display());
+pub async fn handle_batch_command(
+    args: BatchArgs,
+    config_path: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use serde_json::Value;
+    use std::fs;
+    use std::io::Write;
 
-    if config_file_path.exists() {
-        println!("  Status: ✓ Exists");
-    } else {
-        println!("  Status: ✗ Not found (using defaults)");
+    let config = load_config(config_path)?;
+    let port = config.server.default_port;
+
+    // Check if input file exists
+    if !args.input.exists() {
+        eprintln!(
+            "❌ Error: Input file '{}' does not exist",
+            args.input.display()
+        );
+        return Ok(());
     }
 
-    Ok(())
-}
+    let output_dtype: OutputDtype = match args
+        .output_dtype
+        .as_deref()
+        .unwrap_or(&config.server.output_dtype)
+        .parse()
+    {
+        Ok(dtype) => dtype,
+        Err(e) => {
+            eprintln!("❌ Error: {}", e);
+            return Ok(());
+        }
+    };
 
-fn get_config_path(config_path: Option<PathBuf>) -> Result<PathBuf, Box<dyn std::error::Error>> {
-    if let Some(path) = config_path {
-        return Ok(path);
+    let input_format = BatchInputFormat::resolve(args.input_format.as_deref(), &args.input)?;
+    if args.format == "parquet" {
+        return Err("Parquet output is not supported by this build; use npy, csv, json or ndjson".into());
     }
 
-    let home = std::env::var("HOME")
-        .or_else(|_| std::env::var("USERPROFILE"))
-        .map_err(|_| "Could not determine home directory")?;
-
-    Ok(PathBuf::from(home).join(".static-embedding-tool").join("config.toml"))
-}
+    let keep_columns: Vec<String> = args
+        .keep_columns
+        .iter()
+        .flat_map(|columns| columns.split(','))
+        .map(|column| column.trim().to_string())
+        .filter(|column| !column.is_empty())
+        .collect();
+    if !keep_columns.is_empty()
+        && (input_format != BatchInputFormat::Csv || args.format != "csv" || args.input_jsonpath.is_some())
+    {
+        eprintln!("❌ Error: --keep-columns needs CSV input and --format csv");
+        return Ok(());
+    }
 
-pub fn load_config(config_path: Option<PathBuf>) -> Result<Config, Box<dyn std::error::Error>> {
-    let config_file_path = get_config_path(config_path)?;
+    if matches!(args.format.as_str(), "ndjson" | "jsonl") {
+        return stream_batch_ndjson(&args, &config, input_format, output_dtype).await;
+    }
 
-    if !config_file_path.exists() {
-        return Ok(Config::default());
+    // Read input file; line-based readers already accept CRLF endings
+    let mut input_content = fs::read_to_string(&args.input)?;
+    if !args.keep_bom && input_content.starts_with('\u{feff}') {
+        input_content.drain(..'\u{feff}'.len_utf8());
     }
+    let mut input_ids: Option<Vec<String>> = None;
+    let input_data: Vec<String> = if let Some(text_path) = &args.input_jsonpath {
+        // Extract texts (and optionally ids) from an arbitrary JSON document
+        let document: Value = serde_json::from_str(&input_content)?;
+        let texts = match extract_jsonpath_strings(&document, text_path, false) {
+            Ok(texts) => texts,
+            Err(e) => {
+                eprintln!("❌ Error: {}", e);
+                return Ok(());
+            }
+        };
+        if let Some(id_path) = &args.input_id_jsonpath {
+            match extract_jsonpath_strings(&document, id_path, true) {
+                Ok(ids) if ids.len() == texts.len() => input_ids = Some(ids),
+                Ok(ids) => {
+                    eprintln!(
+                        "❌ Error: --input-id-jsonpath matched {} ids but --input-jsonpath matched {} texts",
+                        ids.len(),
+                        texts.len()
+                    );
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("❌ Error: {}", e);
+                    return Ok(());
+                }
+            }
+        }
+        texts
+    } else if input_format == BatchInputFormat::Json {
+        serde_json::from_str(&input_content)?
+    } else if input_format == BatchInputFormat::Text {
+        // One item per line
+        input_content.lines().map(|s| s.to_string()).collect()
+    } else {
+        BatchInputReader::new(input_format, input_content.as_bytes(), args.text_column.as_deref())
+            .collect::<Result<Vec<String>, _>>()?
+    };
 
-    let content = fs::read_to_string(config_file_path)?;
-    let config: Config = toml::from_str(&content)?;
-    Ok(config)
-}
+    let kept_values = if keep_columns.is_empty() {
+        Vec::new()
+    } else {
+        match read_csv_columns(&input_content, &keep_columns) {
+            Ok(values) => values,
+            Err(e) => {
+                eprintln!("❌ Error: {}", e);
+                return Ok(());
+            }
+        }
+    };
 
-fn save_config(
-    config: &Config,
-    config_path: Option<PathBuf>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let config_file_path = get_config_path(config_path)?;
+    // An empty input still makes a valid (0, dims) npy array
+    if input_data.is_empty() && !(args.format == "npy" && args.output.is_some()) {
+        eprintln!("❌ Error: Input file is empty or contains no valid data");
+        return Ok(());
+    }
 
-    // Create directory if it doesn't exist
-    if let Some(parent) = config_file_path.parent() {
-        fs::create_dir_all(parent)?;
+        let endpoint = ServerEndpoint::from_config(&config, port)?;
+        let model_name = args.model.as_deref().unwrap_or("potion-32M");
+    
+        if config.logging.level == "debug" || config.logging.level == "trace" {
+            eprintln!(
+                "🔍 Processing {} texts in batches of {} using model '{}'...",
+                input_data.len(),
+                args.batch_size,
+                model_name
+            );
+            eprintln!("  Input: {}", args.input.display());
+            if let Some(output) = &args.output {
+                eprintln!("  Output: {}", output.display());
+            }
+        }
+    
+        // Resume from a previous run if a checkpoint exists
+        let mut all_embeddings = Vec::new();
+        if let Some(checkpoint_path) = &args.checkpoint {
+            match BatchCheckpoint::load(checkpoint_path, &args.input, model_name, input_data.len()) {
+                Ok(Some(checkpoint)) => {
+                    eprintln!(
+                        "↻ Resuming from checkpoint {}: {}/{} texts already embedded",
+                        checkpoint_path.display(),
+                        checkpoint.completed,
+                        input_data.len()
+                    );
+                    all_embeddings = checkpoint.embeddings.into_owned();
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("❌ Error: {}", e);
+                    return Ok(());
+                }
+            }
+        }
+
+        // With --skip-errors, failed inputs keep their position as an empty
+        // embedding and are listed in the errors file
+        let mut failures: Vec<BatchFailure> = Vec::new();
+        if args.skip_errors {
+            for (index, embedding) in all_embeddings.iter().enumerate() {
+                if embedding.is_empty() {
+                    failures.push(BatchFailure::new(index, &input_data[index], "failed in an earlier run"));
+                }
+            }
+        }
+
+        // Try server first, switching to a local model if it is unavailable
+        let mut embedder =
+            BatchEmbedder::new(&endpoint, model_name, &config, args.skip_errors).with_retries(args.retries);
+        let mut last_checkpoint = std::time::Instant::now();
+        let resume_from = all_embeddings.len();
+        for (chunk_index, chunk) in input_data[resume_from..].chunks(args.batch_size).enumerate() {
+            let offset = resume_from + chunk_index * args.batch_size;
+            let progress = format!("{}/{}", offset + chunk.len(), input_data.len());
+            let results = match embedder.embed_chunk(chunk, offset, &mut failures, &progress).await {
+                Ok(results) => results,
+                Err(e) => {
+                    // Keep what was already embedded for the next run
+                    if let Some(checkpoint_path) = &args.checkpoint {
+                        BatchCheckpoint::save(checkpoint_path, &args.input, model_name, &all_embeddings)?;
+                        eprintln!("ℹ️  Progress saved to {}; re-run to resume", checkpoint_path.display());
+                    }
+                    if let Some(errors_path) = &args.errors_file {
+                        write_errors_file(errors_path, &failures)?;
+                    }
+                    return Err(chunk_failed(offset, chunk.len(), args.retries, e));
+                }
+            };
+
+            all_embeddings.extend(results.into_iter().map(Option::unwrap_or_default));
+
+            if let Some(checkpoint_path) = &args.checkpoint
+                && last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL
+            {
+                BatchCheckpoint::save(checkpoint_path, &args.input, model_name, &all_embeddings)?;
+                last_checkpoint = std::time::Instant::now();
+            }
+        }
+
+        if let Some(errors_path) = &args.errors_file {
+            write_errors_file(errors_path, &failures)?;
+        }
+        if !failures.is_empty() {
+            eprintln!(
+                "⚠️  Skipped {} of {} inputs{}",
+                failures.len(),
+                input_data.len(),
+                args.errors_file
+                    .as_ref()
+                    .map(|p| format!("; details in {}", p.display()))
+                    .unwrap_or_default()
+            );
+        }
+    
+        for embedding in &mut all_embeddings {
+            output_dtype.round_embedding(embedding);
+        }
+
+        // Output results
+        if let Some(output_path) = &args.output {
+            match args.format.as_str() {
+                "json" => {
+                    let output_data = batch_output_json(model_name, &all_embeddings, input_data.len(), input_ids.as_deref());
+                    fs::write(output_path, serde_json::to_string_pretty(&output_data)?)?;
+                }
+                "csv" => {
+                    let mut file = fs::File::create(output_path)?;
+                    // Write header, keyed by id when ids were extracted
+                    write!(file, "{}", if input_ids.is_some() { "id" } else { "index" })?;
+                    for column in &keep_columns {
+                        write!(file, ",{}", crate::utils::csv::quote_field(column))?;
+                    }
+                    writeln!(file, ",embedding")?;
+                    for (i, embedding) in all_embeddings.iter().enumerate() {
+                        // Skipped inputs have no row; the errors file lists them
+                        if embedding.is_empty() {
+                            continue;
+                        }
+                        match &input_ids {
+                            Some(ids) => write!(file, "{}", ids[i])?,
+                            None => write!(file, "{}", i)?,
+                        }
+                        for value in kept_values.get(i).into_iter().flatten() {
+                            write!(file, ",{}", crate::utils::csv::quote_field(value))?;
+                        }
+                        for value in embedding {
+                            write!(file, ",{:.6}", value)?;
+                        }
+                        writeln!(file)?;
+                    }
+                }
+                "npy" => {
+                    let dims = match all_embeddings.iter().map(Vec::len).max().filter(|&dims| dims > 0) {
+                        Some(dims) => dims,
+                        // Nothing was embedded, so ask the model for its width
+                        None => embedder.dimensions().await?,
+                    };
+                    let rows: Cow<[Vec<f32>]> = if failures.is_empty() {
+                        Cow::Borrowed(&all_embeddings)
+                    } else {
+                        // npy needs a rectangular array, so skipped inputs become zero rows
+                        eprintln!("⚠️  Skipped inputs are written as zero rows in {}", output_path.display());
+                        all_embeddings
+                            .iter()
+                            .map(|e| if e.is_empty() { vec![0.0; dims] } else { e.clone() })
+                            .collect()
+                    };
+                    // Mixed widths (e.g. from different models) are an error, not a ragged file
+                    crate::utils::npy::write_npy_with_dims(output_path, &rows, dims, output_dtype)?;
+                    let index_path = write_npy_texts_index(output_path, &input_data, input_ids.as_deref())?;
+                    if config.logging.level == "debug" || config.logging.level == "trace" {
+                        eprintln!("✓ Row index saved to {}", index_path.display());
+                    }
+                }
+                _ => {
+                    eprintln!("❌ Unsupported output format: {}", args.format);
+                    return Ok(());
+                }
+            }
+            if config.logging.level == "debug" || config.logging.level == "trace" {
+                eprintln!("✓ Results saved to {}", output_path.display());
+            }
+        } else {
+            // Print to stdout
+            let output_data = batch_output_json(model_name, &all_embeddings, input_data.len(), input_ids.as_deref());
+            println!("{}", serde_json::to_string_pretty(&output_data)?);
+        }
+    
+        // The job is complete, so there is nothing left to resume
+        if let Some(checkpoint_path) = &args.checkpoint
+            && checkpoint_path.exists()
+        {
+            fs::remove_file(checkpoint_path)?;
+        }
+
+        if config.logging.level == "debug" || config.logging.level == "trace" {
+            eprintln!("✓ Batch processing completed successfully");
+        }
+    
+        Ok(())
+    }
+async fn show_config(config_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    use std::fmt::Write;
+
+    let (config, sources) = load_config_with_sources(config_path, std::env::vars())?;
+    let config_file_path = get_config_path(None)?;
+    let mut out = String::new();
+
+    writeln!(out, "Configuration ({})", config_file_path.display())?;
+    writeln!(out, "{}", "-".repeat(50))?;
+
+    writeln!(out, "version = {}", config.version)?;
+
+    writeln!(out, "\n[server]")?;
+    writeln!(out, "default_port = {}", config.server.default_port)?;
+    writeln!(out, "default_bind = \"{}\"", config.server.default_bind)?;
+    writeln!(out, "default_model = \"{}\"", config.server.default_model)?;
+    writeln!(out, "ready_policy = \"{}\"", config.server.ready_policy)?;
+    writeln!(out, "cors_allowed_origins = {:?}", config.server.cors_allowed_origins)?;
+    writeln!(out, "cors_reject_disallowed = {}", config.server.cors_reject_disallowed)?;
+    writeln!(out, "output_dtype = \"{}\"", config.server.output_dtype)?;
+    writeln!(out, "strict_models = {}", config.server.strict_models)?;
+    writeln!(out, "encode_retries = {}", config.server.encode_retries)?;
+    writeln!(out, "embedding_cache_size = {}", config.server.embedding_cache_size)?;
+    writeln!(out, "enable_tls = {}", config.server.enable_tls)?;
+    if let Some(socket_path) = &config.server.socket_path {
+        writeln!(out, "socket_path = \"{}\"", socket_path)?;
+    }
+    writeln!(out, "socket_mode = \"{}\"", config.server.socket_mode)?;
+    writeln!(out, "mcp_session_idle_timeout_secs = {}", config.server.mcp_session_idle_timeout_secs)?;
+    writeln!(out, "max_body_bytes = {}", config.server.max_body_bytes)?;
+    writeln!(out, "request_timeout_secs = {}", config.server.request_timeout_secs)?;
+    writeln!(out, "max_batch_inputs = {}", config.server.max_batch_inputs)?;
+    writeln!(out, "max_input_chars = {}", config.server.max_input_chars)?;
+    writeln!(out, "shutdown_timeout_secs = {}", config.server.shutdown_timeout_secs)?;
+    writeln!(out, "stop_grace_period_secs = {}", config.server.stop_grace_period_secs)?;
+    if let Some(cert_path) = &config.server.tls_cert_path {
+        writeln!(out, "tls_cert_path = \"{}\"", cert_path)?;
+    }
+    if let Some(key_path) = &config.server.tls_key_path {
+        writeln!(out, "tls_key_path = \"{}\"", key_path)?;
+    }
+    writeln!(out, "min_tls_version = \"{}\"", config.server.min_tls_version)?;
+    writeln!(out, "tls_cipher_suites = {:?}", config.server.tls_cipher_suites)?;
+
+    writeln!(out, "\n[models]")?;
+    if let Some(models_dir) = &config.models.models_dir {
+        writeln!(out, "models_dir = \"{}\"", models_dir)?;
+    }
+    writeln!(out, "auto_download = {}", config.models.auto_download)?;
+    writeln!(out, 
+        "default_distill_dims = {}",
+        config.models.default_distill_dims.map(|d| d.to_string()).unwrap_or_else(|| "default".to_string())
+    )?;
+
+    writeln!(out, "\n[logging]")?;
+    writeln!(out, "level = \"{}\"", config.logging.level)?;
+    if let Some(file) = &config.logging.file {
+        writeln!(out, "file = \"{}\"", file)?;
+    }
+    writeln!(out, "json_format = {}", config.logging.json_format)?;
+    if let Some(max_file_size) = config.logging.max_file_size {
+        writeln!(out, "max_file_size = {}", max_file_size)?;
+    }
+    if let Some(max_files) = config.logging.max_files {
+        writeln!(out, "max_files = {}", max_files)?;
+    }
+    writeln!(out, "sample_rate = {}", config.logging.sample_rate)?;
+    writeln!(out, "console = {}", config.logging.console)?;
+
+    writeln!(out, "\n[cli]")?;
+    writeln!(out, "request_timeout_secs = {}", config.cli.request_timeout_secs)?;
+
+    print!("{}", annotate_sources(&out, &sources));
+    Ok(())
+}
+
+/// Parse a log file size in bytes, with an optional `KB` or `MB` suffix
+/// (1024-based). `0` or an empty value means no limit.
+pub fn parse_byte_size(value: &str) -> Result<Option<u64>, String> {
+    let value = value.trim();
+    let upper = value.to_ascii_uppercase();
+    let (digits, multiplier) = if let Some(digits) = upper.strip_suffix("MB") {
+        (digits, 1024 * 1024)
+    } else if let Some(digits) = upper.strip_suffix("KB") {
+        (digits, 1024)
+    } else {
+        (upper.strip_suffix('B').unwrap_or(&upper), 1)
+    };
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match digits.trim().parse::<u64>().ok().and_then(|n| n.checked_mul(multiplier)) {
+        Some(0) => Ok(None),
+        Some(size) => Ok(Some(size)),
+        None => Err(format!("Invalid size '{}'. Use bytes, or a number with a KB or MB suffix", value)),
+    }
+}
+
+/// Value stored for a TLS file path: `None` for an empty string, which clears
+/// it. Warns when the file does not exist (yet), since it is only read when the
+/// server starts.
+fn tls_path_setting(kind: &str, value: String) -> Option<String> {
+    if value.is_empty() {
+        return None;
+    }
+    if !Path::new(&value).is_file() {
+        eprintln!(
+            "Warning: TLS {} '{}' does not exist yet; the server will fail to start with TLS enabled until it does",
+            kind, value
+        );
+    }
+    Some(value)
+}
+
+/// Keys accepted by `config set`, listed when an unknown key is given.
+const SETTABLE_KEYS: &str = "\
+  server.default_port, server.default_bind, server.default_model, server.ready_policy
+  server.cors_allowed_origins, server.cors_reject_disallowed, server.output_dtype
+  server.strict_models, server.encode_retries, server.embedding_cache_size, server.enable_tls
+  server.tls_cert_path, server.tls_key_path, server.min_tls_version, server.tls_cipher_suites
+  server.socket_path, server.socket_mode
+  server.mcp_session_idle_timeout_secs, server.max_body_bytes, server.request_timeout_secs
+  server.max_batch_inputs, server.max_input_chars
+  server.shutdown_timeout_secs, server.stop_grace_period_secs
+  models.models_dir, models.auto_download, models.default_distill_dims
+  logging.level, logging.file, logging.json_format, logging.sample_rate
+  logging.max_file_size, logging.max_files, logging.console
+  cli.request_timeout_secs";
+
+/// Why `config set` did not change the configuration.
+///
+/// The configuration file is left untouched in every case, and the CLI exits
+/// with a non-zero status.
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    /// The key names no setting.
+    #[error("Unknown configuration key: {0}\nAvailable keys:\n{keys}", keys = SETTABLE_KEYS)]
+    UnknownKey(String),
+
+    /// The value does not parse, or parses to something the setting cannot use.
+    #[error("Invalid value for {key}: expected {expected}")]
+    InvalidValue { key: String, expected: String },
+
+    /// The configuration file could not be read or written.
+    #[error("Failed to save configuration: {0}")]
+    IoError(String),
+}
+
+/// Check that the dotted `key` names a single setting, such as
+/// `server.default_port`, and split it into its section and setting.
+///
+/// `config get`, `config set` and `config unset` all resolve keys this way.
+fn resolve_key(key: &str) -> Result<(&str, &str), ConfigError> {
+    let defaults = serde_json::to_value(Config::default()).map_err(|e| ConfigError::IoError(e.to_string()))?;
+    match key.split_once('.') {
+        Some((section, setting)) if defaults.get(section).and_then(|table| table.get(setting)).is_some() => {
+            Ok((section, setting))
+        }
+        _ => Err(ConfigError::UnknownKey(key.to_string())),
+    }
+}
+
+/// The value of `key` in `config` as `config get` prints it: a setting raw,
+/// so that it can be captured in a shell, or a whole section as TOML.
+///
+/// Unset optional settings print as nothing; lists are comma-separated, the
+/// same as `config set` takes them.
+pub fn config_value_text(config: &Config, key: &str) -> Result<String, ConfigError> {
+    use serde_json::Value;
+
+    let tree = serde_json::to_value(config).map_err(|e| ConfigError::IoError(e.to_string()))?;
+    if let Some(Value::Object(section)) = tree.get(key) {
+        // TOML has no null; unset optional settings are left out, as in the file
+        let section: serde_json::Map<String, Value> =
+            section.iter().filter(|(_, value)| !value.is_null()).map(|(k, v)| (k.clone(), v.clone())).collect();
+        return toml::to_string_pretty(&section).map_err(|e| ConfigError::IoError(e.to_string()));
+    }
+    let raw = |value: &Value| match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    let (section, setting) = resolve_key(key)?;
+    let text = match &tree[section][setting] {
+        Value::Array(items) => items.iter().map(raw).collect::<Vec<_>>().join(","),
+        value => raw(value),
+    };
+    Ok(if text.is_empty() { text } else { format!("{}\n", text) })
+}
+
+async fn unset_config(args: UnsetConfigArgs, config_path: Option<PathBuf>) -> Result<(), ConfigError> {
+    let (section, setting) = resolve_key(&args.key)?;
+    let config = read_config_file(config_path.clone()).map_err(|e| ConfigError::IoError(e.to_string()))?;
+
+    // Copy the default over the current value and read the config back
+    let to_error = |e: serde_json::Error| ConfigError::IoError(e.to_string());
+    let defaults = serde_json::to_value(Config::default()).map_err(to_error)?;
+    let mut tree = serde_json::to_value(&config).map_err(to_error)?;
+    tree[section][setting] = defaults[section][setting].clone();
+    let config: Config = serde_json::from_value(tree).map_err(to_error)?;
+
+    save_config(&config, config_path).map_err(|e| ConfigError::IoError(e.to_string()))?;
+    let default = config_value_text(&config, &args.key)?;
+    match default.trim_end() {
+        "" => println!("✓ Configuration reset: {} (unset)", args.key),
+        default => println!("✓ Configuration reset: {} = {}", args.key, default),
+    }
+
+    Ok(())
+}
+
+async fn set_config(args: SetConfigArgs, config_path: Option<PathBuf>) -> Result<(), ConfigError> {
+    // A file that does not parse is left for `config migrate`, not overwritten
+    let mut config = read_config_file(config_path.clone()).map_err(|e| ConfigError::IoError(e.to_string()))?;
+
+    // Resolve the key path (e.g., "server.default_port" or "models.auto_download")
+    let setting = resolve_key(&args.key)?;
+    let value = args.value.clone(); // Clone to avoid move issues
+    let invalid = |expected: &str| ConfigError::InvalidValue {
+        key: args.key.clone(),
+        expected: expected.to_string(),
+    };
+    let parse_bool = |value: &str| value.parse::<bool>().map_err(|_| invalid("true or false"));
+
+    match setting {
+        ("server", "default_port") => {
+            match value.parse::<u16>() {
+                Ok(port) if port > 0 => config.server.default_port = port,
+                _ => return Err(invalid("a port from 1 to 65535")),
+            }
+        }
+        ("server", "default_bind") => {
+            if value.parse::<IpAddr>().is_err() {
+                return Err(invalid("an IP address such as 127.0.0.1 or ::1"));
+            }
+            config.server.default_bind = value;
+        }
+        ("server", "default_model") => {
+            config.server.default_model = value;
+        }
+        ("server", "ready_policy") => {
+            if !["loaded", "warmed"].contains(&value.as_str()) {
+                return Err(invalid("one of: loaded, warmed"));
+            }
+            config.server.ready_policy = value;
+        }
+        ("server", "cors_allowed_origins") => {
+            // Comma-separated list; an empty value disables CORS
+            config.server.cors_allowed_origins = value
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        ("server", "cors_reject_disallowed") => {
+            config.server.cors_reject_disallowed = parse_bool(&value)?;
+        }
+        ("server", "output_dtype") => {
+            if value.parse::<OutputDtype>().is_err() {
+                return Err(invalid("one of: f32, f16"));
+            }
+            config.server.output_dtype = value;
+        }
+        ("server", "strict_models") => {
+            config.server.strict_models = parse_bool(&value)?;
+        }
+        ("server", "encode_retries") => {
+            match value.parse::<u32>() {
+                Ok(retries) if retries <= MAX_ENCODE_RETRIES => config.server.encode_retries = retries,
+                _ => return Err(invalid(&format!("a whole number from 0 to {}", MAX_ENCODE_RETRIES))),
+            }
+        }
+        ("server", "embedding_cache_size") => {
+            config.server.embedding_cache_size = value
+                .parse()
+                .map_err(|_| invalid("a whole number of embeddings (0 disables the cache)"))?;
+        }
+        ("server", "enable_tls") => {
+            config.server.enable_tls = parse_bool(&value)?;
+        }
+        ("server", "tls_cert_path") => {
+            config.server.tls_cert_path = tls_path_setting("certificate", value);
+        }
+        ("server", "tls_key_path") => {
+            config.server.tls_key_path = tls_path_setting("private key", value);
+        }
+        ("server", "min_tls_version") => {
+            if !["1.2", "1.3"].contains(&value.as_str()) {
+                return Err(invalid("one of: 1.2, 1.3"));
+            }
+            config.server.min_tls_version = value;
+        }
+        ("server", "tls_cipher_suites") => {
+            // Comma-separated list; an empty value offers the default suites
+            let suites: Vec<String> = value
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            #[cfg(feature = "mcp")]
+            crate::server::tls::cipher_suites(&suites).map_err(|e| invalid(&e))?;
+            config.server.tls_cipher_suites = suites;
+        }
+        ("server", "socket_path") => {
+            // An empty value switches back to TCP
+            config.server.socket_path = (!value.is_empty()).then_some(value);
+        }
+        ("server", "socket_mode") => {
+            if parse_socket_mode(&value).is_err() {
+                return Err(invalid("octal permissions such as 0600"));
+            }
+            config.server.socket_mode = value;
+        }
+        ("server", "mcp_session_idle_timeout_secs") => {
+            config.server.mcp_session_idle_timeout_secs = value
+                .parse()
+                .map_err(|_| invalid("a whole number of seconds (0 keeps idle sessions open)"))?;
+        }
+        ("server", "max_body_bytes") => {
+            match value.parse::<usize>() {
+                Ok(bytes) if bytes > 0 => config.server.max_body_bytes = bytes,
+                _ => return Err(invalid("a positive number of bytes")),
+            }
+        }
+        ("server", "request_timeout_secs") => {
+            config.server.request_timeout_secs = value
+                .parse()
+                .map_err(|_| invalid("a whole number of seconds (0 never times out)"))?;
+        }
+        ("server", "max_batch_inputs") => {
+            config.server.max_batch_inputs = value
+                .parse()
+                .map_err(|_| invalid("a whole number of inputs (0 uses the default of 100)"))?;
+        }
+        ("server", "max_input_chars") => {
+            config.server.max_input_chars = value
+                .parse()
+                .map_err(|_| invalid("a whole number of characters (0 uses the default of 65536)"))?;
+        }
+        ("server", "shutdown_timeout_secs") => {
+            config.server.shutdown_timeout_secs =
+                value.parse().map_err(|_| invalid("a whole number of seconds"))?;
+        }
+        ("server", "stop_grace_period_secs") => {
+            config.server.stop_grace_period_secs =
+                value.parse().map_err(|_| invalid("a whole number of seconds (0 kills right away)"))?;
+        }
+        ("models", "models_dir") => {
+            config.models.models_dir = Some(value);
+        }
+        ("models", "auto_download") => {
+            config.models.auto_download = parse_bool(&value)?;
+        }
+        ("models", "default_distill_dims") => {
+            match value.parse::<usize>() {
+                Ok(dims) if dims > 0 => config.models.default_distill_dims = Some(dims),
+                _ => return Err(invalid("a positive number of dimensions")),
+            }
+        }
+        ("logging", "level") => {
+            if !["trace", "debug", "info", "warn", "error"].contains(&value.as_str()) {
+                return Err(invalid("one of: trace, debug, info, warn, error"));
+            }
+            config.logging.level = value;
+        }
+        ("logging", "file") => {
+            config.logging.file = Some(value);
+        }
+        ("logging", "json_format") => {
+            config.logging.json_format = parse_bool(&value)?;
+        }
+        ("logging", "console") => {
+            config.logging.console = parse_bool(&value)?;
+        }
+        ("logging", "sample_rate") => {
+            match value.parse::<f64>() {
+                Ok(rate) if (0.0..=1.0).contains(&rate) => config.logging.sample_rate = rate,
+                _ => return Err(invalid("a number between 0.0 and 1.0")),
+            }
+        }
+        ("logging", "max_file_size") => {
+            config.logging.max_file_size = parse_byte_size(&value)
+                .map_err(|_| invalid("bytes, or a number with a KB or MB suffix (0 or empty for no limit)"))?;
+        }
+        ("logging", "max_files") => {
+            match value.trim() {
+                "" | "0" => config.logging.max_files = None,
+                count => {
+                    let count = count
+                        .parse::<u32>()
+                        .map_err(|_| invalid("a whole number (0 or empty for the default)"))?;
+                    config.logging.max_files = Some(count);
+                }
+            }
+        }
+        ("cli", "request_timeout_secs") => {
+            match value.parse::<u64>() {
+                Ok(secs) if secs > 0 => config.cli.request_timeout_secs = secs,
+                _ => return Err(invalid("a whole number of seconds greater than 0")),
+            }
+        }
+        _ => return Err(ConfigError::UnknownKey(args.key)),
+    }
+
+    save_config(&config, config_path).map_err(|e| ConfigError::IoError(e.to_string()))?;
+    println!("✓ Configuration updated: {} = {}", args.key, args.value);
+
+    Ok(())
+}
+
+async fn reset_config(config_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let config_file_path = get_config_path(config_path)?;
+
+    if config_file_path.exists() {
+        print!("Reset configuration to defaults? [y/N]: ");
+        use std::io::{self, Write};
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if input.trim().to_lowercase().starts_with('y') {
+            fs::remove_file(&config_file_path)?;
+            println!("✓ Configuration reset to defaults");
+        } else {
+            println!("Cancelled.");
+        }
+    } else {
+        println!("Configuration file does not exist (already at defaults)");
+    }
+
+    Ok(())
+}
+
+/// JSON Schema of the config file, with each key's default filled in.
+pub fn config_schema() -> serde_json::Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(Config)).unwrap_or_default();
+    let defaults = serde_json::to_value(Config::default()).unwrap_or_default();
+    let definitions = schema.get("$defs").cloned().unwrap_or_default();
+    annotate_defaults(&mut schema, &defaults);
+    // Sections are described under `$defs` and referenced from the top level
+    if let Some(sections) = schema.get("properties").cloned().and_then(|p| p.as_object().cloned()) {
+        for (key, property) in sections {
+            if let Some(name) = property.get("$ref").and_then(|r| r.as_str()).and_then(|r| r.strip_prefix("#/$defs/")) {
+                let mut section = definitions[name].clone();
+                annotate_defaults(&mut section, &defaults[&key]);
+                schema["$defs"][name] = section;
+            }
+        }
+    }
+    schema
+}
+
+/// Set `default` on each property of `schema` that has a non-null value in `defaults`.
+fn annotate_defaults(schema: &mut serde_json::Value, defaults: &serde_json::Value) {
+    let Some(properties) = schema.get_mut("properties").and_then(|p| p.as_object_mut()) else {
+        return;
+    };
+    for (key, property) in properties.iter_mut() {
+        let default = &defaults[key];
+        if default.is_null() || property.get("$ref").is_some() {
+            continue;
+        }
+        if let Some(property) = property.as_object_mut() {
+            property.entry("default").or_insert_with(|| default.clone());
+        }
+    }
+}
+
+async fn show_config_path(config_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let config_file_path = get_config_path(config_path)?;
+    println!("{}", config_file_path.display());
+
+    if config_file_path.exists() {
+        println!("  Status: ✓ Exists");
+    } else {
+        println!("  Status: ✗ Not found (using defaults)");
+    }
+
+    Ok(())
+}
+
+/// Deprecated keys and the keys that replaced them.
+const RENAMED_KEYS: &[(&str, &str)] = &[
+    ("server.port", "server.default_port"),
+    ("server.host", "server.default_bind"),
+    ("models.default", "server.default_model"),
+    ("models.path", "models.models_dir"),
+];
+
+fn split_key(key: &str) -> (&str, &str) {
+    key.split_once('.').unwrap_or(("", key))
+}
+
+fn take_key(table: &mut toml::Table, key: &str) -> Option<toml::Value> {
+    let (section, name) = split_key(key);
+    table.get_mut(section)?.as_table_mut()?.remove(name)
+}
+
+fn has_key(table: &toml::Table, key: &str) -> bool {
+    let (section, name) = split_key(key);
+    table
+        .get(section)
+        .and_then(|s| s.as_table())
+        .is_some_and(|s| s.contains_key(name))
+}
+
+fn set_key(table: &mut toml::Table, key: &str, value: toml::Value) {
+    let (section, name) = split_key(key);
+    if let Some(section) = table
+        .entry(section)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        .as_table_mut()
+    {
+        section.insert(name.to_string(), value);
+    }
+}
+
+/// Upgrade a raw config table to the current format.
+///
+/// Deprecated keys are mapped to their replacements, missing fields get their
+/// defaults and unknown keys are dropped. Returns the config and a description
+/// of every change; no changes means the file is already current.
+fn migrate_config_table(
+    mut table: toml::Table,
+) -> Result<(Config, Vec<String>), Box<dyn std::error::Error>> {
+    let mut changes = Vec::new();
+
+    for (old, new) in RENAMED_KEYS {
+        if let Some(value) = take_key(&mut table, old) {
+            if has_key(&table, new) {
+                changes.push(format!("removed {} ({} is already set)", old, new));
+            } else {
+                changes.push(format!("renamed {} to {}", old, new));
+                set_key(&mut table, new, value);
+            }
+        }
+    }
+
+    // `logging.format = "json"` became the `logging.json_format` flag
+    if let Some(format) = take_key(&mut table, "logging.format") {
+        let json = format.as_str() == Some("json");
+        if !has_key(&table, "logging.json_format") {
+            set_key(&mut table, "logging.json_format", toml::Value::Boolean(json));
+        }
+        changes.push(format!("replaced logging.format = {} with logging.json_format = {}", format, json));
+    }
+
+    // Fill in fields added since the file was written
+    let defaults = toml::Table::try_from(Config::default())?;
+    for (section, default_values) in &defaults {
+        let Some(default_values) = default_values.as_table() else {
+            continue;
+        };
+        for (name, value) in default_values {
+            let key = format!("{}.{}", section, name);
+            if !has_key(&table, &key) {
+                changes.push(format!("added {} = {}", key, value));
+                set_key(&mut table, &key, value.clone());
+            }
+        }
+    }
+
+    let previous_version = table.get("version").and_then(|v| v.as_integer()).unwrap_or(0);
+    let mut config: Config = table.clone().try_into()?;
+    config.version = CONFIG_VERSION;
+
+    // Anything the current format does not know about is dropped on save
+    let current = toml::Table::try_from(&config)?;
+    for (section, values) in &table {
+        match values.as_table() {
+            Some(values) => {
+                for name in values.keys() {
+                    let key = format!("{}.{}", section, name);
+                    if !has_key(&current, &key) {
+                        changes.push(format!("removed unknown key {}", key));
+                    }
+                }
+            }
+            None if !current.contains_key(section) => {
+                changes.push(format!("removed unknown key {}", section));
+            }
+            None => {}
+        }
+    }
+
+    if previous_version != i64::from(CONFIG_VERSION) {
+        changes.push(format!("set version = {}", CONFIG_VERSION));
+    }
+
+    Ok((config, changes))
+}
+
+async fn migrate_config(config_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let config_file_path = get_config_path(config_path)?;
+    if !config_file_path.exists() {
+        println!("No configuration file at {}; nothing to migrate", config_file_path.display());
+        return Ok(());
+    }
+
+    let table: toml::Table = toml::from_str(&fs::read_to_string(&config_file_path)?)?;
+    let (config, changes) = migrate_config_table(table)?;
+    if changes.is_empty() {
+        println!("✓ Configuration is already up to date (version {})", CONFIG_VERSION);
+        return Ok(());
+    }
+
+    let mut backup_path = config_file_path.clone().into_os_string();
+    backup_path.push(".bak");
+    let backup_path = PathBuf::from(backup_path);
+    fs::copy(&config_file_path, &backup_path)?;
+    save_config(&config, Some(config_file_path.clone()))?;
+
+    println!("✓ Migrated {} to version {}", config_file_path.display(), CONFIG_VERSION);
+    println!("  Backup: {}", backup_path.display());
+    for change in &changes {
+        println!("  - {}", change);
+    }
+
+    Ok(())
+}
+
+fn get_config_path(config_path: Option<PathBuf>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if let Some(path) = config_path {
+        return Ok(path);
+    }
+
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map_err(|_| "Could not determine home directory")?;
+
+    Ok(PathBuf::from(home).join(".static-embedding-tool").join("config.toml"))
+}
+
+pub fn load_config(config_path: Option<PathBuf>) -> Result<Config, Box<dyn std::error::Error>> {
+    let config = read_config_file(config_path)?;
+    Ok(apply_env_overrides(config, std::env::vars()))
+}
+
+/// Read the config file alone, without environment overrides, for commands
+/// that write it back.
+fn read_config_file(config_path: Option<PathBuf>) -> Result<Config, Box<dyn std::error::Error>> {
+    let config_file_path = get_config_path(config_path)?;
+
+    if !config_file_path.exists() {
+        return Ok(Config::default());
+    }
+
+    let content = fs::read_to_string(&config_file_path)?;
+    let config: Config = toml::from_str(&content).map_err(|e| {
+        format!(
+            "Failed to parse {}: {}\nIf this file was written by an older version, run 'static-embedding-tool config migrate'.",
+            config_file_path.display(),
+            e
+        )
+    })?;
+    Ok(config)
+}
+
+/// Where the effective value of a config key comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValueSource {
+    /// Built-in default
+    Default,
+    /// Set in the config file
+    File,
+    /// Overridden by the named environment variable
+    Env(String),
+}
+
+impl std::fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueSource::Default => write!(f, "default"),
+            ValueSource::File => write!(f, "file"),
+            ValueSource::Env(name) => write!(f, "env {}", name),
+        }
+    }
+}
+
+/// The config loaded from `config_path` with the environment `vars`, and the
+/// source of each of its `section.key` values.
+pub(crate) fn load_config_with_sources(
+    config_path: Option<PathBuf>,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<(Config, std::collections::HashMap<String, ValueSource>), Box<dyn std::error::Error>> {
+    let config_file_path = get_config_path(config_path.clone())?;
+    let file: toml::Table = if config_file_path.exists() {
+        toml::from_str(&fs::read_to_string(&config_file_path)?)?
+    } else {
+        toml::Table::new()
+    };
+    let (config, overridden) = overlay_env(read_config_file(config_path)?, vars);
+
+    let defaults = serde_json::to_value(Config::default())?;
+    let mut sources = std::collections::HashMap::new();
+    for (section, table) in defaults.as_object().into_iter().flatten() {
+        for key in table.as_object().into_iter().flat_map(|table| table.keys()) {
+            let in_file = file.get(section).and_then(|table| table.get(key)).is_some();
+            let source = if in_file { ValueSource::File } else { ValueSource::Default };
+            sources.insert(format!("{}.{}", section, key), source);
+        }
+    }
+    for (key, name) in overridden {
+        sources.insert(key, ValueSource::Env(name));
+    }
+    Ok((config, sources))
+}
+
+/// Append the source of each `key = value` line of a `config get` listing as
+/// a TOML comment.
+fn annotate_sources(listing: &str, sources: &std::collections::HashMap<String, ValueSource>) -> String {
+    let mut section = None;
+    let mut annotated = String::new();
+    for line in listing.lines() {
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            section = Some(name);
+        }
+        let source = section
+            .zip(line.split_once(" = "))
+            .and_then(|(section, (key, _))| sources.get(&format!("{}.{}", section, key)));
+        match source {
+            Some(source) => annotated.push_str(&format!("{}  # {}\n", line, source)),
+            None => annotated.push_str(&format!("{}\n", line)),
+        }
+    }
+    annotated
+}
+
+/// Prefix of environment variables that override config keys.
+const ENV_PREFIX: &str = "EMBED_TOOL_";
+
+/// Overlay `EMBED_TOOL_<SECTION>_<KEY>` variables from `vars` onto `config`.
+///
+/// Values are parsed as the type of the key they override; list keys take a
+/// comma-separated value. Variables whose name does not start with a config
+/// section (such as `EMBED_TOOL_TEST_MODE`) are not config overrides and are
+/// skipped silently; unknown keys and unparseable values are reported and
+/// skipped.
+fn apply_env_overrides(config: Config, vars: impl IntoIterator<Item = (String, String)>) -> Config {
+    overlay_env(config, vars).0
+}
+
+/// [`apply_env_overrides`], also returning each `section.key` that was
+/// overridden with the name of the variable that did it.
+fn overlay_env(config: Config, vars: impl IntoIterator<Item = (String, String)>) -> (Config, Vec<(String, String)>) {
+    use serde_json::Value;
+
+    let Ok(mut tree) = serde_json::to_value(&config) else {
+        return (config, Vec::new());
+    };
+    let mut overrides: Vec<(String, String)> = vars
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+        .collect();
+    // Apply in a stable order so the result does not depend on the environment's
+    overrides.sort();
+
+    let mut applied = Vec::new();
+    for (name, raw) in overrides {
+        let rest = name[ENV_PREFIX.len()..].to_ascii_lowercase();
+        let Some((section, key)) = ["server", "models", "logging", "cli"]
+            .iter()
+            .find_map(|section| Some((*section, rest.strip_prefix(section)?.strip_prefix('_')?)))
+        else {
+            continue;
+        };
+        let Some(current) = tree.get(section).and_then(|table| table.get(key)) else {
+            eprintln!("⚠️  Ignoring {}: {}.{} is not a config key", name, section, key);
+            continue;
+        };
+
+        // Candidate values in order of preference
+        let candidates: Vec<Value> = match current {
+            Value::Bool(_) => raw.trim().parse().map(Value::Bool).into_iter().collect(),
+            Value::Number(_) => serde_json::from_str(raw.trim()).ok().filter(Value::is_number).into_iter().collect(),
+            Value::String(_) => vec![Value::String(raw.clone())],
+            Value::Array(_) => vec![Value::Array(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| Value::String(item.to_string()))
+                    .collect(),
+            )],
+            // Unset optional keys: a number or flag if it reads as one, else text
+            Value::Null => serde_json::from_str(raw.trim())
+                .ok()
+                .filter(|value: &Value| value.is_number() || value.is_boolean())
+                .into_iter()
+                .chain([Value::String(raw.clone())])
+                .collect(),
+            Value::Object(_) => Vec::new(),
+        };
+
+        // Keep an override only if the whole config still deserializes with it
+        let accepted = candidates.into_iter().find_map(|value| {
+            let mut candidate = tree.clone();
+            candidate[section][key] = value;
+            serde_json::from_value::<Config>(candidate.clone()).is_ok().then_some(candidate)
+        });
+        match accepted {
+            Some(candidate) => {
+                tree = candidate;
+                applied.push((format!("{}.{}", section, key), name));
+            }
+            None => eprintln!("⚠️  Ignoring {}: '{}' is not a valid value for {}.{}", name, raw, section, key),
+        }
+    }
+
+    if applied.is_empty() {
+        return (config, applied);
+    }
+    match serde_json::from_value(tree) {
+        Ok(config) => (config, applied),
+        Err(_) => (config, Vec::new()),
+    }
+}
+
+fn save_config(
+    config: &Config,
+    config_path: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config_file_path = get_config_path(config_path)?;
+
+    // Create directory if it doesn't exist
+    if let Some(parent) = config_file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = toml::to_string_pretty(config)?;
+    fs::write(config_file_path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    // Helper to create an isolated config file path for each test
+    fn make_temp_config_path() -> (TempDir, PathBuf) {
+        let dir = TempDir::new().expect("failed to create temp dir");
+        let path = dir.path().join("test_config.toml");
+        (dir, path)
+    }
+
+    #[tokio::test]
+    async fn test_handle_embed_command_smoke() {
+        // Embeds when a model or server is available, otherwise fails naming the model
+        let args = EmbedArgs {
+            texts: vec!["Hello test".to_string()],
+            stdin: false,
+            lines: false,
+            model: None,
+            format: "json".to_string(),
+            watch: false,
+            daemon: false,
+        };
+        if let Err(e) = handle_embed_command(args, None).await {
+            assert!(e.to_string().contains("potion-32M"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_embed_command_missing_models_dir() {
+        let (_dir, config_path) = make_temp_config_path();
+        let mut config = Config::default();
+        config.models.models_dir = Some("/definitely/not/a/models/dir".to_string());
+        save_config(&config, Some(config_path.clone())).unwrap();
+
+        let args = EmbedArgs {
+            texts: vec!["Hello test".to_string()],
+            stdin: false,
+            lines: false,
+            model: None,
+            format: "csv".to_string(),
+            watch: false,
+            daemon: false,
+        };
+        let error = handle_embed_command(args, Some(config_path)).await.unwrap_err();
+        assert!(error.to_string().contains("/definitely/not/a/models/dir"));
+        assert!(error.to_string().contains("models.models_dir"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_embed_command_rejects_unknown_format() {
+        let args = EmbedArgs {
+            texts: vec!["Hello test".to_string()],
+            stdin: false,
+            lines: false,
+            model: None,
+            format: "xml".to_string(),
+            watch: false,
+            daemon: false,
+        };
+        let error = handle_embed_command(args, None).await.unwrap_err();
+        assert!(error.to_string().contains("Unsupported output format 'xml'"));
+    }
+
+    #[test]
+    fn test_format_embeddings() {
+        let embeddings = [vec![0.5, -0.25]];
+        let texts = ["Hello test".to_string()];
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_embeddings(&embeddings, &texts, "m", "json").unwrap()).unwrap();
+        assert_eq!(json["data"][0]["embedding"], serde_json::json!([0.5, -0.25]));
+        assert_eq!(json["model"], "m");
+        assert_eq!(json["usage"]["prompt_tokens"], 3);
+
+        assert_eq!(
+            format_embeddings(&embeddings, &texts, "m", "csv").unwrap(),
+            "embedding\n0.500000,-0.250000"
+        );
+        assert_eq!(format_embeddings(&embeddings, &texts, "m", "raw").unwrap(), "0.5 -0.25");
+        assert!(format_embeddings(&embeddings, &texts, "m", "xml").is_err());
+    }
+
+    #[test]
+    fn test_format_embeddings_multiple_texts() {
+        let embeddings = [vec![0.5, -0.25], vec![1.0, 0.0]];
+        let texts = ["a".to_string(), "bcdef".to_string()];
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_embeddings(&embeddings, &texts, "m", "json").unwrap()).unwrap();
+        assert_eq!(json["data"].as_array().unwrap().len(), 2);
+        assert_eq!(json["data"][1]["index"], 1);
+        assert_eq!(json["data"][1]["embedding"], serde_json::json!([1.0, 0.0]));
+        assert_eq!(json["usage"]["prompt_tokens"], 3);
+
+        assert_eq!(
+            format_embeddings(&embeddings, &texts, "m", "csv").unwrap(),
+            "embedding\n0.500000,-0.250000\n1.000000,0.000000"
+        );
+        assert_eq!(format_embeddings(&embeddings, &texts, "m", "raw").unwrap(), "0.5 -0.25\n1 0");
+    }
+
+    #[test]
+    fn test_read_embed_inputs() {
+        // Whole input as one text, without the newline `echo` adds
+        let texts = read_embed_inputs("first line\nsecond line\n".as_bytes(), false).unwrap();
+        assert_eq!(texts, vec!["first line\nsecond line"]);
+        let texts = read_embed_inputs("windows\r\n".as_bytes(), false).unwrap();
+        assert_eq!(texts, vec!["windows"]);
+
+        // One text per non-empty line
+        let texts = read_embed_inputs("a\n\n  \nb\r\nc".as_bytes(), true).unwrap();
+        assert_eq!(texts, vec!["a", "b", "c"]);
+
+        assert!(read_embed_inputs("\n\n".as_bytes(), false).unwrap().is_empty());
+        assert!(read_embed_inputs("".as_bytes(), true).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handle_batch_command_with_missing_input() {
+        // Provide a non-existent file path; function should not error (prints message)
+        let args = BatchArgs {
+            input: PathBuf::from("/definitely/does/not/exist.json"),
+            input_format: None,
+            text_column: None,
+            keep_columns: None,
+            output: None,
+            model: Some("potion-8M".to_string()),
+            format: "json".to_string(),
+            batch_size: 32,
+            watch: false,
+            daemon: false,
+            checkpoint: None,
+            input_jsonpath: None,
+            input_id_jsonpath: None,
+            output_dtype: None,
+            skip_errors: false,
+            errors_file: None,
+            keep_bom: false,
+            retries: 2,
+        };
+        let result = handle_batch_command(args, None).await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_get_config_path_default() {
+        // Using a custom path to avoid environment interaction
+        let (_dir, custom) = make_temp_config_path();
+        let result = get_config_path(Some(custom.clone())).unwrap();
+        assert_eq!(result, custom);
+    }
+
+    #[test]
+    fn test_get_config_path_custom() {
+        let custom_path = PathBuf::from("/custom/path/config.toml");
+        let result = get_config_path(Some(custom_path.clone())).unwrap();
+        assert_eq!(result, custom_path);
+    }
+
+    #[test]
+    fn test_load_config_defaults() {
+        let (_dir, custom) = make_temp_config_path();
+        // Ensure config file doesn't exist
+        assert!(!custom.exists());
+
+        let config = load_config(Some(custom)).unwrap();
+        // Check default values
+        assert_eq!(config.server.default_port, 8084);
+        assert_eq!(config.server.default_bind, "127.0.0.1");
+        assert_eq!(config.server.default_model, "potion-32M");
+        assert_eq!(config.logging.level, "info");
+    }
+
+    /// Sets an environment variable for the lifetime of the guard.
+    struct ScopedEnvVar(&'static str);
+
+    impl ScopedEnvVar {
+        fn set(name: &'static str, value: &str) -> Self {
+            unsafe { std::env::set_var(name, value) };
+            Self(name)
+        }
+    }
+
+    impl Drop for ScopedEnvVar {
+        fn drop(&mut self) {
+            unsafe { std::env::remove_var(self.0) };
+        }
+    }
+
+    fn env_vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_apply_env_overrides_parses_field_types() {
+        let config = apply_env_overrides(
+            Config::default(),
+            env_vars(&[
+                ("EMBED_TOOL_SERVER_DEFAULT_PORT", "9090"),
+                ("EMBED_TOOL_SERVER_STRICT_MODELS", "true"),
+                ("EMBED_TOOL_SERVER_CORS_ALLOWED_ORIGINS", "https://a.example, https://b.example"),
+                ("EMBED_TOOL_MODELS_MODELS_DIR", "/custom/models"),
+                ("EMBED_TOOL_LOGGING_MAX_FILES", "3"),
+                ("EMBED_TOOL_LOGGING_SAMPLE_RATE", "0.5"),
+                ("EMBED_TOOL_CLI_REQUEST_TIMEOUT_SECS", "7"),
+                ("EMBED_TOOL_SERVER_MAX_BODY_BYTES", "1048576"),
+                ("EMBED_TOOL_SERVER_REQUEST_TIMEOUT_SECS", "15"),
+                ("EMBED_TOOL_SERVER_MAX_BATCH_INPUTS", "10"),
+                ("EMBED_TOOL_SERVER_MAX_INPUT_CHARS", "2000"),
+                ("PATH", "/usr/bin"),
+            ]),
+        );
+        assert_eq!(config.server.default_port, 9090);
+        assert!(config.server.strict_models);
+        assert_eq!(config.server.cors_allowed_origins, vec!["https://a.example", "https://b.example"]);
+        assert_eq!(config.models.models_dir.as_deref(), Some("/custom/models"));
+        assert_eq!(config.logging.max_files, Some(3));
+        assert_eq!(config.logging.sample_rate, 0.5);
+        assert_eq!(config.cli.request_timeout_secs, 7);
+        assert_eq!(config.server.max_body_bytes, 1048576);
+        assert_eq!(config.server.request_timeout_secs, 15);
+        assert_eq!((config.server.max_batch_inputs, config.server.max_input_chars), (10, 2000));
+
+        // An optional text key keeps a value that happens to look like a number
+        let config = apply_env_overrides(Config::default(), env_vars(&[("EMBED_TOOL_LOGGING_FILE", "2024")]));
+        assert_eq!(config.logging.file.as_deref(), Some("2024"));
+    }
+
+    #[test]
+    fn test_apply_env_overrides_ignores_bad_variables() {
+        let config = apply_env_overrides(
+            Config::default(),
+            env_vars(&[
+                ("EMBED_TOOL_SERVER_DEFAULT_PORT", "not-a-port"),
+                ("EMBED_TOOL_SERVER_NO_SUCH_KEY", "1"),
+                ("EMBED_TOOL_LOGGING_MAX_FILES", "-1"),
+                ("EMBED_TOOL_TEST_MODE", "1"),
+                ("EMBED_TOOL_SERVER_DEFAULT_MODEL", "potion-8M"),
+            ]),
+        );
+        assert_eq!(config.server.default_port, ServerConfig::default().default_port);
+        assert_eq!(config.logging.max_files, None);
+        // Valid overrides next to invalid ones still apply
+        assert_eq!(config.server.default_model, "potion-8M");
+    }
+
+    #[test]
+    fn test_load_config_env_overrides_file() {
+        let (_dir, custom) = make_temp_config_path();
+        let mut config = Config::default();
+        config.logging.json_format = false;
+        save_config(&config, Some(custom.clone())).unwrap();
+
+        {
+            let _json = ScopedEnvVar::set("EMBED_TOOL_LOGGING_JSON_FORMAT", "true");
+            assert!(load_config(Some(custom.clone())).unwrap().logging.json_format);
+            // Overrides are not written back by `config set`
+            assert!(!read_config_file(Some(custom.clone())).unwrap().logging.json_format);
+        }
+        assert!(!load_config(Some(custom)).unwrap().logging.json_format);
+    }
+
+    #[test]
+    fn test_config_sources_env_over_file_over_default() {
+        let (dir, custom) = make_temp_config_path();
+        let mut config = Config::default();
+        config.server.default_port = 9000;
+        config.server.default_bind = "0.0.0.0".to_string();
+        save_config(&config, Some(custom.clone())).unwrap();
+        // Keys left out of the file take their defaults
+        let content = fs::read_to_string(&custom).unwrap();
+        let content: Vec<&str> = content.lines().filter(|line| !line.starts_with("strict_models")).collect();
+        fs::write(&custom, content.join("\n")).unwrap();
+
+        let (config, sources) =
+            load_config_with_sources(Some(custom.clone()), env_vars(&[("EMBED_TOOL_SERVER_DEFAULT_PORT", "9090")]))
+                .unwrap();
+        assert_eq!((config.server.default_port, config.server.default_bind.as_str()), (9090, "0.0.0.0"));
+        assert_eq!(sources["server.default_port"], ValueSource::Env("EMBED_TOOL_SERVER_DEFAULT_PORT".to_string()));
+        assert_eq!(sources["server.default_bind"], ValueSource::File);
+        assert_eq!(sources["server.default_model"], ValueSource::File);
+        assert_eq!(sources["server.strict_models"], ValueSource::Default);
+
+        // Without the variable the file wins, and without the file the default
+        let (config, sources) = load_config_with_sources(Some(custom), Vec::new()).unwrap();
+        assert_eq!((config.server.default_port, &sources["server.default_port"]), (9000, &ValueSource::File));
+        let listing = "version = 1\n\n[server]\ndefault_port = 9000\nstrict_models = false\n";
+        assert_eq!(
+            annotate_sources(listing, &sources),
+            "version = 1\n\n[server]\ndefault_port = 9000  # file\nstrict_models = false  # default\n"
+        );
+        let (config, _) = load_config_with_sources(Some(dir.path().join("missing.toml")), Vec::new()).unwrap();
+        assert_eq!(config.server.default_port, 8084);
+    }
+
+    #[test]
+    fn test_save_and_load_config() {
+        let (_dir, custom) = make_temp_config_path();
+
+        let mut config = Config::default();
+        config.server.default_port = 9090;
+        config.server.default_model = "custom-model".to_string();
+
+        save_config(&config, Some(custom.clone())).unwrap();
+        assert!(custom.exists());
+
+        let loaded = load_config(Some(custom)).unwrap();
+        assert_eq!(loaded.server.default_port, 9090);
+        assert_eq!(loaded.server.default_model, "custom-model");
+    }
+
+    #[test]
+    fn test_show_config() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            // Should not panic
+            let result = show_config(Some(custom)).await;
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    fn test_show_config_path() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            // Should not panic
+            let result = show_config_path(Some(custom)).await;
+            assert!(result.is_ok());
+        });
+    }
+
+    #[test]
+    fn test_set_config_server_values() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            // Test setting server.default_port
+            let args = SetConfigArgs {
+                key: "server.default_port".to_string(),
+                value: "9090".to_string(),
+            };
+            let result = set_config(args, Some(custom.clone())).await;
+            assert!(result.is_ok());
+
+            // Verify the change
+            let config = load_config(Some(custom)).unwrap();
+            assert_eq!(config.server.default_port, 9090);
+        });
+    }
+
+    #[test]
+    fn test_set_config_server_ready_policy() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            assert_eq!(load_config(Some(custom.clone())).unwrap().server.ready_policy, "warmed");
+
+            let args = SetConfigArgs {
+                key: "server.ready_policy".to_string(),
+                value: "loaded".to_string(),
+            };
+            assert!(set_config(args, Some(custom.clone())).await.is_ok());
+            assert_eq!(load_config(Some(custom.clone())).unwrap().server.ready_policy, "loaded");
+
+            // Invalid values are rejected without touching the file
+            let args = SetConfigArgs {
+                key: "server.ready_policy".to_string(),
+                value: "eventually".to_string(),
+            };
+            assert!(matches!(
+                set_config(args, Some(custom.clone())).await,
+                Err(ConfigError::InvalidValue { .. })
+            ));
+            assert_eq!(load_config(Some(custom)).unwrap().server.ready_policy, "loaded");
+        });
+    }
+
+    #[test]
+    fn test_set_config_server_tls_paths() {
+        let (dir, custom) = make_temp_config_path();
+        let cert = dir.path().join("cert.pem");
+        std::fs::write(&cert, "").unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let set = |key: &str, value: &str| SetConfigArgs {
+                key: key.to_string(),
+                value: value.to_string(),
+            };
+            set_config(set("server.tls_cert_path", cert.to_str().unwrap()), Some(custom.clone()))
+                .await
+                .unwrap();
+            // A file that does not exist yet is stored with a warning
+            set_config(set("server.tls_key_path", "/not/yet/key.pem"), Some(custom.clone()))
+                .await
+                .unwrap();
+            let config = load_config(Some(custom.clone())).unwrap();
+            assert_eq!(config.server.tls_cert_path.as_deref(), cert.to_str());
+            assert_eq!(config.server.tls_key_path.as_deref(), Some("/not/yet/key.pem"));
+
+            // An empty value clears the path
+            set_config(set("server.tls_key_path", ""), Some(custom.clone())).await.unwrap();
+            let config = load_config(Some(custom)).unwrap();
+            assert_eq!(config.server.tls_key_path, None);
+            assert!(config.server.tls_cert_path.is_some());
+        });
+    }
+
+    #[test]
+    fn test_set_config_server_tls_policy() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let set = |key: &str, value: &str| SetConfigArgs {
+                key: key.to_string(),
+                value: value.to_string(),
+            };
+            assert_eq!(load_config(Some(custom.clone())).unwrap().server.min_tls_version, "1.2");
+
+            set_config(set("server.min_tls_version", "1.3"), Some(custom.clone())).await.unwrap();
+            let suites = "TLS13_AES_256_GCM_SHA384, TLS13_CHACHA20_POLY1305_SHA256";
+            set_config(set("server.tls_cipher_suites", suites), Some(custom.clone())).await.unwrap();
+            let config = load_config(Some(custom.clone())).unwrap();
+            assert_eq!(config.server.min_tls_version, "1.3");
+            assert_eq!(config.server.tls_cipher_suites, vec!["TLS13_AES_256_GCM_SHA384", "TLS13_CHACHA20_POLY1305_SHA256"]);
+
+            for (key, value) in [("server.min_tls_version", "1.1"), ("server.tls_cipher_suites", "TLS_RSA_WITH_RC4_128_MD5")] {
+                let result = set_config(set(key, value), Some(custom.clone())).await;
+                assert!(matches!(result, Err(ConfigError::InvalidValue { .. })), "{}", key);
+            }
+
+            // An empty value goes back to the default suites
+            set_config(set("server.tls_cipher_suites", ""), Some(custom.clone())).await.unwrap();
+            assert!(load_config(Some(custom)).unwrap().server.tls_cipher_suites.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_parse_socket_mode() {
+        assert_eq!(parse_socket_mode("0600"), Ok(0o600));
+        assert_eq!(parse_socket_mode("660"), Ok(0o660));
+        assert_eq!(parse_socket_mode("0o700"), Ok(0o700));
+        assert!(parse_socket_mode("").is_err());
+        assert!(parse_socket_mode("0800").is_err());
+        assert!(parse_socket_mode("7777").is_err());
+    }
+
+    #[test]
+    fn test_set_config_server_socket() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let set = |key: &str, value: &str| SetConfigArgs {
+                key: key.to_string(),
+                value: value.to_string(),
+            };
+            assert_eq!(load_config(Some(custom.clone())).unwrap().server.socket_mode, "0600");
+
+            set_config(set("server.socket_path", "/tmp/embed.sock"), Some(custom.clone())).await.unwrap();
+            set_config(set("server.socket_mode", "0660"), Some(custom.clone())).await.unwrap();
+            // Invalid modes are rejected without touching the file
+            set_config(set("server.socket_mode", "rw"), Some(custom.clone())).await.unwrap_err();
+            let config = load_config(Some(custom.clone())).unwrap();
+            assert_eq!(config.server.socket_path.as_deref(), Some("/tmp/embed.sock"));
+            assert_eq!(config.server.socket_mode, "0660");
+
+            let endpoint = ServerEndpoint::from_config(&config, 9000).unwrap();
+            assert_eq!(endpoint.to_string(), "unix:/tmp/embed.sock");
+            assert_eq!(endpoint.url("/health"), "http://localhost/health");
+
+            // An empty path switches back to TCP
+            set_config(set("server.socket_path", ""), Some(custom.clone())).await.unwrap();
+            let config = load_config(Some(custom)).unwrap();
+            assert_eq!(config.server.socket_path, None);
+            let endpoint = ServerEndpoint::from_config(&config, 9000).unwrap();
+            assert_eq!(endpoint.url("/health"), "http://localhost:9000/health");
+        });
+    }
+
+    #[test]
+    fn test_set_config_server_cors() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let args = SetConfigArgs {
+                key: "server.cors_allowed_origins".to_string(),
+                value: "https://a.example, https://b.example".to_string(),
+            };
+            assert!(set_config(args, Some(custom.clone())).await.is_ok());
+            let args = SetConfigArgs {
+                key: "server.cors_reject_disallowed".to_string(),
+                value: "true".to_string(),
+            };
+            assert!(set_config(args, Some(custom.clone())).await.is_ok());
+
+            let config = load_config(Some(custom)).unwrap();
+            assert_eq!(
+                config.server.cors_allowed_origins,
+                vec!["https://a.example", "https://b.example"]
+            );
+            assert!(config.server.cors_reject_disallowed);
+        });
+    }
+
+    #[test]
+    fn test_set_config_server_output_dtype() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            assert_eq!(load_config(Some(custom.clone())).unwrap().server.output_dtype, "f32");
+
+            let args = SetConfigArgs {
+                key: "server.output_dtype".to_string(),
+                value: "f16".to_string(),
+            };
+            assert!(set_config(args, Some(custom.clone())).await.is_ok());
+            assert_eq!(load_config(Some(custom.clone())).unwrap().server.output_dtype, "f16");
+
+            let args = SetConfigArgs {
+                key: "server.output_dtype".to_string(),
+                value: "f64".to_string(),
+            };
+            assert!(set_config(args, Some(custom.clone())).await.is_err());
+            assert_eq!(load_config(Some(custom)).unwrap().server.output_dtype, "f16");
+        });
+    }
+
+    #[test]
+    fn test_set_config_cli_request_timeout() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            assert_eq!(load_config(Some(custom.clone())).unwrap().cli.request_timeout_secs, 30);
+
+            let args = SetConfigArgs {
+                key: "cli.request_timeout_secs".to_string(),
+                value: "5".to_string(),
+            };
+            assert!(set_config(args, Some(custom.clone())).await.is_ok());
+            assert_eq!(load_config(Some(custom.clone())).unwrap().cli.request_timeout_secs, 5);
+
+            let args = SetConfigArgs {
+                key: "cli.request_timeout_secs".to_string(),
+                value: "0".to_string(),
+            };
+            assert!(set_config(args, Some(custom.clone())).await.is_err());
+            assert_eq!(load_config(Some(custom)).unwrap().cli.request_timeout_secs, 5);
+        });
+    }
+
+    #[test]
+    fn test_set_config_server_request_limits() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let config = load_config(Some(custom.clone())).unwrap();
+            assert_eq!((config.server.max_body_bytes, config.server.request_timeout_secs), (4 * 1024 * 1024, 60));
+
+            assert_eq!(config.server.shutdown_timeout_secs, 30);
+
+            for (key, value) in [
+                ("server.max_body_bytes", "65536"),
+                ("server.request_timeout_secs", "0"),
+                ("server.shutdown_timeout_secs", "5"),
+                ("server.stop_grace_period_secs", "45"),
+                ("server.max_batch_inputs", "2"),
+                ("server.max_input_chars", "0"),
+            ] {
+                let args = SetConfigArgs { key: key.to_string(), value: value.to_string() };
+                assert!(set_config(args, Some(custom.clone())).await.is_ok());
+            }
+            // Rejected: a zero or malformed limit keeps the previous value
+            for (key, value) in [
+                ("server.max_body_bytes", "0"),
+                ("server.request_timeout_secs", "soon"),
+                ("server.shutdown_timeout_secs", "-1"),
+                ("server.max_batch_inputs", "many"),
+            ] {
+                let args = SetConfigArgs { key: key.to_string(), value: value.to_string() };
+                assert!(set_config(args, Some(custom.clone())).await.is_err());
+            }
+            let config = load_config(Some(custom)).unwrap();
+            assert_eq!((config.server.max_body_bytes, config.server.request_timeout_secs), (65536, 0));
+            assert_eq!(config.server.shutdown_timeout_secs, 5);
+            assert_eq!(config.server.stop_grace_period_secs, 45);
+            assert_eq!((config.server.max_batch_inputs, config.server.max_input_chars), (2, 0));
+        });
+    }
+
+    #[test]
+    fn test_config_schema_describes_sections_and_defaults() {
+        let schema = config_schema();
+        for section in ["server", "models", "logging", "cli"] {
+            assert!(schema["properties"][section]["$ref"].is_string(), "missing section {}", section);
+        }
+        let server = &schema["$defs"]["ServerConfig"]["properties"];
+        assert_eq!(server["default_port"]["type"], "integer");
+        assert_eq!(server["default_port"]["default"], 8084);
+        assert_eq!(server["ready_policy"]["default"], "warmed");
+        assert_eq!(schema["$defs"]["LoggingConfig"]["properties"]["sample_rate"]["default"], 1.0);
+        // Unset optional keys carry no default
+        assert!(schema["$defs"]["ModelConfig"]["properties"]["models_dir"].get("default").is_none());
+    }
+
+    #[test]
+    fn test_set_config_logging_sample_rate() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            assert_eq!(load_config(Some(custom.clone())).unwrap().logging.sample_rate, 1.0);
+
+            let args = SetConfigArgs {
+                key: "logging.sample_rate".to_string(),
+                value: "0.05".to_string(),
+            };
+            assert!(set_config(args, Some(custom.clone())).await.is_ok());
+            assert_eq!(load_config(Some(custom.clone())).unwrap().logging.sample_rate, 0.05);
+
+            let args = SetConfigArgs {
+                key: "logging.sample_rate".to_string(),
+                value: "1.5".to_string(),
+            };
+            assert!(set_config(args, Some(custom.clone())).await.is_err());
+            assert_eq!(load_config(Some(custom)).unwrap().logging.sample_rate, 0.05);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_http_client_times_out_on_wedged_server() {
+        // Accept connections but never answer
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let config = CliConfig { request_timeout_secs: 1 };
+        let client = http_client(&config).unwrap();
+        let err = client
+            .get(format!("http://{}/health", addr))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(err.is_timeout());
+        let message = describe_request_error(&err, &config);
+        assert!(message.contains("did not respond in time (1s)"));
+        assert!(message.contains("server status"));
+    }
+
+    #[test]
+    fn test_migrate_config_table_legacy_keys() {
+        let legacy: toml::Table = toml::from_str(
+            r#"
+[server]
+port = 9000
+host = "0.0.0.0"
+workers = 4
+
+[models]
+default = "potion-8M"
+path = "/opt/models"
+
+[logging]
+level = "debug"
+format = "json"
+"#,
+        )
+        .unwrap();
+
+        let (config, changes) = migrate_config_table(legacy).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.server.default_port, 9000);
+        assert_eq!(config.server.default_bind, "0.0.0.0");
+        assert_eq!(config.server.default_model, "potion-8M");
+        assert_eq!(config.models.models_dir.as_deref(), Some("/opt/models"));
+        assert!(config.logging.json_format);
+        assert_eq!(config.logging.level, "debug");
+        assert!(config.models.auto_download);
+
+        assert!(changes.contains(&"renamed server.port to server.default_port".to_string()));
+        assert!(changes.contains(&"removed unknown key server.workers".to_string()));
+        assert!(changes.iter().any(|c| c.starts_with("added models.auto_download")));
+        assert!(changes.contains(&format!("set version = {}", CONFIG_VERSION)));
+
+        // Migrating the result again is a no-op
+        let current = toml::Table::try_from(&config).unwrap();
+        let (_, changes) = migrate_config_table(current).unwrap();
+        assert!(changes.is_empty(), "unexpected changes: {:?}", changes);
+    }
+
+    #[test]
+    fn test_migrate_config_writes_backup() {
+        let (_dir, custom) = make_temp_config_path();
+        fs::write(&custom, "[server]\nport = 9100\n").unwrap();
+        assert!(load_config(Some(custom.clone()))
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("config migrate"));
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            assert!(handle_config_command(ConfigAction::Migrate, Some(custom.clone())).await.is_ok());
+        });
+
+        let backup = custom.with_file_name("test_config.toml.bak");
+        assert_eq!(fs::read_to_string(backup).unwrap(), "[server]\nport = 9100\n");
+        let config = load_config(Some(custom)).unwrap();
+        assert_eq!(config.server.default_port, 9100);
+        assert_eq!(config.version, CONFIG_VERSION);
+    }
+
+    #[test]
+    fn test_set_config_logging_level() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            // Test setting logging.level
+            let args = SetConfigArgs {
+                key: "logging.level".to_string(),
+                value: "debug".to_string(),
+            };
+            let result = set_config(args, Some(custom.clone())).await;
+            assert!(result.is_ok());
+
+            // Verify the change
+            let config = load_config(Some(custom)).unwrap();
+            assert_eq!(config.logging.level, "debug");
+        });
+    }
+
+    #[test]
+    fn test_handle_embed_command_executes() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let args = EmbedArgs {
+                texts: vec!["Hello test".to_string()],
+                stdin: false,
+                lines: false,
+                model: Some("potion-32M".to_string()),
+                format: "json".to_string(),
+                watch: false,
+                daemon: false,
+            };
+            // Without a local model or running server this fails instead of printing guidance
+            if let Err(e) = handle_embed_command(args, None).await {
+                assert!(e.to_string().contains("potion-32M"));
+            }
+        });
+    }
+
+    #[test]
+    fn test_handle_batch_command_missing_input() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let args = BatchArgs {
+                input: PathBuf::from("/definitely/not/exist/input.json"),
+                input_format: None,
+                text_column: None,
+                keep_columns: None,
+                output: None,
+                model: None,
+                format: "json".to_string(),
+                batch_size: 32,
+                watch: false,
+                daemon: false,
+                checkpoint: None,
+                input_jsonpath: None,
+                input_id_jsonpath: None,
+                output_dtype: None,
+                skip_errors: false,
+                errors_file: None,
+                keep_bom: false,
+                retries: 2,
+            };
+            // Should return Ok after printing error when file missing
+            let result = handle_batch_command(args, None).await;
+            assert!(result.is_ok());
+        });
+    }
+
+    #[tokio::test]
+    async fn test_handle_batch_command_with_input_file() {
+        use crate::server::mock::MockModel;
+        use crate::server::state::{AppState, Model};
+
+        let mut models: std::collections::HashMap<String, Arc<dyn Model>> = std::collections::HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 3)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+        let (addr, handle) = crate::server::test_utils::spawn_test_server_with_state(state).await;
+
+        let tmp = TempDir::new().unwrap();
+        let config_path = tmp.path().join("config.toml");
+        let mut config = Config::default();
+        config.server.default_port = addr.rsplit(':').next().unwrap().parse().unwrap();
+        save_config(&config, Some(config_path.clone())).unwrap();
+        let input_path = tmp.path().join("embed_tool_batch_test_input.json");
+        let output_path = tmp.path().join("embed_tool_batch_test_output.csv");
+        fs::write(&input_path, "[\"a\", \"b\"]").unwrap();
+
+        let args = BatchArgs {
+            input: input_path.clone(),
+            input_format: None,
+            text_column: None,
+            keep_columns: None,
+            output: Some(output_path.clone()),
+            model: Some("mock".to_string()),
+            format: "csv".to_string(),
+            batch_size: 1,
+            watch: false,
+            daemon: false,
+            checkpoint: None,
+            input_jsonpath: None,
+            input_id_jsonpath: None,
+            output_dtype: None,
+            skip_errors: false,
+            errors_file: None,
+            keep_bom: false,
+            retries: 2,
+        };
+        handle_batch_command(args, Some(config_path)).await.unwrap();
+        handle.abort();
+
+        let output = fs::read_to_string(&output_path).unwrap();
+        assert_eq!(output.lines().count(), 3, "{}", output);
+        assert!(output.lines().nth(2).unwrap().starts_with("1,"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_batch_command_streams_jsonl() {
+        use crate::server::mock::MockModel;
+        use crate::server::state::{AppState, Model};
+
+        let mut models: std::collections::HashMap<String, Arc<dyn Model>> = std::collections::HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 3)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+        let (addr, handle) = crate::server::test_utils::spawn_test_server_with_state(state).await;
+
+        let tmp = TempDir::new().unwrap();
+        let config_path = tmp.path().join("config.toml");
+        let mut config = Config::default();
+        config.server.default_port = addr.rsplit(':').next().unwrap().parse().unwrap();
+        save_config(&config, Some(config_path.clone())).unwrap();
+        let input_path = tmp.path().join("corpus.jsonl");
+        let output_path = tmp.path().join("out.jsonl");
+        fs::write(&input_path, "\"a\"\n{\"text\": \"b\", \"id\": 7}\n\"c\"\n").unwrap();
+
+        let args = BatchArgs {
+            input: input_path,
+            input_format: None,
+            text_column: None,
+            keep_columns: None,
+            output: Some(output_path.clone()),
+            model: Some("mock".to_string()),
+            format: "jsonl".to_string(),
+            batch_size: 2,
+            watch: false,
+            daemon: false,
+            checkpoint: None,
+            input_jsonpath: None,
+            input_id_jsonpath: None,
+            output_dtype: None,
+            skip_errors: false,
+            errors_file: None,
+            keep_bom: false,
+            retries: 2,
+        };
+        handle_batch_command(args, Some(config_path)).await.unwrap();
+        handle.abort();
+
+        let output = fs::read_to_string(&output_path).unwrap();
+        let records: Vec<serde_json::Value> = output.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(records.len(), 3);
+        for (i, record) in records.iter().enumerate() {
+            assert_eq!(record["index"], i);
+            assert_eq!(record["embedding"].as_array().unwrap().len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_read_csv_columns_by_name_or_index() {
+        let content = "id,body,tag\n1,\"a, b\",x\n2,c\n";
+        let rows = read_csv_columns(content, &["tag".to_string(), "0".to_string()]).unwrap();
+        assert_eq!(rows, vec![vec!["x", "1"], vec!["", "2"]]);
+
+        let error = read_csv_columns(content, &["missing".to_string()]).unwrap_err();
+        assert!(error.contains("no column 'missing'"), "{}", error);
+        assert!(read_csv_columns(content, &["3".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handle_batch_command_keeps_csv_columns() {
+        use crate::server::mock::MockModel;
+        use crate::server::state::{AppState, Model};
+
+        let mut models: std::collections::HashMap<String, Arc<dyn Model>> = std::collections::HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 2)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+        let (addr, handle) = crate::server::test_utils::spawn_test_server_with_state(state).await;
+
+        let tmp = TempDir::new().unwrap();
+        let config_path = tmp.path().join("config.toml");
+        let mut config = Config::default();
+        config.server.default_port = addr.rsplit(':').next().unwrap().parse().unwrap();
+        save_config(&config, Some(config_path.clone())).unwrap();
+        let input_path = tmp.path().join("export.csv");
+        let output_path = tmp.path().join("out.csv");
+        fs::write(&input_path, "doc_id,body,source\nd1,first text,\"web, eu\"\nd2,second text,mail\n").unwrap();
+
+        let args = BatchArgs {
+            input: input_path,
+            input_format: None,
+            text_column: Some("1".to_string()),
+            keep_columns: Some("doc_id, source".to_string()),
+            output: Some(output_path.clone()),
+            model: Some("mock".to_string()),
+            format: "csv".to_string(),
+            batch_size: 8,
+            watch: false,
+            daemon: false,
+            checkpoint: None,
+            input_jsonpath: None,
+            input_id_jsonpath: None,
+            output_dtype: None,
+            skip_errors: false,
+            errors_file: None,
+            keep_bom: false,
+            retries: 2,
+        };
+        handle_batch_command(args, Some(config_path)).await.unwrap();
+        handle.abort();
+
+        let output = fs::read_to_string(&output_path).unwrap();
+        let mut records = crate::utils::csv::CsvReader::new(output.as_bytes());
+        assert_eq!(records.next_record().unwrap().unwrap(), vec!["index", "doc_id", "source", "embedding"]);
+        let row = records.next_record().unwrap().unwrap();
+        assert_eq!(&row[..3], ["0", "d1", "web, eu"]);
+        assert_eq!(row.len(), 5);
+        assert_eq!(&records.next_record().unwrap().unwrap()[..3], ["1", "d2", "mail"]);
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_batch_fails_on_unreadable_input() {
+        let tmp = TempDir::new().unwrap();
+        let input_path = tmp.path().join("input.csv");
+        let errors_path = tmp.path().join("errors.jsonl");
+        fs::write(&input_path, "text\nhello\n").unwrap();
+        let args = |checkpoint: Option<PathBuf>| BatchArgs {
+            input: input_path.clone(),
+            input_format: None,
+            text_column: Some("nope".to_string()),
+            keep_columns: None,
+            output: Some(tmp.path().join("output.ndjson")),
+            model: None,
+            format: "ndjson".to_string(),
+            batch_size: 8,
+            watch: false,
+            daemon: false,
+            checkpoint,
+            input_jsonpath: None,
+            input_id_jsonpath: None,
+            output_dtype: None,
+            skip_errors: false,
+            errors_file: Some(errors_path.clone()),
+            keep_bom: false,
+            retries: 0,
+        };
+
+        let error = handle_batch_command(args(None), None).await.unwrap_err();
+        assert!(error.to_string().starts_with("Failed to read input 0:"), "{}", error);
+        assert!(errors_path.exists());
+
+        let error = handle_batch_command(args(Some(tmp.path().join("job.ckpt"))), None).await.unwrap_err();
+        assert!(error.to_string().contains("does not support --checkpoint"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_batch_fails_on_bad_csv_or_format() {
+        let tmp = TempDir::new().unwrap();
+        let input_path = tmp.path().join("input.csv");
+        fs::write(&input_path, "text\n\"never closed\n").unwrap();
+        let args = |format: &str, input_format: Option<&str>| BatchArgs {
+            input: input_path.clone(),
+            input_format: input_format.map(str::to_string),
+            text_column: None,
+            keep_columns: None,
+            output: Some(tmp.path().join("output")),
+            model: None,
+            format: format.to_string(),
+            batch_size: 8,
+            watch: false,
+            daemon: false,
+            checkpoint: None,
+            input_jsonpath: None,
+            input_id_jsonpath: None,
+            output_dtype: None,
+            skip_errors: false,
+            errors_file: None,
+            keep_bom: false,
+            retries: 0,
+        };
+
+        let error = handle_batch_command(args("json", None), None).await.unwrap_err();
+        assert!(error.to_string().contains("is never closed"), "{}", error);
+        let error = handle_batch_command(args("parquet", None), None).await.unwrap_err();
+        assert!(error.to_string().contains("Parquet output is not supported"), "{}", error);
+        let error = handle_batch_command(args("json", Some("xml")), None).await.unwrap_err();
+        assert!(error.to_string().contains("Unsupported input format 'xml'"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_handle_batch_command_fails_when_a_chunk_fails() {
+        // Nothing listens on this port, and the model exists nowhere
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let tmp = TempDir::new().unwrap();
+        let config_path = tmp.path().join("config.toml");
+        let mut config = Config::default();
+        config.server.default_port = port;
+        save_config(&config, Some(config_path.clone())).unwrap();
+        let input_path = tmp.path().join("input.json");
+        let checkpoint_path = tmp.path().join("job.ckpt");
+        fs::write(&input_path, "[\"a\"]").unwrap();
+
+        let args = BatchArgs {
+            input: input_path,
+            input_format: None,
+            text_column: None,
+            keep_columns: None,
+            output: Some(tmp.path().join("output.json")),
+            model: Some("no-such-model-for-batch-tests".to_string()),
+            format: "json".to_string(),
+            batch_size: 8,
+            watch: false,
+            daemon: false,
+            checkpoint: Some(checkpoint_path.clone()),
+            input_jsonpath: None,
+            input_id_jsonpath: None,
+            output_dtype: None,
+            skip_errors: false,
+            errors_file: None,
+            keep_bom: false,
+            retries: 0,
+        };
+        let error = handle_batch_command(args, Some(config_path)).await.unwrap_err();
+        assert!(error.to_string().contains("Inputs 0..1 could not be embedded after 0 retries"), "{}", error);
+        assert!(checkpoint_path.exists(), "progress should be saved for a re-run");
+    }
+
+    #[tokio::test]
+    async fn test_batch_embedder_retries_server_errors() {
+        use axum::{Json, Router, http::StatusCode, routing::{get, post}};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Fails twice with 503, then answers
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let app = Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route(
+                "/v1/embeddings",
+                post(move || {
+                    let counter = Arc::clone(&counter);
+                    async move {
+                        if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                            return Err((StatusCode::SERVICE_UNAVAILABLE, "busy"));
+                        }
+                        Ok(Json(serde_json::json!({ "data": [{ "embedding": [0.5, 0.5] }] })))
+                    }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let config = Config::default();
+        let endpoint = ServerEndpoint::new(&config.cli, port, None).unwrap();
+        let mut embedder = BatchEmbedder::new(&endpoint, "mock", &config, false).with_retries(2);
+        let results = embedder
+            .embed_chunk(&["a".to_string()], 0, &mut Vec::new(), "1/1")
+            .await
+            .unwrap();
+        server.abort();
+
+        assert_eq!(results, vec![Some(vec![0.5, 0.5])]);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(!embedder.use_local);
+    }
+
+    #[tokio::test]
+    async fn test_batch_embedder_loads_local_model_from_models_dir() {
+        let models_dir = TempDir::new().unwrap();
+        crate::server::test_utils::write_test_model(&models_dir.path().join("tiny"), 4);
+        let mut config = Config::default();
+        config.models.models_dir = Some(models_dir.path().to_string_lossy().into_owned());
+
+        let endpoint = ServerEndpoint::new(&config.cli, 1, None).unwrap();
+        let mut embedder = BatchEmbedder::new(&endpoint, "tiny", &config, false);
+        embedder.server_checked = true;
+        embedder.use_local = true;
+        let results = embedder
+            .embed_chunk(&["hello world".to_string()], 0, &mut Vec::new(), "1/1")
+            .await
+            .unwrap();
+        assert_eq!(results[0].as_ref().map(Vec::len), Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_batch_embedder_rejects_embedding_count_mismatch() {
+        use axum::{Json, Router, http::StatusCode, routing::{get, post}};
+
+        // Rejects the chunk, then answers each single input with no embedding
+        let app = Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route(
+                "/v1/embeddings",
+                post(|Json(body): Json<serde_json::Value>| async move {
+                    if body["input"].as_array().is_some_and(|input| input.len() > 1) {
+                        return Err((StatusCode::BAD_REQUEST, "one at a time"));
+                    }
+                    Ok(Json(serde_json::json!({ "data": [] })))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let config = Config::default();
+        let endpoint = ServerEndpoint::new(&config.cli, port, None).unwrap();
+        let mut embedder = BatchEmbedder::new(&endpoint, "mock", &config, true).with_retries(0);
+        let mut failures = Vec::new();
+        let results = embedder
+            .embed_chunk(&["a".to_string(), "b".to_string()], 4, &mut failures, "2/2")
+            .await
+            .unwrap();
+        server.abort();
+
+        assert_eq!(results, vec![None, None]);
+        assert_eq!(failures.iter().map(|f| f.index).collect::<Vec<_>>(), vec![4, 5]);
+        assert_eq!(failures[0].error, "server returned 0 embeddings for 1 texts");
+    }
+
+    #[test]
+    fn test_batch_checkpoint_round_trip_and_mismatch() {
+        let tmp = TempDir::new().unwrap();
+        let checkpoint_path = tmp.path().join("job.ckpt");
+        let input = tmp.path().join("input.json");
+
+        assert!(BatchCheckpoint::load(&checkpoint_path, &input, "potion-8M", 3).unwrap().is_none());
+
+        let embeddings = vec![vec![0.1, 0.2], vec![0.3, 0.4]];
+        BatchCheckpoint::save(&checkpoint_path, &input, "potion-8M", &embeddings).unwrap();
+        let loaded = BatchCheckpoint::load(&checkpoint_path, &input, "potion-8M", 3)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.completed, 2);
+        assert_eq!(loaded.embeddings.as_ref(), embeddings.as_slice());
+
+        // A different model, or an input that shrank, must not be resumed
+        assert!(BatchCheckpoint::load(&checkpoint_path, &input, "potion-32M", 3).is_err());
+        assert!(BatchCheckpoint::load(&checkpoint_path, &input, "potion-8M", 1).is_err());
+    }
+
+    #[test]
+    fn test_handle_batch_command_resumes_completed_checkpoint() {
+        let tmp = TempDir::new().unwrap();
+        let input_path = tmp.path().join("input.json");
+        let output_path = tmp.path().join("output.json");
+        let checkpoint_path = tmp.path().join("job.ckpt");
+        fs::write(&input_path, "[\"a\", \"b\"]").unwrap();
+        BatchCheckpoint::save(
+            &checkpoint_path,
+            &input_path,
+            "potion-8M",
+            &[vec![1.0, 0.0], vec![0.0, 1.0]],
+        )
+        .unwrap();
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let args = BatchArgs {
+                input: input_path.clone(),
+                input_format: None,
+                text_column: None,
+                keep_columns: None,
+                output: Some(output_path.clone()),
+                model: Some("potion-8M".to_string()),
+                format: "json".to_string(),
+                batch_size: 1,
+                watch: false,
+                daemon: false,
+                checkpoint: Some(checkpoint_path.clone()),
+                input_jsonpath: None,
+                input_id_jsonpath: None,
+                output_dtype: None,
+                skip_errors: false,
+                errors_file: None,
+                keep_bom: false,
+                retries: 2,
+            };
+            // Every input is already in the checkpoint, so no model is needed
+            let result = handle_batch_command(args, None).await;
+            assert!(result.is_ok());
+        });
+
+        let output: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+        assert_eq!(output["input_count"], 2);
+        assert_eq!(output["embeddings"][1][1], 1.0);
+        assert!(!checkpoint_path.exists(), "checkpoint should be removed on completion");
     }
 
-    let content = toml::to_string_pretty(config)?;
-    fs::write(config_file_path, content)?;
-    Ok(())
-}
+    #[test]
+    fn test_handle_batch_command_writes_f16_npy() {
+        let tmp = TempDir::new().unwrap();
+        let input_path = tmp.path().join("input.json");
+        let output_path = tmp.path().join("output.npy");
+        let checkpoint_path = tmp.path().join("job.ckpt");
+        fs::write(&input_path, "[\"a\", \"b\"]").unwrap();
+        BatchCheckpoint::save(
+            &checkpoint_path,
+            &input_path,
+            "potion-8M",
+            &[vec![0.1, 0.2, 0.3], vec![0.4, 0.5, 0.6]],
+        )
+        .unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::TempDir;
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let args = BatchArgs {
+                input: input_path.clone(),
+                input_format: None,
+                text_column: None,
+                keep_columns: None,
+                output: Some(output_path.clone()),
+                model: Some("potion-8M".to_string()),
+                format: "npy".to_string(),
+                batch_size: 1,
+                watch: false,
+                daemon: false,
+                checkpoint: Some(checkpoint_path.clone()),
+                input_jsonpath: None,
+                input_id_jsonpath: None,
+                output_dtype: Some("f16".to_string()),
+                skip_errors: false,
+                errors_file: None,
+                keep_bom: false,
+                retries: 2,
+            };
+            assert!(handle_batch_command(args, None).await.is_ok());
+        });
 
-    // Helper to create an isolated config file path for each test
-    fn make_temp_config_path() -> (TempDir, PathBuf) {
-        let dir = TempDir::new().expect("failed to create temp dir");
-        let path = dir.path().join("test_config.toml");
-        (dir, path)
-    }
+        let bytes = fs::read(&output_path).unwrap();
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        let header = String::from_utf8_lossy(&bytes[10..10 + header_len]);
+        assert!(header.contains("'descr': '<f2'"));
+        assert!(header.contains("'shape': (2, 3)"));
+        assert_eq!(bytes.len() - 10 - header_len, 2 * 3 * 2);
 
-    #[tokio::test]
-    async fn test_handle_embed_command_smoke() {
-        // Exercise handle_embed_command printing path with defaults
-        let args = EmbedArgs {
-            text: "Hello test".to_string(),
-            model: None,
-            format: "json".to_string(),
-            watch: false,
-            daemon: false,
-        };
-        let result = handle_embed_command(args, None).await;
-        assert!(result.is_ok());
+        // Rows line up with the inputs through the adjacent index
+        let index: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(tmp.path().join("output.texts.json")).unwrap()).unwrap();
+        assert_eq!(index, serde_json::json!([{"row": 0, "text": "a"}, {"row": 1, "text": "b"}]));
     }
 
     #[tokio::test]
-    async fn test_handle_batch_command_with_missing_input() {
-        // Provide a non-existent file path; function should not error (prints message)
+    async fn test_handle_batch_command_empty_input_npy_keeps_dimensions() {
+        use crate::server::mock::MockModel;
+        use crate::server::state::{AppState, Model};
+
+        let mut models: std::collections::HashMap<String, std::sync::Arc<dyn Model>> = std::collections::HashMap::new();
+        models.insert("mock".to_string(), std::sync::Arc::new(MockModel::new("mock".to_string(), 5)));
+        let state = std::sync::Arc::new(AppState::from_models(models, "mock"));
+        let (addr, handle) = crate::server::test_utils::spawn_test_server_with_state(state).await;
+
+        let tmp = TempDir::new().unwrap();
+        let config_path = tmp.path().join("config.toml");
+        let mut config = Config::default();
+        config.server.default_port = addr.rsplit(':').next().unwrap().parse().unwrap();
+        save_config(&config, Some(config_path.clone())).unwrap();
+        let input_path = tmp.path().join("input.json");
+        let output_path = tmp.path().join("empty.npy");
+        fs::write(&input_path, "[]").unwrap();
+
         let args = BatchArgs {
-            input: PathBuf::from("/definitely/does/not/exist.json"),
-            output: None,
-            model: Some("potion-8M".to_string()),
-            format: "json".to_string(),
-            batch_size: 32,
+            input: input_path,
+            input_format: None,
+            text_column: None,
+            keep_columns: None,
+            output: Some(output_path.clone()),
+            model: Some("mock".to_string()),
+            format: "npy".to_string(),
+            batch_size: 8,
             watch: false,
             daemon: false,
+            checkpoint: None,
+            input_jsonpath: None,
+            input_id_jsonpath: None,
+            output_dtype: None,
+            skip_errors: false,
+            errors_file: None,
+            keep_bom: false,
+            retries: 2,
         };
-        let result = handle_batch_command(args, None).await;
-        assert!(result.is_ok());
+        handle_batch_command(args, Some(config_path)).await.unwrap();
+        handle.abort();
+
+        let bytes = fs::read(&output_path).unwrap();
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        let header = String::from_utf8_lossy(&bytes[10..10 + header_len]);
+        assert!(header.contains("'shape': (0, 5)"), "{}", header);
+        assert_eq!(bytes.len(), 10 + header_len);
+        assert_eq!(fs::read_to_string(tmp.path().join("empty.texts.json")).unwrap(), "[]");
     }
 
     #[test]
-    fn test_get_config_path_default() {
-        // Using a custom path to avoid environment interaction
-        let (_dir, custom) = make_temp_config_path();
-        let result = get_config_path(Some(custom.clone())).unwrap();
-        assert_eq!(result, custom);
+    fn test_extract_jsonpath_strings() {
+        let document = serde_json::json!({
+            "records": [
+                {"id": 7, "body": {"text": "first"}},
+                {"id": "b", "body": {"text": "second"}}
+            ]
+        });
+
+        let texts = extract_jsonpath_strings(&document, "$.records[*].body.text", false).unwrap();
+        assert_eq!(texts, vec!["first", "second"]);
+        let ids = extract_jsonpath_strings(&document, "$.records[*].id", true).unwrap();
+        assert_eq!(ids, vec!["7", "b"]);
+
+        // Numbers are not embeddable text
+        let err = extract_jsonpath_strings(&document, "$.records[*].id", false).unwrap_err();
+        assert!(err.to_string().contains("non-string value at position 0"));
+        // Invalid and empty paths
+        assert!(extract_jsonpath_strings(&document, "$.records[", false)
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid JSONPath"));
+        assert!(extract_jsonpath_strings(&document, "$.missing[*]", false)
+            .unwrap_err()
+            .to_string()
+            .contains("matched no values"));
     }
 
     #[test]
-    fn test_get_config_path_custom() {
-        let custom_path = PathBuf::from("/custom/path/config.toml");
-        let result = get_config_path(Some(custom_path.clone())).unwrap();
-        assert_eq!(result, custom_path);
+    fn test_validate_batch_input_and_excerpt() {
+        assert!(validate_batch_input("hello").is_ok());
+        assert!(validate_batch_input("   ").is_err());
+        // Long inputs are for the server or model to judge
+        assert!(validate_batch_input(&"a".repeat(20_000)).is_ok());
+
+        let failure = BatchFailure::new(3, &"é".repeat(100), "too long");
+        assert_eq!(failure.input_excerpt.chars().count(), BatchFailure::EXCERPT_CHARS + 1);
+        assert!(failure.input_excerpt.ends_with('…'));
+        assert_eq!(BatchFailure::new(0, "short", "x").input_excerpt, "short");
     }
 
     #[test]
-    fn test_load_config_defaults() {
-        let (_dir, custom) = make_temp_config_path();
-        // Ensure config file doesn't exist
-        assert!(!custom.exists());
+    fn test_batch_input_reader_streams_json_array() {
+        let read = |input: &str| -> Vec<Result<String, String>> {
+            BatchInputReader::json_array(std::io::Cursor::new(input.as_bytes().to_vec()))
+                .map(|item| item.map_err(|e| e.to_string()))
+                .collect()
+        };
 
-        let config = load_config(Some(custom)).unwrap();
-        // Check default values
-        assert_eq!(config.server.default_port, 8084);
-        assert_eq!(config.server.default_bind, "127.0.0.1");
-        assert_eq!(config.server.default_model, "potion-32M");
-        assert_eq!(config.logging.level, "info");
+        assert_eq!(
+            read(" [\"a\",\n \"b, \\\"c\\\"\" , \"\\u00e9\"] "),
+            vec![Ok("a".to_string()), Ok("b, \"c\"".to_string()), Ok("é".to_string())]
+        );
+        assert!(read("[]").is_empty());
+        assert!(read("  [ ]").is_empty());
+
+        // Malformed input yields one error and then stops
+        let items = read("[\"a\", 3, \"b\"]");
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0], Ok("a".to_string()));
+        assert!(items[1].as_ref().unwrap_err().contains("JSON array of strings"));
+        assert_eq!(read("{\"a\": 1}").len(), 1);
+        assert!(read("[\"a\"").last().unwrap().is_err());
+
+        let lines: Vec<String> = BatchInputReader::lines(std::io::Cursor::new(b"one\ntwo\n".to_vec()))
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(lines, vec!["one", "two"]);
     }
 
     #[test]
-    fn test_save_and_load_config() {
-        let (_dir, custom) = make_temp_config_path();
+    fn test_batch_input_reader_json_lines() {
+        let input = "\"plain\"\n{\"id\": 1, \"text\": \"from object\"}\n\n";
+        let texts: Vec<String> = BatchInputReader::json_lines(input.as_bytes())
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(texts, vec!["plain", "from object"]);
 
-        let mut config = Config::default();
-        config.server.default_port = 9090;
-        config.server.default_model = "custom-model".to_string();
-
-        save_config(&config, Some(custom.clone())).unwrap();
-        assert!(custom.exists());
+        let error = BatchInputReader::json_lines("{\"body\": \"x\"}".as_bytes()).next().unwrap().unwrap_err();
+        assert!(error.to_string().contains("'text'"));
+        assert!(parse_jsonl_input("not json").unwrap_err().starts_with("Invalid JSONL line"));
+        assert!(parse_jsonl_input("42").is_err());
 
-        let loaded = load_config(Some(custom)).unwrap();
-        assert_eq!(loaded.server.default_port, 9090);
-        assert_eq!(loaded.server.default_model, "custom-model");
+        assert!(matches!(
+            BatchInputReader::new(BatchInputFormat::JsonLines, "".as_bytes(), None),
+            BatchInputReader::JsonLines(_)
+        ));
+        assert!(matches!(
+            BatchInputReader::new(BatchInputFormat::Text, "".as_bytes(), None),
+            BatchInputReader::Lines(_)
+        ));
     }
 
     #[test]
-    fn test_show_config() {
-        let (_dir, custom) = make_temp_config_path();
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            // Should not panic
-            let result = show_config(Some(custom)).await;
-            assert!(result.is_ok());
-        });
+    fn test_batch_input_format_resolve() {
+        let resolve = |format, path| BatchInputFormat::resolve(format, Path::new(path));
+        assert_eq!(resolve(None, "in.json"), Ok(BatchInputFormat::Json));
+        assert_eq!(resolve(None, "in.NDJSON"), Ok(BatchInputFormat::JsonLines));
+        assert_eq!(resolve(None, "in.csv"), Ok(BatchInputFormat::Csv));
+        assert_eq!(resolve(None, "in.txt"), Ok(BatchInputFormat::Text));
+        assert_eq!(resolve(None, "inputs"), Ok(BatchInputFormat::Text));
+        // An explicit format wins over the extension
+        assert_eq!(resolve(Some("csv"), "export.dat"), Ok(BatchInputFormat::Csv));
+        assert_eq!(resolve(Some("text"), "in.json"), Ok(BatchInputFormat::Text));
+
+        assert!(resolve(Some("xml"), "in.json").unwrap_err().contains("'xml'"));
+        assert!(resolve(None, "in.parquet").unwrap_err().contains("Parquet"));
     }
 
     #[test]
-    fn test_show_config_path() {
-        let (_dir, custom) = make_temp_config_path();
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            // Should not panic
-            let result = show_config_path(Some(custom)).await;
-            assert!(result.is_ok());
-        });
+    fn test_batch_input_reader_csv() {
+        let input = "id,text,lang\n1,\"Hello, world\",en\n2,\"multi\nline\",en\n";
+        let texts: Vec<String> = BatchInputReader::new(BatchInputFormat::Csv, input.as_bytes(), None)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(texts, vec!["Hello, world", "multi\nline"]);
+
+        let ids: Vec<String> = BatchInputReader::new(BatchInputFormat::Csv, input.as_bytes(), Some("id"))
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(ids, vec!["1", "2"]);
+
+        // A single column needs no name
+        let texts: Vec<String> = BatchInputReader::new(BatchInputFormat::Csv, "body\na\nb\n".as_bytes(), None)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(texts, vec!["a", "b"]);
     }
 
     #[test]
-    fn test_set_config_server_values() {
-        let (_dir, custom) = make_temp_config_path();
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            // Test setting server.default_port
-            let args = SetConfigArgs {
-                key: "server.default_port".to_string(),
-                value: "9090".to_string(),
-            };
-            let result = set_config(args, Some(custom.clone())).await;
-            assert!(result.is_ok());
+    fn test_batch_input_reader_csv_errors_name_rows() {
+        let error = BatchInputReader::new(BatchInputFormat::Csv, "id,body\n1,a\n".as_bytes(), None)
+            .next()
+            .unwrap()
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("no 'text' column") && error.contains("id, body"), "{}", error);
 
-            // Verify the change
-            let config = load_config(Some(custom)).unwrap();
-            assert_eq!(config.server.default_port, 9090);
-        });
+        let error = BatchInputReader::new(BatchInputFormat::Csv, "text\na\n".as_bytes(), Some("body"))
+            .next()
+            .unwrap()
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("no column 'body'"), "{}", error);
+
+        let input = "id,text\n1,a\n2\n3,\"bad\"quote\n";
+        let results: Vec<_> = BatchInputReader::new(BatchInputFormat::Csv, input.as_bytes(), None).collect();
+        assert_eq!(results[0].as_ref().unwrap(), "a");
+        let error = results[1].as_ref().unwrap_err().to_string();
+        assert!(error.contains("CSV row 3 has 1 field(s)"), "{}", error);
+        let error = results[2].as_ref().unwrap_err().to_string();
+        assert!(error.contains("row 4"), "{}", error);
     }
 
     #[test]
-    fn test_set_config_logging_level() {
-        let (_dir, custom) = make_temp_config_path();
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            // Test setting logging.level
-            let args = SetConfigArgs {
-                key: "logging.level".to_string(),
-                value: "debug".to_string(),
-            };
-            let result = set_config(args, Some(custom.clone())).await;
-            assert!(result.is_ok());
+    fn test_batch_input_reader_bom_prefixed_jsonl() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("windows.jsonl");
+        fs::write(&path, b"\xef\xbb\xbf\"first\"\r\n{\"text\": \"second\"}\r\n").unwrap();
 
-            // Verify the change
-            let config = load_config(Some(custom)).unwrap();
-            assert_eq!(config.logging.level, "debug");
-        });
+        let mut reader = std::io::BufReader::new(fs::File::open(&path).unwrap());
+        skip_bom(&mut reader).unwrap();
+        let texts: Vec<String> = BatchInputReader::new(BatchInputFormat::JsonLines, reader, None)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(texts, vec!["first", "second"]);
+
+        // With --keep-bom the mark stays, so the first line is no longer valid JSON
+        let reader = std::io::BufReader::new(fs::File::open(&path).unwrap());
+        let mut inputs = BatchInputReader::new(BatchInputFormat::JsonLines, reader, None);
+        assert!(inputs.next().unwrap().is_err());
+
+        // Plain text lines keep the BOM only when asked to
+        let mut reader = "\u{feff}one\r\ntwo".as_bytes();
+        skip_bom(&mut reader).unwrap();
+        let lines: Vec<String> = BatchInputReader::lines(reader).map(Result::unwrap).collect();
+        assert_eq!(lines, vec!["one", "two"]);
     }
 
     #[test]
-    fn test_handle_embed_command_executes() {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            let args = EmbedArgs {
-                text: "Hello test".to_string(),
-                model: Some("potion-32M".to_string()),
-                format: "json".to_string(),
-                watch: false,
-                daemon: false,
-            };
-            // Should print guidance and return Ok
-            let result = handle_embed_command(args, None).await;
-            assert!(result.is_ok());
-        });
+    fn test_write_ndjson_record() {
+        let mut out = Vec::new();
+        write_ndjson_record(&mut out, 7, "abc", &[0.5, -1.0]).unwrap();
+        write_ndjson_record(&mut out, 8, "abc", &[]).unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(record["index"], 7);
+        assert_eq!(
+            record["text_hash"],
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(record["embedding"], serde_json::json!([0.5, -1.0]));
+    }
+
+    fn skip_errors_args(input: &Path, output: &Path, checkpoint: &Path, errors: &Path) -> BatchArgs {
+        BatchArgs {
+            input: input.to_path_buf(),
+            input_format: None,
+            text_column: None,
+            keep_columns: None,
+            output: Some(output.to_path_buf()),
+            model: Some("potion-8M".to_string()),
+            format: "json".to_string(),
+            batch_size: 8,
+            watch: false,
+            daemon: false,
+            checkpoint: Some(checkpoint.to_path_buf()),
+            input_jsonpath: None,
+            input_id_jsonpath: None,
+            output_dtype: None,
+            skip_errors: true,
+            errors_file: Some(errors.to_path_buf()),
+            keep_bom: false,
+            retries: 2,
+        }
     }
 
     #[test]
-    fn test_handle_batch_command_missing_input() {
+    fn test_handle_batch_command_skip_errors_writes_errors_file() {
+        let tmp = TempDir::new().unwrap();
+        let input_path = tmp.path().join("input.json");
+        let output_path = tmp.path().join("output.json");
+        let checkpoint_path = tmp.path().join("job.ckpt");
+        let errors_path = tmp.path().join("errors.json");
+        fs::write(&input_path, "[\"a\", \"b\", \"  \"]").unwrap();
+        BatchCheckpoint::save(&checkpoint_path, &input_path, "potion-8M", &[vec![1.0], vec![2.0]]).unwrap();
+
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            let args = BatchArgs {
-                input: PathBuf::from("/definitely/not/exist/input.json"),
-                output: None,
-                model: None,
-                format: "json".to_string(),
-                batch_size: 32,
-                watch: false,
-                daemon: false,
-            };
-            // Should return Ok after printing error when file missing
-            let result = handle_batch_command(args, None).await;
-            assert!(result.is_ok());
+            // The only remaining input is invalid, so it is skipped without a model
+            let args = skip_errors_args(&input_path, &output_path, &checkpoint_path, &errors_path);
+            assert!(handle_batch_command(args, None).await.is_ok());
         });
+
+        let errors: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&errors_path).unwrap()).unwrap();
+        assert_eq!(errors.as_array().unwrap().len(), 1);
+        assert_eq!(errors[0]["index"], 2);
+        assert_eq!(errors[0]["input_excerpt"], "  ");
+        assert_eq!(errors[0]["error"], "input is empty");
+
+        // The skipped input keeps its position in the output
+        let output: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output_path).unwrap()).unwrap();
+        assert_eq!(output["embeddings"].as_array().unwrap().len(), 3);
+        assert_eq!(output["embeddings"][2], serde_json::json!([]));
     }
 
     #[test]
-    fn test_handle_batch_command_with_input_file() {
+    fn test_handle_batch_command_errors_file_empty_without_errors() {
         let tmp = TempDir::new().unwrap();
-        let input_path = tmp.path().join("embed_tool_batch_test_input.json");
+        let input_path = tmp.path().join("input.json");
+        let output_path = tmp.path().join("output.json");
+        let checkpoint_path = tmp.path().join("job.ckpt");
+        let errors_path = tmp.path().join("errors.json");
         fs::write(&input_path, "[\"a\", \"b\"]").unwrap();
+        BatchCheckpoint::save(&checkpoint_path, &input_path, "potion-8M", &[vec![1.0], vec![2.0]]).unwrap();
 
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            let args = BatchArgs {
-                input: input_path.clone(),
-                output: Some(tmp.path().join("embed_tool_batch_test_output.json")),
-                model: Some("potion-32M".to_string()),
-                format: "csv".to_string(),
-                batch_size: 10,
-                watch: false,
-                daemon: false,
-            };
-            let result = handle_batch_command(args, None).await;
-            assert!(result.is_ok());
+            let args = skip_errors_args(&input_path, &output_path, &checkpoint_path, &errors_path);
+            assert!(handle_batch_command(args, None).await.is_ok());
         });
+
+        assert_eq!(fs::read_to_string(&errors_path).unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_batch_output_json_includes_ids() {
+        let ids = vec!["a".to_string()];
+        let output = batch_output_json("potion-8M", &[vec![0.5, 0.5]], 1, Some(&ids));
+        assert_eq!(output["ids"], serde_json::json!(["a"]));
+        assert_eq!(output["dimensions"], 2);
+        assert!(batch_output_json("potion-8M", &[], 0, None).get("ids").is_none());
     }
 
     #[test]
@@ -844,9 +4303,20 @@ mod tests {
                 key: "unknown.key".to_string(),
                 value: "value".to_string(),
             };
-            let result = set_config(args, Some(custom)).await;
-            // set_config returns Ok(()) for unknown keys (just prints error)
-            assert!(result.is_ok());
+            let result = set_config(args, Some(custom.clone())).await;
+            match result {
+                Err(ConfigError::UnknownKey(key)) => assert_eq!(key, "unknown.key"),
+                other => panic!("expected an unknown key error, got {:?}", other),
+            }
+            assert!(!custom.exists());
+
+            // The CLI surfaces it as a failure
+            let action = ConfigAction::Set(SetConfigArgs {
+                key: "server.no_such_key".to_string(),
+                value: "1".to_string(),
+            });
+            let error = handle_config_command(action, Some(custom)).await.unwrap_err();
+            assert!(error.to_string().contains("server.default_port"));
         });
     }
 
@@ -993,7 +4463,7 @@ json_format = false
             assert!(result.is_ok());
 
             let config = load_config(Some(custom)).unwrap();
-            assert_eq!(config.models.auto_download, false);
+            assert!(!config.models.auto_download);
         });
     }
 
@@ -1046,7 +4516,7 @@ json_format = false
             assert!(result.is_ok());
 
             let config = load_config(Some(custom)).unwrap();
-            assert_eq!(config.logging.json_format, true);
+            assert!(config.logging.json_format);
         });
     }
 
@@ -1059,9 +4529,115 @@ json_format = false
                 key: "logging.level".to_string(),
                 value: "invalid".to_string(),
             };
-            // Should not panic, just print error
-            let result = set_config(args, Some(custom)).await;
-            assert!(result.is_ok());
+            match set_config(args, Some(custom)).await {
+                Err(ConfigError::InvalidValue { key, expected }) => {
+                    assert_eq!(key, "logging.level");
+                    assert!(expected.contains("debug"));
+                }
+                other => panic!("expected an invalid value error, got {:?}", other),
+            }
+        });
+    }
+
+    #[test]
+    fn test_config_value_text_scalars_and_sections() {
+        let mut config = Config::default();
+        config.server.cors_allowed_origins = vec!["https://a.example".to_string(), "https://b.example".to_string()];
+
+        assert_eq!(config_value_text(&config, "server.default_port").unwrap(), "8084\n");
+        assert_eq!(config_value_text(&config, "server.default_bind").unwrap(), "127.0.0.1\n");
+        assert_eq!(config_value_text(&config, "models.auto_download").unwrap(), "true\n");
+        assert_eq!(
+            config_value_text(&config, "server.cors_allowed_origins").unwrap(),
+            "https://a.example,https://b.example\n"
+        );
+        // An unset optional setting prints nothing
+        assert_eq!(config_value_text(&config, "models.models_dir").unwrap(), "");
+
+        let section = config_value_text(&config, "server").unwrap();
+        let table: toml::Table = toml::from_str(&section).unwrap();
+        assert_eq!(table["default_port"].as_integer(), Some(8084));
+        assert!(!table.contains_key("socket_path"));
+
+        for key in ["server.no_such_key", "nope", "server.default_port.extra", ""] {
+            assert!(matches!(config_value_text(&config, key), Err(ConfigError::UnknownKey(_))), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_unset_config_reverts_one_key() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            for (key, value) in [("server.default_port", "9090"), ("server.default_model", "custom"), ("models.models_dir", "/m")] {
+                let args = SetConfigArgs { key: key.to_string(), value: value.to_string() };
+                set_config(args, Some(custom.clone())).await.unwrap();
+            }
+
+            for key in ["server.default_port", "models.models_dir"] {
+                unset_config(UnsetConfigArgs { key: key.to_string() }, Some(custom.clone())).await.unwrap();
+            }
+            let config = load_config(Some(custom.clone())).unwrap();
+            assert_eq!(config.server.default_port, 8084);
+            assert_eq!(config.models.models_dir, None);
+            // Other keys keep their values
+            assert_eq!(config.server.default_model, "custom");
+
+            let args = UnsetConfigArgs { key: "server.no_such_key".to_string() };
+            assert!(matches!(unset_config(args, Some(custom)).await, Err(ConfigError::UnknownKey(_))));
+        });
+    }
+
+    #[test]
+    fn test_set_config_keeps_unparseable_file() {
+        let (_dir, custom) = make_temp_config_path();
+        let content = "[server]\ndefault_port = 9999\nmax_batch_inputs = \"lots\"\n";
+        fs::write(&custom, content).unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let args = SetConfigArgs { key: "server.encode_retries".to_string(), value: "2".to_string() };
+            assert!(matches!(set_config(args, Some(custom.clone())).await, Err(ConfigError::IoError(_))));
+        });
+        assert_eq!(fs::read_to_string(&custom).unwrap(), content);
+    }
+
+    #[test]
+    fn test_unset_config_keeps_unparseable_file() {
+        let (_dir, custom) = make_temp_config_path();
+        let content = "[server]\ndefault_port = 9999\nmax_batch_inputs = \"lots\"\n";
+        fs::write(&custom, content).unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let args = UnsetConfigArgs { key: "server.encode_retries".to_string() };
+            assert!(matches!(unset_config(args, Some(custom.clone())).await, Err(ConfigError::IoError(_))));
+        });
+        assert_eq!(fs::read_to_string(&custom).unwrap(), content);
+    }
+
+    #[test]
+    fn test_set_config_rejects_nonsense_values() {
+        let (_dir, custom) = make_temp_config_path();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            for (key, value) in [
+                ("server.default_port", "0"),
+                ("server.default_port", "70000"),
+                ("server.default_bind", "localhost"),
+                ("server.default_bind", "127.0.0.1:8084"),
+                ("server.enable_tls", "yes"),
+                ("models.default_distill_dims", "0"),
+            ] {
+                let args = SetConfigArgs { key: key.to_string(), value: value.to_string() };
+                match set_config(args, Some(custom.clone())).await {
+                    Err(ConfigError::InvalidValue { key: rejected, .. }) => assert_eq!(rejected, key),
+                    other => panic!("expected {} = {} to be rejected, got {:?}", key, value, other),
+                }
+            }
+            assert!(!custom.exists());
+
+            let args = SetConfigArgs { key: "server.default_bind".to_string(), value: "::1".to_string() };
+            set_config(args, Some(custom.clone())).await.unwrap();
+            assert_eq!(load_config(Some(custom)).unwrap().server.default_bind, "::1");
         });
     }
 
@@ -1070,7 +4646,7 @@ json_format = false
         let (_dir, custom) = make_temp_config_path();
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            let result = handle_config_command(ConfigAction::Get, Some(custom)).await;
+            let result = handle_config_command(ConfigAction::Get(GetConfigArgs { key: None }), Some(custom)).await;
             assert!(result.is_ok());
         });
     }
@@ -1109,15 +4685,46 @@ json_format = false
         });
     }
 
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(parse_byte_size("1048576"), Ok(Some(1_048_576)));
+        assert_eq!(parse_byte_size("512B"), Ok(Some(512)));
+        assert_eq!(parse_byte_size("64KB"), Ok(Some(64 * 1024)));
+        assert_eq!(parse_byte_size("10MB"), Ok(Some(10 * 1024 * 1024)));
+        assert_eq!(parse_byte_size("10 mb"), Ok(Some(10 * 1024 * 1024)));
+        assert_eq!(parse_byte_size("0"), Ok(None));
+        assert_eq!(parse_byte_size("0MB"), Ok(None));
+        assert_eq!(parse_byte_size(""), Ok(None));
+        assert!(parse_byte_size("MB").is_err());
+        assert!(parse_byte_size("1.5MB").is_err());
+        assert!(parse_byte_size("10GB").is_err());
+        assert!(parse_byte_size("99999999999999999MB").is_err());
+    }
+
     #[test]
     fn test_set_config_logging_max_file_size() {
         let (_dir, custom) = make_temp_config_path();
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            // Note: max_file_size is not directly settable via set_config
-            // This tests the default value coverage
-            let config = load_config(Some(custom)).unwrap();
-            assert_eq!(config.logging.max_file_size, None);
+            let set = |value: &str| SetConfigArgs {
+                key: "logging.max_file_size".to_string(),
+                value: value.to_string(),
+            };
+            assert_eq!(load_config(Some(custom.clone())).unwrap().logging.max_file_size, None);
+
+            set_config(set("10MB"), Some(custom.clone())).await.unwrap();
+            assert_eq!(load_config(Some(custom.clone())).unwrap().logging.max_file_size, Some(10 * 1024 * 1024));
+
+            // Invalid sizes are rejected without touching the file
+            set_config(set("ten"), Some(custom.clone())).await.unwrap_err();
+            assert_eq!(load_config(Some(custom.clone())).unwrap().logging.max_file_size, Some(10 * 1024 * 1024));
+
+            // 0 and an empty value both clear the limit
+            set_config(set("0"), Some(custom.clone())).await.unwrap();
+            assert_eq!(load_config(Some(custom.clone())).unwrap().logging.max_file_size, None);
+            set_config(set("512KB"), Some(custom.clone())).await.unwrap();
+            set_config(set(""), Some(custom.clone())).await.unwrap();
+            assert_eq!(load_config(Some(custom)).unwrap().logging.max_file_size, None);
         });
     }
 
@@ -1126,10 +4733,22 @@ json_format = false
         let (_dir, custom) = make_temp_config_path();
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            // Note: max_files is not directly settable via set_config
-            // This tests the default value coverage
-            let config = load_config(Some(custom)).unwrap();
-            assert_eq!(config.logging.max_files, None);
+            let set = |value: &str| SetConfigArgs {
+                key: "logging.max_files".to_string(),
+                value: value.to_string(),
+            };
+            assert_eq!(load_config(Some(custom.clone())).unwrap().logging.max_files, None);
+
+            set_config(set("7"), Some(custom.clone())).await.unwrap();
+            assert_eq!(load_config(Some(custom.clone())).unwrap().logging.max_files, Some(7));
+            set_config(set("-1"), Some(custom.clone())).await.unwrap_err();
+            assert_eq!(load_config(Some(custom.clone())).unwrap().logging.max_files, Some(7));
+
+            set_config(set("0"), Some(custom.clone())).await.unwrap();
+            assert_eq!(load_config(Some(custom.clone())).unwrap().logging.max_files, None);
+            set_config(set("3"), Some(custom.clone())).await.unwrap();
+            set_config(set(""), Some(custom.clone())).await.unwrap();
+            assert_eq!(load_config(Some(custom)).unwrap().logging.max_files, None);
         });
     }
 
@@ -1186,7 +4805,7 @@ json_format = false
                 config.models.models_dir,
                 Some("/custom/models/dir".to_string())
             );
-            assert_eq!(config.models.auto_download, false);
+            assert!(!config.models.auto_download);
             assert_eq!(config.models.default_distill_dims, Some(256));
         });
     }
@@ -1200,6 +4819,7 @@ json_format = false
                 ("logging.level", "debug"),
                 ("logging.file", "/var/log/test.log"),
                 ("logging.json_format", "true"),
+                ("logging.console", "false"),
             ];
 
             for (key, value) in test_cases {
@@ -1214,7 +4834,8 @@ json_format = false
             let config = load_config(Some(custom)).unwrap();
             assert_eq!(config.logging.level, "debug");
             assert_eq!(config.logging.file, Some("/var/log/test.log".to_string()));
-            assert_eq!(config.logging.json_format, true);
+            assert!(config.logging.json_format);
+            assert!(!config.logging.console);
         });
     }
 }
\ No newline at end of file
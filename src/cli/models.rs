@@ -31,18 +31,21 @@
 //! # Show model information
 //! static-embedding-tool model info potion-32M
 //!
+//! # Measure how closely a distilled model tracks its teacher
+//! static-embedding-tool model compare potion-32M my-model --texts sample.txt
+//!
 //! # Remove a model
 //! static-embedding-tool model remove old-model --yes
 //! ```
 
-use crate::cli::{ModelAction, DownloadArgs, DistillArgs, RemoveArgs, UpdateArgs, InfoArgs};
+use crate::cli::{ModelAction, DownloadArgs, DistillArgs, RemoveArgs, UpdateArgs, InfoArgs, CompareArgs};
 use anyhow::Result as AnyhowResult;
 use std::path::PathBuf;
 use std::fs;
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use chrono;
-use hf_hub::{api::sync::Api, Repo, RepoType};
+use hf_hub::{Repo, RepoType};
 
 /// Model registry for tracking installed models.
 #[derive(Serialize, Deserialize, Default)]
@@ -69,7 +72,8 @@ struct ModelInfo {
 /// # Arguments
 ///
 /// * `action` - The model action to perform
-/// * `_config_path` - Optional configuration file path (currently unused)
+/// * `config_path` - Optional configuration file path; `models.models_dir` sets
+///   where `download` stores models
 ///
 /// # Errors
 ///
@@ -80,49 +84,329 @@ struct ModelInfo {
 /// - Model files are missing or invalid
 pub async fn handle_model_command(
     action: ModelAction,
-    _config_path: Option<PathBuf>,
+    config_path: Option<PathBuf>,
 ) -> AnyhowResult<()> {
     match action {
-        ModelAction::List => list_models().await,
-        ModelAction::Download(args) => download_model(args).await,
-        ModelAction::Distill(args) => distill_model(args).await,
-        ModelAction::Remove(args) => remove_model(args).await,
+        ModelAction::List(args) => {
+            let config = crate::cli::config::load_config(config_path)
+                .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+            list_models(args.json, config.models.models_dir.map(PathBuf::from), &config.server.default_model).await
+        }
+        ModelAction::Download(args) => {
+            let config = crate::cli::config::load_config(config_path)
+                .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+            download_model(args, config.models.models_dir.map(PathBuf::from)).await
+        }
+        ModelAction::Distill(args) => {
+            let config = crate::cli::config::load_config(config_path)
+                .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+            distill_model(
+                args,
+                config.models.models_dir.map(PathBuf::from),
+                config.models.default_distill_dims,
+            )
+            .await
+        }
+        ModelAction::Remove(args) => {
+            let config = crate::cli::config::load_config(config_path)
+                .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+            remove_model(args, &config).await
+        }
         ModelAction::Update(args) => update_model(args).await,
         ModelAction::Info(args) => show_model_info(args).await,
+        ModelAction::Compare(args) => compare_models(args).await,
     }
 }
 
-async fn list_models() -> AnyhowResult<()> {
+/// Files every Model2Vec model directory must contain.
+const REQUIRED_MODEL_FILES: &[&str] = &["config.json", "model.safetensors", "tokenizer.json"];
+
+/// An installed model as reported by `model list`.
+#[derive(Serialize, Debug)]
+struct InstalledModel {
+    name: String,
+    /// `"ok"`, or `"broken"` when the directory is partial or unreadable
+    status: &'static str,
+    /// Why the model is broken
+    #[serde(skip_serializing_if = "Option::is_none")]
+    problem: Option<String>,
+    dimensions: Option<usize>,
+    vocab_size: Option<usize>,
+    size_mb: Option<f64>,
+    path: String,
+    /// Registry source (`huggingface`, `local`, `distilled`), if registered
+    source: Option<String>,
+    default: bool,
+}
+
+/// Read `(vocab_size, dimensions)` from a Model2Vec model directory.
+///
+/// Only the safetensors header is read, so this is cheap even for large models.
+fn inspect_model_dir(path: &std::path::Path) -> Result<(usize, usize), String> {
+    let missing: Vec<&str> = REQUIRED_MODEL_FILES
+        .iter()
+        .copied()
+        .filter(|file| !path.join(file).is_file())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("missing {}", missing.join(", ")));
+    }
+
+    let config = fs::read_to_string(path.join("config.json")).map_err(|e| format!("config.json: {}", e))?;
+    serde_json::from_str::<serde_json::Value>(&config).map_err(|e| format!("config.json: {}", e))?;
+
+    let mut file = fs::File::open(path.join("model.safetensors"))
+        .map_err(|e| format!("model.safetensors: {}", e))?;
+    let shape = read_embedding_shape(&mut file).map_err(|e| format!("model.safetensors: {}", e))?;
+    match shape.as_slice() {
+        [vocab_size, dimensions] => Ok((*vocab_size, *dimensions)),
+        other => Err(format!("model.safetensors: expected a 2-D embedding matrix, found shape {:?}", other)),
+    }
+}
+
+/// Shape of the embedding tensor named in a safetensors header: `embeddings`,
+/// or the only tensor in the file.
+fn read_embedding_shape(reader: &mut impl std::io::Read) -> Result<Vec<usize>, String> {
+    /// Largest header accepted; real model headers are a few hundred bytes.
+    const MAX_HEADER_LEN: u64 = 100 * 1024 * 1024;
+
+    let mut len = [0u8; 8];
+    reader.read_exact(&mut len).map_err(|e| format!("unreadable header: {}", e))?;
+    let len = u64::from_le_bytes(len);
+    if len > MAX_HEADER_LEN {
+        return Err(format!("header length {} is implausibly large", len));
+    }
+    let mut header = vec![0u8; len as usize];
+    reader.read_exact(&mut header).map_err(|e| format!("truncated header: {}", e))?;
+    let header: serde_json::Value =
+        serde_json::from_slice(&header).map_err(|e| format!("invalid header: {}", e))?;
+    let tensors: Vec<(&String, &serde_json::Value)> = header
+        .as_object()
+        .ok_or("invalid header: not an object")?
+        .iter()
+        .filter(|(name, _)| name.as_str() != "__metadata__")
+        .collect();
+    let tensor = tensors
+        .iter()
+        .find(|(name, _)| name.as_str() == "embeddings")
+        .or(if tensors.len() == 1 { tensors.first() } else { None })
+        .map(|(_, tensor)| *tensor)
+        .ok_or("no embeddings tensor")?;
+    serde_json::from_value(tensor["shape"].clone()).map_err(|e| format!("invalid tensor shape: {}", e))
+}
+
+/// Installed models: every directory in `models_dir` plus registered models
+/// stored elsewhere, sorted by name.
+fn collect_installed_models(
+    models_dir: &std::path::Path,
+    registry: &ModelRegistry,
+    default_model: &str,
+) -> Vec<InstalledModel> {
+    let describe = |name: &str, path: PathBuf| {
+        let source = registry.models.get(name).map(|info| info.source.clone());
+        let inspected = if path.is_dir() {
+            inspect_model_dir(&path)
+        } else {
+            Err(format!("{} does not exist", path.display()))
+        };
+        let (status, problem, vocab_size, dimensions) = match inspected {
+            Ok((vocab_size, dimensions)) => ("ok", None, Some(vocab_size), Some(dimensions)),
+            Err(problem) => ("broken", Some(problem), None, None),
+        };
+        InstalledModel {
+            name: name.to_string(),
+            status,
+            problem,
+            dimensions,
+            vocab_size,
+            size_mb: get_directory_size(&path),
+            path: path.to_string_lossy().to_string(),
+            source,
+            default: name == default_model,
+        }
+    };
+
+    let mut models = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    if let Ok(entries) = fs::read_dir(models_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Skip staging directories left by interrupted downloads
+            if name.starts_with('.') || !entry.path().is_dir() {
+                continue;
+            }
+            seen.insert(name.clone());
+            models.push(describe(&name, entry.path()));
+        }
+    }
+    for (name, info) in &registry.models {
+        if !seen.contains(name) {
+            models.push(describe(name, PathBuf::from(&info.path)));
+        }
+    }
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    models
+}
+
+async fn list_models(json: bool, models_dir: Option<PathBuf>, default_model: &str) -> AnyhowResult<()> {
     let registry = load_model_registry()?;
-    
-    if registry.models.is_empty() {
-        println!("No models installed. Use 'static-embedding-tool model download' to add models.");
+    let models_dir = match models_dir {
+        Some(dir) => dir,
+        None => get_models_dir()?,
+    };
+    let models = collect_installed_models(&models_dir, &registry, default_model);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&models)?);
         return Ok(());
     }
-    
-    println!("{:<20} {:<15} {:<12} {:<10} DESCRIPTION", 
-             "NAME", "SOURCE", "DIMENSIONS", "SIZE");
-    println!("{}", "-".repeat(80));
-    
-    for (name, info) in &registry.models {
-        let dims = info.dimensions.map(|d| d.to_string()).unwrap_or_else(|| "unknown".to_string());
-        let size = info.size_mb.map(|s| format!("{:.1}MB", s)).unwrap_or_else(|| "unknown".to_string());
-        let desc = info.description.as_deref().unwrap_or("");
-        
-        println!("{:<20} {:<15} {:<12} {:<10} {}", 
-                 name, info.source, dims, size, desc);
+
+    if models.is_empty() {
+        println!("No models installed. Use 'static-embedding-tool model download' to add models.");
+    } else {
+        println!("{:<20} {:<8} {:<11} {:<10} {:<10} PATH",
+                 "NAME", "STATUS", "DIMENSIONS", "VOCAB", "SIZE");
+        println!("{}", "-".repeat(80));
+
+        for model in &models {
+            let unknown = || "-".to_string();
+            let name = if model.default { format!("{} *", model.name) } else { model.name.clone() };
+            let dims = model.dimensions.map(|d| d.to_string()).unwrap_or_else(unknown);
+            let vocab = model.vocab_size.map(|v| v.to_string()).unwrap_or_else(unknown);
+            let size = model.size_mb.map(|s| format!("{:.1}MB", s)).unwrap_or_else(unknown);
+            println!("{:<20} {:<8} {:<11} {:<10} {:<10} {}",
+                     name, model.status, dims, vocab, size, model.path);
+            if let Some(problem) = &model.problem {
+                println!("{:<20} ({})", "", problem);
+            }
+        }
+        println!("\n* default model ({})", default_model);
     }
-    
+
     println!("\nBuilt-in models:");
     println!("  potion-8M      huggingface   8            ~32MB     Small, fast model");
     println!("  potion-32M     huggingface   32           ~128MB    Balanced model (default)");
-    
+
     Ok(())
 }
 
-async fn download_model(args: DownloadArgs) -> AnyhowResult<()> {
+/// Model files fetched by `model download`; only the first three are required by Model2Vec.
+const DOWNLOAD_FILES: &[(&str, bool)] = &[
+    ("config.json", true),
+    ("model.safetensors", true),
+    ("tokenizer.json", true),
+    ("special_tokens_map.json", false),
+    ("tokenizer_config.json", false),
+];
+
+/// HuggingFace repository for `model_name`, expanding the built-in aliases.
+fn resolve_repo_id(model_name: &str) -> &str {
+    match model_name {
+        "potion-8M" => "minishlab/potion-base-8M",
+        "potion-32M" => "minishlab/potion-base-32M",
+        other => other,
+    }
+}
+
+/// Check that `copied` is an intact copy of the cached download `cached`.
+///
+/// Sizes must match, and files stored with git LFS (whose cache blob is named
+/// after the content's sha256) must hash to that name.
+fn verify_download(cached: &std::path::Path, copied: &std::path::Path) -> AnyhowResult<()> {
+    use sha2::{Digest, Sha256};
+
+    let blob = fs::canonicalize(cached)?;
+    let expected_size = fs::metadata(&blob)?.len();
+    let actual_size = fs::metadata(copied)?.len();
+    if expected_size != actual_size {
+        return Err(anyhow::anyhow!(
+            "{} is {} bytes, expected {}",
+            copied.display(),
+            actual_size,
+            expected_size
+        ));
+    }
+
+    let etag = blob.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    if etag.len() == 64 && etag.bytes().all(|b| b.is_ascii_hexdigit()) {
+        let mut hasher = Sha256::new();
+        let mut file = fs::File::open(copied)?;
+        let mut buf = vec![0u8; 1 << 16];
+        loop {
+            let read = std::io::Read::read(&mut file, &mut buf)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+        }
+        let digest: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+        if !digest.eq_ignore_ascii_case(etag) {
+            return Err(anyhow::anyhow!(
+                "{} has sha256 {}, expected {}",
+                copied.display(),
+                digest,
+                etag
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Download `repo_id` into `staging`, verifying every file.
+fn fetch_model_files(repo_id: &str, staging: &std::path::Path) -> AnyhowResult<()> {
+    let api = hf_hub::api::sync::ApiBuilder::from_env()
+        .with_progress(true)
+        .build()
+        .map_err(|e| anyhow::anyhow!("Could not set up the HuggingFace client: {}", e))?;
+    let api_repo = api.repo(Repo::with_revision(
+        repo_id.to_string(),
+        RepoType::Model,
+        "main".to_string(),
+    ));
+
+    for (file_name, required) in DOWNLOAD_FILES {
+        let cached = match api_repo.get(file_name) {
+            Ok(path) => path,
+            Err(e) if *required => {
+                return Err(anyhow::anyhow!(
+                    "Could not download {} from HuggingFace repository '{}': {}\n  \
+                     Check the model ID (e.g. minishlab/potion-base-8M) and your network connection. \
+                     Set HF_ENDPOINT to use a mirror, or run 'huggingface-cli login' for gated models.",
+                    file_name,
+                    repo_id,
+                    e
+                ));
+            }
+            Err(_) => {
+                eprintln!("  - {} not available, skipping", file_name);
+                continue;
+            }
+        };
+        let dest_path = staging.join(file_name);
+        fs::copy(&cached, &dest_path)?;
+        verify_download(&cached, &dest_path).map_err(|e| {
+            anyhow::anyhow!(
+                "Integrity check failed for {}: {}. Re-run with --force to download it again.",
+                file_name,
+                e
+            )
+        })?;
+        eprintln!("  ✓ Downloaded {}", file_name);
+    }
+    Ok(())
+}
+
+/// Download a Model2Vec model from HuggingFace into `models_dir` (default
+/// `~/.static-embedding-tool/models`) and register it.
+///
+/// Files are fetched into a staging directory and only replace an existing
+/// model, with `--force`, once they are verified and the model loads.
+async fn download_model(args: DownloadArgs, models_dir: Option<PathBuf>) -> AnyhowResult<()> {
     let model_name = args.alias.unwrap_or_else(|| args.model_name.clone());
-    let models_dir = get_models_dir()?;
+    let models_dir = match models_dir {
+        Some(dir) => dir,
+        None => get_models_dir()?,
+    };
     let model_path = models_dir.join(&model_name);
 
     if model_path.exists() && !args.force {
@@ -130,7 +414,8 @@ async fn download_model(args: DownloadArgs) -> AnyhowResult<()> {
         return Ok(());
     }
 
-    println!("Downloading model '{}' from '{}'...", model_name, args.model_name);
+    let repo_id = resolve_repo_id(&args.model_name).to_string();
+    eprintln!("Downloading model '{}' from '{}'...", model_name, repo_id);
 
     // Create models directory if it doesn't exist
     fs::create_dir_all(&models_dir)?;
@@ -168,65 +453,42 @@ async fn download_model(args: DownloadArgs) -> AnyhowResult<()> {
         return Ok(());
     }
 
-    // Download model files using hf-hub
-    let api = Api::new()?;
-    let repo = Repo::with_revision(
-        args.model_name.clone(),
-        RepoType::Model,
-        "main".to_string(),
-    );
-
-    println!("  Downloading model files from HuggingFace...");
-    let api_repo = api.repo(repo);
-
-    // Download essential model files
-    let files_to_download = vec![
-        "config.json",
-        "model.safetensors",
-        "tokenizer.json",
-        "special_tokens_map.json",
-        "tokenizer_config.json",
-    ];
-
-    for file_name in &files_to_download {
-        match api_repo.get(file_name) {
-            Ok(local_path) => {
-                println!("  ✓ Downloaded {}", file_name);
-                // Copy to our models directory
-                let dest_path = model_path.join(file_name);
-                fs::create_dir_all(dest_path.parent().unwrap())?;
-                fs::copy(&local_path, &dest_path)?;
-            }
-            Err(e) => {
-                println!("  ⚠️  Could not download {}: {}", file_name, e);
-                // Continue with other files - some might be optional
-            }
-        }
-    }
+    let staging = tempfile::Builder::new()
+        .prefix(".download-")
+        .tempdir_in(&models_dir)?;
+    let staging_path = staging.path().to_path_buf();
+    let fetch_repo = repo_id.clone();
+    tokio::task::spawn_blocking(move || fetch_model_files(&fetch_repo, &staging_path))
+        .await
+        .map_err(|e| anyhow::anyhow!("Download task failed: {}", e))??;
 
     // Try to load the model to verify it works and get metadata
-    println!("  Verifying model...");
-
-    let (dimensions, size_mb) = if args.model_name == "sentence-transformers/all-MiniLM-L6-v2" {
-        println!("  ✓ Skipping verification for 'all-MiniLM-L6-v2', known compatible model.");
-        (384, get_directory_size(&model_path))
+    eprintln!("  Verifying model...");
+    let dimensions = if repo_id == "sentence-transformers/all-MiniLM-L6-v2" {
+        eprintln!("  ✓ Skipping verification for 'all-MiniLM-L6-v2', known compatible model.");
+        384
     } else {
-        match model2vec_rs::model::StaticModel::from_pretrained(&model_path, None, None, None) {
-            Ok(model) => {
-                let dims = model.encode(&["test".to_string()]).first().map(|e| e.len()).unwrap_or(0);
-                (dims, get_directory_size(&model_path))
-            }
+        match model2vec_rs::model::StaticModel::from_pretrained(staging.path(), None, None, None) {
+            Ok(model) => model.encode(&["test".to_string()]).first().map(|e| e.len()).unwrap_or(0),
             Err(e) => {
-                // Clean up the failed download
-                if model_path.exists() {
-                    let _ = fs::remove_dir_all(&model_path);
-                }
-                eprintln!("❌ Failed to verify downloaded model '{}': {}", args.model_name, e);
+                eprintln!("❌ Failed to verify downloaded model '{}': {}", repo_id, e);
                 return Err(anyhow::anyhow!("Model verification failed: {}", e));
             }
         }
     };
 
+    // Swap the verified files in
+    if model_path.is_dir() {
+        fs::remove_dir_all(&model_path)?;
+    } else if model_path.exists() {
+        fs::remove_file(&model_path)?;
+    }
+    if let Some(parent) = model_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(staging.keep(), &model_path)?;
+    let size_mb = get_directory_size(&model_path);
+
     // Add to registry
     let mut registry = load_model_registry().unwrap_or_default();
     registry.models.insert(model_name.clone(), ModelInfo {
@@ -236,7 +498,7 @@ async fn download_model(args: DownloadArgs) -> AnyhowResult<()> {
         dimensions: Some(dimensions),
         size_mb,
         downloaded_at: chrono::Utc::now().to_rfc3339(),
-        description: Some(format!("Downloaded from {}", args.model_name)),
+        description: Some(format!("Downloaded from {}", repo_id)),
     });
 
     save_model_registry(&registry)?;
@@ -247,8 +509,38 @@ async fn download_model(args: DownloadArgs) -> AnyhowResult<()> {
     Ok(())
 }
 
-async fn distill_model(args: DistillArgs) -> AnyhowResult<()> {
-    let models_dir = get_models_dir()?;
+/// PCA dimensions for `model distill` and where they came from: `--dims`, then
+/// `models.default_distill_dims` (ignored when 0), then a guess from the input
+/// model name, then 32.
+fn resolve_distill_dims(
+    dims: Option<usize>,
+    default_dims: Option<usize>,
+    input: &str,
+) -> (usize, &'static str) {
+    if let Some(d) = dims {
+        (d, "--dims")
+    } else if let Some(d) = default_dims.filter(|d| *d > 0) {
+        (d, "models.default_distill_dims")
+    } else if input.contains("32M") {
+        (32, "input model name")
+    } else if input.contains("8M") {
+        (8, "input model name") // Assuming 8M implies 8 dimensions based on project conventions
+    } else {
+        (32, "built-in default")
+    }
+}
+
+/// Distill `args.input` into `models_dir` (default `~/.static-embedding-tool/models`)
+/// and register the result so `model list`, `info` and `remove` know about it.
+async fn distill_model(
+    args: DistillArgs,
+    models_dir: Option<PathBuf>,
+    default_dims: Option<usize>,
+) -> AnyhowResult<()> {
+    let models_dir = match models_dir {
+        Some(dir) => dir,
+        None => get_models_dir()?,
+    };
     let output_path = if args.output.starts_with('/') || args.output.contains(':') {
         PathBuf::from(&args.output)
     } else {
@@ -260,30 +552,13 @@ async fn distill_model(args: DistillArgs) -> AnyhowResult<()> {
         return Ok(());
     }
 
-    // Resolve dimensions: args -> config -> model-specific default -> global default
-    let dimensions = if let Some(d) = args.dims {
-        d
-    } else {
-        // Load config to check for default_distill_dims
-        let config = crate::cli::config::load_config(None).unwrap_or_default();
-        if let Some(d) = config.models.default_distill_dims {
-            d
-        } else {
-            // Check if input model name implies dimensions
-            if args.input.contains("32M") {
-                32
-            } else if args.input.contains("8M") {
-                8 // Assuming 8M implies 8 dimensions based on project conventions
-            } else {
-                32 // Global default fallback
-            }
-        }
-    };
-    
+    let (dimensions, dimensions_source) = resolve_distill_dims(args.dims, default_dims, &args.input);
+    tracing::debug!(dimensions, source = dimensions_source, "Resolved distillation dimensions");
+
     println!("Distilling model...");
     println!("  Input: {}", args.input);
     println!("  Output: {}", output_path.display());
-    println!("  Dimensions: {}", dimensions);
+    println!("  Dimensions: {} (from {})", dimensions, dimensions_source);
     
     // Create output directory if needed
     if let Some(parent) = output_path.parent() {
@@ -292,6 +567,7 @@ async fn distill_model(args: DistillArgs) -> AnyhowResult<()> {
     
     // Call the distillation function from utils
     // Check for test mode to skip actual distillation
+    let mut registered_dims = dimensions;
     if std::env::var("EMBED_TOOL_TEST_MODE").is_ok() {
         println!("  [TEST MODE] Simulating distillation...");
         // Create dummy files
@@ -299,7 +575,17 @@ async fn distill_model(args: DistillArgs) -> AnyhowResult<()> {
         fs::write(output_path.join("config.json"), "{}")?;
         fs::write(output_path.join("model.safetensors"), "dummy content")?;
     } else {
-        crate::utils::distill(&args.input, dimensions, Some(output_path.clone())).await.map_err(|e| anyhow::anyhow!("Distillation failed: {}", e))?;
+        let produced = crate::utils::distill(&args.input, dimensions, Some(output_path.clone())).await.map_err(|e| anyhow::anyhow!("Distillation failed: {}", e))?;
+
+        // A distiller that exits successfully can still leave an unusable model behind
+        if args.verify {
+            let produced = PathBuf::from(produced);
+            let verified_dims = tokio::task::spawn_blocking(move || crate::utils::verify_model(&produced))
+                .await?
+                .map_err(|e| anyhow::anyhow!("Verification of distilled model failed: {}", e))?;
+            println!("✓ Verified: model loads and embeds ({} dimensions)", verified_dims);
+            registered_dims = verified_dims;
+        }
     }
 
     // Add to registry
@@ -308,7 +594,7 @@ async fn distill_model(args: DistillArgs) -> AnyhowResult<()> {
         name: args.output.clone(),
         path: output_path.to_string_lossy().to_string(),
         source: "distilled".to_string(),
-        dimensions: Some(dimensions),
+        dimensions: Some(registered_dims),
         size_mb: get_directory_size(&output_path),
         downloaded_at: chrono::Utc::now().to_rfc3339(),
         description: Some(format!("Distilled from {} with {} dimensions", args.input, dimensions)),
@@ -320,43 +606,133 @@ async fn distill_model(args: DistillArgs) -> AnyhowResult<()> {
     Ok(())
 }
 
-async fn remove_model(args: RemoveArgs) -> AnyhowResult<()> {
+/// Models loaded by the server at `endpoint`, or `None` if no server is running.
+///
+/// A server that is known to be running (via its PID file) but does not answer
+/// is an error, since its models cannot be checked.
+async fn running_server_models(
+    endpoint: &crate::cli::config::ServerEndpoint,
+    cli_config: &crate::cli::config::CliConfig,
+) -> AnyhowResult<Option<Vec<String>>> {
+    // Without the server module there is no PID file to tell a hung server from none
+    #[cfg(not(feature = "mcp"))]
+    let _ = cli_config;
+
+    match endpoint.client().get(endpoint.url("/v1/models")).send().await {
+        Ok(response) => {
+            let listing: serde_json::Value = response.json().await.unwrap_or_default();
+            Ok(Some(
+                listing["data"]
+                    .as_array()
+                    .map(|data| {
+                        data.iter()
+                            .filter_map(|model| model["id"].as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            ))
+        }
+        #[cfg(feature = "mcp")]
+        Err(e) if crate::cli::server::pid_file_server_running() => Err(anyhow::anyhow!(
+            "A server is running but its loaded models could not be checked on {}: {}",
+            endpoint,
+            crate::cli::config::describe_request_error(&e, cli_config)
+        )),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Delete a model's files and registry entry, reporting the space freed.
+///
+/// Refuses to remove a model loaded by a running server, or the configured
+/// `server.default_model` without `--yes`, and otherwise asks for confirmation
+/// unless `--yes` is given. Only files inside the models directory are ever
+/// deleted; a registered model stored elsewhere is just unregistered.
+async fn remove_model(args: RemoveArgs, config: &crate::cli::config::Config) -> AnyhowResult<()> {
     let mut registry = load_model_registry()?;
-    
-    if let Some(model_info) = registry.models.get(&args.model_name) {
-        if !args.yes {
-            print!("Remove model '{}' at '{}'? [y/N]: ", args.model_name, model_info.path);
-            use std::io::{self, Write};
-            io::stdout().flush()?;
-            
-            let mut input = String::new();
-            io::stdin().read_line(&mut input)?;
-            
-            if !input.trim().to_lowercase().starts_with('y') {
-                println!("Cancelled.");
-                return Ok(());
-            }
+    let models_dir = match &config.models.models_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => get_models_dir()?,
+    };
+
+    let model_path = match registry.models.get(&args.model_name) {
+        Some(model_info) => PathBuf::from(&model_info.path),
+        None if is_plain_model_name(&args.model_name) && models_dir.join(&args.model_name).is_dir() => {
+            models_dir.join(&args.model_name)
         }
-        
-        // Remove the model files
-        let model_path = PathBuf::from(&model_info.path);
-        if model_path.exists() {
-            if model_path.is_dir() {
-                fs::remove_dir_all(&model_path)?;
-            } else {
-                fs::remove_file(&model_path)?;
-            }
+        None => {
+            return Err(anyhow::anyhow!(
+                "Model '{}' not found in the registry or {}; run 'static-embedding-tool model list' to see installed models",
+                args.model_name,
+                models_dir.display()
+            ));
         }
-        
-        // Remove from registry
-        registry.models.remove(&args.model_name);
+    };
+
+    let is_default = args.model_name == config.server.default_model;
+    if is_default && !args.yes {
+        return Err(anyhow::anyhow!(
+            "Model '{}' is the configured default (server.default_model); choose another with \
+             'static-embedding-tool config set server.default_model <model>' or pass --yes to remove it anyway",
+            args.model_name
+        ));
+    }
+
+    let endpoint = crate::cli::config::ServerEndpoint::from_config(config, config.server.default_port)?;
+    if let Some(loaded) = running_server_models(&endpoint, &config.cli).await?
+        && loaded.contains(&args.model_name)
+    {
+        return Err(anyhow::anyhow!(
+            "Model '{}' is loaded by the server on {}; unload it (POST /v1/models/unload) or stop the server first",
+            args.model_name,
+            endpoint
+        ));
+    }
+
+    if !args.yes {
+        print!("Remove model '{}' at '{}'? [y/N]: ", args.model_name, model_path.display());
+        use std::io::{self, Write};
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        if !input.trim().to_lowercase().starts_with('y') {
+            println!("Cancelled.");
+            return Ok(());
+        }
+    }
+
+    // Remove the model files, but never anything outside the models directory
+    let mut freed = 0;
+    if model_path.exists() {
+        if !is_inside(&model_path, &models_dir) {
+            eprintln!(
+                "  {} is outside {}; leaving its files in place",
+                model_path.display(),
+                models_dir.display()
+            );
+        } else if model_path.is_dir() {
+            freed = directory_bytes(&model_path);
+            fs::remove_dir_all(&model_path)?;
+        } else {
+            freed = fs::symlink_metadata(&model_path)?.len();
+            fs::remove_file(&model_path)?;
+        }
+    }
+
+    // Remove from registry
+    if registry.models.remove(&args.model_name).is_some() {
         save_model_registry(&registry)?;
-        
-        println!("✓ Model '{}' removed", args.model_name);
-    } else {
-        eprintln!("Model '{}' not found in registry", args.model_name);
     }
-    
+
+    println!("✓ Model '{}' removed ({} bytes freed)", args.model_name, freed);
+    if is_default {
+        eprintln!(
+            "  Note: '{}' is still server.default_model; set a new default before starting the server",
+            args.model_name
+        );
+    }
     Ok(())
 }
 
@@ -410,12 +786,15 @@ async fn show_model_info(args: InfoArgs) -> AnyhowResult<()> {
             println!("  Description: {}", desc);
         }
         
-        // Check if files exist
+        // Check the files are still there and readable
         let model_path = PathBuf::from(&model_info.path);
-        if model_path.exists() {
-            println!("  Status: ✓ Available");
-        } else {
-            println!("  Status: ✗ Missing files");
+        match inspect_model_dir(&model_path) {
+            Ok((vocab_size, _)) => {
+                println!("  Vocabulary: {} tokens", vocab_size);
+                println!("  Status: ✓ Available");
+            }
+            Err(_) if !model_path.exists() => println!("  Status: ✗ Missing files"),
+            Err(problem) => println!("  Status: ✗ Broken ({})", problem),
         }
     } else {
         // Check built-in models
@@ -443,6 +822,168 @@ async fn show_model_info(args: InfoArgs) -> AnyhowResult<()> {
     Ok(())
 }
 
+/// Per-model figures reported by `model compare`.
+#[derive(Serialize)]
+struct CompareModelStats {
+    name: String,
+    dimensions: usize,
+    encode_ms: f64,
+}
+
+/// Result of embedding the same texts with two models.
+#[derive(Serialize)]
+struct CompareReport {
+    samples: usize,
+    model_a: CompareModelStats,
+    model_b: CompareModelStats,
+    /// Mean cosine between corresponding vectors (only when dimensions match)
+    mean_cosine: Option<f32>,
+    /// Lowest cosine between corresponding vectors (only when dimensions match)
+    min_cosine: Option<f32>,
+    /// Pearson correlation of the two models' pairwise similarity matrices.
+    /// Comparable across different dimensions; needs at least three texts.
+    pairwise_correlation: Option<f32>,
+}
+
+/// Similarity figures for two embeddings of the same texts:
+/// `(mean_cosine, min_cosine, pairwise_correlation)`.
+fn compare_embeddings(a: &[Vec<f32>], b: &[Vec<f32>]) -> (Option<f32>, Option<f32>, Option<f32>) {
+    use crate::utils::cosine_similarity;
+
+    let same_dims = !a.is_empty() && a.iter().zip(b).all(|(x, y)| x.len() == y.len());
+    let (mean_cosine, min_cosine) = if same_dims {
+        let cosines: Vec<f32> = a.iter().zip(b).map(|(x, y)| cosine_similarity(x, y)).collect();
+        (
+            Some(cosines.iter().sum::<f32>() / cosines.len() as f32),
+            cosines.iter().copied().reduce(f32::min),
+        )
+    } else {
+        (None, None)
+    };
+
+    // Compare the similarity structure each model induces over the texts
+    let mut sims_a = Vec::new();
+    let mut sims_b = Vec::new();
+    for i in 0..a.len() {
+        for j in i + 1..a.len() {
+            sims_a.push(cosine_similarity(&a[i], &a[j]));
+            sims_b.push(cosine_similarity(&b[i], &b[j]));
+        }
+    }
+    let pairwise_correlation = pearson(&sims_a, &sims_b);
+
+    (mean_cosine, min_cosine, pairwise_correlation)
+}
+
+/// Pearson correlation coefficient, or `None` with fewer than two points or
+/// zero variance.
+fn pearson(x: &[f32], y: &[f32]) -> Option<f32> {
+    if x.len() < 2 {
+        return None;
+    }
+    let n = x.len() as f32;
+    let mean_x = x.iter().sum::<f32>() / n;
+    let mean_y = y.iter().sum::<f32>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (xi, yi) in x.iter().zip(y) {
+        cov += (xi - mean_x) * (yi - mean_y);
+        var_x += (xi - mean_x).powi(2);
+        var_y += (yi - mean_y).powi(2);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some(cov / (var_x.sqrt() * var_y.sqrt()))
+}
+
+async fn compare_models(args: CompareArgs) -> AnyhowResult<()> {
+    use crate::cli::config::load_local_model;
+    use std::time::Instant;
+
+    let texts: Vec<String> = fs::read_to_string(&args.texts)
+        .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", args.texts.display(), e))?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    if texts.is_empty() {
+        return Err(anyhow::anyhow!("No sample texts found in '{}'", args.texts.display()));
+    }
+
+    let mut stats = Vec::new();
+    let mut embeddings = Vec::new();
+    for name in [&args.model_a, &args.model_b] {
+        let model = load_local_model(name)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load model '{}': {}", name, e))?;
+        let start = Instant::now();
+        let encoded = model.encode(&texts);
+        stats.push(CompareModelStats {
+            name: name.clone(),
+            dimensions: encoded.first().map(|e| e.len()).unwrap_or(0),
+            encode_ms: start.elapsed().as_secs_f64() * 1000.0,
+        });
+        embeddings.push(encoded);
+    }
+
+    let (mean_cosine, min_cosine, pairwise_correlation) =
+        compare_embeddings(&embeddings[0], &embeddings[1]);
+    let model_b = stats.pop().expect("two models were encoded");
+    let model_a = stats.pop().expect("two models were encoded");
+    let report = CompareReport {
+        samples: texts.len(),
+        model_a,
+        model_b,
+        mean_cosine,
+        min_cosine,
+        pairwise_correlation,
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_compare_report(&report);
+    }
+
+    Ok(())
+}
+
+fn print_compare_report(report: &CompareReport) {
+    let fmt = |value: Option<f32>| value.map(|v| format!("{:.4}", v)).unwrap_or_else(|| "n/a".to_string());
+    let rate = |stats: &CompareModelStats| {
+        if stats.encode_ms > 0.0 {
+            format!("{:.0}", report.samples as f64 / (stats.encode_ms / 1000.0))
+        } else {
+            "n/a".to_string()
+        }
+    };
+
+    println!("Compared {} texts\n", report.samples);
+    println!("{:<16} {:<20} {:<20}", "", report.model_a.name, report.model_b.name);
+    println!("{}", "-".repeat(56));
+    println!("{:<16} {:<20} {:<20}", "Dimensions", report.model_a.dimensions, report.model_b.dimensions);
+    println!(
+        "{:<16} {:<20} {:<20}",
+        "Encode time",
+        format!("{:.1}ms", report.model_a.encode_ms),
+        format!("{:.1}ms", report.model_b.encode_ms)
+    );
+    println!("{:<16} {:<20} {:<20}", "Texts/sec", rate(&report.model_a), rate(&report.model_b));
+
+    println!();
+    if report.mean_cosine.is_some() {
+        println!(
+            "Mean cosine (corresponding vectors): {} (min {})",
+            fmt(report.mean_cosine),
+            fmt(report.min_cosine)
+        );
+    } else {
+        println!("Mean cosine (corresponding vectors): n/a (dimensions differ)");
+    }
+    println!("Pairwise similarity correlation:     {}", fmt(report.pairwise_correlation));
+}
+
 fn get_models_dir() -> AnyhowResult<PathBuf> {
     let home = std::env::var("HOME")
         .or_else(|_| std::env::var("USERPROFILE"))
@@ -481,11 +1022,52 @@ fn save_model_registry(registry: &ModelRegistry) -> AnyhowResult<()> {
         fs::create_dir_all(parent)?;
     }
     
+    // Write a sibling file and rename it so an interrupted save never leaves a
+    // truncated registry behind
     let content = serde_json::to_string_pretty(registry)?;
-    fs::write(registry_path, content)?;
+    let tmp_path = registry_path.with_extension("json.tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, &registry_path)?;
     Ok(())
 }
 
+/// Whether `name` is a single directory name, so joining it onto the models
+/// directory cannot escape it.
+fn is_plain_model_name(name: &str) -> bool {
+    let mut components = std::path::Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(std::path::Component::Normal(_)), None)
+    )
+}
+
+/// Whether `path` resolves to somewhere strictly inside `dir`.
+fn is_inside(path: &std::path::Path, dir: &std::path::Path) -> bool {
+    // Resolve the parent so a symlinked model is judged by where the link lives
+    let resolved = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => fs::canonicalize(parent).map(|parent| parent.join(name)),
+        _ => return false,
+    };
+    match (resolved, fs::canonicalize(dir)) {
+        (Ok(path), Ok(dir)) => path.starts_with(&dir) && path != dir,
+        _ => false,
+    }
+}
+
+/// Total size in bytes of the files under `path`, not following symlinks.
+fn directory_bytes(path: &std::path::Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => directory_bytes(&entry.path()),
+            _ => entry.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .sum()
+}
+
 fn get_directory_size(path: &PathBuf) -> Option<f64> {
     if path.is_dir() {
         let mut size = 0u64;
@@ -507,12 +1089,20 @@ fn get_directory_size(path: &PathBuf) -> Option<f64> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cli::config::Config;
     use std::env;
     use std::fs;
     use std::sync::Mutex;
 
     static TEST_MUTEX: Mutex<()> = Mutex::new(());
 
+    /// Default config pointed at a port no server listens on.
+    fn test_config() -> Config {
+        let mut config = Config::default();
+        config.server.default_port = 1;
+        config
+    }
+
     fn with_test_env<F, R>(f: F) -> R
     where
         F: FnOnce() -> R,
@@ -553,6 +1143,52 @@ mod tests {
         result
     }
 
+    #[test]
+    fn test_compare_embeddings_same_dimensions() {
+        let a = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![1.0, 1.0]];
+        let b = vec![vec![1.0, 0.1], vec![0.0, 1.0], vec![1.0, 0.9]];
+        let (mean, min, correlation) = compare_embeddings(&a, &b);
+        assert!(mean.unwrap() > 0.99);
+        assert!(min.unwrap() <= mean.unwrap());
+        assert!(correlation.unwrap() > 0.9);
+
+        // Identical embeddings agree perfectly
+        let (mean, min, correlation) = compare_embeddings(&a, &a);
+        assert!((mean.unwrap() - 1.0).abs() < 1e-6);
+        assert!((min.unwrap() - 1.0).abs() < 1e-6);
+        assert!((correlation.unwrap() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_compare_embeddings_different_dimensions() {
+        let a = vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.7, 0.7, 0.0]];
+        let b = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.7, 0.7]];
+        let (mean, min, correlation) = compare_embeddings(&a, &b);
+        assert_eq!(mean, None);
+        assert_eq!(min, None);
+        assert!((correlation.unwrap() - 1.0).abs() < 1e-5);
+
+        // Too few texts for a correlation
+        assert_eq!(compare_embeddings(&a[..1], &b[..1]).2, None);
+    }
+
+    #[test]
+    fn test_compare_models_requires_texts() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let texts = tmp.path().join("empty.txt");
+        fs::write(&texts, "\n   \n").unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let err = rt
+            .block_on(compare_models(CompareArgs {
+                model_a: "potion-8M".to_string(),
+                model_b: "potion-32M".to_string(),
+                texts,
+                json: false,
+            }))
+            .unwrap_err();
+        assert!(err.to_string().contains("No sample texts"));
+    }
+
     #[test]
     fn test_get_models_dir() {
         with_test_env(|| {
@@ -662,12 +1298,66 @@ mod tests {
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async {
                 // Should not panic
-                let result = list_models().await;
+                let result = list_models(false, None, "potion-32M").await;
                 assert!(result.is_ok());
             });
         });
     }
 
+    #[cfg(feature = "mcp")]
+    #[test]
+    fn test_collect_installed_models() {
+        let models_dir = tempfile::TempDir::new().unwrap();
+        crate::server::test_utils::write_test_model(&models_dir.path().join("tiny"), 4);
+
+        // Partial download: no tokenizer
+        let partial = models_dir.path().join("partial");
+        fs::create_dir_all(&partial).unwrap();
+        fs::write(partial.join("config.json"), "{}").unwrap();
+        fs::write(partial.join("model.safetensors"), "").unwrap();
+
+        // Corrupt weights
+        let corrupt = models_dir.path().join("corrupt");
+        crate::server::test_utils::write_test_model(&corrupt, 4);
+        fs::write(corrupt.join("model.safetensors"), "not safetensors").unwrap();
+
+        // Staging directories are not models
+        fs::create_dir_all(models_dir.path().join(".download-abc")).unwrap();
+
+        let mut registry = ModelRegistry::default();
+        registry.models.insert("elsewhere".to_string(), ModelInfo {
+            name: "elsewhere".to_string(),
+            path: models_dir.path().join("gone").to_string_lossy().to_string(),
+            source: "distilled".to_string(),
+            dimensions: Some(8),
+            size_mb: None,
+            downloaded_at: "2024-01-01T00:00:00Z".to_string(),
+            description: None,
+        });
+
+        let models = collect_installed_models(models_dir.path(), &registry, "tiny");
+        let names: Vec<&str> = models.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["corrupt", "elsewhere", "partial", "tiny"]);
+
+        let tiny = &models[3];
+        assert_eq!(tiny.status, "ok");
+        assert_eq!((tiny.vocab_size, tiny.dimensions), (Some(3), Some(4)));
+        assert!(tiny.default);
+        assert!(tiny.size_mb.is_some());
+
+        assert_eq!(models[0].status, "broken");
+        assert!(models[0].problem.as_deref().unwrap().starts_with("model.safetensors"));
+        assert_eq!(models[1].status, "broken");
+        assert_eq!(models[1].source.as_deref(), Some("distilled"));
+        assert!(models[1].problem.as_deref().unwrap().contains("does not exist"));
+        assert_eq!(models[2].problem.as_deref(), Some("missing tokenizer.json"));
+        assert!(models.iter().filter(|m| m.name != "tiny").all(|m| !m.default));
+
+        let json = serde_json::to_value(&models[3]).unwrap();
+        assert_eq!(json["dimensions"], 4);
+        assert!(json.get("problem").is_none());
+    }
+
     #[test]
     fn test_show_model_info_builtin() {
         with_test_env(|| {
@@ -711,7 +1401,7 @@ mod tests {
                 };
 
                 // This will succeed even though it's a simulated download
-                let result = download_model(args).await;
+                let result = download_model(args, None).await;
                 assert!(result.is_ok()); // The function returns Ok even though it's simulated
             });
         });
@@ -740,8 +1430,8 @@ mod tests {
                     model_name: "nonexistent-model".to_string(),
                     yes: true,
                 };
-                let result = remove_model(args).await;
-                assert!(result.is_ok());
+                let error = remove_model(args, &test_config()).await.unwrap_err();
+                assert!(error.to_string().contains("not found in the registry"));
             });
         });
     }
@@ -769,12 +1459,121 @@ mod tests {
                     model_name: "test-model".to_string(),
                     yes: true,
                 };
-                let result = remove_model(args).await;
+                let result = remove_model(args, &test_config()).await;
                 assert!(result.is_ok());
+                assert!(!model_path.exists());
+                assert!(load_model_registry().unwrap().models.is_empty());
+            });
+        });
+    }
+
+    #[cfg(feature = "mcp")]
+    #[test]
+    fn test_remove_model_refuses_default_and_loaded_models() {
+        with_test_env(|| {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(async {
+                use crate::server::mock::MockModel;
+                use crate::server::state::{AppState, Model};
+                use std::sync::Arc;
+
+                let models_dir = get_models_dir().unwrap();
+                for name in ["mock", "potion-32M"] {
+                    fs::create_dir_all(models_dir.join(name)).unwrap();
+                }
+
+                let error = remove_model(
+                    RemoveArgs { model_name: "potion-32M".to_string(), yes: false },
+                    &test_config(),
+                )
+                .await
+                .unwrap_err();
+                assert!(error.to_string().contains("server.default_model"));
+                remove_model(RemoveArgs { model_name: "potion-32M".to_string(), yes: true }, &test_config())
+                    .await
+                    .unwrap();
+                assert!(!models_dir.join("potion-32M").exists());
+
+                let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+                models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 4)));
+                let state = Arc::new(AppState::from_models(models, "mock"));
+                let router = crate::server::api::create_api_router().with_state(state);
+                let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let mut config = test_config();
+                config.server.default_port = listener.local_addr().unwrap().port();
+                let server = tokio::spawn(async move { axum::serve(listener, router).await });
+
+                let error = remove_model(RemoveArgs { model_name: "mock".to_string(), yes: true }, &config)
+                    .await
+                    .unwrap_err();
+                assert!(error.to_string().contains("is loaded by the server"));
+                assert!(models_dir.join("mock").exists());
+
+                // Unregistered directories can still be removed once nothing uses them
+                server.abort();
+                remove_model(RemoveArgs { model_name: "mock".to_string(), yes: true }, &test_config())
+                    .await
+                    .unwrap();
+                assert!(!models_dir.join("mock").exists());
             });
         });
     }
 
+    #[test]
+    fn test_remove_model_stays_inside_models_dir() {
+        with_test_env(|| {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(async {
+                let models_dir = get_models_dir().unwrap();
+                fs::create_dir_all(models_dir.join("nested").join("weights")).unwrap();
+                fs::write(models_dir.join("nested").join("weights").join("a.bin"), [0u8; 10]).unwrap();
+                fs::write(models_dir.join("nested").join("config.json"), "{}").unwrap();
+
+                // A name that walks out of the models directory is not resolved
+                let outside = tempfile::TempDir::new().unwrap();
+                let victim = outside.path().join("victim");
+                fs::create_dir_all(&victim).unwrap();
+                let crafted = format!("../../../../../../..{}", victim.display());
+                assert!(remove_model(RemoveArgs { model_name: crafted, yes: true }, &test_config()).await.is_err());
+                assert!(victim.exists());
+
+                // A registered model outside the directory is only unregistered
+                let mut registry = ModelRegistry::default();
+                registry.models.insert("external".to_string(), ModelInfo {
+                    name: "external".to_string(),
+                    path: victim.to_string_lossy().to_string(),
+                    source: "local".to_string(),
+                    dimensions: None,
+                    size_mb: None,
+                    downloaded_at: "2024-01-01T00:00:00Z".to_string(),
+                    description: None,
+                });
+                save_model_registry(&registry).unwrap();
+                remove_model(RemoveArgs { model_name: "external".to_string(), yes: true }, &test_config())
+                    .await
+                    .unwrap();
+                assert!(victim.exists());
+                assert!(load_model_registry().unwrap().models.is_empty());
+
+                assert_eq!(directory_bytes(&models_dir.join("nested")), 12);
+                remove_model(RemoveArgs { model_name: "nested".to_string(), yes: true }, &test_config())
+                    .await
+                    .unwrap();
+                assert!(!models_dir.join("nested").exists());
+            });
+        });
+    }
+
+    #[test]
+    fn test_is_plain_model_name() {
+        assert!(is_plain_model_name("potion-32M"));
+        assert!(!is_plain_model_name(""));
+        assert!(!is_plain_model_name(".."));
+        assert!(!is_plain_model_name("../other"));
+        assert!(!is_plain_model_name("a/b"));
+        assert!(!is_plain_model_name("/etc"));
+    }
+
     #[test]
     fn test_distill_model_basic() {
         with_test_env(|| {
@@ -785,9 +1584,10 @@ mod tests {
                     output: "distilled-model".to_string(),
                     dims: Some(128),
                     force: true,
+                    verify: true,
                 };
                 // This will call the simulated distill function
-                let result = distill_model(args).await;
+                let result = distill_model(args, None, None).await;
                 assert!(result.is_ok());
             });
         });
@@ -808,12 +1608,72 @@ mod tests {
                 fs::create_dir_all(&model_path).unwrap(); // Create as directory
                 fs::write(model_path.join("config.json"), "dummy").unwrap();
                 
-                let result = download_model(args).await;
+                let result = download_model(args, None).await;
                 assert!(result.is_ok());
             });
         });
     }
 
+    #[test]
+    fn test_download_model_uses_models_dir() {
+        with_test_env(|| {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(async {
+                let models_dir = tempfile::TempDir::new().unwrap();
+                let args = DownloadArgs {
+                    model_name: "test-model".to_string(),
+                    alias: None,
+                    force: false,
+                };
+                download_model(args, Some(models_dir.path().to_path_buf())).await.unwrap();
+
+                let model_path = models_dir.path().join("test-model");
+                assert!(model_path.join("model.safetensors").exists());
+                assert!(!get_models_dir().unwrap().join("test-model").exists());
+                let registry = load_model_registry().unwrap();
+                assert_eq!(registry.models["test-model"].path, model_path.to_string_lossy());
+            });
+        });
+    }
+
+    #[test]
+    fn test_resolve_repo_id() {
+        assert_eq!(resolve_repo_id("potion-8M"), "minishlab/potion-base-8M");
+        assert_eq!(resolve_repo_id("potion-32M"), "minishlab/potion-base-32M");
+        assert_eq!(resolve_repo_id("org/custom"), "org/custom");
+    }
+
+    #[test]
+    fn test_verify_download() {
+        use sha2::{Digest, Sha256};
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let content = b"model weights";
+        let etag: String = Sha256::digest(content).iter().map(|b| format!("{:02x}", b)).collect();
+        let blob = tmp.path().join(&etag);
+        fs::write(&blob, content).unwrap();
+
+        let copy = tmp.path().join("model.safetensors");
+        fs::write(&copy, content).unwrap();
+        assert!(verify_download(&blob, &copy).is_ok());
+
+        // Same size, different content
+        fs::write(&copy, b"model weightz").unwrap();
+        let err = verify_download(&blob, &copy).unwrap_err();
+        assert!(err.to_string().contains("sha256"));
+
+        // Truncated copy
+        fs::write(&copy, b"model").unwrap();
+        let err = verify_download(&blob, &copy).unwrap_err();
+        assert!(err.to_string().contains("expected 13"));
+
+        // Non-LFS blobs are only checked by size
+        let small = tmp.path().join("config-blob");
+        fs::write(&small, b"{}").unwrap();
+        fs::write(&copy, b"[]").unwrap();
+        assert!(verify_download(&small, &copy).is_ok());
+    }
+
     #[test]
     fn test_load_model_registry_corrupt_file() {
         with_test_env(|| {
@@ -825,6 +1685,61 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_resolve_distill_dims() {
+        assert_eq!(resolve_distill_dims(Some(64), Some(128), "potion-8M"), (64, "--dims"));
+        assert_eq!(
+            resolve_distill_dims(None, Some(128), "potion-8M"),
+            (128, "models.default_distill_dims")
+        );
+        assert_eq!(resolve_distill_dims(None, Some(0), "potion-8M"), (8, "input model name"));
+        assert_eq!(resolve_distill_dims(None, None, "potion-32M"), (32, "input model name"));
+        assert_eq!(resolve_distill_dims(None, None, "org/model"), (32, "built-in default"));
+    }
+
+    #[test]
+    fn test_distill_model_registers_in_models_dir() {
+        with_test_env(|| {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(async {
+                let models_dir = tempfile::TempDir::new().unwrap();
+                let args = DistillArgs {
+                    input: "some/teacher".to_string(),
+                    output: "student".to_string(),
+                    dims: None,
+                    force: false,
+                    verify: false,
+                };
+                distill_model(args, Some(models_dir.path().to_path_buf()), Some(64)).await.unwrap();
+
+                let model_path = models_dir.path().join("student");
+                assert!(model_path.join("model.safetensors").exists());
+
+                let registry = load_model_registry().unwrap();
+                let info = &registry.models["student"];
+                assert_eq!(info.source, "distilled");
+                assert_eq!(info.dimensions, Some(64));
+                assert_eq!(info.path, model_path.to_string_lossy());
+                assert!(chrono::DateTime::parse_from_rfc3339(&info.downloaded_at).is_ok());
+                assert!(!get_registry_path().unwrap().with_extension("json.tmp").exists());
+
+                // `model list` sees it with its registry source
+                let listed = collect_installed_models(models_dir.path(), &registry, "potion-32M");
+                assert_eq!(listed.len(), 1);
+                assert_eq!(listed[0].source.as_deref(), Some("distilled"));
+
+                // ...and `model remove` deletes both the files and the entry
+                let mut config = test_config();
+                config.models.models_dir = Some(models_dir.path().to_string_lossy().to_string());
+                remove_model(RemoveArgs { model_name: "student".to_string(), yes: true }, &config)
+                    .await
+                    .unwrap();
+                assert!(!model_path.exists());
+                assert!(!load_model_registry().unwrap().models.contains_key("student"));
+            });
+        });
+    }
+
     #[test]
     fn test_save_model_registry_io_error() {
         // Simulate unwritable directory
@@ -844,7 +1759,7 @@ mod tests {
         with_test_env(|| {
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async {
-                let result = handle_model_command(ModelAction::List, None).await;
+                let result = handle_model_command(ModelAction::List(crate::cli::ListArgs { json: false }), None).await;
                 assert!(result.is_ok());
             });
         });
@@ -890,6 +1805,7 @@ mod tests {
                     output: "output".to_string(),
                     dims: Some(64),
                     force: false,
+                    verify: true,
                 };
                 let result = handle_model_command(ModelAction::Distill(args), None).await;
                 assert!(result.is_ok());
@@ -921,7 +1837,7 @@ mod tests {
                     yes: true,
                 };
                 let result = handle_model_command(ModelAction::Remove(args), None).await;
-                assert!(result.is_ok());
+                assert!(result.is_err());
             });
         });
     }
@@ -936,7 +1852,7 @@ mod tests {
                     alias: None,
                     force: false,
                 };
-                let result = download_model(args).await;
+                let result = download_model(args, None).await;
                 assert!(result.is_ok());
             });
         });
@@ -957,7 +1873,7 @@ mod tests {
                     alias: None,
                     force: false,
                 };
-                let result = download_model(args).await;
+                let result = download_model(args, None).await;
                 assert!(result.is_ok());
             });
         });
@@ -973,8 +1889,9 @@ mod tests {
                     output: "output2".to_string(),
                     dims: Some(256),
                     force: false,
+                    verify: true,
                 };
-                let result = distill_model(args).await;
+                let result = distill_model(args, None, None).await;
                 assert!(result.is_ok());
             });
         });
@@ -985,12 +1902,13 @@ mod tests {
         with_test_env(|| {
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async {
+                // Not installed, so it fails before prompting
                 let args = RemoveArgs {
                     model_name: "test-no-confirm".to_string(),
                     yes: false,
                 };
-                let result = remove_model(args).await;
-                assert!(result.is_ok());
+                let result = remove_model(args, &test_config()).await;
+                assert!(result.is_err());
             });
         });
     }
@@ -1012,7 +1930,7 @@ mod tests {
             
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async {
-                let result = list_models().await;
+                let result = list_models(false, None, "potion-32M").await;
                 assert!(result.is_ok());
             });
         });
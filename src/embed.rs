@@ -1,10 +1,286 @@
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use model2vec_rs::model::StaticModel;
-use std::path::PathBuf;
+use safetensors::{Dtype, SafeTensors};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokenizers::Tokenizer;
+
+/// Tokens per text considered when pooling, as in Model2Vec's own encode.
+const MAX_TOKENS: usize = 512;
+
+/// How an [`Embedder`] combines token embeddings into one text embedding.
+///
+/// Model2Vec models are distilled for mean pooling, and both bundled models
+/// (`potion-8M` and `potion-32M`) recommend [`PoolingStrategy::Mean`]. The other
+/// strategies are for tuning custom distilled models: `max` keeps the strongest
+/// signal of each dimension, which can help code and keyword-heavy text where a
+/// single identifier matters, while `cls` and `last_token` suit models distilled
+/// from encoders that summarize a text into its first or last token. Static
+/// models add no special tokens, so `cls` is simply the first token of the text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PoolingStrategy {
+    /// Average of the token embeddings
+    #[default]
+    Mean,
+    /// Element-wise maximum over the token embeddings
+    Max,
+    /// Embedding of the first token
+    Cls,
+    /// Embedding of the last token
+    LastToken,
+}
+
+impl PoolingStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PoolingStrategy::Mean => "mean",
+            PoolingStrategy::Max => "max",
+            PoolingStrategy::Cls => "cls",
+            PoolingStrategy::LastToken => "last_token",
+        }
+    }
+}
+
+impl fmt::Display for PoolingStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PoolingStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "mean" => Ok(PoolingStrategy::Mean),
+            "max" => Ok(PoolingStrategy::Max),
+            "cls" => Ok(PoolingStrategy::Cls),
+            "last_token" => Ok(PoolingStrategy::LastToken),
+            other => Err(anyhow!(
+                "Invalid pooling strategy '{}'. Use: mean, max, cls, last_token",
+                other
+            )),
+        }
+    }
+}
+
+/// Post-processing applied to the inputs and outputs of an encode.
+///
+/// The HTTP API, the MCP tools and [`Embedder::encode_batch`] all go through
+/// these options, so a request's embeddings come out the same whichever way
+/// they were asked for. The default leaves inputs and embeddings untouched.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EncodeOptions {
+    /// Rescale every embedding to unit (L2) length; all-zero embeddings stay zero
+    pub normalize: bool,
+    /// Keep only the first N values of each embedding, as for Matryoshka-trained models
+    pub truncate_dims: Option<usize>,
+    /// Cut each input to its first N characters before encoding
+    pub max_length: Option<usize>,
+}
+
+impl EncodeOptions {
+    /// Options for an embeddings request's `dimensions`, `normalize` and
+    /// `max_length` fields.
+    ///
+    /// Truncated embeddings are rescaled to unit length unless `normalize` is
+    /// explicitly `false`, as OpenAI's `dimensions` does.
+    pub fn from_request(dimensions: Option<usize>, normalize: Option<bool>, max_length: Option<usize>) -> Self {
+        Self {
+            normalize: normalize.unwrap_or(dimensions.is_some()),
+            truncate_dims: dimensions,
+            max_length,
+        }
+    }
+
+    /// `text` cut to `max_length` characters.
+    pub fn truncate_input<'a>(&self, text: &'a str) -> &'a str {
+        match self.max_length.and_then(|max| text.char_indices().nth(max)) {
+            Some((end, _)) => &text[..end],
+            None => text,
+        }
+    }
+
+    /// Truncate `embeddings` to `truncate_dims`, then normalize them if asked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `truncate_dims` is zero or larger than the native size.
+    pub fn apply(&self, embeddings: &mut [Vec<f32>]) -> Result<()> {
+        if let Some(dims) = self.truncate_dims {
+            if dims == 0 {
+                return Err(anyhow!("dimensions must be at least 1"));
+            }
+            for embedding in embeddings.iter_mut() {
+                if dims > embedding.len() {
+                    return Err(anyhow!(
+                        "Requested {} dimensions, but the model's native dimension is {}",
+                        dims,
+                        embedding.len()
+                    ));
+                }
+                embedding.truncate(dims);
+            }
+        }
+        if self.normalize {
+            for embedding in embeddings.iter_mut() {
+                let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+                if norm > 0.0 {
+                    embedding.iter_mut().for_each(|v| *v /= norm);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where a model's files live.
+enum ModelSource {
+    Local(PathBuf),
+    Hub(String),
+}
+
+impl ModelSource {
+    /// Path to the model file `name`, fetched from the hub cache if needed.
+    fn file(&self, name: &str) -> Result<PathBuf> {
+        match self {
+            ModelSource::Local(dir) => Ok(dir.join(name)),
+            ModelSource::Hub(repo) => Ok(hf_hub::api::sync::Api::new()?.model(repo.clone()).get(name)?),
+        }
+    }
+}
+
+/// A model's token embedding table, for pooling strategies other than mean.
+struct TokenEmbeddings {
+    tokenizer: Tokenizer,
+    /// Row-major `(vocabulary, dims)` matrix
+    embeddings: Vec<f32>,
+    dims: usize,
+    /// Per-token scale of quantized vocabularies
+    weights: Option<Vec<f32>>,
+    /// Token id to embedding row, for quantized vocabularies
+    mapping: Option<Vec<usize>>,
+    unk_id: Option<u32>,
+    normalize: bool,
+}
+
+impl TokenEmbeddings {
+    /// Read the tokenizer, config and weights the same way Model2Vec does.
+    fn load(source: &ModelSource) -> Result<Self> {
+        let tokenizer_path = source.file("tokenizer.json")?;
+        let tokenizer = Tokenizer::from_file(&tokenizer_path).map_err(|e| anyhow!("Failed to load tokenizer: {}", e))?;
+        let spec: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&tokenizer_path)?)?;
+        let unk_id = spec["model"]["unk_token"]
+            .as_str()
+            .and_then(|unk| tokenizer.token_to_id(unk));
+
+        let config: serde_json::Value = serde_json::from_reader(std::fs::File::open(source.file("config.json")?)?)
+            .context("Failed to parse config.json")?;
+        let normalize = config.get("normalize").and_then(|v| v.as_bool()).unwrap_or(true);
+
+        let bytes = std::fs::read(source.file("model.safetensors")?)?;
+        let tensors = SafeTensors::deserialize(&bytes).context("Failed to parse model.safetensors")?;
+        let tensor = tensors
+            .tensor("embeddings")
+            .or_else(|_| tensors.tensor("0"))
+            .context("embeddings tensor not found")?;
+        let [_, dims]: [usize; 2] = tensor
+            .shape()
+            .try_into()
+            .map_err(|_| anyhow!("embeddings tensor is not 2-D"))?;
+        let embeddings = decode_floats(tensor.dtype(), tensor.data())?;
+        let weights = match tensors.tensor("weights") {
+            Ok(tensor) => Some(decode_floats(tensor.dtype(), tensor.data())?),
+            Err(_) => None,
+        };
+        let mapping = tensors.tensor("mapping").ok().map(|tensor| {
+            tensor
+                .data()
+                .chunks_exact(4)
+                .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+                .collect()
+        });
+
+        Ok(Self { tokenizer, embeddings, dims, weights, mapping, unk_id, normalize })
+    }
+
+    /// Pool the (weighted) embeddings of `text`'s tokens with `pooling`.
+    fn pool(&self, text: &str, pooling: PoolingStrategy) -> Vec<f32> {
+        let mut ids: Vec<u32> = self
+            .tokenizer
+            .encode(text, false)
+            .map(|encoding| encoding.get_ids().to_vec())
+            .unwrap_or_default();
+        ids.retain(|id| Some(*id) != self.unk_id);
+        ids.truncate(MAX_TOKENS);
+
+        let rows: Vec<Vec<f32>> = ids.iter().filter_map(|&id| self.row(id as usize)).collect();
+        let mut pooled = match pooling {
+            PoolingStrategy::Mean => {
+                let mut sum = vec![0.0; self.dims];
+                for row in &rows {
+                    sum.iter_mut().zip(row).for_each(|(s, v)| *s += v);
+                }
+                let count = rows.len().max(1) as f32;
+                sum.iter_mut().for_each(|s| *s /= count);
+                sum
+            }
+            PoolingStrategy::Max => rows
+                .iter()
+                .cloned()
+                .reduce(|mut max, row| {
+                    max.iter_mut().zip(&row).for_each(|(m, v)| *m = m.max(*v));
+                    max
+                })
+                .unwrap_or_else(|| vec![0.0; self.dims]),
+            PoolingStrategy::Cls => rows.first().cloned().unwrap_or_else(|| vec![0.0; self.dims]),
+            PoolingStrategy::LastToken => rows.last().cloned().unwrap_or_else(|| vec![0.0; self.dims]),
+        };
+
+        if self.normalize {
+            let norm = pooled.iter().map(|v| v * v).sum::<f32>().sqrt().max(1e-12);
+            pooled.iter_mut().for_each(|v| *v /= norm);
+        }
+        pooled
+    }
+
+    /// Embedding of token `id`, scaled by its weight.
+    fn row(&self, id: usize) -> Option<Vec<f32>> {
+        let row = self.mapping.as_ref().map_or(id, |mapping| *mapping.get(id).unwrap_or(&id));
+        let scale = self.weights.as_ref().map_or(1.0, |weights| *weights.get(id).unwrap_or(&1.0));
+        let values = self.embeddings.get(row * self.dims..(row + 1) * self.dims)?;
+        Some(values.iter().map(|v| v * scale).collect())
+    }
+}
+
+/// Decode a little-endian float tensor to `f32`.
+fn decode_floats(dtype: Dtype, data: &[u8]) -> Result<Vec<f32>> {
+    Ok(match dtype {
+        Dtype::F64 => data
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()) as f32)
+            .collect(),
+        Dtype::F32 => data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+        Dtype::F16 => data
+            .chunks_exact(2)
+            .map(|b| half::f16::from_le_bytes([b[0], b[1]]).to_f32())
+            .collect(),
+        Dtype::I8 => data.iter().map(|&b| f32::from(b as i8)).collect(),
+        other => return Err(anyhow!("Unsupported tensor dtype: {:?}", other)),
+    })
+}
 
 /// A high-performance static text embedder using Model2Vec.
 pub struct Embedder {
     model: StaticModel,
+    source: ModelSource,
+    pooling: PoolingStrategy,
+    /// Loaded by [`Embedder::with_pooling`] for strategies other than mean
+    tokens: Option<TokenEmbeddings>,
 }
 
 impl Embedder {
@@ -18,41 +294,126 @@ impl Embedder {
     ///
     /// * `model_name` - Name of the model (e.g. "potion-32M", "minishlab/potion-base-32M") or path.
     pub fn new(model_name: &str) -> Result<Self> {
-        let model_path = resolve_model_path(model_name)?;
+        Self::with_models_dir(model_name, default_models_dir()?)
+    }
+
+    /// Create an Embedder that looks for local models in `models_dir` instead
+    /// of `~/.static-embedding-tool/models`.
+    ///
+    /// Remote fallbacks behave as in [`Embedder::new`].
+    ///
+    /// # Arguments
+    ///
+    /// * `model_name` - Name of the model, or path relative to `models_dir`.
+    /// * `models_dir` - Directory local models are stored in.
+    pub fn with_models_dir(model_name: &str, models_dir: impl AsRef<Path>) -> Result<Self> {
+        let model_path = models_dir.as_ref().join(model_name);
         
-        let model = if model_path.exists() {
-            StaticModel::from_pretrained(&model_path, None, None, None)
-                .map_err(|e| anyhow!("Failed to load model from path: {}", e))?
+        let (model, source) = if model_path.exists() {
+            let model = StaticModel::from_pretrained(&model_path, None, None, None)
+                .map_err(|e| anyhow!("Failed to load model from path: {}", e))?;
+            (model, ModelSource::Local(model_path))
         } else {
             // Try as HF ID directly
             let hf_id = resolve_hf_id(model_name);
-            StaticModel::from_pretrained(hf_id, None, None, None)
-                .map_err(|e| anyhow!("Failed to load model '{}' (tried local path and HF): {}", model_name, e))?
+            let model = StaticModel::from_pretrained(hf_id, None, None, None)
+                .map_err(|e| anyhow!("Failed to load model '{}' (tried local path and HF): {}", model_name, e))?;
+            (model, ModelSource::Hub(hf_id.to_string()))
         };
 
-        Ok(Self { model })
+        Ok(Self {
+            model,
+            source,
+            pooling: PoolingStrategy::default(),
+            tokens: None,
+        })
+    }
+
+    /// Use `pooling` to combine token embeddings from now on.
+    ///
+    /// Strategies other than [`PoolingStrategy::Mean`] read the model's token
+    /// embedding table, which is loaded here.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model's tokenizer, config or weights cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use static_embedding_tool::embed::{Embedder, PoolingStrategy};
+    ///
+    /// let embedder = Embedder::new("my-code-model")?.with_pooling(PoolingStrategy::Max)?;
+    /// let embedding = embedder.embed("fn main() {}");
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn with_pooling(mut self, pooling: PoolingStrategy) -> Result<Self> {
+        if pooling != PoolingStrategy::Mean && self.tokens.is_none() {
+            self.tokens = Some(TokenEmbeddings::load(&self.source)?);
+        }
+        self.pooling = pooling;
+        Ok(self)
+    }
+
+    /// The pooling strategy in use.
+    pub fn pooling(&self) -> PoolingStrategy {
+        self.pooling
     }
 
     /// Generate embedding for a single text string.
     pub fn embed(&self, text: &str) -> Vec<f32> {
-        self.model.encode(&[text.to_string()])[0].clone()
+        self.embed_batch(&[text.to_string()]).swap_remove(0)
     }
 
     /// Generate embeddings for a batch of texts.
     pub fn embed_batch(&self, texts: &[String]) -> Vec<Vec<f32>> {
-        self.model.encode(texts)
+        match (&self.tokens, self.pooling) {
+            (Some(tokens), pooling) if pooling != PoolingStrategy::Mean => {
+                texts.iter().map(|text| tokens.pool(text, pooling)).collect()
+            }
+            // Model2Vec's own encode is mean pooling
+            _ => self.model.encode(texts),
+        }
+    }
+
+    /// Generate embeddings for a batch of texts, post-processed by `opts`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `opts.truncate_dims` is zero or larger than the
+    /// model's dimension.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use static_embedding_tool::embed::{EncodeOptions, Embedder};
+    ///
+    /// let embedder = Embedder::new("potion-32M")?;
+    /// let opts = EncodeOptions { normalize: true, truncate_dims: Some(128), max_length: Some(2000) };
+    /// let embeddings = embedder.encode_batch(&["Hello world".to_string()], opts)?;
+    /// assert_eq!(embeddings[0].len(), 128);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn encode_batch(&self, texts: &[String], opts: EncodeOptions) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = if opts.max_length.is_some() {
+            let texts: Vec<String> = texts.iter().map(|text| opts.truncate_input(text).to_string()).collect();
+            self.embed_batch(&texts)
+        } else {
+            self.embed_batch(texts)
+        };
+        opts.apply(&mut embeddings)?;
+        Ok(embeddings)
     }
 }
 
-fn resolve_model_path(model_name: &str) -> Result<PathBuf> {
+fn default_models_dir() -> Result<PathBuf> {
     let home = std::env::var("HOME")
         .or_else(|_| std::env::var("USERPROFILE"))
         .map_err(|_| anyhow!("Could not determine home directory"))?;
     
     Ok(PathBuf::from(home)
         .join(".static-embedding-tool")
-        .join("models")
-        .join(model_name))
+        .join("models"))
 }
 
 fn resolve_hf_id(model_name: &str) -> &str {
@@ -62,3 +423,156 @@ fn resolve_hf_id(model_name: &str) -> &str {
         other => other,
     }
 }
+
+// The model fixture lives with the server's test helpers
+#[cfg(all(test, feature = "mcp"))]
+mod tests {
+    use super::*;
+
+    fn normalized(values: &[f32]) -> Vec<f32> {
+        let norm = values.iter().map(|v| v * v).sum::<f32>().sqrt();
+        values.iter().map(|v| v / norm).collect()
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_embedder_pooling_strategies() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        crate::server::test_utils::write_test_model(&tmp.path().join("tiny"), 4);
+        let load = || Embedder::with_models_dir("tiny", tmp.path()).unwrap();
+
+        // Token rows: hello = [5, 6, 7, 1], world = [2, 3, 4, 5]
+        let mean = load();
+        assert_eq!(mean.pooling(), PoolingStrategy::Mean);
+        assert_close(&mean.embed("hello world"), &normalized(&[3.5, 4.5, 5.5, 3.0]));
+
+        let pooled = |pooling| load().with_pooling(pooling).unwrap().embed("hello world");
+        assert_close(&pooled(PoolingStrategy::Mean), &mean.embed("hello world"));
+        assert_close(&pooled(PoolingStrategy::Max), &normalized(&[5.0, 6.0, 7.0, 5.0]));
+        assert_close(&pooled(PoolingStrategy::Cls), &normalized(&[5.0, 6.0, 7.0, 1.0]));
+        assert_close(&pooled(PoolingStrategy::LastToken), &normalized(&[2.0, 3.0, 4.0, 5.0]));
+
+        // Unknown tokens are skipped, as in Model2Vec's mean
+        let max = load().with_pooling(PoolingStrategy::Max).unwrap();
+        assert_eq!(max.embed_batch(&["hello".to_string(), "nothing known".to_string()])[1], vec![0.0; 4]);
+    }
+
+    fn norm(values: &[f32]) -> f32 {
+        values.iter().map(|v| v * v).sum::<f32>().sqrt()
+    }
+
+    /// Random embeddings of `dims` values, some of them all zero.
+    fn random_embeddings(count: usize, dims: usize) -> Vec<Vec<f32>> {
+        (0..count)
+            .map(|i| match i % 10 {
+                0 => vec![0.0; dims],
+                _ => (0..dims).map(|_| rand::random_range(-10.0..10.0)).collect(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_options_truncate_and_normalize() {
+        let mut embeddings = vec![vec![3.0, 4.0, 12.0], vec![0.0, 0.0, 1.0]];
+        EncodeOptions::default().apply(&mut embeddings).unwrap();
+        assert_eq!(embeddings[0], vec![3.0, 4.0, 12.0]);
+
+        let opts = EncodeOptions::from_request(Some(2), None, None);
+        opts.apply(&mut embeddings).unwrap();
+        assert_eq!(embeddings, vec![vec![0.6, 0.8], vec![0.0, 0.0]]);
+
+        let err = opts.apply(&mut [vec![1.0]]).unwrap_err();
+        assert!(err.to_string().contains("native dimension is 1"));
+        assert!(EncodeOptions::from_request(Some(0), None, None).apply(&mut embeddings).is_err());
+    }
+
+    #[test]
+    fn test_encode_options_normalize_gives_unit_norm() {
+        let opts = EncodeOptions { normalize: true, ..Default::default() };
+        for dims in [1, 2, 7, 64, 256] {
+            let original = random_embeddings(50, dims);
+            let mut embeddings = original.clone();
+            opts.apply(&mut embeddings).unwrap();
+            for (embedding, original) in embeddings.iter().zip(&original) {
+                if norm(original) == 0.0 {
+                    assert_eq!(embedding, original);
+                } else {
+                    assert!((norm(embedding) - 1.0).abs() < 1e-5, "{:?}", embedding);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_options_truncation_keeps_prefix() {
+        let original = random_embeddings(20, 32);
+        for dims in 1..=32 {
+            let mut embeddings = original.clone();
+            let opts = EncodeOptions::from_request(Some(dims), Some(false), None);
+            opts.apply(&mut embeddings).unwrap();
+            for (embedding, original) in embeddings.iter().zip(&original) {
+                assert_eq!(embedding.as_slice(), &original[..dims]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_options_truncate_input() {
+        let opts = EncodeOptions { max_length: Some(3), ..Default::default() };
+        assert_eq!(opts.truncate_input("hello"), "hel");
+        assert_eq!(opts.truncate_input("hé"), "hé");
+        // Characters, not bytes
+        assert_eq!(opts.truncate_input("ééééé"), "ééé");
+        assert_eq!(EncodeOptions::default().truncate_input("hello"), "hello");
+    }
+
+    #[test]
+    fn test_embedder_encode_batch() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        crate::server::test_utils::write_test_model(&tmp.path().join("tiny"), 4);
+        let embedder = Embedder::with_models_dir("tiny", tmp.path()).unwrap();
+        let texts = vec!["hello world".to_string(), "world".to_string(), "nothing known".to_string()];
+        let full = embedder.embed_batch(&texts);
+
+        let normalized = embedder
+            .encode_batch(&texts, EncodeOptions::from_request(Some(2), None, None))
+            .unwrap();
+        for (embedding, full) in normalized.iter().zip(&full) {
+            assert_eq!(embedding.len(), 2);
+            if norm(full) > 0.0 {
+                assert!((norm(embedding) - 1.0).abs() < 1e-5);
+            }
+        }
+
+        for dims in 1..=4 {
+            let truncated = embedder
+                .encode_batch(&texts, EncodeOptions { truncate_dims: Some(dims), ..Default::default() })
+                .unwrap();
+            for (embedding, full) in truncated.iter().zip(&full) {
+                assert_eq!(embedding.as_slice(), &full[..dims]);
+            }
+        }
+
+        // "hello world" cut to 5 characters is just "hello"
+        let cut = embedder
+            .encode_batch(&texts[..1], EncodeOptions { max_length: Some(5), ..Default::default() })
+            .unwrap();
+        assert_close(&cut[0], &embedder.embed("hello"));
+
+        assert!(embedder.encode_batch(&texts, EncodeOptions::from_request(Some(5), None, None)).is_err());
+    }
+
+    #[test]
+    fn test_pooling_strategy_parse() {
+        assert_eq!("last-token".parse::<PoolingStrategy>().unwrap(), PoolingStrategy::LastToken);
+        assert_eq!("CLS".parse::<PoolingStrategy>().unwrap(), PoolingStrategy::Cls);
+        assert!("sum".parse::<PoolingStrategy>().is_err());
+        assert_eq!(PoolingStrategy::default().to_string(), "mean");
+    }
+}
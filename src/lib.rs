@@ -13,4 +13,4 @@ pub mod resources;
 pub mod utils;
 pub mod embed;
 
-pub use embed::Embedder;
\ No newline at end of file
+pub use embed::{Embedder, EncodeOptions, PoolingStrategy};
\ No newline at end of file
@@ -1,5 +1,16 @@
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Run the CLI
-    static_embedding_tool::cli::run_cli().await
-}
\ No newline at end of file
+async fn main() -> std::process::ExitCode {
+    // Run the CLI, reporting failures with a non-zero exit code
+    match static_embedding_tool::cli::run_cli().await {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            // `server status` has already reported what its exit code stands for
+            #[cfg(feature = "mcp")]
+            if let Some(status) = e.downcast_ref::<static_embedding_tool::cli::StatusExit>() {
+                return std::process::ExitCode::from(status.code());
+            }
+            eprintln!("Error: {}", e);
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
@@ -1,4 +1,36 @@
+use rmcp::ErrorData as McpError;
 use rmcp::model::{Annotated, RawResource, ReadResourceResult, Resource, ResourceContents};
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::server::state::AppState;
+
+/// Errors producing or looking up a resource.
+#[derive(Error, Debug)]
+pub enum ResourceError {
+    /// No provider serves the requested URI.
+    #[error("Unknown resource: {0}")]
+    NotFound(String),
+
+    /// Content could not be serialized.
+    #[error("Failed to serialize resource content: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// Content could not be read.
+    #[error("Failed to read resource content: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl From<ResourceError> for McpError {
+    fn from(error: ResourceError) -> Self {
+        match &error {
+            ResourceError::NotFound(uri) => {
+                McpError::resource_not_found(error.to_string(), Some(serde_json::json!({ "uri": uri })))
+            }
+            _ => McpError::internal_error(error.to_string(), None),
+        }
+    }
+}
 
 /// Trait for MCP resource providers.
 ///
@@ -20,7 +52,11 @@ pub trait ResourceProvider {
     /// Generate the resource content.
     ///
     /// This may be static text or dynamically generated based on server state.
-    fn content(&self) -> String;
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if dynamic content cannot be read or serialized.
+    fn content(&self) -> Result<String, ResourceError>;
 
     /// Get additional resource metadata (optional).
     fn metadata(&self) -> Option<serde_json::Value> {
@@ -28,8 +64,8 @@ pub trait ResourceProvider {
     }
 
     /// Get the resource metadata
-    fn meta(&self) -> Resource {
-        let size = self.content().len() as u32;
+    fn meta(&self) -> Result<Resource, ResourceError> {
+        let size = self.content()?.len() as u32;
         let raw = RawResource {
             size: Some(size),
             uri: self.uri().to_string(),
@@ -40,13 +76,13 @@ pub trait ResourceProvider {
             title: Some(self.name().to_string()),
             meta: None,
         };
-        Annotated::new(raw, None)
+        Ok(Annotated::new(raw, None))
     }
 
-    fn read(&self) -> ReadResourceResult {
-        ReadResourceResult {
-            contents: vec![ResourceContents::text(self.content(), self.uri())],
-        }
+    fn read(&self) -> Result<ReadResourceResult, ResourceError> {
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(self.content()?, self.uri())],
+        })
     }
 }
 
@@ -70,45 +106,230 @@ impl ResourceProvider for InstructionsResource {
         "Full instructions and guidelines for the Static Embedding Tool MCP server"
     }
 
-    fn content(&self) -> String {
+    fn content(&self) -> Result<String, ResourceError> {
         // Prefer the repository's `copilot-instructions.md` under `.github`.
         // Fall back to a crate-local `instructions.md` if present.
         // Using `include_str!` ensures the content is compiled in and available
         // at runtime without requiring file I/O.
-        include_str!("../../.github/copilot-instructions.md").to_string()
+        Ok(include_str!("../../.github/copilot-instructions.md").to_string())
+    }
+}
+
+// Metrics resource
+pub struct MetricsResource {
+    state: Arc<AppState>,
+}
+
+impl ResourceProvider for MetricsResource {
+    fn uri(&self) -> &'static str {
+        "metrics://server"
+    }
+
+    fn name(&self) -> &'static str {
+        "Server Metrics"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn description(&self) -> &'static str {
+        "Uptime plus the request, error and tool counters and connection gauges served by GET /metrics"
+    }
+
+    fn content(&self) -> Result<String, ResourceError> {
+        let uptime_secs = self.state.startup_time.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+        // Same snapshot as the Prometheus endpoint, with the original metric names
+        let mut metrics = serde_json::to_value(crate::server::metrics::snapshot())?;
+        if let Some(fields) = metrics.as_object_mut() {
+            fields.insert("uptime_secs".to_string(), uptime_secs.into());
+        }
+        Ok(serde_json::to_string_pretty(&metrics)?)
+    }
+}
+
+// API documentation resource
+pub struct ApiDocsResource;
+
+impl ResourceProvider for ApiDocsResource {
+    fn uri(&self) -> &'static str {
+        "docs://api"
+    }
+
+    fn name(&self) -> &'static str {
+        "HTTP API Reference"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "text/markdown"
+    }
+
+    fn description(&self) -> &'static str {
+        "Endpoints, request fields and responses of the OpenAI-compatible HTTP API"
+    }
+
+    fn content(&self) -> Result<String, ResourceError> {
+        Ok(include_str!("api.md").to_string())
+    }
+}
+
+// Current configuration resource
+pub struct ConfigResource {
+    state: Arc<AppState>,
+}
+
+impl ResourceProvider for ConfigResource {
+    fn uri(&self) -> &'static str {
+        "config://current"
+    }
+
+    fn name(&self) -> &'static str {
+        "Current Configuration"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn description(&self) -> &'static str {
+        "Settings the running server uses: default model, output precision, readiness policy, retries, cache size and input limits"
+    }
+
+    fn content(&self) -> Result<String, ResourceError> {
+        let state = &self.state;
+        let mut models = state.models.names();
+        models.sort();
+        let config = serde_json::json!({
+            "default_model": state.default_model,
+            "output_dtype": state.default_output_dtype.as_str(),
+            "ready_policy": state.ready_policy.as_str(),
+            "encode_retries": state.encode_retries,
+            "embedding_cache_size": state.models.cache().capacity(),
+            "max_batch_inputs": state.input_limits.max_batch_inputs,
+            "max_input_chars": state.input_limits.max_input_chars,
+            "models": models,
+        });
+        Ok(serde_json::to_string_pretty(&config)?)
+    }
+}
+
+// Loaded models resource
+pub struct ModelsResource {
+    state: Arc<AppState>,
+}
+
+impl ResourceProvider for ModelsResource {
+    fn uri(&self) -> &'static str {
+        "models://list"
+    }
+
+    fn name(&self) -> &'static str {
+        "Loaded Models"
+    }
+
+    fn mime_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn description(&self) -> &'static str {
+        "Models currently loaded, with their embedding dimensions and which one is the default"
+    }
+
+    fn content(&self) -> Result<String, ResourceError> {
+        let models = &self.state.models;
+        let mut names = models.names();
+        names.sort();
+        let entries: Vec<serde_json::Value> = names
+            .into_iter()
+            .filter_map(|name| {
+                // Skips models unloaded since the names were read
+                let dimensions = models.dimensions(&name)?;
+                Some(serde_json::json!({
+                    "name": name,
+                    "dimensions": dimensions,
+                    "default": name == self.state.default_model,
+                }))
+            })
+            .collect();
+        let list = serde_json::json!({
+            "models": entries,
+            "count": entries.len(),
+            "default_model": self.state.default_model,
+        });
+        Ok(serde_json::to_string_pretty(&list)?)
     }
 }
 
-/// Registry of all available resources
-pub struct ResourceRegistry;
+/// Registry of all available resources, reading live values from the server state
+pub struct ResourceRegistry {
+    state: Arc<AppState>,
+}
 
 impl ResourceRegistry {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+
     /// Get all available resource providers
-    pub fn get_providers() -> Vec<Box<dyn ResourceProvider>> {
-        vec![Box::new(InstructionsResource)]
+    pub fn get_providers(&self) -> Vec<Box<dyn ResourceProvider>> {
+        vec![
+            Box::new(InstructionsResource),
+            Box::new(ApiDocsResource),
+            Box::new(ConfigResource { state: Arc::clone(&self.state) }),
+            Box::new(ModelsResource { state: Arc::clone(&self.state) }),
+            Box::new(MetricsResource { state: Arc::clone(&self.state) }),
+        ]
     }
 
     /// Find a resource provider by URI
-    pub fn find_by_uri(uri: &str) -> Option<Box<dyn ResourceProvider>> {
-        Self::get_providers().into_iter().find(|p| p.uri() == uri)
+    pub fn find_by_uri(&self, uri: &str) -> Option<Box<dyn ResourceProvider>> {
+        self.get_providers().into_iter().find(|p| p.uri() == uri)
     }
-}
 
-/// List all available resources
-pub fn list_resources() -> Vec<Resource> {
-    ResourceRegistry::get_providers()
-        .into_iter()
-        .map(|p| p.meta())
-        .collect()
-}
+    /// List all available resources
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error a provider reports producing its content.
+    pub fn list_resources(&self) -> Result<Vec<Resource>, ResourceError> {
+        self.get_providers()
+            .into_iter()
+            .map(|p| p.meta())
+            .collect()
+    }
 
-pub fn read_resource(uri: &str) -> Option<ReadResourceResult> {
-    ResourceRegistry::find_by_uri(uri).map(|provider| provider.read())
+    /// Read the resource at `uri`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ResourceError::NotFound`] if no provider serves `uri`, or the
+    /// provider's error producing its content.
+    pub fn read_resource(&self, uri: &str) -> Result<ReadResourceResult, ResourceError> {
+        self.find_by_uri(uri)
+            .ok_or_else(|| ResourceError::NotFound(uri.to_string()))?
+            .read()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::server::mock::MockModel;
+    use crate::server::state::Model;
+    use std::collections::HashMap;
+
+    fn test_registry() -> ResourceRegistry {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("potion-32M".to_string(), Arc::new(MockModel::new("potion-32M".to_string(), 8)));
+        models.insert("custom".to_string(), Arc::new(MockModel::new("custom".to_string(), 4)));
+        let mut state = AppState::from_models(models, "potion-32M");
+        state.encode_retries = 2;
+        ResourceRegistry::new(Arc::new(state))
+    }
+
+    fn read_json(registry: &ResourceRegistry, uri: &str) -> serde_json::Value {
+        serde_json::from_str(&registry.find_by_uri(uri).unwrap().content().unwrap()).unwrap()
+    }
 
     #[test]
     fn test_instructions_resource_metadata() {
@@ -120,7 +341,7 @@ mod tests {
         assert_eq!(resource.description(), "Full instructions and guidelines for the Static Embedding Tool MCP server");
 
         // Content should not be empty
-        let content = resource.content();
+        let content = resource.content().unwrap();
         assert!(!content.is_empty());
         assert!(content.contains("#")); // Should contain markdown headers
     }
@@ -128,7 +349,7 @@ mod tests {
     #[test]
     fn test_instructions_resource_meta() {
         let resource = InstructionsResource;
-        let meta = resource.meta();
+        let meta = resource.meta().unwrap();
         
         assert_eq!(meta.raw.uri, "embedtool://instructions");
         assert_eq!(meta.raw.name, "Static Embedding Tool Instructions");
@@ -139,7 +360,7 @@ mod tests {
     }    #[test]
     fn test_instructions_resource_read() {
         let resource = InstructionsResource;
-        let result = resource.read();
+        let result = resource.read().unwrap();
 
         assert_eq!(result.contents.len(), 1);
         // Since we can't easily pattern match on the ResourceContents enum
@@ -149,44 +370,141 @@ mod tests {
 
     #[test]
     fn test_resource_registry_get_providers() {
-        let providers = ResourceRegistry::get_providers();
-        assert_eq!(providers.len(), 1);
-
-        // Should contain InstructionsResource
-        let provider = &providers[0];
-        assert_eq!(provider.uri(), "embedtool://instructions");
+        let providers = test_registry().get_providers();
+        let uris: Vec<&str> = providers.iter().map(|p| p.uri()).collect();
+        assert_eq!(
+            uris,
+            ["embedtool://instructions", "docs://api", "config://current", "models://list", "metrics://server"]
+        );
     }
 
     #[test]
     fn test_resource_registry_find_by_uri() {
+        let registry = test_registry();
         // Found
-        let provider = ResourceRegistry::find_by_uri("embedtool://instructions");
+        let provider = registry.find_by_uri("embedtool://instructions");
         assert!(provider.is_some());
         assert_eq!(provider.unwrap().uri(), "embedtool://instructions");
 
         // Not found
-        let not_found = ResourceRegistry::find_by_uri("nonexistent://uri");
+        let not_found = registry.find_by_uri("nonexistent://uri");
         assert!(not_found.is_none());
     }
 
     #[test]
     fn test_list_resources() {
-        let resources = list_resources();
-        assert_eq!(resources.len(), 1);
+        let resources = test_registry().list_resources().unwrap();
+        assert_eq!(resources.len(), 5);
         
         let resource = &resources[0];
         assert_eq!(resource.raw.uri, "embedtool://instructions");
         assert_eq!(resource.raw.name, "Static Embedding Tool Instructions");
     }    #[test]
     fn test_read_resource() {
+        let registry = test_registry();
         // Found
-        let result = read_resource("embedtool://instructions");
-        assert!(result.is_some());
+        let result = registry.read_resource("embedtool://instructions");
+        assert!(result.is_ok());
         let result = result.unwrap();
         assert_eq!(result.contents.len(), 1);
 
         // Not found
-        let not_found = read_resource("nonexistent://uri");
-        assert!(not_found.is_none());
+        let not_found = registry.read_resource("nonexistent://uri");
+        assert!(matches!(not_found, Err(ResourceError::NotFound(uri)) if uri == "nonexistent://uri"));
+    }
+
+    #[test]
+    fn test_api_docs_resource() {
+        let registry = test_registry();
+        let resource = registry.find_by_uri("docs://api").unwrap();
+        assert_eq!(resource.mime_type(), "text/markdown");
+        let content = resource.content().unwrap();
+        for endpoint in ["/v1/embeddings", "/v1/similarity", "/v1/rank", "/v1/models"] {
+            assert!(content.contains(endpoint), "missing {}", endpoint);
+        }
+        assert_eq!(registry.read_resource("docs://api").unwrap().contents.len(), 1);
+    }
+
+    #[test]
+    fn test_config_resource_reports_effective_settings() {
+        let config = read_json(&test_registry(), "config://current");
+        assert_eq!(config["default_model"], "potion-32M");
+        assert_eq!(config["output_dtype"], "f32");
+        assert_eq!(config["ready_policy"], "warmed");
+        assert_eq!(config["encode_retries"], 2);
+        assert_eq!(config["embedding_cache_size"], 0);
+        assert_eq!(config["max_batch_inputs"], 100);
+        assert_eq!(config["max_input_chars"], 65536);
+        assert_eq!(config["models"], serde_json::json!(["custom", "potion-32M"]));
+    }
+
+    #[test]
+    fn test_models_resource_lists_loaded_models() {
+        let registry = test_registry();
+        let list = read_json(&registry, "models://list");
+        assert_eq!(list["count"], 2);
+        assert_eq!(list["default_model"], "potion-32M");
+        assert_eq!(
+            list["models"],
+            serde_json::json!([
+                { "name": "custom", "dimensions": 4, "default": false },
+                { "name": "potion-32M", "dimensions": 8, "default": true },
+            ])
+        );
+
+        // Reads the live state, so unloaded models drop out
+        registry.state.models.remove("custom");
+        assert_eq!(read_json(&registry, "models://list")["count"], 1);
+    }
+
+    struct FailingResource;
+
+    impl ResourceProvider for FailingResource {
+        fn uri(&self) -> &'static str {
+            "test://failing"
+        }
+
+        fn name(&self) -> &'static str {
+            "Failing"
+        }
+
+        fn mime_type(&self) -> &'static str {
+            "text/plain"
+        }
+
+        fn description(&self) -> &'static str {
+            "Always fails to produce content"
+        }
+
+        fn content(&self) -> Result<String, ResourceError> {
+            Err(std::io::Error::new(std::io::ErrorKind::NotFound, "gone").into())
+        }
+    }
+
+    #[test]
+    fn test_resource_errors_propagate_as_mcp_errors() {
+        let resource = FailingResource;
+        assert!(matches!(resource.meta(), Err(ResourceError::Io(_))));
+        let error = McpError::from(resource.read().unwrap_err());
+        assert_eq!(error.code, rmcp::model::ErrorCode::INTERNAL_ERROR);
+        assert!(error.message.contains("gone"));
+
+        let error = McpError::from(test_registry().read_resource("nonexistent://uri").unwrap_err());
+        assert_eq!(error.code, rmcp::model::ErrorCode::RESOURCE_NOT_FOUND);
+        assert_eq!(error.data, Some(serde_json::json!({ "uri": "nonexistent://uri" })));
+    }
+
+    #[test]
+    fn test_metrics_resource_reports_recorded_metrics() {
+        crate::server::metrics::install();
+        metrics::counter!("test.resources.metrics_reads").increment(1);
+
+        let registry = test_registry();
+        assert_eq!(registry.find_by_uri("metrics://server").unwrap().mime_type(), "application/json");
+        let snapshot = read_json(&registry, "metrics://server");
+        assert!(snapshot["uptime_secs"].is_u64());
+        let counters = snapshot["counters"].as_array().unwrap();
+        assert!(counters.iter().any(|c| c["name"] == "test.resources.metrics_reads" && c["value"] == 1.0));
+        assert!(snapshot["gauges"].is_array());
     }
 }
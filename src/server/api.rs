@@ -1,9 +1,18 @@
 //! HTTP API handlers for OpenAI-compatible embedding endpoints.
 //!
 //! This module implements the core HTTP API with:
-//! - **POST /v1/embeddings**: Generate embeddings from text input
+//! - **POST /v1/embeddings**: Generate embeddings from text input, with one model
+//!   or several (`"model": ["a", "b"]` groups results under `by_model`)
+//! - **POST /v1/embeddings/jobs**, **GET /v1/embeddings/jobs/{id}**: Embed large
+//!   batches in the background and poll for the result
+//! - **POST /v1/similarity**: Cosine similarity between two texts or two batches
+//! - **POST /v1/rank**: Documents sorted by similarity to a query
+//! - **GET /v1/tokenize**: Token count of a text, for checking `usage` and the input limit
 //! - **GET /v1/models**: List available embedding models
+//! - **GET /v1/models/{model}**: Dimensions, source path and load time of one model
 //! - **GET /health**: Health check endpoint
+//! - **POST /admin/models/{name}/reload**: Reload one model from disk without downtime
+//! - **POST /v1/models/load**, **POST /v1/models/unload**: Add or remove models at runtime
 //!
 //! All endpoints use OpenAI-compatible request/response formats for easy integration.
 //!
@@ -35,17 +44,32 @@
 //! ```
 
 use axum::{
-    extract::{Json, Query, State},
+    extract::{Json, Path, Query, State},
     http::StatusCode,
-    response::Json as ResponseJson,
+    middleware,
+    response::{IntoResponse, Json as ResponseJson, Response},
     routing::{get, post},
     Router,
 };
+use metrics::counter;
 use std::sync::Arc;
-use tracing::error;
-
-use super::state::AppState;
-use super::{EmbeddingRequest, QueryParams, EmbeddingResponse, EmbeddingData, Usage, ModelsResponse, ModelInfo, ApiError, ErrorDetails};
+use std::time::{Duration, Instant};
+use tracing::{error, warn};
+
+use super::cache;
+use super::deadline;
+use super::jobs::{create_job_handler, get_job_handler};
+use super::limits::{InputLimitError, InputLimits, MAX_INPUT_TOKENS};
+use super::errors::AppError;
+use super::state::{AppState, Model};
+use crate::embed::EncodeOptions;
+use crate::utils::{EncodingFormat, OutputDtype, UniqueTexts};
+use super::{
+    ApiError, ByModelEmbeddingResponse, EmbeddingData, EmbeddingInput, EmbeddingRequest, EmbeddingResponse,
+    EmbeddingValue, ErrorDetails, LoadModelRequest, ModelDetail, ModelInfo, ModelSelection, ModelsResponse,
+    QueryParams, RankData, RankRequest, RankResponse, SimilarityData, SimilarityRequest, SimilarityResponse,
+    TokenizeParams, TokenizeResponse, UnloadModelRequest, Usage,
+};
 
 // ============================================================================
 // Route Handlers
@@ -68,9 +92,12 @@ use super::{EmbeddingRequest, QueryParams, EmbeddingResponse, EmbeddingData, Usa
 ///
 /// # Errors
 ///
-/// - `400 invalid_request_error`: Empty input, invalid encoding format
+/// - `400 invalid_request_error`: Empty input, invalid encoding format,
+///   `dimensions` above the model's native size, or token-id input for a
+///   model without a tokenizer
 /// - `404 model_not_found_error`: Requested model not loaded
 /// - `500 server_error`: Model computation failed
+/// - `504 timeout_error`: The client's deadline passed before encoding finished
 ///
 /// # Examples
 ///
@@ -83,88 +110,544 @@ pub async fn embeddings_handler(
     Query(params): Query<QueryParams>,
     Json(request): Json<EmbeddingRequest>,
 ) -> Result<ResponseJson<EmbeddingResponse>, (StatusCode, ResponseJson<ApiError>)> {
-    // Input validation
-    if request.input.is_empty() {
+    validate_input_count(&request.input, &state.input_limits)?;
+    let output_dtype = resolve_output_dtype(request.output_dtype.as_deref(), &state)?;
+    let encoding = resolve_encoding_format(request.encoding_format.as_deref())?;
+    let options = request.encode_options();
+
+    // Determine which model to use
+    let model_name = request.model
+        .as_ref()
+        .and_then(ModelSelection::primary)
+        .map(str::to_string)
+        .or(params.model)
+        .unwrap_or_else(|| state.default_model.clone());
+    
+    let model = resolve_model(&state, &model_name)?;
+
+    let EmbeddedInput { data, prompt_tokens, dimensions } = embed_input(
+        model,
+        request.input,
+        output_dtype,
+        encoding,
+        options,
+        &state,
+        0,
+    )
+    .await?;
+
+    let response = EmbeddingResponse {
+        object: "list".to_string(),
+        data,
+        model: model_name,
+        dimensions,
+        usage: Usage {
+            prompt_tokens,
+            total_tokens: prompt_tokens,
+        },
+    };
+
+    Ok(ResponseJson(response))
+}
+
+/// Look up `name`, falling back to the default model when it is not loaded.
+pub(super) fn resolve_model(state: &AppState, name: &str) -> Result<Arc<dyn Model>, (StatusCode, ResponseJson<ApiError>)> {
+    match state.models.get(name).or_else(|| state.models.get(&state.default_model)) {
+        Some(model) => Ok(model),
+        None => {
+            let error = ApiError {
+                error: ErrorDetails {
+                    message: "No models available".to_string(),
+                    r#type: "server_error".to_string(),
+                    param: None,
+                    code: None,
+                },
+            };
+            Err((StatusCode::INTERNAL_SERVER_ERROR, ResponseJson(error)))
+        }
+    }
+}
+
+/// Cosine similarity between two texts, or pairwise between two batches.
+///
+/// POST /v1/similarity
+///
+/// Both sides are embedded with the same model, resolved exactly as for
+/// [`embeddings_handler`]. Pairs involving an all-zero embedding score `0.0`.
+///
+/// # Errors
+///
+/// - `400 invalid_request_error`: Either side is empty or invalid (as for
+///   `input` in [`embeddings_handler`]), or the batches differ in length
+/// - `500 server_error`: Model computation failed
+///
+/// # Examples
+///
+/// ```bash
+/// curl -X POST http://localhost:8080/v1/similarity \
+///   -d '{"text_a":"cat", "text_b":"kitten", "model":"potion-32M"}'
+/// ```
+pub async fn similarity_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<QueryParams>,
+    Json(request): Json<SimilarityRequest>,
+) -> Result<ResponseJson<SimilarityResponse>, (StatusCode, ResponseJson<ApiError>)> {
+    // Errors from the shared input checks name the side they came from
+    validate_input_count(&request.text_a, &state.input_limits).map_err(input_error_for("text_a"))?;
+    validate_input_count(&request.text_b, &state.input_limits).map_err(input_error_for("text_b"))?;
+    if request.text_a.len() != request.text_b.len() {
         let error = ApiError {
             error: ErrorDetails {
-                message: "Input too long or empty".to_string(),
+                message: format!(
+                    "text_a has {} inputs but text_b has {}; pairs must line up",
+                    request.text_a.len(),
+                    request.text_b.len()
+                ),
                 r#type: "invalid_request_error".to_string(),
-                param: Some("input".to_string()),
+                param: Some("text_b".to_string()),
                 code: None,
             },
         };
         return Err((StatusCode::BAD_REQUEST, ResponseJson(error)));
     }
 
-    if request.input.len() > 100 {
+    let model_name = request.model
+        .or(params.model)
+        .unwrap_or_else(|| state.default_model.clone());
+    let model = resolve_model(&state, &model_name)?;
+
+    let a = embed_input(model.clone(), request.text_a, OutputDtype::F32, EncodingFormat::Float, EncodeOptions::default(), &state, 0)
+        .await
+        .map_err(input_error_for("text_a"))?;
+    let b = embed_input(model, request.text_b, OutputDtype::F32, EncodingFormat::Float, EncodeOptions::default(), &state, 0)
+        .await
+        .map_err(input_error_for("text_b"))?;
+
+    // Both sides were embedded as floats
+    fn floats(data: &EmbeddingData) -> &[f32] {
+        data.embedding.as_floats().unwrap_or_default()
+    }
+    let data = a
+        .data
+        .iter()
+        .zip(&b.data)
+        .enumerate()
+        .map(|(index, (a, b))| SimilarityData {
+            object: "similarity".to_string(),
+            similarity: crate::utils::cosine_similarity(floats(a), floats(b)),
+            index,
+        })
+        .collect();
+
+    Ok(ResponseJson(SimilarityResponse {
+        object: "list".to_string(),
+        data,
+        model: model_name,
+        dimensions: a.dimensions,
+        usage: Usage {
+            prompt_tokens: a.prompt_tokens + b.prompt_tokens,
+            total_tokens: a.prompt_tokens + b.prompt_tokens,
+        },
+    }))
+}
+
+/// Rank documents by cosine similarity to a query.
+///
+/// POST /v1/rank
+///
+/// The query and documents are embedded with the same model, resolved as for
+/// [`similarity_handler`]. Ties keep document order.
+///
+/// # Errors
+///
+/// - `400 invalid_request_error`: `documents` is empty or has more than 100
+///   entries, or the query or a document is empty or too long
+/// - `500 server_error`: Model computation failed
+///
+/// # Examples
+///
+/// ```bash
+/// curl -X POST http://localhost:8080/v1/rank \
+///   -d '{"query":"cat", "documents":["kitten", "car", "tiger"], "top_k":2}'
+/// ```
+pub async fn rank_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<QueryParams>,
+    Json(request): Json<RankRequest>,
+) -> Result<ResponseJson<RankResponse>, (StatusCode, ResponseJson<ApiError>)> {
+    if request.documents.is_empty() {
         let error = ApiError {
             error: ErrorDetails {
-                message: "Batch size too large. Maximum 100 inputs allowed.".to_string(),
+                message: "documents must contain at least one text".to_string(),
                 r#type: "invalid_request_error".to_string(),
-                param: Some("input".to_string()),
+                param: Some("documents".to_string()),
                 code: None,
             },
         };
         return Err((StatusCode::BAD_REQUEST, ResponseJson(error)));
     }
+    let documents = EmbeddingInput::TextBatch(request.documents);
+    validate_input_count(&documents, &state.input_limits).map_err(input_error_for("documents"))?;
+
+    let model_name = request.model
+        .or(params.model)
+        .unwrap_or_else(|| state.default_model.clone());
+    let model = resolve_model(&state, &model_name)?;
+
+    let query = embed_input(
+        model.clone(),
+        EmbeddingInput::Text(request.query),
+        OutputDtype::F32,
+        EncodingFormat::Float,
+        EncodeOptions::default(),
+        &state,
+        0,
+    )
+    .await
+    .map_err(input_error_for("query"))?;
+    let documents = embed_input(model, documents, OutputDtype::F32, EncodingFormat::Float, EncodeOptions::default(), &state, 0)
+        .await
+        .map_err(input_error_for("documents"))?;
+
+    let query_embedding = query.data[0].embedding.as_floats().unwrap_or_default();
+    let mut data: Vec<RankData> = documents
+        .data
+        .iter()
+        .map(|document| RankData {
+            object: "rank".to_string(),
+            index: document.index,
+            score: crate::utils::cosine_similarity(
+                query_embedding,
+                document.embedding.as_floats().unwrap_or_default(),
+            ),
+        })
+        .collect();
+    // Stable sort, so equal scores stay in document order
+    data.sort_by(|a, b| b.score.total_cmp(&a.score));
+    if let Some(top_k) = request.top_k {
+        data.truncate(top_k);
+    }
 
-    for text in &request.input {
-        if text.is_empty() || text.len() > 8192 {
+    let prompt_tokens = query.prompt_tokens + documents.prompt_tokens;
+    Ok(ResponseJson(RankResponse {
+        object: "list".to_string(),
+        data,
+        model: model_name,
+        usage: Usage {
+            prompt_tokens,
+            total_tokens: prompt_tokens,
+        },
+    }))
+}
+
+/// Rename the `input` param in an error from the shared input checks to `param`,
+/// for endpoints whose request fields are named differently.
+fn input_error_for(
+    param: &'static str,
+) -> impl Fn((StatusCode, ResponseJson<ApiError>)) -> (StatusCode, ResponseJson<ApiError>) {
+    move |(status, ResponseJson(mut error))| {
+        if error.error.param.as_deref() == Some("input") {
+            error.error.param = Some(param.to_string());
+        }
+        (status, ResponseJson(error))
+    }
+}
+
+/// Embed the same input with several models.
+///
+/// POST /v1/embeddings with `"model": ["a", "b"]` - embeddings grouped by model
+///
+/// Unlike a single `model`, which falls back to the default model, every
+/// listed model must be loaded. Repeated names are embedded once.
+///
+/// # Errors
+///
+/// - `400 invalid_request_error`: Empty model list, or any error of [`embeddings_handler`]
+/// - `404 model_not_found_error`: A listed model is not loaded
+pub async fn by_model_embeddings_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<EmbeddingRequest>,
+) -> Result<ResponseJson<ByModelEmbeddingResponse>, (StatusCode, ResponseJson<ApiError>)> {
+    validate_input_count(&request.input, &state.input_limits)?;
+    let output_dtype = resolve_output_dtype(request.output_dtype.as_deref(), &state)?;
+    let encoding = resolve_encoding_format(request.encoding_format.as_deref())?;
+    let options = request.encode_options();
+
+    let mut names: Vec<String> = match request.model {
+        Some(ModelSelection::Many(names)) => names,
+        Some(ModelSelection::One(name)) => vec![name],
+        None => Vec::new(),
+    };
+    if names.is_empty() {
+        let error = ApiError {
+            error: ErrorDetails {
+                message: "Model list is empty".to_string(),
+                r#type: "invalid_request_error".to_string(),
+                param: Some("model".to_string()),
+                code: None,
+            },
+        };
+        return Err((StatusCode::BAD_REQUEST, ResponseJson(error)));
+    }
+    let mut seen = std::collections::HashSet::new();
+    names.retain(|name| seen.insert(name.clone()));
+
+    // Resolve every model before encoding with any of them
+    let mut models = Vec::with_capacity(names.len());
+    for name in &names {
+        match state.models.get(name) {
+            Some(model) => models.push(model),
+            None => {
+                let error = ApiError {
+                    error: ErrorDetails {
+                        message: AppError::ModelNotFound(name.clone()).to_string(),
+                        r#type: "model_not_found_error".to_string(),
+                        param: Some("model".to_string()),
+                        code: Some("model_not_found".to_string()),
+                    },
+                };
+                return Err((StatusCode::NOT_FOUND, ResponseJson(error)));
+            }
+        }
+    }
+
+    let mut by_model = std::collections::BTreeMap::new();
+    let mut prompt_tokens = 0;
+    for (name, model) in names.iter().zip(models) {
+        let embedded = embed_input(
+            model,
+            request.input.clone(),
+            output_dtype,
+            encoding,
+            options,
+            &state,
+            0,
+        )
+        .await?;
+        by_model.insert(name.clone(), embedded.data);
+        prompt_tokens += embedded.prompt_tokens;
+    }
+
+    Ok(ResponseJson(ByModelEmbeddingResponse {
+        object: "list".to_string(),
+        by_model,
+        models: names,
+        usage: Usage {
+            prompt_tokens,
+            total_tokens: prompt_tokens,
+        },
+    }))
+}
+
+/// POST /v1/embeddings: one model answers with `data`, several with `by_model`.
+async fn create_embeddings(
+    state: State<Arc<AppState>>,
+    query: Query<QueryParams>,
+    Json(request): Json<EmbeddingRequest>,
+) -> Response {
+    match &request.model {
+        Some(ModelSelection::Many(names)) if names.len() != 1 => {
+            by_model_embeddings_handler(state, Json(request)).await.into_response()
+        }
+        _ => embeddings_handler(state, query, Json(request)).await.into_response(),
+    }
+}
+
+/// Delay before the first encode retry; doubled after each further failure.
+const ENCODE_RETRY_BACKOFF: Duration = Duration::from_millis(25);
+
+/// Call [`Model::try_encode`], retrying failures up to `retries` times with
+/// exponential backoff. Blocks the calling thread while backing off.
+fn encode_with_retry(
+    model: &dyn Model,
+    inputs: &[String],
+    retries: u32,
+    deadline: Option<Instant>,
+) -> Result<Vec<Vec<f32>>, String> {
+    let mut attempt = 0;
+    loop {
+        match model.try_encode(inputs) {
+            Ok(embeddings) => {
+                if attempt > 0 {
+                    counter!("embedtool.encode.retry_successes").increment(1);
+                }
+                return Ok(embeddings);
+            }
+            Err(e) if attempt < retries && !deadline::is_expired(deadline) => {
+                attempt += 1;
+                counter!("embedtool.encode.retries").increment(1);
+                warn!(attempt, retries, error = %e, "Encode failed, retrying");
+                std::thread::sleep(ENCODE_RETRY_BACKOFF * 2u32.pow(attempt - 1));
+            }
+            Err(e) => {
+                counter!("embedtool.encode.failures").increment(1);
+                return Err(e);
+            }
+        }
+    }
+}
+
+fn encode_failed(message: &str) -> (StatusCode, ResponseJson<ApiError>) {
+    error!("Encode failed: {}", message);
+    let error = ApiError {
+        error: ErrorDetails {
+            message: format!("Embedding generation failed: {}", message),
+            r#type: "server_error".to_string(),
+            param: None,
+            code: None,
+        },
+    };
+    (StatusCode::INTERNAL_SERVER_ERROR, ResponseJson(error))
+}
+
+/// Reject empty input and batches over the configured batch limit.
+fn validate_input_count(input: &EmbeddingInput, limits: &InputLimits) -> Result<(), (StatusCode, ResponseJson<ApiError>)> {
+    limits.check_count(input.len()).map_err(input_limit_error)
+}
+
+/// `400` for inputs rejected by the shared input limits.
+fn input_limit_error(e: InputLimitError) -> (StatusCode, ResponseJson<ApiError>) {
+    let error = ApiError {
+        error: ErrorDetails {
+            message: e.to_string(),
+            r#type: "invalid_request_error".to_string(),
+            param: Some("input".to_string()),
+            code: None,
+        },
+    };
+    (StatusCode::BAD_REQUEST, ResponseJson(error))
+}
+
+/// Resolve output precision before doing any work.
+pub(super) fn resolve_output_dtype(
+    requested: Option<&str>,
+    state: &AppState,
+) -> Result<OutputDtype, (StatusCode, ResponseJson<ApiError>)> {
+    match requested {
+        Some(dtype) => dtype.parse::<OutputDtype>().map_err(|e| {
             let error = ApiError {
                 error: ErrorDetails {
-                    message: "Input too long or empty".to_string(),
+                    message: e.to_string(),
                     r#type: "invalid_request_error".to_string(),
-                    param: Some("input".to_string()),
+                    param: Some("output_dtype".to_string()),
                     code: None,
                 },
             };
-            return Err((StatusCode::BAD_REQUEST, ResponseJson(error)));
-        }
+            (StatusCode::BAD_REQUEST, ResponseJson(error))
+        }),
+        None => Ok(state.default_output_dtype),
     }
-    // Determine which model to use
-    let model_name = request.model
-        .or(params.model)
-        .unwrap_or_else(|| state.default_model.clone());
-    
-    // Get the model
-    let model = match state.models.get(&model_name) {
-        Some(model) => model,
-        None => {
-            // Fallback to default model if requested model not found
-            match state.models.get(&state.default_model) {
-                Some(model) => model,
-                None => {
-                    let error = ApiError {
-                        error: ErrorDetails {
-                            message: "No models available".to_string(),
-                            r#type: "server_error".to_string(),
-                            param: None,
-                            code: None,
-                        },
-                    };
-                    return Err((StatusCode::INTERNAL_SERVER_ERROR, ResponseJson(error)));
-                }
-            }
+}
+
+/// Parse `encoding_format`, defaulting to `"float"` when absent.
+pub(super) fn resolve_encoding_format(
+    requested: Option<&str>,
+) -> Result<EncodingFormat, (StatusCode, ResponseJson<ApiError>)> {
+    requested.map_or(Ok(EncodingFormat::Float), |format| {
+        format.parse::<EncodingFormat>().map_err(|e| {
+            let error = ApiError {
+                error: ErrorDetails {
+                    message: e.to_string(),
+                    r#type: "invalid_request_error".to_string(),
+                    param: Some("encoding_format".to_string()),
+                    code: None,
+                },
+            };
+            (StatusCode::BAD_REQUEST, ResponseJson(error))
+        })
+    })
+}
+
+/// Embeddings for one request input, ready to return.
+pub(super) struct EmbeddedInput {
+    pub(super) data: Vec<EmbeddingData>,
+    pub(super) prompt_tokens: usize,
+    /// Length of each embedding, after any truncation to the requested `dimensions`
+    pub(super) dimensions: usize,
+}
+
+/// Encode `input` with `model`, post-processed by `options`.
+///
+/// Inputs are checked against `state.input_limits`, and a failed encode is
+/// retried up to `state.encode_retries` times while the deadline allows.
+/// `first_index` is the position of the first input in the request, used for
+/// the returned indexes and in limit errors.
+pub(super) async fn embed_input(
+    model: Arc<dyn Model>,
+    input: EmbeddingInput,
+    output_dtype: OutputDtype,
+    encoding: EncodingFormat,
+    options: EncodeOptions,
+    state: &AppState,
+    first_index: usize,
+) -> Result<EmbeddedInput, (StatusCode, ResponseJson<ApiError>)> {
+    let retries = state.encode_retries;
+    // Resolve token-id input to text with the selected model's tokenizer
+    let token_count = input.token_count();
+    let inputs = match input.into_texts(model.as_ref()) {
+        Ok(inputs) if options.max_length.is_some() => inputs
+            .iter()
+            .map(|text| options.truncate_input(text).to_string())
+            .collect(),
+        Ok(inputs) => inputs,
+        Err(message) => {
+            let error = ApiError {
+                error: ErrorDetails {
+                    message,
+                    r#type: "invalid_request_error".to_string(),
+                    param: Some("input".to_string()),
+                    code: Some("unsupported_input".to_string()),
+                },
+            };
+            return Err((StatusCode::BAD_REQUEST, ResponseJson(error)));
         }
     };
-    
-    // Generate embeddings with optional parallel chunking for large batches
-    let embeddings: Vec<Vec<f32>> = if request.input.len() <= 32 {
+
+    // Counted once, for both the input limit and `usage`
+    let text_tokens: Vec<usize> = inputs.iter().map(|text| model.count_tokens(text)).collect();
+    for (index, (text, &tokens)) in inputs.iter().zip(&text_tokens).enumerate() {
+        state
+            .input_limits
+            .check_text(first_index + index, text, tokens)
+            .map_err(input_limit_error)?;
+    }
+
+    // Respect the client's deadline: don't start work that cannot finish in time
+    let deadline = deadline::current();
+    if deadline::is_expired(deadline) {
+        return Err(deadline::deadline_exceeded());
+    }
+
+    // Generate embeddings with optional parallel chunking for large batches.
+    // Retries back off with a blocking sleep, so they never run on the async runtime.
+    // Repeated texts are encoded once and copied back to each position
+    let unique = UniqueTexts::new(&inputs);
+    let usage = cache::current_usage();
+    let encoded: Vec<Vec<f32>> = if unique.texts.len() <= 32 && retries == 0 {
         // Small batch: encode directly
-        model.encode(&request.input)
+        match cache::count_usage(usage, || model.try_encode(&unique.texts)) {
+            Ok(embeddings) => embeddings,
+            Err(e) => return Err(encode_failed(&e)),
+        }
     } else {
         // Large batch: split into chunks of 32 and process in parallel
         use futures::future::join_all;
         use tokio::task::spawn_blocking;
 
         let chunk_size = 32;
-        let chunks: Vec<_> = request.input.chunks(chunk_size).collect();
+        let chunks: Vec<_> = unique.texts.chunks(chunk_size).collect();
         let mut chunk_futures = Vec::new();
 
         for chunk in chunks {
             let chunk_vec: Vec<String> = chunk.to_vec();
             let model_clone = model.clone();
-            chunk_futures.push(spawn_blocking(move || model_clone.encode(&chunk_vec)));
+            let usage = usage.clone();
+            // Chunks that start after the deadline are skipped
+            chunk_futures.push(spawn_blocking(move || {
+                (!deadline::is_expired(deadline)).then(|| {
+                    cache::count_usage(usage, || encode_with_retry(model_clone.as_ref(), &chunk_vec, retries, deadline))
+                })
+            }));
         }
 
         let results = join_all(chunk_futures).await;
@@ -172,7 +655,9 @@ pub async fn embeddings_handler(
 
         for result in results {
             match result {
-                Ok(embeddings) => all_embeddings.extend(embeddings),
+                Ok(Some(Ok(embeddings))) => all_embeddings.extend(embeddings),
+                Ok(Some(Err(e))) => return Err(encode_failed(&e)),
+                Ok(None) => return Err(deadline::deadline_exceeded()),
                 Err(e) => {
                     error!("Spawn blocking failed: {}", e);
                     let error = ApiError {
@@ -190,71 +675,291 @@ pub async fn embeddings_handler(
 
         all_embeddings
     };
-    
+    let mut embeddings = match unique.fan_out(encoded) {
+        Ok(embeddings) => embeddings,
+        Err(e) => return Err(encode_failed(&e.to_string())),
+    };
+
+    if let Err(e) = options.apply(&mut embeddings) {
+        let error = ApiError {
+            error: ErrorDetails {
+                message: e.to_string(),
+                r#type: "invalid_request_error".to_string(),
+                param: Some("dimensions".to_string()),
+                code: None,
+            },
+        };
+        return Err((StatusCode::BAD_REQUEST, ResponseJson(error)));
+    }
+    let dimensions = embeddings.first().map_or(0, Vec::len);
+
     // Build response data
     let data = embeddings
         .into_iter()
         .enumerate()
-        .map(|(index, embedding)| EmbeddingData {
-            object: "embedding".to_string(),
-            embedding,
-            index,
+        .map(|(index, mut embedding)| {
+            output_dtype.round_embedding(&mut embedding);
+            EmbeddingData {
+                object: "embedding".to_string(),
+                embedding: EmbeddingValue::new(embedding, encoding),
+                index: first_index + index,
+            }
         })
         .collect();
 
-    // Approximate token usage (roughly 4 characters per token)
-    let prompt_tokens: usize = request.input.iter().map(|s| s.len().div_ceil(4)).sum();
+    // Exact for pre-tokenized input, otherwise counted by the model's tokenizer
+    let prompt_tokens: usize = token_count.unwrap_or_else(|| text_tokens.iter().sum());
 
-    let response = EmbeddingResponse {
-        object: "list".to_string(),
-        data,
+    Ok(EmbeddedInput { data, prompt_tokens, dimensions })
+}
+
+/// Count the tokens of a text without embedding it.
+///
+/// GET /v1/tokenize - Debug endpoint for checking `usage` and the input limit
+///
+/// Counts come from the model's tokenizer, or the four-bytes-per-token
+/// estimate for models without one, exactly as when embedding.
+///
+/// # Errors
+///
+/// - `404 model_not_found_error`: Requested model not loaded
+///
+/// # Examples
+///
+/// ```bash
+/// curl 'http://localhost:8080/v1/tokenize?text=Hello%20world&model=potion-32M'
+/// ```
+pub async fn tokenize_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<TokenizeParams>,
+) -> Result<ResponseJson<TokenizeResponse>, (StatusCode, ResponseJson<ApiError>)> {
+    let model_name = params.model.unwrap_or_else(|| state.default_model.clone());
+    let model = resolve_model(&state, &model_name)?;
+    Ok(ResponseJson(TokenizeResponse {
+        object: "tokenize".to_string(),
         model: model_name,
-        usage: Usage {
-            prompt_tokens,
-            total_tokens: prompt_tokens,
-        },
+        tokens: model.count_tokens(&params.text),
+        max_tokens: MAX_INPUT_TOKENS,
+    }))
+}
+
+/// List all available embedding models.
+///
+/// GET /v1/models - List available models
+///
+/// # Arguments
+///
+/// * `state` - Application state containing loaded models
+///
+/// # Returns
+///
+/// JSON list of available models with metadata
+///
+/// # Examples
+///
+/// ```bash
+/// curl http://localhost:8080/v1/models
+/// ```
+pub async fn models_handler(
+    State(state): State<Arc<AppState>>,
+) -> ResponseJson<ModelsResponse> {
+    let models = state.models.names()
+        .into_iter()
+        .map(|model_id| ModelInfo {
+            owned_by: owned_by(&model_id),
+            id: model_id,
+            object: "model".to_string(),
+            created: 1640995200, // Fixed timestamp for Model2Vec models
+        })
+        .collect();
+
+    ResponseJson(ModelsResponse {
+        object: "list".to_string(),
+        data: models,
+    })
+}
+
+/// Owner reported for a model: the Model2Vec authors for built-ins, else "custom".
+fn owned_by(model_id: &str) -> String {
+    if model_id.starts_with("potion") {
+        "minishlab".to_string()
+    } else {
+        "custom".to_string()
+    }
+}
+
+/// Describe one loaded model.
+///
+/// GET /v1/models/{model}
+///
+/// Dimensions are probed once per loaded instance and cached, so repeated
+/// lookups do no encode work.
+///
+/// # Errors
+///
+/// - `404 model_not_found_error`: No model with that name is loaded
+///
+/// # Examples
+///
+/// ```bash
+/// curl http://localhost:8080/v1/models/potion-32M
+/// ```
+pub async fn model_detail_handler(
+    State(state): State<Arc<AppState>>,
+    Path(model_id): Path<String>,
+) -> Result<ResponseJson<ModelDetail>, (StatusCode, ResponseJson<ApiError>)> {
+    // The first lookup of a model may encode a probe, so keep it off the runtime
+    let lookup = {
+        let state = state.clone();
+        let model_id = model_id.clone();
+        tokio::task::spawn_blocking(move || state.models.details(&model_id))
+    };
+    let Some(details) = lookup.await.ok().flatten() else {
+        let error = ApiError {
+            error: ErrorDetails {
+                message: AppError::ModelNotFound(model_id).to_string(),
+                r#type: "model_not_found_error".to_string(),
+                param: Some("model".to_string()),
+                code: Some("model_not_found".to_string()),
+            },
+        };
+        return Err((StatusCode::NOT_FOUND, ResponseJson(error)));
     };
 
-    Ok(ResponseJson(response))
+    let loaded_at = details
+        .loaded_at
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    Ok(ResponseJson(ModelDetail {
+        info: ModelInfo {
+            owned_by: owned_by(&model_id),
+            object: "model".to_string(),
+            created: loaded_at,
+            id: model_id.clone(),
+        },
+        dimensions: details.dimensions,
+        path: details.path.map(|path| path.display().to_string()),
+        loaded_at,
+        default: model_id == state.default_model,
+    }))
+}
+
+/// Reload a single model from its files and swap it in.
+///
+/// POST /admin/models/{name}/reload
+///
+/// Re-reads the model's registry path (or built-in source). The swap is atomic:
+/// requests already running finish on the old instance, and if loading fails the
+/// old instance stays active and an error is returned.
+///
+/// # Errors
+///
+/// - `404 model_not_found_error`: No model with that name is loaded
+/// - `500 model_load_error`: The files could not be loaded or failed a probe encode
+///
+/// # Examples
+///
+/// ```bash
+/// curl -X POST http://localhost:8080/admin/models/my-distilled/reload
+/// ```
+pub async fn reload_model_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<ResponseJson<serde_json::Value>, (StatusCode, ResponseJson<ApiError>)> {
+    match state.reload_model(&name).await {
+        Ok(dimensions) => Ok(ResponseJson(serde_json::json!({
+            "id": name,
+            "object": "model",
+            "reloaded": true,
+            "dimensions": dimensions,
+        }))),
+        Err(e) => {
+            error!("Reload of model '{}' failed: {}", name, e);
+            Err(app_error(e))
+        }
+    }
+}
+
+/// Load a model from a directory on the server and start serving it.
+///
+/// POST /v1/models/load
+///
+/// The new model is probed before it is swapped in, so a model of the same
+/// name keeps serving if loading fails.
+///
+/// # Errors
+///
+/// - `400 invalid_request_error`: `path` does not exist
+/// - `500 model_load_error`: The files could not be loaded or failed a probe encode
+///
+/// # Examples
+///
+/// ```bash
+/// curl -X POST http://localhost:8080/v1/models/load \
+///   -d '{"name":"my-distilled", "path":"/models/my-distilled"}'
+/// ```
+pub async fn load_model_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<LoadModelRequest>,
+) -> Result<ResponseJson<serde_json::Value>, (StatusCode, ResponseJson<ApiError>)> {
+    match state.models.load_from_path(&request.name, &request.path).await {
+        Ok(dimensions) => Ok(ResponseJson(serde_json::json!({
+            "id": request.name,
+            "object": "model",
+            "loaded": true,
+            "dimensions": dimensions,
+        }))),
+        Err(e) => {
+            error!("Load of model '{}' from {} failed: {}", request.name, request.path, e);
+            Err(app_error(e))
+        }
+    }
 }
 
-/// List all available embedding models.
-///
-/// GET /v1/models - List available models
+/// Stop serving a model.
 ///
-/// # Arguments
+/// POST /v1/models/unload
 ///
-/// * `state` - Application state containing loaded models
+/// Requests already using the model finish with it.
 ///
-/// # Returns
+/// # Errors
 ///
-/// JSON list of available models with metadata
+/// - `400 invalid_request_error`: The model is the default model
+/// - `404 model_not_found_error`: No model with that name is loaded
 ///
 /// # Examples
 ///
 /// ```bash
-/// curl http://localhost:8080/v1/models
+/// curl -X POST http://localhost:8080/v1/models/unload -d '{"name":"my-distilled"}'
 /// ```
-pub async fn models_handler(
+pub async fn unload_model_handler(
     State(state): State<Arc<AppState>>,
-) -> ResponseJson<ModelsResponse> {
-    let models = state.models.keys()
-        .map(|model_id| ModelInfo {
-            id: model_id.clone(),
-            object: "model".to_string(),
-            created: 1640995200, // Fixed timestamp for Model2Vec models
-            owned_by: if model_id.starts_with("potion") { 
-                "minishlab".to_string() 
-            } else { 
-                "custom".to_string() 
-            },
-        })
-        .collect();
+    Json(request): Json<UnloadModelRequest>,
+) -> Result<ResponseJson<serde_json::Value>, (StatusCode, ResponseJson<ApiError>)> {
+    state.unload_model(&request.name).map_err(app_error)?;
+    Ok(ResponseJson(serde_json::json!({
+        "id": request.name,
+        "object": "model",
+        "unloaded": true,
+    })))
+}
 
-    ResponseJson(ModelsResponse {
-        object: "list".to_string(),
-        data: models,
-    })
+/// The API error for a failed model operation.
+fn app_error(e: AppError) -> (StatusCode, ResponseJson<ApiError>) {
+    let status = match e {
+        AppError::ModelNotFound(_) => StatusCode::NOT_FOUND,
+        AppError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let error = ApiError {
+        error: ErrorDetails {
+            message: e.to_string(),
+            r#type: e.error_type().to_string(),
+            param: None,
+            code: e.code().map(str::to_string),
+        },
+    };
+    (status, ResponseJson(error))
 }
 
 /// Reject requests to unsupported endpoints.
@@ -285,8 +990,19 @@ pub async fn unsupported_handler() -> (StatusCode, ResponseJson<ApiError>) {
 pub fn create_api_router() -> Router<Arc<AppState>> {
     Router::new()
         // Core embedding functionality
-        .route("/v1/embeddings", post(embeddings_handler))
+        .route("/v1/embeddings", post(create_embeddings))
+        .route("/v1/embeddings/jobs", post(create_job_handler))
+        .route("/v1/embeddings/jobs/{id}", get(get_job_handler))
+        .route("/v1/similarity", post(similarity_handler))
+        .route("/v1/rank", post(rank_handler))
+        .route("/v1/tokenize", get(tokenize_handler))
         .route("/v1/models", get(models_handler))
+        .route("/v1/models/{model}", get(model_detail_handler))
+        .route("/v1/models/load", post(load_model_handler))
+        .route("/v1/models/unload", post(unload_model_handler))
+
+        // Administration
+        .route("/admin/models/{name}/reload", post(reload_model_handler))
 
         // Standard OpenAI endpoints (unsupported but properly handled)
         .route("/v1/chat/completions", post(unsupported_handler))
@@ -300,6 +1016,8 @@ pub fn create_api_router() -> Router<Arc<AppState>> {
         .route("/v1/fine-tuning/jobs", get(unsupported_handler))
         .route("/v1/files", post(unsupported_handler))
         .route("/v1/files", get(unsupported_handler))
+        .layer(middleware::from_fn(deadline::enforce_deadline))
+        .layer(middleware::from_fn(cache::report_cache_usage))
 }
 
 #[cfg(test)]
@@ -309,6 +1027,7 @@ mod tests {
     use axum::response::Json;
     use std::collections::HashMap;
     use std::sync::Arc;
+    use crate::server::EmbeddingInput;
     use crate::server::state::{Model, MockModel};
 
     // Mock model that panics when encoding to simulate spawn_blocking JoinError
@@ -321,35 +1040,26 @@ mod tests {
         }
     }
 
-    // Mock model for API tests with predictable output
-    struct ApiMockModel;
-    impl Model for ApiMockModel {
-        fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
-            inputs.iter().map(|_| vec![0.1, 0.2, 0.3]).collect()
-        }
-    }
-
     fn create_test_app_state() -> Arc<AppState> {
         let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
-        models.insert("potion-32M".to_string(), Arc::new(ApiMockModel));
-        models.insert("test-model".to_string(), Arc::new(ApiMockModel));
+        models.insert("potion-32M".to_string(), Arc::new(MockModel::new("potion-32M".to_string(), 3)));
+        models.insert("test-model".to_string(), Arc::new(MockModel::new("test-model".to_string(), 3)));
 
-        Arc::new(AppState {
-            models,
-            default_model: "potion-32M".to_string(),
-            startup_time: std::time::SystemTime::now(),
-        })
+        Arc::new(AppState::from_models(models, "potion-32M"))
     }
 
     #[tokio::test]
     async fn test_embeddings_handler_empty_input() {
         let state = create_test_app_state();
         let request = EmbeddingRequest {
-            input: vec![],
+            input: vec![].into(),
             model: None,
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let result = embeddings_handler(
@@ -361,7 +1071,7 @@ mod tests {
         assert!(result.is_err());
         let (status, Json(error)) = result.err().unwrap();
         assert_eq!(status, StatusCode::BAD_REQUEST);
-        assert_eq!(error.error.message, "Input too long or empty");
+        assert_eq!(error.error.message, "inputs must not be empty");
     }
 
     #[tokio::test]
@@ -373,6 +1083,9 @@ mod tests {
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let result = embeddings_handler(
@@ -384,18 +1097,21 @@ mod tests {
         assert!(result.is_err());
         let (status, Json(error)) = result.err().unwrap();
         assert_eq!(status, StatusCode::BAD_REQUEST);
-        assert_eq!(error.error.message, "Batch size too large. Maximum 100 inputs allowed.");
+        assert_eq!(error.error.message, "Batch size too large: input 100 is over the maximum of 100 inputs");
     }
 
     #[tokio::test]
     async fn test_embeddings_handler_empty_text() {
         let state = create_test_app_state();
         let request = EmbeddingRequest {
-            input: vec!["".to_string()],
+            input: vec!["".to_string()].into(),
             model: None,
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let result = embeddings_handler(
@@ -407,19 +1123,23 @@ mod tests {
         assert!(result.is_err());
         let (status, Json(error)) = result.err().unwrap();
         assert_eq!(status, StatusCode::BAD_REQUEST);
-        assert_eq!(error.error.message, "Input too long or empty");
+        assert_eq!(error.error.message, "Input 0 is empty");
     }
 
     #[tokio::test]
     async fn test_embeddings_handler_text_too_long() {
         let state = create_test_app_state();
-        let long_text = "a".repeat(8193);
+        // Four bytes per token without a tokenizer
+        let long_text = "a".repeat(4 * MAX_INPUT_TOKENS + 1);
         let request = EmbeddingRequest {
-            input: vec![long_text],
+            input: vec![long_text].into(),
             model: None,
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let result = embeddings_handler(
@@ -431,7 +1151,37 @@ mod tests {
         assert!(result.is_err());
         let (status, Json(error)) = result.err().unwrap();
         assert_eq!(status, StatusCode::BAD_REQUEST);
-        assert_eq!(error.error.message, "Input too long or empty");
+        assert_eq!(error.error.message, "Input 0 has 8193 tokens, over the 8192 token limit");
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_handler_configured_input_limits() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("potion-32M".to_string(), Arc::new(MockModel::new("potion-32M".to_string(), 3)));
+        let mut state = AppState::from_models(models, "potion-32M");
+        state.input_limits = InputLimits::new(2, 10);
+        let state = Arc::new(state);
+        let embed = |input: Vec<&str>| {
+            let request: EmbeddingRequest = serde_json::from_value(serde_json::json!({"input": input})).unwrap();
+            embeddings_handler(
+                axum::extract::State(state.clone()),
+                axum::extract::Query(QueryParams { model: None }),
+                Json(request),
+            )
+        };
+
+        // The third input is over a batch limit of two, and its index is reported
+        let (status, Json(error)) = embed(vec!["a", "b", "c"]).await.err().unwrap();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.error.message, "Batch size too large: input 2 is over the maximum of 2 inputs");
+        assert_eq!(error.error.param.as_deref(), Some("input"));
+
+        let (_, Json(error)) = embed(vec!["short", "eleven chars"]).await.err().unwrap();
+        assert_eq!(error.error.message, "Input 1 has 12 characters, over the 10 character limit");
+        assert!(embed(vec!["short", "exactly 10"]).await.is_ok());
+
+        // Zero keeps the built-in defaults
+        assert_eq!(InputLimits::new(0, 0), InputLimits::default());
     }
 
     #[tokio::test]
@@ -439,18 +1189,17 @@ mod tests {
         let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
         models.insert("existing-model".to_string(), Arc::new(MockModel::new("existing-model".to_string(), 384)));
 
-        let state = Arc::new(AppState {
-            models,
-            default_model: "nonexistent".to_string(),
-            startup_time: std::time::SystemTime::now(),
-        });
+        let state = Arc::new(AppState::from_models(models, "nonexistent"));
 
         let request = EmbeddingRequest {
-            input: vec!["test text".to_string()],
-            model: Some("nonexistent-model".to_string()),
+            input: vec!["test text".to_string()].into(),
+            model: Some("nonexistent-model".into()),
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let result = embeddings_handler(
@@ -469,11 +1218,14 @@ mod tests {
     async fn test_embeddings_handler_success_single_input() {
         let state = create_test_app_state();
         let request = EmbeddingRequest {
-            input: vec!["test text".to_string()],
+            input: vec!["test text".to_string()].into(),
             model: None,
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let result = embeddings_handler(
@@ -486,22 +1238,28 @@ mod tests {
         let Json(response) = result.unwrap();
         assert_eq!(response.object, "list");
         assert_eq!(response.data.len(), 1);
-        assert_eq!(response.data[0].embedding, vec![0.1, 0.2, 0.3]);
+        assert_eq!(
+            response.data[0].embedding,
+            MockModel::new("potion-32M".to_string(), 3).embed("test text")
+        );
         assert_eq!(response.data[0].index, 0);
         assert_eq!(response.model, "potion-32M");
-        assert_eq!(response.usage.prompt_tokens, 0);
-        assert_eq!(response.usage.total_tokens, 0);
+        assert_eq!(response.usage.prompt_tokens, 3);
+        assert_eq!(response.usage.total_tokens, 3);
     }
 
     #[tokio::test]
     async fn test_embeddings_handler_success_multiple_inputs() {
         let state = create_test_app_state();
         let request = EmbeddingRequest {
-            input: vec!["text 1".to_string(), "text 2".to_string()],
-            model: Some("test-model".to_string()),
+            input: vec!["text 1".to_string(), "text 2".to_string()].into(),
+            model: Some("test-model".into()),
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let result = embeddings_handler(
@@ -538,22 +1296,797 @@ mod tests {
         assert!(err.error.message.contains("only supports embedding"));
     }
 
+    /// Embeds each text as `[len, 1]`, counting the texts it was asked to encode.
+    #[derive(Default)]
+    struct CountingModel {
+        encoded: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Model for CountingModel {
+        fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
+            self.encoded.fetch_add(inputs.len(), std::sync::atomic::Ordering::SeqCst);
+            inputs.iter().map(|text| vec![text.len() as f32, 1.0]).collect()
+        }
+
+        fn dimensions(&self) -> usize {
+            2
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_handler_encodes_repeated_inputs_once() {
+        let model = Arc::new(CountingModel::default());
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("counting".to_string(), model.clone());
+        let state = Arc::new(AppState::from_models(models, "counting"));
+        let embed = |inputs: Vec<String>| {
+            let state = state.clone();
+            async move {
+                let request = EmbeddingRequest {
+                    input: inputs.into(),
+                    model: None,
+                    encoding_format: None,
+                    dimensions: None,
+                    user: None,
+                    output_dtype: None,
+                    normalize: None,
+                    max_length: None,
+                };
+                let Json(response) = embeddings_handler(
+                    axum::extract::State(state),
+                    axum::extract::Query(QueryParams { model: None }),
+                    Json(request),
+                )
+                .await
+                .unwrap();
+                response
+            }
+        };
+
+        let response = embed(vec!["templated text".to_string(); 100]).await;
+        assert_eq!(model.encoded.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(response.data.len(), 100);
+        for (index, item) in response.data.iter().enumerate() {
+            assert_eq!(item.index, index);
+            assert_eq!(item.embedding, vec![14.0, 1.0]);
+        }
+        // Usage still counts every original input
+        assert_eq!(response.usage.prompt_tokens, 100 * crate::server::state::estimate_tokens("templated text"));
+
+        // More than 32 distinct texts go through the chunked path, still once each
+        let inputs: Vec<String> = (0..90).map(|i| "x".repeat(1 + i % 40)).collect();
+        let response = embed(inputs.clone()).await;
+        assert_eq!(model.encoded.load(std::sync::atomic::Ordering::SeqCst), 41);
+        for (item, input) in response.data.iter().zip(&inputs) {
+            assert_eq!(item.embedding, vec![input.len() as f32, 1.0]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_handler_output_dtype() {
+        struct PreciseModel;
+        impl Model for PreciseModel {
+            fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
+                inputs.iter().map(|_| vec![0.1234567, -0.7654321]).collect()
+            }
+        }
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("precise".to_string(), Arc::new(PreciseModel));
+        let state = Arc::new(AppState::from_models(models, "precise"));
+
+        let request = |dtype: Option<&str>| EmbeddingRequest {
+            input: vec!["test text".to_string()].into(),
+            model: None,
+            encoding_format: None,
+            dimensions: None,
+            user: None,
+            output_dtype: dtype.map(str::to_string),
+            normalize: None,
+            max_length: None,
+        };
+
+        let Json(response) = embeddings_handler(
+            axum::extract::State(state.clone()),
+            axum::extract::Query(QueryParams { model: None }),
+            Json(request(None)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.data[0].embedding, vec![0.1234567, -0.7654321]);
+
+        let Json(response) = embeddings_handler(
+            axum::extract::State(state.clone()),
+            axum::extract::Query(QueryParams { model: None }),
+            Json(request(Some("f16"))),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            response.data[0].embedding,
+            vec![OutputDtype::F16.round(0.1234567), OutputDtype::F16.round(-0.7654321)]
+        );
+
+        let (status, Json(error)) = embeddings_handler(
+            axum::extract::State(state),
+            axum::extract::Query(QueryParams { model: None }),
+            Json(request(Some("f64"))),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.error.param.as_deref(), Some("output_dtype"));
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_handler_deadline_exceeded() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("test-model".to_string(), Arc::new(MockModel::new("test-model".to_string(), 2)));
+        let state = Arc::new(AppState::from_models(models, "test-model"));
+
+        let request = EmbeddingRequest {
+            input: (0..40).map(|i| format!("text {}", i)).collect(),
+            model: None,
+            encoding_format: None,
+            dimensions: None,
+            user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
+        };
+        let past = std::time::Instant::now() - std::time::Duration::from_millis(1);
+        let result = deadline::scope(
+            past,
+            embeddings_handler(
+                axum::extract::State(state),
+                axum::extract::Query(QueryParams { model: None }),
+                Json(request),
+            ),
+        )
+        .await;
+
+        let (status, Json(error)) = result.err().unwrap();
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(error.error.code.as_deref(), Some("deadline_exceeded"));
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_handler_token_id_input() {
+        // Decodes each id to a word, like a word-level tokenizer would
+        struct WordModel;
+        impl Model for WordModel {
+            fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
+                inputs.iter().map(|s| vec![s.split(' ').count() as f32]).collect()
+            }
+            fn decode_tokens(&self, ids: &[u32]) -> Option<String> {
+                Some(ids.iter().map(|id| format!("w{}", id)).collect::<Vec<_>>().join(" "))
+            }
+        }
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("word".to_string(), Arc::new(WordModel));
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 4)));
+        let state = Arc::new(AppState::from_models(models, "word"));
+
+        let request = |model: &str| {
+            serde_json::from_value::<EmbeddingRequest>(serde_json::json!({
+                "input": [[1, 2, 3], [4]],
+                "model": model
+            }))
+            .unwrap()
+        };
+
+        let Json(response) = embeddings_handler(
+            axum::extract::State(state.clone()),
+            axum::extract::Query(QueryParams { model: None }),
+            Json(request("word")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.data.len(), 2);
+        assert_eq!(response.data[0].embedding, vec![3.0]);
+        assert_eq!(response.data[1].embedding, vec![1.0]);
+        assert_eq!(response.usage.prompt_tokens, 4);
+
+        // Models without a tokenizer reject token ids clearly
+        let (status, Json(error)) = embeddings_handler(
+            axum::extract::State(state),
+            axum::extract::Query(QueryParams { model: None }),
+            Json(request("mock")),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.error.code.as_deref(), Some("unsupported_input"));
+        assert!(error.error.message.contains("Token-id input"));
+    }
+
+    #[test]
+    fn test_embedding_input_forms() {
+        let parse = |value: serde_json::Value| serde_json::from_value::<EmbeddingInput>(value).unwrap();
+        assert_eq!(parse(serde_json::json!("hi")), EmbeddingInput::Text("hi".to_string()));
+        assert_eq!(
+            parse(serde_json::json!(["a", "b"])),
+            EmbeddingInput::TextBatch(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(parse(serde_json::json!([1, 2])), EmbeddingInput::Tokens(vec![1, 2]));
+        assert_eq!(
+            parse(serde_json::json!([[1], [2, 3]])),
+            EmbeddingInput::TokenBatch(vec![vec![1], vec![2, 3]])
+        );
+        assert!(serde_json::from_value::<EmbeddingInput>(serde_json::json!([1.5])).is_err());
+        assert_eq!(parse(serde_json::json!([[1], [2, 3]])).token_count(), Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_reload_model_handler_unknown_model() {
+        let state = Arc::new(AppState::from_models(HashMap::new(), "none"));
+        let (status, Json(error)) =
+            reload_model_handler(axum::extract::State(state), Path("missing".to_string()))
+                .await
+                .err()
+                .unwrap();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error.error.r#type, "model_not_found_error");
+        assert_eq!(error.error.code.as_deref(), Some("model_not_found"));
+    }
+
+    // Mock model whose first `failures` encode calls fail
+    struct FlakyModel {
+        failures: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Model for FlakyModel {
+        fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
+            inputs.iter().map(|_| vec![1.0]).collect()
+        }
+
+        fn try_encode(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+            use std::sync::atomic::Ordering;
+            if self.failures.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok() {
+                Err("backend unavailable".to_string())
+            } else {
+                Ok(self.encode(inputs))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_handler_retries_failed_encode() {
+        let flaky_state = |failures: usize, retries: u32| {
+            let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+            models.insert(
+                "flaky".to_string(),
+                Arc::new(FlakyModel { failures: failures.into() }),
+            );
+            let mut state = AppState::from_models(models, "flaky");
+            state.encode_retries = retries;
+            Arc::new(state)
+        };
+
+        let (status, body) = post_embeddings(flaky_state(2, 2), serde_json::json!({"input": "a"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"][0]["embedding"], serde_json::json!([1.0]));
+
+        // Retries are off by default
+        let (status, body) = post_embeddings(flaky_state(1, 0), serde_json::json!({"input": "a"})).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"]["message"], "Embedding generation failed: backend unavailable");
+
+        let (status, _) = post_embeddings(flaky_state(3, 2), serde_json::json!({"input": "a"})).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    async fn post_embeddings(state: Arc<AppState>, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let request: EmbeddingRequest = serde_json::from_value(body).unwrap();
+        let response = create_embeddings(
+            axum::extract::State(state),
+            axum::extract::Query(QueryParams { model: None }),
+            Json(request),
+        )
+        .await;
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_handler_encoding_format() {
+        use base64::Engine;
+
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 8)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+        let expected = MockModel::new("mock".to_string(), 8).embed("hello");
+
+        let embeddings = |body: serde_json::Value| {
+            let state = state.clone();
+            async move {
+                let response = embeddings_handler(
+                    axum::extract::State(state),
+                    axum::extract::Query(QueryParams { model: None }),
+                    Json(serde_json::from_value(body).unwrap()),
+                )
+                .await
+                .into_response();
+                let status = response.status();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        for body in [
+            serde_json::json!({"input": "hello"}),
+            serde_json::json!({"input": "hello", "encoding_format": "float"}),
+        ] {
+            let (status, body) = embeddings(body).await;
+            assert_eq!(status, StatusCode::OK);
+            let floats: Vec<f32> = serde_json::from_value(body["data"][0]["embedding"].clone()).unwrap();
+            assert_eq!(floats, expected);
+        }
+
+        let (status, body) = embeddings(serde_json::json!({"input": ["hello"], "encoding_format": "base64"})).await;
+        assert_eq!(status, StatusCode::OK);
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(body["data"][0]["embedding"].as_str().unwrap())
+            .unwrap();
+        assert_eq!(bytes.len(), 8 * 4);
+        let decoded: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(decoded, expected);
+
+        let (status, body) = embeddings(serde_json::json!({"input": "hello", "encoding_format": "binary"})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["type"], "invalid_request_error");
+        assert_eq!(body["error"]["param"], "encoding_format");
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_handler_dimensions() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 8)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+
+        let embeddings = |body: serde_json::Value| {
+            let state = state.clone();
+            async move {
+                let response = embeddings_handler(
+                    axum::extract::State(state),
+                    axum::extract::Query(QueryParams { model: None }),
+                    Json(serde_json::from_value(body).unwrap()),
+                )
+                .await
+                .into_response();
+                let status = response.status();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        let (status, full) = embeddings(serde_json::json!({"input": "hello world"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(full["dimensions"], 8);
+
+        let (status, body) = embeddings(serde_json::json!({"input": "hello world", "dimensions": 3})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["dimensions"], 3);
+        assert_eq!(body["usage"], full["usage"]);
+        let truncated: Vec<f32> = serde_json::from_value(body["data"][0]["embedding"].clone()).unwrap();
+        let mut expected = MockModel::new("mock".to_string(), 8).embed("hello world");
+        expected.truncate(3);
+        let norm = expected.iter().map(|v| v * v).sum::<f32>().sqrt();
+        for (value, original) in truncated.iter().zip(&expected) {
+            assert!((value - original / norm).abs() < 1e-6);
+        }
+
+        let (status, body) = embeddings(serde_json::json!({"input": "hello world", "dimensions": 9})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["param"], "dimensions");
+        assert!(body["error"]["message"].as_str().unwrap().contains("native dimension is 8"));
+
+        // Without normalization a truncated embedding is a prefix of the full one
+        let (status, body) =
+            embeddings(serde_json::json!({"input": "hello world", "dimensions": 3, "normalize": false})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"][0]["embedding"], serde_json::json!(full["data"][0]["embedding"].as_array().unwrap()[..3]));
+
+        let (_, body) = embeddings(serde_json::json!({"input": "hello world", "normalize": true})).await;
+        let normalized: Vec<f32> = serde_json::from_value(body["data"][0]["embedding"].clone()).unwrap();
+        assert!((normalized.iter().map(|v| v * v).sum::<f32>().sqrt() - 1.0).abs() < 1e-5);
+
+        // Inputs are cut to max_length characters before encoding, and counted as cut
+        let (status, body) = embeddings(serde_json::json!({"input": "hello world", "max_length": 5})).await;
+        let (_, hello) = embeddings(serde_json::json!({"input": "hello"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"], hello["data"]);
+        assert_eq!(body["usage"], hello["usage"]);
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_report_cache_usage_header() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 4)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+        state.models.cache().set_capacity(64);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move { axum::serve(listener, create_api_router().with_state(state)).await });
+
+        let client = reqwest::Client::new();
+        let cache_header = |input: serde_json::Value| {
+            let request = client.post(format!("{}/v1/embeddings", base)).json(&serde_json::json!({"input": input}));
+            async move {
+                let response = request.send().await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                response.headers().get(cache::CACHE_HEADER).map(|v| v.to_str().unwrap().to_string())
+            }
+        };
+
+        assert_eq!(cache_header(serde_json::json!(["a", "b"])).await.unwrap(), "hits=0, misses=2");
+        assert_eq!(cache_header(serde_json::json!(["a", "b", "c"])).await.unwrap(), "hits=2, misses=1");
+
+        // Counts add up over the chunks of a large batch
+        let batch: Vec<String> = (0..40).map(|i| format!("text {}", i)).collect();
+        assert_eq!(cache_header(serde_json::json!(batch)).await.unwrap(), "hits=0, misses=40");
+        assert_eq!(cache_header(serde_json::json!(batch)).await.unwrap(), "hits=40, misses=0");
+
+        // Endpoints that do not encode carry no header
+        let response = client.get(format!("{}/v1/models", base)).send().await.unwrap();
+        assert!(response.headers().get(cache::CACHE_HEADER).is_none());
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_model_detail_handler() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 12)));
+        models.insert("other".to_string(), Arc::new(MockModel::new("other".to_string(), 4)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+
+        let get = |model: &str| {
+            let state = state.clone();
+            let model = model.to_string();
+            async move {
+                let response = model_detail_handler(axum::extract::State(state), Path(model))
+                    .await
+                    .into_response();
+                let status = response.status();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        let (status, body) = get("mock").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], "mock");
+        assert_eq!(body["object"], "model");
+        assert_eq!(body["owned_by"], "custom");
+        assert_eq!(body["dimensions"], 12);
+        assert_eq!(body["default"], true);
+        assert_eq!(body["path"], serde_json::Value::Null);
+        assert_eq!(body["created"], body["loaded_at"]);
+        assert!(body["loaded_at"].as_u64().unwrap() > 0);
+
+        let (_, body) = get("other").await;
+        assert_eq!(body["dimensions"], 4);
+        assert_eq!(body["default"], false);
+
+        let (status, body) = get("missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["type"], "model_not_found_error");
+        assert_eq!(body["error"]["code"], "model_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_rank_handler() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 16)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+
+        let rank = |body: serde_json::Value| {
+            let state = state.clone();
+            async move {
+                let response = rank_handler(
+                    axum::extract::State(state),
+                    axum::extract::Query(QueryParams { model: None }),
+                    Json(serde_json::from_value(body).unwrap()),
+                )
+                .await
+                .into_response();
+                let status = response.status();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        // Enough documents to be encoded in parallel chunks
+        let documents: Vec<String> = (0..40).map(|i| format!("document {}", i)).collect();
+        let mock = MockModel::new("mock".to_string(), 16);
+        let query = mock.embed("document 7");
+        let mut expected: Vec<(usize, f32)> = documents
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (i, crate::utils::cosine_similarity(&query, &mock.embed(d))))
+            .collect();
+        expected.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let (status, body) = rank(serde_json::json!({"query": "document 7", "documents": documents})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["model"], "mock");
+        let data = body["data"].as_array().unwrap();
+        assert_eq!(data.len(), 40);
+        assert_eq!(data[0]["index"], 7);
+        assert!((data[0]["score"].as_f64().unwrap() - 1.0).abs() < 1e-5);
+        let indices: Vec<u64> = data.iter().map(|d| d["index"].as_u64().unwrap()).collect();
+        let expected_indices: Vec<u64> = expected.iter().map(|(i, _)| *i as u64).collect();
+        assert_eq!(indices, expected_indices);
+
+        let (status, body) = rank(serde_json::json!({"query": "document 7", "documents": documents, "top_k": 3})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"].as_array().unwrap().len(), 3);
+        assert_eq!(body["data"][0]["index"], 7);
+
+        let (status, body) = rank(serde_json::json!({"query": "cat", "documents": []})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["param"], "documents");
+
+        let (status, body) = rank(serde_json::json!({"query": "", "documents": ["a"]})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["param"], "query");
+    }
+
+    #[tokio::test]
+    async fn test_usage_counts_tokenizer_tokens() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 8)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+        let tmp = tempfile::TempDir::new().unwrap();
+        crate::server::test_utils::write_test_model(tmp.path(), 4);
+        state.models.load_from_path("tokenized", tmp.path().to_str().unwrap()).await.unwrap();
+
+        let usage = |model: &str, input: serde_json::Value| {
+            let request: EmbeddingRequest =
+                serde_json::from_value(serde_json::json!({"input": input, "model": model})).unwrap();
+            let state = state.clone();
+            async move {
+                embeddings_handler(
+                    axum::extract::State(state),
+                    axum::extract::Query(QueryParams { model: None }),
+                    Json(request),
+                )
+                .await
+                .unwrap()
+                .0
+                .usage
+                .prompt_tokens
+            }
+        };
+
+        // The test tokenizer splits on whitespace, whatever the byte length
+        assert_eq!(usage("tokenized", serde_json::json!("héllo wörld")).await, 2);
+        assert_eq!(usage("tokenized", serde_json::json!(["hello world", "hello"])).await, 3);
+        // Models without a tokenizer fall back to four bytes per token
+        assert_eq!(usage("mock", serde_json::json!("héllo wörld")).await, 4);
+
+        // The input limit is in tokens: 36000 bytes but only 6000 tokens
+        let long = "hello ".repeat(6000);
+        assert_eq!(usage("tokenized", serde_json::json!(long)).await, 6000);
+        let too_long: EmbeddingRequest = serde_json::from_value(
+            serde_json::json!({"input": "hello ".repeat(MAX_INPUT_TOKENS + 1), "model": "tokenized"}),
+        )
+        .unwrap();
+        let (status, _) = embeddings_handler(
+            axum::extract::State(state.clone()),
+            axum::extract::Query(QueryParams { model: None }),
+            Json(too_long),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let tokenize = |text: &str, model: Option<&str>| {
+            let params = TokenizeParams { text: text.to_string(), model: model.map(str::to_string) };
+            let state = state.clone();
+            async move {
+                tokenize_handler(axum::extract::State(state), axum::extract::Query(params))
+                    .await
+                    .unwrap()
+                    .0
+            }
+        };
+        let counted = tokenize("héllo wörld", Some("tokenized")).await;
+        assert_eq!(counted.object, "tokenize");
+        assert_eq!(counted.model, "tokenized");
+        assert_eq!(counted.tokens, 2);
+        assert_eq!(counted.max_tokens, MAX_INPUT_TOKENS);
+        let estimated = tokenize("héllo wörld", None).await;
+        assert_eq!((estimated.model.as_str(), estimated.tokens), ("mock", 4));
+    }
+
+    #[tokio::test]
+    async fn test_load_and_unload_model_handlers() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 8)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+        let tmp = tempfile::TempDir::new().unwrap();
+        crate::server::test_utils::write_test_model(tmp.path(), 6);
+
+        let status_and_body = |response: Response| async move {
+            let status = response.status();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+        };
+        let load = |name: &str, path: &str| {
+            let state = state.clone();
+            let request = LoadModelRequest { name: name.to_string(), path: path.to_string() };
+            async move { load_model_handler(axum::extract::State(state), Json(request)).await.into_response() }
+        };
+        let unload = |name: &str| {
+            let state = state.clone();
+            let request = UnloadModelRequest { name: name.to_string() };
+            async move { unload_model_handler(axum::extract::State(state), Json(request)).await.into_response() }
+        };
+
+        let (status, body) = status_and_body(load("fresh", tmp.path().to_str().unwrap()).await).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["dimensions"], 6);
+
+        // Immediately servable, with its on-disk path recorded
+        let request: EmbeddingRequest =
+            serde_json::from_value(serde_json::json!({"input": "hello world", "model": "fresh"})).unwrap();
+        let response = embeddings_handler(
+            axum::extract::State(state.clone()),
+            axum::extract::Query(QueryParams { model: None }),
+            Json(request),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.0.model, "fresh");
+        assert_eq!(response.0.dimensions, 6);
+        assert_eq!(state.models.details("fresh").unwrap().path.as_deref(), Some(tmp.path()));
+
+        let (status, body) = status_and_body(load("other", "/no/such/model").await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["type"], "invalid_request_error");
+        assert!(!state.models.contains("other"));
+
+        let (status, body) = status_and_body(unload("mock").await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"]["message"].as_str().unwrap().contains("default model"));
+
+        let (status, _) = status_and_body(unload("fresh").await).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!state.models.contains("fresh"));
+        let (status, body) = status_and_body(unload("fresh").await).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["code"], "model_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_similarity_handler() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 16)));
+        models.insert("zero".to_string(), Arc::new(MockModel::new("zero".to_string(), 0)));
+        let state = Arc::new(AppState::from_models(models, "mock"));
+
+        let similarity = |body: serde_json::Value| {
+            let state = state.clone();
+            async move {
+                let response = similarity_handler(
+                    axum::extract::State(state),
+                    axum::extract::Query(QueryParams { model: None }),
+                    Json(serde_json::from_value(body).unwrap()),
+                )
+                .await
+                .into_response();
+                let status = response.status();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap())
+            }
+        };
+
+        let (status, body) = similarity(serde_json::json!({"text_a": "cat", "text_b": "cat"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!((body["data"][0]["similarity"].as_f64().unwrap() - 1.0).abs() < 1e-5);
+        assert_eq!(body["model"], "mock");
+        assert_eq!(body["dimensions"], 16);
+
+        // Batches are compared pairwise, in order
+        let (status, body) = similarity(serde_json::json!({
+            "text_a": ["cat", "dog"],
+            "text_b": ["kitten", "dog"],
+            "model": "mock"
+        }))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let pairs = body["data"].as_array().unwrap();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[1]["index"], 1);
+        assert!(pairs[0]["similarity"].as_f64().unwrap() < 0.99);
+        assert!((pairs[1]["similarity"].as_f64().unwrap() - 1.0).abs() < 1e-5);
+
+        // Zero vectors score 0 rather than NaN
+        let (status, body) = similarity(serde_json::json!({"text_a": "a", "text_b": "b", "model": "zero"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"][0]["similarity"], 0.0);
+
+        let (status, body) = similarity(serde_json::json!({"text_a": ["a", "b"], "text_b": ["a"]})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["param"], "text_b");
+
+        let (status, body) = similarity(serde_json::json!({"text_a": [], "text_b": []})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["param"], "text_a");
+
+        let (status, body) = similarity(serde_json::json!({"text_a": "a", "text_b": ""})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["param"], "text_b");
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_grouped_by_model() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("small".to_string(), Arc::new(MockModel::new("small".to_string(), 4)));
+        models.insert("large".to_string(), Arc::new(MockModel::new("large".to_string(), 8)));
+        let state = Arc::new(AppState::from_models(models, "small"));
+
+        let (status, body) = post_embeddings(
+            state.clone(),
+            serde_json::json!({"input": ["a", "b"], "model": ["large", "small", "large"]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["models"], serde_json::json!(["large", "small"]));
+        assert_eq!(body["by_model"]["large"].as_array().unwrap().len(), 2);
+        assert_eq!(body["by_model"]["large"][1]["embedding"].as_array().unwrap().len(), 8);
+        assert_eq!(body["by_model"]["small"][0]["embedding"].as_array().unwrap().len(), 4);
+        assert!(body.get("data").is_none());
+
+        // A single model, as a string or a one-element list, keeps the usual shape
+        for model in [serde_json::json!("large"), serde_json::json!(["large"])] {
+            let (status, body) =
+                post_embeddings(state.clone(), serde_json::json!({"input": "a", "model": model})).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body["model"], "large");
+            assert_eq!(body["data"][0]["embedding"].as_array().unwrap().len(), 8);
+        }
+
+        // Unknown models in a list are not replaced by the default
+        let (status, body) = post_embeddings(
+            state.clone(),
+            serde_json::json!({"input": "a", "model": ["small", "missing"]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"]["message"], "Model not found: missing");
+        assert_eq!(body["error"]["param"], "model");
+
+        let (status, _) = post_embeddings(state, serde_json::json!({"input": "a", "model": []})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
     #[test]
     fn test_create_api_router_compiles() {
         // Ensure router can be created without panicking
         let _router = create_api_router();
-        assert!(true);
     }
 
     #[tokio::test]
     async fn test_embeddings_handler_model_from_query_params() {
         let state = create_test_app_state();
         let request = EmbeddingRequest {
-            input: vec!["test text".to_string()],
+            input: vec!["test text".to_string()].into(),
             model: None,
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let result = embeddings_handler(
@@ -606,11 +2139,14 @@ mod tests {
         let inputs: Vec<String> = (0..33).map(|i| format!("text {}", i)).collect();
 
         let request = EmbeddingRequest {
-            input: inputs,
-            model: Some("test-model".to_string()),
+            input: inputs.into(),
+            model: Some("test-model".into()),
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let result = embeddings_handler(
@@ -632,20 +2168,19 @@ mod tests {
         let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
         models.insert("panic-model".to_string(), Arc::new(MockModelPanics));
 
-        let state = Arc::new(AppState {
-            models,
-            default_model: "panic-model".to_string(),
-            startup_time: std::time::SystemTime::now(),
-        });
+        let state = Arc::new(AppState::from_models(models, "panic-model"));
 
         // Trigger the parallel path (>32 items)
         let inputs: Vec<String> = (0..33).map(|i| format!("text {}", i)).collect();
         let request = EmbeddingRequest {
-            input: inputs,
-            model: Some("panic-model".to_string()),
+            input: inputs.into(),
+            model: Some("panic-model".into()),
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let result = embeddings_handler(
@@ -669,7 +2204,6 @@ mod tests {
         // The router should have the expected routes
         // We can't easily test the exact routes without more complex setup,
         // but we can verify the router is created successfully
-        assert!(true); // If we get here, router creation worked
     }
 
     #[test]
@@ -679,15 +2213,24 @@ mod tests {
             "model": "test-model",
             "encoding_format": "float",
             "dimensions": 128,
-            "user": "test-user"
+            "user": "test-user",
+            "normalize": false,
+            "max_length": 1000
         }"#;
 
         let request: EmbeddingRequest = serde_json::from_str(json).unwrap();
-        assert_eq!(request.input, vec!["text1", "text2"]);
-        assert_eq!(request.model, Some("test-model".to_string()));
+        assert_eq!(
+            request.input,
+            EmbeddingInput::TextBatch(vec!["text1".to_string(), "text2".to_string()])
+        );
+        assert_eq!(request.model, Some("test-model".into()));
         assert_eq!(request.encoding_format, Some("float".to_string()));
         assert_eq!(request.dimensions, Some(128));
         assert_eq!(request.user, Some("test-user".to_string()));
+        assert_eq!(
+            request.encode_options(),
+            EncodeOptions { normalize: false, truncate_dims: Some(128), max_length: Some(1000) }
+        );
     }
 
     #[test]
@@ -696,10 +2239,11 @@ mod tests {
             object: "list".to_string(),
             data: vec![EmbeddingData {
                 object: "embedding".to_string(),
-                embedding: vec![0.1, 0.2, 0.3],
+                embedding: vec![0.1, 0.2, 0.3].into(),
                 index: 0,
             }],
             model: "test-model".to_string(),
+            dimensions: 3,
             usage: Usage {
                 prompt_tokens: 10,
                 total_tokens: 10,
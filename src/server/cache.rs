@@ -0,0 +1,277 @@
+//! In-memory LRU cache of embeddings.
+//!
+//! Retrieval pipelines often re-embed the same query, so with
+//! `server.embedding_cache_size` set the server remembers recent results and
+//! skips `Model::encode` for texts it has already seen. Entries are keyed by the
+//! model instance and the SHA-256 of the text: a reloaded or replaced model
+//! starts with a cold cache instead of serving its predecessor's vectors.
+//!
+//! The cache holds raw embeddings; precision, truncation and encoding are
+//! applied per request afterwards. Hits and misses are counted in the
+//! `embedtool.embedding_cache.hits` and `embedtool.embedding_cache.misses`
+//! metrics. A size of 0 (the default) disables it.
+//!
+//! API responses whose inputs went through the cache also report that
+//! request's counts in an `X-Embedding-Cache: hits=2, misses=1` header.
+
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use lru::LruCache;
+use metrics::counter;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Header reporting the cache hits and misses of one request.
+pub const CACHE_HEADER: &str = "x-embedding-cache";
+
+/// Cache hits and misses of one request, summed over all of its encodes.
+#[derive(Debug, Default)]
+pub struct CacheUsage {
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl CacheUsage {
+    fn record(&self, hits: usize, misses: usize) {
+        self.hits.fetch_add(hits, Ordering::Relaxed);
+        self.misses.fetch_add(misses, Ordering::Relaxed);
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+tokio::task_local! {
+    static REQUEST_USAGE: Arc<CacheUsage>;
+}
+
+thread_local! {
+    static ENCODE_USAGE: RefCell<Option<Arc<CacheUsage>>> = const { RefCell::new(None) };
+}
+
+/// Cache usage of the request being handled on this task, if it reports one.
+pub fn current_usage() -> Option<Arc<CacheUsage>> {
+    REQUEST_USAGE.try_with(Arc::clone).ok()
+}
+
+/// Run `encode` on this thread with its cache hits and misses added to `usage`.
+///
+/// Encodes run on the blocking pool, out of reach of the request's task, so
+/// the caller passes along what [`current_usage`] returned.
+pub fn count_usage<T>(usage: Option<Arc<CacheUsage>>, encode: impl FnOnce() -> T) -> T {
+    let previous = ENCODE_USAGE.with(|current| current.replace(usage));
+    let result = encode();
+    ENCODE_USAGE.with(|current| *current.borrow_mut() = previous);
+    result
+}
+
+/// Middleware adding [`CACHE_HEADER`] to responses that used the cache.
+pub async fn report_cache_usage(request: Request, next: Next) -> Response {
+    let usage = Arc::new(CacheUsage::default());
+    let mut response = REQUEST_USAGE.scope(Arc::clone(&usage), next.run(request)).await;
+    let (hits, misses) = (usage.hits(), usage.misses());
+    if hits + misses > 0
+        && let Ok(value) = HeaderValue::from_str(&format!("hits={}, misses={}", hits, misses))
+    {
+        response.headers_mut().insert(CACHE_HEADER, value);
+    }
+    response
+}
+
+/// Model instance id and SHA-256 of the text.
+type CacheKey = (u64, [u8; 32]);
+
+/// Shared LRU cache of embeddings, disabled until given a capacity.
+#[derive(Default)]
+pub struct EmbeddingCache {
+    lru: Mutex<Option<LruCache<CacheKey, Arc<[f32]>>>>,
+}
+
+impl EmbeddingCache {
+    /// A cache holding up to `capacity` embeddings; 0 disables it.
+    pub fn new(capacity: usize) -> Self {
+        let cache = Self::default();
+        cache.set_capacity(capacity);
+        cache
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<LruCache<CacheKey, Arc<[f32]>>>> {
+        self.lru.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Resize the cache, evicting the least recently used entries if it shrinks.
+    /// A capacity of 0 disables the cache and drops everything in it.
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut lru = self.lock();
+        match (NonZeroUsize::new(capacity), lru.as_mut()) {
+            (None, _) => *lru = None,
+            (Some(capacity), Some(existing)) => existing.resize(capacity),
+            (Some(capacity), None) => *lru = Some(LruCache::new(capacity)),
+        }
+    }
+
+    /// Maximum number of embeddings held; 0 when disabled.
+    pub fn capacity(&self) -> usize {
+        self.lock().as_ref().map_or(0, |lru| lru.cap().get())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    /// Number of embeddings currently cached.
+    pub fn len(&self) -> usize {
+        self.lock().as_ref().map_or(0, |lru| lru.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Embed `inputs` for model instance `instance`, calling `encode` only for
+    /// texts that are not cached and caching what it returns.
+    ///
+    /// If `encode` returns the wrong number of embeddings nothing is cached and
+    /// its result is returned as is, so the caller reports the mismatch.
+    pub(crate) fn encode<E>(&self, instance: u64, inputs: &[String], encode: E) -> Result<Vec<Vec<f32>>, String>
+    where
+        E: FnOnce(&[String]) -> Result<Vec<Vec<f32>>, String>,
+    {
+        let keys: Vec<CacheKey> = inputs
+            .iter()
+            .map(|text| (instance, Sha256::digest(text.as_bytes()).into()))
+            .collect();
+
+        let mut found: Vec<Option<Arc<[f32]>>> = {
+            let mut lru = self.lock();
+            match lru.as_mut() {
+                Some(lru) => keys.iter().map(|key| lru.get(key).cloned()).collect(),
+                None => return encode(inputs),
+            }
+        };
+
+        let missing: Vec<usize> = (0..inputs.len()).filter(|&i| found[i].is_none()).collect();
+        counter!("embedtool.embedding_cache.hits").increment((inputs.len() - missing.len()) as u64);
+        counter!("embedtool.embedding_cache.misses").increment(missing.len() as u64);
+        ENCODE_USAGE.with(|usage| {
+            if let Some(usage) = usage.borrow().as_ref() {
+                usage.record(inputs.len() - missing.len(), missing.len());
+            }
+        });
+        if missing.is_empty() {
+            return Ok(found.into_iter().flatten().map(|e| e.to_vec()).collect());
+        }
+
+        // Encode outside the lock so slow models don't serialize other requests
+        let texts: Vec<String> = missing.iter().map(|&i| inputs[i].clone()).collect();
+        let encoded = encode(&texts)?;
+        if encoded.len() != texts.len() {
+            return Ok(encoded);
+        }
+
+        let mut lru = self.lock();
+        for (&i, embedding) in missing.iter().zip(encoded) {
+            let embedding: Arc<[f32]> = embedding.into();
+            if let Some(lru) = lru.as_mut() {
+                lru.put(keys[i], Arc::clone(&embedding));
+            }
+            found[i] = Some(embedding);
+        }
+        Ok(found.into_iter().flatten().map(|e| e.to_vec()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn texts(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Embeds each text as `[len]`, counting how many texts were encoded.
+    fn counting_encoder(calls: &AtomicUsize) -> impl FnOnce(&[String]) -> Result<Vec<Vec<f32>>, String> + '_ {
+        move |inputs| {
+            calls.fetch_add(inputs.len(), Ordering::SeqCst);
+            Ok(inputs.iter().map(|t| vec![t.len() as f32]).collect())
+        }
+    }
+
+    #[test]
+    fn test_cache_skips_encoding_cached_texts() {
+        let cache = EmbeddingCache::new(8);
+        let calls = AtomicUsize::new(0);
+
+        let first = cache.encode(1, &texts(&["a", "bb"]), counting_encoder(&calls)).unwrap();
+        assert_eq!(first, vec![vec![1.0], vec![2.0]]);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Only the new text is encoded, and results keep input order
+        let second = cache.encode(1, &texts(&["ccc", "bb", "a"]), counting_encoder(&calls)).unwrap();
+        assert_eq!(second, vec![vec![3.0], vec![2.0], vec![1.0]]);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(cache.len(), 3);
+
+        // Another model instance does not see these entries
+        cache.encode(2, &texts(&["a"]), counting_encoder(&calls)).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = EmbeddingCache::new(2);
+        let calls = AtomicUsize::new(0);
+        cache.encode(1, &texts(&["a", "b"]), counting_encoder(&calls)).unwrap();
+        cache.encode(1, &texts(&["a"]), counting_encoder(&calls)).unwrap();
+        cache.encode(1, &texts(&["c"]), counting_encoder(&calls)).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // "b" was evicted, "a" was not
+        cache.encode(1, &texts(&["a"]), counting_encoder(&calls)).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        cache.encode(1, &texts(&["b"]), counting_encoder(&calls)).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_disabled_cache_always_encodes() {
+        let cache = EmbeddingCache::default();
+        assert!(!cache.is_enabled());
+        let calls = AtomicUsize::new(0);
+        for _ in 0..2 {
+            cache.encode(1, &texts(&["a"]), counting_encoder(&calls)).unwrap();
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        cache.set_capacity(4);
+        assert_eq!(cache.capacity(), 4);
+        cache.set_capacity(0);
+        assert!(!cache.is_enabled());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_cache_does_not_store_mismatched_results() {
+        let cache = EmbeddingCache::new(4);
+        let result = cache.encode(1, &texts(&["a", "b"]), |_| Ok(vec![vec![1.0]])).unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(cache.is_empty());
+
+        let error = cache.encode(1, &texts(&["a"]), |_| Err("boom".to_string())).unwrap_err();
+        assert_eq!(error, "boom");
+        assert!(cache.is_empty());
+    }
+}
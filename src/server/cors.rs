@@ -0,0 +1,220 @@
+//! Cross-origin access control for browser clients.
+//!
+//! CORS is enabled by listing origins in `server.cors_allowed_origins`. Allowed
+//! origins receive the usual CORS response headers; disallowed ones get
+//! standard CORS behavior (no headers, so the browser blocks the response).
+//!
+//! Because that failure is opaque to the web client, every rejected origin is
+//! logged. With `server.cors_reject_disallowed = true`, non-preflight requests
+//! from a disallowed origin are answered with an explicit 403 instead:
+//!
+//! ```json
+//! {
+//!   "error": {
+//!     "message": "Origin 'https://evil.example' is not allowed",
+//!     "type": "invalid_request_error",
+//!     "param": null,
+//!     "code": "origin_not_allowed"
+//!   }
+//! }
+//! ```
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Json as ResponseJson, Response},
+};
+use anyhow::{Result as AnyhowResult, anyhow};
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing::warn;
+
+use super::{ApiError, ErrorDetails};
+
+/// Origins permitted to call the API from a browser.
+#[derive(Clone, Debug)]
+pub struct OriginPolicy {
+    /// Allowed origins; `*` allows any origin
+    pub allowed_origins: Vec<String>,
+    /// Answer disallowed non-preflight requests with 403 instead of omitting CORS headers
+    pub reject_disallowed: bool,
+}
+
+impl OriginPolicy {
+    /// Whether `origin` may call the API.
+    pub fn is_allowed(&self, origin: &str) -> bool {
+        let origin = origin.trim_end_matches('/');
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed == "*" || allowed.trim_end_matches('/') == origin)
+    }
+
+    /// Build the CORS layer answering preflights and adding response headers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configured origin is not a valid header value.
+    pub fn cors_layer(&self) -> AnyhowResult<CorsLayer> {
+        let allow_origin = if self.allowed_origins.iter().any(|o| o == "*") {
+            AllowOrigin::any()
+        } else {
+            let origins = self
+                .allowed_origins
+                .iter()
+                .map(|origin| {
+                    HeaderValue::from_str(origin.trim_end_matches('/'))
+                        .map_err(|e| anyhow!("Invalid CORS origin '{}': {}", origin, e))
+                })
+                .collect::<AnyhowResult<Vec<_>>>()?;
+            AllowOrigin::list(origins)
+        };
+
+        Ok(CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
+            .allow_headers(Any))
+    }
+}
+
+/// Middleware logging, and optionally rejecting, requests from disallowed origins.
+///
+/// Preflight (`OPTIONS`) requests are always passed on to the CORS layer so the
+/// browser receives a standard CORS answer.
+pub async fn check_origin(
+    State(policy): State<Arc<OriginPolicy>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let origin = request
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    if let Some(origin) = origin
+        && !policy.is_allowed(&origin)
+    {
+        warn!(
+            origin = %origin,
+            method = %request.method(),
+            uri = %request.uri(),
+            "Rejected request from disallowed origin"
+        );
+        if policy.reject_disallowed && request.method() != Method::OPTIONS {
+            let error = ApiError {
+                error: ErrorDetails {
+                    message: format!("Origin '{}' is not allowed", origin),
+                    r#type: "invalid_request_error".to_string(),
+                    param: None,
+                    code: Some("origin_not_allowed".to_string()),
+                },
+            };
+            return (StatusCode::FORBIDDEN, ResponseJson(error)).into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, middleware, routing::get};
+
+    async fn spawn_with_policy(policy: OriginPolicy) -> String {
+        let cors = policy.cors_layer().unwrap();
+        let app = Router::new()
+            .route("/ping", get(|| async { "pong" }))
+            .layer(cors)
+            .layer(middleware::from_fn_with_state(Arc::new(policy), check_origin));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        format!("http://{}", addr)
+    }
+
+    fn policy(reject_disallowed: bool) -> OriginPolicy {
+        OriginPolicy {
+            allowed_origins: vec!["https://app.example/".to_string()],
+            reject_disallowed,
+        }
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let policy = policy(false);
+        assert!(policy.is_allowed("https://app.example"));
+        assert!(!policy.is_allowed("https://evil.example"));
+
+        let any = OriginPolicy {
+            allowed_origins: vec!["*".to_string()],
+            reject_disallowed: true,
+        };
+        assert!(any.is_allowed("https://evil.example"));
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_origin_gets_standard_cors_by_default() {
+        let addr = spawn_with_policy(policy(false)).await;
+        let client = reqwest::Client::new();
+
+        let allowed = client
+            .get(format!("{}/ping", addr))
+            .header("Origin", "https://app.example")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(allowed.status(), 200);
+        assert_eq!(
+            allowed.headers()["access-control-allow-origin"],
+            "https://app.example"
+        );
+
+        let disallowed = client
+            .get(format!("{}/ping", addr))
+            .header("Origin", "https://evil.example")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(disallowed.status(), 200);
+        assert!(disallowed.headers().get("access-control-allow-origin").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_origin_rejected_when_configured() {
+        let addr = spawn_with_policy(policy(true)).await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .get(format!("{}/ping", addr))
+            .header("Origin", "https://evil.example")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "origin_not_allowed");
+        assert!(body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("https://evil.example"));
+
+        // Preflight still gets a standard CORS answer rather than a 403
+        let preflight = client
+            .request(reqwest::Method::OPTIONS, format!("{}/ping", addr))
+            .header("Origin", "https://evil.example")
+            .header("Access-Control-Request-Method", "GET")
+            .send()
+            .await
+            .unwrap();
+        assert_ne!(preflight.status(), 403);
+        assert!(preflight.headers().get("access-control-allow-origin").is_none());
+
+        // Requests without an Origin header are not cross-origin
+        let response = client.get(format!("{}/ping", addr)).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+    }
+}
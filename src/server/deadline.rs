@@ -0,0 +1,278 @@
+//! Client-supplied request deadlines.
+//!
+//! Callers may bound how long the server works on a request with either:
+//!
+//! - `grpc-timeout`: a relative budget such as `500m` (milliseconds), `2S`
+//!   (seconds) or `1M` (minutes), using the gRPC unit suffixes `H M S m u n`.
+//! - `X-Request-Deadline`: an absolute deadline in Unix epoch milliseconds.
+//!
+//! When both are present the earlier deadline wins. Requests without either
+//! header run without a deadline. Once the deadline passes, the request is
+//! answered with `504` and encode work that has not started yet is skipped:
+//!
+//! ```json
+//! {
+//!   "error": {
+//!     "message": "Request deadline exceeded",
+//!     "type": "timeout_error",
+//!     "param": null,
+//!     "code": "deadline_exceeded"
+//!   }
+//! }
+//! ```
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json as ResponseJson, Response},
+};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+use super::{ApiError, ErrorDetails};
+
+/// Relative timeout header using gRPC's `<digits><unit>` format.
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// Absolute deadline header in Unix epoch milliseconds.
+pub const REQUEST_DEADLINE_HEADER: &str = "x-request-deadline";
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Deadline of the request being handled on this task, if the client set one.
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Run `work` with `deadline` visible to it through [`current`].
+pub async fn scope<F: Future>(deadline: Instant, work: F) -> F::Output {
+    DEADLINE.scope(deadline, work).await
+}
+
+/// Whether `deadline` has passed.
+pub fn is_expired(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+/// Error returned when a request runs out of time.
+pub fn deadline_exceeded() -> (StatusCode, ResponseJson<ApiError>) {
+    let error = ApiError {
+        error: ErrorDetails {
+            message: "Request deadline exceeded".to_string(),
+            r#type: "timeout_error".to_string(),
+            param: None,
+            code: Some("deadline_exceeded".to_string()),
+        },
+    };
+    (StatusCode::GATEWAY_TIMEOUT, ResponseJson(error))
+}
+
+/// Parse a `grpc-timeout` value such as `250m` into a duration.
+fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    // The spec allows at most 8 digits followed by a single unit
+    if value.len() < 2 || value.len() > 9 {
+        return None;
+    }
+    let (digits, unit) = value.split_at(value.len() - 1);
+    let amount: u64 = digits.parse().ok()?;
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 3600)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// Convert an absolute Unix epoch millisecond deadline into an `Instant`.
+fn parse_epoch_deadline(value: &str, now: Instant) -> Option<Instant> {
+    let deadline = UNIX_EPOCH + Duration::from_millis(value.trim().parse().ok()?);
+    match deadline.duration_since(SystemTime::now()) {
+        Ok(remaining) => Some(now + remaining),
+        // Already in the past
+        Err(_) => Some(now),
+    }
+}
+
+/// Read the request deadline from `headers`.
+///
+/// # Errors
+///
+/// Returns a message naming the header when a value cannot be parsed.
+pub fn from_headers(headers: &HeaderMap) -> Result<Option<Instant>, String> {
+    let now = Instant::now();
+    let mut deadline: Option<Instant> = None;
+
+    if let Some(value) = headers.get(GRPC_TIMEOUT_HEADER) {
+        let timeout = value
+            .to_str()
+            .ok()
+            .and_then(parse_grpc_timeout)
+            .ok_or_else(|| format!("Invalid {} header: expected e.g. '500m' or '2S'", GRPC_TIMEOUT_HEADER))?;
+        deadline = Some(now + timeout);
+    }
+
+    if let Some(value) = headers.get(REQUEST_DEADLINE_HEADER) {
+        let absolute = value
+            .to_str()
+            .ok()
+            .and_then(|v| parse_epoch_deadline(v, now))
+            .ok_or_else(|| {
+                format!(
+                    "Invalid {} header: expected Unix epoch milliseconds",
+                    REQUEST_DEADLINE_HEADER
+                )
+            })?;
+        deadline = Some(deadline.map_or(absolute, |d| d.min(absolute)));
+    }
+
+    Ok(deadline)
+}
+
+/// Middleware enforcing the client's deadline on the wrapped routes.
+///
+/// The deadline is made available to handlers through [`current`] so that
+/// long-running work can stop early, and the response is cut off with `504`
+/// if the handler has not finished in time.
+pub async fn enforce_deadline(request: Request, next: Next) -> Response {
+    let deadline = match from_headers(request.headers()) {
+        Ok(Some(deadline)) => deadline,
+        Ok(None) => return next.run(request).await,
+        Err(message) => {
+            let error = ApiError {
+                error: ErrorDetails {
+                    message,
+                    r#type: "invalid_request_error".to_string(),
+                    param: None,
+                    code: None,
+                },
+            };
+            return (StatusCode::BAD_REQUEST, ResponseJson(error)).into_response();
+        }
+    };
+
+    if is_expired(Some(deadline)) {
+        warn!(uri = %request.uri(), "Request deadline already passed on arrival");
+        return deadline_exceeded().into_response();
+    }
+
+    let uri = request.uri().clone();
+    let work = scope(deadline, next.run(request));
+    match tokio::time::timeout_at(deadline.into(), work).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!(uri = %uri, "Request deadline exceeded");
+            deadline_exceeded().into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, middleware, routing::get};
+
+    async fn spawn_slow_server() -> String {
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    "done"
+                }),
+            )
+            .route(
+                "/deadline",
+                get(|| async { current().is_some().to_string() }),
+            )
+            .layer(middleware::from_fn(enforce_deadline));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        assert_eq!(parse_grpc_timeout("500m"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_grpc_timeout("2S"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_grpc_timeout("1H"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_grpc_timeout("10u"), Some(Duration::from_micros(10)));
+        assert_eq!(parse_grpc_timeout("m"), None);
+        assert_eq!(parse_grpc_timeout("5x"), None);
+        assert_eq!(parse_grpc_timeout("123456789S"), None);
+    }
+
+    #[test]
+    fn test_from_headers_picks_earliest_deadline() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(from_headers(&headers).unwrap(), None);
+
+        let far = SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + Duration::from_secs(3600);
+        headers.insert(REQUEST_DEADLINE_HEADER, far.as_millis().to_string().parse().unwrap());
+        headers.insert(GRPC_TIMEOUT_HEADER, "100m".parse().unwrap());
+        let deadline = from_headers(&headers).unwrap().unwrap();
+        assert!(deadline <= Instant::now() + Duration::from_millis(100));
+
+        headers.insert(GRPC_TIMEOUT_HEADER, "soon".parse().unwrap());
+        assert!(from_headers(&headers).unwrap_err().contains(GRPC_TIMEOUT_HEADER));
+    }
+
+    #[tokio::test]
+    async fn test_enforce_deadline() {
+        let addr = spawn_slow_server().await;
+        let client = reqwest::Client::new();
+
+        // No header: no deadline
+        let response = client.get(format!("{}/slow", addr)).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        let response = client.get(format!("{}/deadline", addr)).send().await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "false");
+
+        // Deadline visible to the handler
+        let response = client
+            .get(format!("{}/deadline", addr))
+            .header(GRPC_TIMEOUT_HEADER, "5S")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "true");
+
+        // Deadline exceeded while handling
+        let response = client
+            .get(format!("{}/slow", addr))
+            .header(GRPC_TIMEOUT_HEADER, "50m")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 504);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "deadline_exceeded");
+
+        // Deadline already in the past
+        let response = client
+            .get(format!("{}/slow", addr))
+            .header(REQUEST_DEADLINE_HEADER, "1000")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 504);
+
+        // Malformed header
+        let response = client
+            .get(format!("{}/slow", addr))
+            .header(REQUEST_DEADLINE_HEADER, "tomorrow")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+    }
+}
@@ -32,6 +32,10 @@ pub enum AppError {
     #[error("Failed to load model '{0}': {1}")]
     ModelLoad(String, String),
 
+    /// Requested model is not loaded or has no known source.
+    #[error("Model not found: {0}")]
+    ModelNotFound(String),
+
     /// No models were successfully loaded on server startup.
     #[error("No models available")]
     NoModelsAvailable,
@@ -66,6 +70,7 @@ impl AppError {
     pub fn error_type(&self) -> &'static str {
         match self {
             AppError::ModelLoad(_, _) => "model_load_error",
+            AppError::ModelNotFound(_) => "model_not_found_error",
             AppError::NoModelsAvailable => "server_error",
             AppError::InvalidInput(_) => "invalid_request_error",
             AppError::DatabaseError(_) => "server_error",
@@ -75,7 +80,8 @@ impl AppError {
 
     pub fn code(&self) -> Option<&'static str> {
         match self {
-            AppError::InvalidInput(_) => Some("invalid_input"),            _ => None,
+            AppError::InvalidInput(_) => Some("invalid_input"),
+            AppError::ModelNotFound(_) => Some("model_not_found"),            _ => None,
            }
     }
 }
@@ -97,6 +103,7 @@ mod tests {
     fn test_app_error_error_type() {
         assert_eq!(AppError::ModelLoad("test".to_string(), "error".to_string()).error_type(), "model_load_error");
         assert_eq!(AppError::NoModelsAvailable.error_type(), "server_error");
+        assert_eq!(AppError::ModelNotFound("x".to_string()).error_type(), "model_not_found_error");
         assert_eq!(AppError::InvalidInput("bad input".to_string()).error_type(), "invalid_request_error");
         assert_eq!(AppError::DatabaseError("db error".to_string()).error_type(), "server_error");
     }
@@ -1,8 +1,11 @@
 //! HTTP utility handlers for health checks and monitoring.
 //!
 //! This module provides lightweight endpoints for infrastructure monitoring:
-//! - **GET /health**: Simple health check endpoint
-//! - Returns 200 OK if server is running
+//! - **GET /health**: Liveness check with version, uptime and loaded models
+//! - Returns 200 OK whenever the server process is serving requests
+//! - **GET /ready**: Readiness probe following the configured `server.ready_policy`
+//! - Returns 503 until at least one model is loaded and the server may accept traffic
+//! - **GET /metrics**: Counters and gauges in the Prometheus text format
 //!
 //! ## Use Cases
 //!
@@ -15,44 +18,196 @@
 //! ```bash
 //! # Check server health
 //! curl http://localhost:8080/health
-//! # Returns: 200 OK (no body)
+//! # Returns: 200 {"status":"ok","version":"1.0.0","uptime_secs":42,"default_model":"potion-32M",
+//! #              "models":["potion-32M"],"ready":true,"warmup":"completed"}
+//!
+//! # Check readiness
+//! curl http://localhost:8080/ready
+//! # Returns: 200 {"ready":true,"policy":"warmed","warmup":"completed"} or 503 while warming up
+//!
+//! # Scrape metrics
+//! curl http://localhost:8080/metrics
+//! # Returns: 200 "# TYPE embedtool_total_embedding_requests counter\nembedtool_total_embedding_requests 12\n..."
 //! ```
 
-use axum::http::StatusCode;
+use axum::extract::{Json, State};
+use axum::http::{StatusCode, header};
+use serde_json::{Value, json};
+use std::sync::Arc;
 
-/// Health check endpoint for load balancer health status checking.
-///
-/// Returns 200 OK if the server process is running. Does not check:
-/// - Model availability (use `/v1/models` instead)
-/// - Database connectivity
-/// - External service dependencies
-///
-/// # Returns
+use crate::server::state::{AppState, WarmupStatus};
+use crate::utils::format_duration;
+
+/// Health check endpoint for load balancers and liveness probes.
 ///
-/// HTTP 200 OK status code with no body
+/// Always returns 200 OK while the process is serving; use `/ready` to decide
+/// whether to route traffic. The body reports:
+/// - `version`: crate version of the running server
+/// - `uptime_secs`: seconds since the server state was created, and `uptime`
+///   the same as text such as `2h 15m 30s`
+/// - `default_model`, `models` and `models_loaded`: the default and every
+///   loaded model name, and how many are loaded
+/// - `ready` and `warmup`: the same readiness information as `/ready`
 ///
 /// # Examples
 ///
 /// ```
 /// use static_embedding_tool::server::http::health;
+/// use static_embedding_tool::server::state::AppState;
+/// # use axum::extract::State;
 /// # use axum::http::StatusCode;
+/// # use std::sync::Arc;
 /// # #[tokio::main]
 /// # async fn main() {
-/// let status = health().await;
+/// let state = Arc::new(AppState::from_models(Default::default(), "potion-32M"));
+/// let (status, body) = health(State(state)).await;
 /// assert_eq!(status, StatusCode::OK);
+/// assert_eq!(body["default_model"], "potion-32M");
 /// # }
 /// ```
-pub async fn health() -> StatusCode {
-    StatusCode::OK
+pub async fn health(State(state): State<Arc<AppState>>) -> (StatusCode, Json<Value>) {
+    let uptime = state.startup_time.elapsed().unwrap_or_default();
+    let mut models = state.models.names();
+    models.sort();
+    (
+        StatusCode::OK,
+        Json(json!({
+            "status": "ok",
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime_secs": uptime.as_secs(),
+            "uptime": format_duration(uptime),
+            "default_model": state.default_model,
+            "models_loaded": models.len(),
+            "models": models,
+            "ready": state.is_ready(),
+            "warmup": state.warmup_status().as_str(),
+        })),
+    )
+}
+
+/// Readiness endpoint for orchestrators that should hold traffic during startup.
+///
+/// With the `loaded` policy the server is ready once its models are constructed;
+/// with `warmed` it additionally waits for the startup probe encode to succeed.
+/// Either way it is not ready while no model is loaded.
+///
+/// # Returns
+///
+/// - 200 OK with `{"ready": true, "policy": ..., "warmup": ...}` once ready
+/// - 503 Service Unavailable with `{"ready": false, ...}` otherwise, plus an
+///   `error` when the warm-up failed
+pub async fn ready(State(state): State<Arc<AppState>>) -> (StatusCode, Json<Value>) {
+    let ready = state.is_ready();
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let warmup = state.warmup_status();
+    let mut body = json!({
+        "ready": ready,
+        "policy": state.ready_policy.as_str(),
+        "warmup": warmup.as_str(),
+    });
+    if let WarmupStatus::Failed(error) = warmup {
+        body["error"] = json!(error);
+    }
+    (status, Json(body))
+}
+
+/// Prometheus scrape endpoint.
+///
+/// Returns every metric recorded by the server (request, error and tool
+/// counters, connection gauges) in the text exposition format, with metric
+/// names' dots replaced by underscores. The `metrics://server` MCP resource
+/// serves the same values as JSON.
+pub async fn metrics() -> ([(header::HeaderName, &'static str); 1], String) {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        crate::server::metrics::snapshot().to_prometheus(),
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::server::mock::MockModel;
+    use crate::server::state::Model;
+    use std::collections::HashMap;
+
+    fn mock_state() -> Arc<AppState> {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        for name in ["mock-b", "mock-a"] {
+            models.insert(name.to_string(), Arc::new(MockModel::new(name.to_string(), 4)));
+        }
+        Arc::new(AppState::from_models(models, "mock-a"))
+    }
+
     #[tokio::test]
     async fn test_health_endpoint() {
-        let status = health().await;
+        let state = mock_state();
+        let (status, Json(body)) = health(State(Arc::clone(&state))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert!(body["uptime_secs"].is_u64());
+        assert!(body["uptime"].is_string());
+        assert_eq!(body["default_model"], "mock-a");
+        assert_eq!(body["models_loaded"], 2);
+        assert_eq!(body["models"], json!(["mock-a", "mock-b"]));
+        assert_eq!(body["ready"], false);
+        assert_eq!(body["warmup"], "pending");
+
+        state.apply_ready_policy().await;
+        let (_, Json(body)) = health(State(state)).await;
+        assert_eq!(body["ready"], true);
+        assert_eq!(body["warmup"], "completed");
+    }
+
+    #[tokio::test]
+    async fn test_health_endpoint_reports_uptime() {
+        let mut state = AppState::from_models(Default::default(), "none");
+        state.startup_time = std::time::SystemTime::now() - std::time::Duration::from_secs(3725);
+        let (_, Json(body)) = health(State(Arc::new(state))).await;
+        assert_eq!(body["uptime_secs"], 3725);
+        assert_eq!(body["uptime"], "1h 2m 5s");
+        assert_eq!(body["models_loaded"], 0);
+        assert_eq!(body["models"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_ready_endpoint_without_models() {
+        let state = Arc::new(AppState::from_models(Default::default(), "none"));
+        state.apply_ready_policy().await;
+        let (status, Json(body)) = ready(State(state)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["warmup"], "failed");
+        assert_eq!(body["error"], "No models are loaded");
+    }
+
+    #[tokio::test]
+    async fn test_ready_endpoint_flips_after_mark_ready() {
+        let state = mock_state();
+
+        let (status, Json(body)) = ready(State(Arc::clone(&state))).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["ready"], false);
+        assert_eq!(body["policy"], "warmed");
+
+        state.mark_ready();
+        let (status, Json(body)) = ready(State(state)).await;
         assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["ready"], true);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_renders_prometheus_text() {
+        crate::server::metrics::install();
+        ::metrics::counter!("test.http.scrapes").increment(2);
+
+        let (headers, body) = metrics().await;
+        assert!(headers[0].1.starts_with("text/plain; version=0.0.4"));
+        assert!(body.contains("# TYPE test_http_scrapes counter\ntest_http_scrapes 2\n"), "{}", body);
     }
 }
@@ -0,0 +1,428 @@
+//! Asynchronous embedding jobs for batches too large for one request.
+//!
+//! `POST /v1/embeddings/jobs` takes the same body as `POST /v1/embeddings`,
+//! but accepts up to [`MAX_JOB_INPUTS`] inputs and answers `202` at once with
+//! a job id. The inputs are embedded in the background, [`JOB_CHUNK_SIZE`] at
+//! a time, and `GET /v1/embeddings/jobs/{id}` reports progress until the job
+//! completes with the usual embeddings response as its `result`, or fails
+//! with an OpenAI-style `error`:
+//!
+//! ```json
+//! {
+//!   "id": "job-6f9c...",
+//!   "object": "embedding.job",
+//!   "status": "running",
+//!   "model": "potion-32M",
+//!   "input_count": 5000,
+//!   "processed": 1200,
+//!   "created_at": 1735689600,
+//!   "completed_at": null,
+//!   "result": null,
+//!   "error": null
+//! }
+//! ```
+//!
+//! Jobs live in memory in the [`JobStore`] of the server's
+//! [`AppState`](super::state::AppState) and are forgotten a TTL after they
+//! finish, or when the server restarts.
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::Json as ResponseJson,
+};
+use metrics::counter;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use super::api::{embed_input, resolve_encoding_format, resolve_model, resolve_output_dtype};
+use super::state::AppState;
+use super::{ApiError, EmbeddingInput, EmbeddingRequest, EmbeddingResponse, ErrorDetails, ModelSelection, Usage};
+
+/// Most inputs one job accepts.
+pub const MAX_JOB_INPUTS: usize = 100_000;
+
+/// Inputs embedded per step; progress is reported after each.
+pub const JOB_CHUNK_SIZE: usize = 100;
+
+/// How long a finished job stays available by default.
+pub const DEFAULT_JOB_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Where a job is in its lifecycle.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Accepted but not started
+    Queued,
+    /// Inputs are being embedded
+    Running,
+    /// Finished; `result` holds the embeddings
+    Completed,
+    /// Stopped; `error` says why
+    Failed,
+}
+
+/// An embedding job as reported by `GET /v1/embeddings/jobs/{id}`.
+#[derive(Serialize, Clone, Debug)]
+pub struct Job {
+    pub id: String,
+    /// Object type identifier ("embedding.job")
+    pub object: String,
+    pub status: JobStatus,
+    pub model: String,
+    pub input_count: usize,
+    /// Inputs embedded so far
+    pub processed: usize,
+    /// Unix timestamp at which the job was accepted
+    pub created_at: u64,
+    /// Unix timestamp at which the job completed or failed
+    pub completed_at: Option<u64>,
+    /// The embeddings response, once completed
+    pub result: Option<serde_json::Value>,
+    /// The error the job failed with
+    pub error: Option<ErrorDetails>,
+    /// When the job finished, for expiry
+    #[serde(skip)]
+    finished: Option<Instant>,
+}
+
+impl Job {
+    fn new(model: String, input_count: usize) -> Self {
+        Self {
+            id: format!("job-{}", uuid::Uuid::new_v4().simple()),
+            object: "embedding.job".to_string(),
+            status: JobStatus::Queued,
+            model,
+            input_count,
+            processed: 0,
+            created_at: unix_now(),
+            completed_at: None,
+            result: None,
+            error: None,
+            finished: None,
+        }
+    }
+
+    fn finish(&mut self, status: JobStatus) {
+        self.status = status;
+        self.completed_at = Some(unix_now());
+        self.finished = Some(Instant::now());
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Embedding jobs by id.
+///
+/// Cloning is cheap and every clone sees the same jobs. Finished jobs older
+/// than the TTL are dropped whenever a job is created or looked up.
+#[derive(Clone, Debug)]
+pub struct JobStore {
+    jobs: Arc<Mutex<HashMap<String, Job>>>,
+    ttl: Duration,
+}
+
+impl Default for JobStore {
+    fn default() -> Self {
+        Self::with_ttl(DEFAULT_JOB_TTL)
+    }
+}
+
+impl JobStore {
+    /// A store keeping finished jobs for `ttl`.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Job>> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn insert(&self, job: Job) {
+        self.purge_expired();
+        self.lock().insert(job.id.clone(), job);
+    }
+
+    /// The job `id`, unless it is unknown or has expired.
+    pub fn get(&self, id: &str) -> Option<Job> {
+        self.purge_expired();
+        self.lock().get(id).cloned()
+    }
+
+    fn update(&self, id: &str, update: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.lock().get_mut(id) {
+            update(job);
+        }
+    }
+
+    /// Drop jobs that finished longer than the TTL ago, returning how many.
+    pub fn purge_expired(&self) -> usize {
+        let mut jobs = self.lock();
+        let before = jobs.len();
+        jobs.retain(|_, job| job.finished.is_none_or(|finished| finished.elapsed() < self.ttl));
+        before - jobs.len()
+    }
+
+    /// Number of jobs held, finished or not.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn invalid_request(message: impl Into<String>, param: &str) -> (StatusCode, ResponseJson<ApiError>) {
+    let error = ApiError {
+        error: ErrorDetails {
+            message: message.into(),
+            r#type: "invalid_request_error".to_string(),
+            param: Some(param.to_string()),
+            code: None,
+        },
+    };
+    (StatusCode::BAD_REQUEST, ResponseJson(error))
+}
+
+/// Split `input` into inputs of at most `size` entries, in order.
+fn chunk_input(input: EmbeddingInput, size: usize) -> Vec<EmbeddingInput> {
+    match input {
+        EmbeddingInput::TextBatch(texts) => texts.chunks(size).map(|c| EmbeddingInput::TextBatch(c.to_vec())).collect(),
+        EmbeddingInput::TokenBatch(batch) => {
+            batch.chunks(size).map(|c| EmbeddingInput::TokenBatch(c.to_vec())).collect()
+        }
+        single => vec![single],
+    }
+}
+
+/// Start an embedding job.
+///
+/// POST /v1/embeddings/jobs - answers `202` with the queued job
+///
+/// # Errors
+///
+/// - `400 invalid_request_error`: Empty input, more than [`MAX_JOB_INPUTS`]
+///   inputs, several models, or an invalid `encoding_format` or `output_dtype`
+/// - `500 server_error`: No models are loaded
+pub async fn create_job_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<EmbeddingRequest>,
+) -> Result<(StatusCode, ResponseJson<Job>), (StatusCode, ResponseJson<ApiError>)> {
+    if request.input.is_empty() {
+        return Err(invalid_request("Input is empty", "input"));
+    }
+    if request.input.len() > MAX_JOB_INPUTS {
+        return Err(invalid_request(
+            format!("Job too large. Maximum {} inputs allowed.", MAX_JOB_INPUTS),
+            "input",
+        ));
+    }
+    let output_dtype = resolve_output_dtype(request.output_dtype.as_deref(), &state)?;
+    let encoding = resolve_encoding_format(request.encoding_format.as_deref())?;
+    let model_name = match &request.model {
+        Some(ModelSelection::Many(names)) if names.len() > 1 => {
+            return Err(invalid_request("Jobs embed with a single model", "model"));
+        }
+        selection => selection
+            .as_ref()
+            .and_then(ModelSelection::primary)
+            .map_or_else(|| state.default_model.clone(), str::to_string),
+    };
+    let model = resolve_model(&state, &model_name)?;
+
+    let job = Job::new(model_name.clone(), request.input.len());
+    let id = job.id.clone();
+    state.jobs.insert(job.clone());
+    counter!("embedtool.jobs.created").increment(1);
+    info!(job_id = %id, inputs = job.input_count, model = %model_name, "Embedding job queued");
+
+    let jobs = state.jobs.clone();
+    let options = request.encode_options();
+    tokio::spawn(async move {
+        jobs.update(&id, |job| job.status = JobStatus::Running);
+        let mut data = Vec::with_capacity(request.input.len());
+        let mut prompt_tokens = 0;
+        let mut embedding_dimensions = 0;
+        for chunk in chunk_input(request.input, JOB_CHUNK_SIZE) {
+            let len = chunk.len();
+            match embed_input(Arc::clone(&model), chunk, output_dtype, encoding, options, &state, data.len()).await {
+                Ok(embedded) => {
+                    data.extend(embedded.data);
+                    prompt_tokens += embedded.prompt_tokens;
+                    embedding_dimensions = embedded.dimensions;
+                    jobs.update(&id, |job| job.processed += len);
+                }
+                Err((status, ResponseJson(error))) => {
+                    warn!(job_id = %id, status = %status, error = %error.error.message, "Embedding job failed");
+                    counter!("embedtool.jobs.failed").increment(1);
+                    jobs.update(&id, |job| {
+                        job.error = Some(error.error);
+                        job.finish(JobStatus::Failed);
+                    });
+                    return;
+                }
+            }
+        }
+
+        let response = EmbeddingResponse {
+            object: "list".to_string(),
+            data,
+            model: model_name,
+            dimensions: embedding_dimensions,
+            usage: Usage {
+                prompt_tokens,
+                total_tokens: prompt_tokens,
+            },
+        };
+        let result = serde_json::to_value(&response).ok();
+        counter!("embedtool.jobs.completed").increment(1);
+        info!(job_id = %id, "Embedding job completed");
+        jobs.update(&id, |job| {
+            job.result = result;
+            job.finish(JobStatus::Completed);
+        });
+    });
+
+    Ok((StatusCode::ACCEPTED, ResponseJson(job)))
+}
+
+/// Report an embedding job's status, and its result once completed.
+///
+/// GET /v1/embeddings/jobs/{id}
+///
+/// # Errors
+///
+/// - `404 not_found_error`: No such job, or it expired
+pub async fn get_job_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<ResponseJson<Job>, (StatusCode, ResponseJson<ApiError>)> {
+    state.jobs.get(&id).map(ResponseJson).ok_or_else(|| {
+        let error = ApiError {
+            error: ErrorDetails {
+                message: format!("Job not found: {}", id),
+                r#type: "not_found_error".to_string(),
+                param: Some("id".to_string()),
+                code: Some("job_not_found".to_string()),
+            },
+        };
+        (StatusCode::NOT_FOUND, ResponseJson(error))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::mock::MockModel;
+    use crate::server::state::Model;
+
+    fn test_state() -> Arc<AppState> {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("mock".to_string(), Arc::new(MockModel::new("mock".to_string(), 4)));
+        Arc::new(AppState::from_models(models, "mock"))
+    }
+
+    async fn wait_for(state: &AppState, id: &str) -> Job {
+        for _ in 0..200 {
+            let job = state.jobs.get(id).unwrap();
+            if matches!(job.status, JobStatus::Completed | JobStatus::Failed) {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job {} did not finish", id);
+    }
+
+    fn request(input: EmbeddingInput) -> EmbeddingRequest {
+        EmbeddingRequest {
+            input,
+            model: None,
+            encoding_format: None,
+            dimensions: None,
+            user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_embeds_more_than_one_request_allows() {
+        let state = test_state();
+        let texts: Vec<String> = (0..250).map(|i| format!("text {}", i)).collect();
+        let (status, ResponseJson(job)) =
+            create_job_handler(State(Arc::clone(&state)), Json(request(texts.into()))).await.unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(job.input_count, 250);
+
+        let job = wait_for(&state, &job.id).await;
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.processed, 250);
+        let result = job.result.unwrap();
+        let data = result["data"].as_array().unwrap();
+        assert_eq!(data.len(), 250);
+        assert_eq!(data[249]["index"], 249);
+        assert_eq!(result["dimensions"], 4);
+
+        let ResponseJson(fetched) = get_job_handler(State(Arc::clone(&state)), Path(job.id)).await.unwrap();
+        assert_eq!(fetched.status, JobStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_failed_job_reports_error() {
+        let state = test_state();
+        // The empty input is in the second chunk; its index counts from the start of the job
+        let mut texts = vec!["ok".to_string(); JOB_CHUNK_SIZE + 2];
+        texts[JOB_CHUNK_SIZE + 1] = String::new();
+        let (_, ResponseJson(job)) =
+            create_job_handler(State(Arc::clone(&state)), Json(request(texts.into()))).await.unwrap();
+
+        let job = wait_for(&state, &job.id).await;
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.result.is_none());
+        let error = job.error.unwrap();
+        assert_eq!(error.r#type, "invalid_request_error");
+        assert_eq!(error.message, format!("Input {} is empty", JOB_CHUNK_SIZE + 1));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_job_is_404() {
+        let (status, _) = get_job_handler(State(test_state()), Path("job-missing".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_finished_jobs_expire() {
+        let store = JobStore::with_ttl(Duration::ZERO);
+        let mut running = Job::new("mock".to_string(), 1);
+        running.status = JobStatus::Running;
+        let mut done = Job::new("mock".to_string(), 1);
+        done.finish(JobStatus::Completed);
+        let (running_id, done_id) = (running.id.clone(), done.id.clone());
+        store.insert(running);
+        store.insert(done);
+
+        assert!(store.get(&done_id).is_none());
+        assert!(store.get(&running_id).is_some());
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_chunk_input() {
+        let texts: Vec<String> = (0..5).map(|i| i.to_string()).collect();
+        let chunks = chunk_input(texts.into(), 2);
+        assert_eq!(chunks.iter().map(EmbeddingInput::len).collect::<Vec<_>>(), vec![2, 2, 1]);
+        assert_eq!(chunk_input(EmbeddingInput::Text("one".to_string()), 2).len(), 1);
+    }
+}
@@ -0,0 +1,277 @@
+//! Request size and time limits for the HTTP API.
+//!
+//! [`apply`] bounds the body of every request to `server.max_body_bytes`
+//! before it is deserialized, and the time a request may take to
+//! `server.request_timeout_secs`. The tower-http layers enforcing them answer
+//! with bare `413` and `408` responses; [`api_error_body`] rewrites those into
+//! OpenAI-style errors, so clients see the same shape as for any other
+//! failure instead of an empty body or a reset connection:
+//!
+//! ```json
+//! {
+//!   "error": {
+//!     "message": "Request body is larger than the 1048576 byte limit",
+//!     "type": "invalid_request_error",
+//!     "param": null,
+//!     "code": "request_too_large"
+//!   }
+//! }
+//! ```
+//!
+//! The inputs of an embedding request are bounded separately, by
+//! [`InputLimits`] from `server.max_batch_inputs` and `server.max_input_chars`.
+//! The HTTP API and the MCP tools both check them, so they accept and reject
+//! the same inputs with the same messages, which name the limit and the index
+//! of the offending input.
+
+use axum::{
+    Router,
+    extract::{DefaultBodyLimit, Request},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Json as ResponseJson, Response},
+};
+use std::time::Duration;
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::timeout::TimeoutLayer;
+use tracing::warn;
+
+use super::{ApiError, ErrorDetails};
+
+/// Largest request body accepted by default, in bytes.
+pub const DEFAULT_MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// Default time a request may take, in seconds.
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 60;
+
+/// Limits applied to every request to the HTTP API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// Largest request body, in bytes
+    pub max_body_bytes: usize,
+    /// Time after which a request is answered with `408`; `None` never times out
+    pub request_timeout: Option<Duration>,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            request_timeout: Some(Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS)),
+        }
+    }
+}
+
+/// Wrap `router` with the body size and timeout `limits`, answering
+/// requests that exceed them with OpenAI-style errors.
+pub fn apply<S: Clone + Send + Sync + 'static>(router: Router<S>, limits: RequestLimits) -> Router<S> {
+    // Replace axum's own 2 MiB default so the configured limit is the only one
+    let mut router = router
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(limits.max_body_bytes));
+    if let Some(timeout) = limits.request_timeout {
+        router = router.layer(TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, timeout));
+    }
+    router.layer(middleware::from_fn_with_state(limits, api_error_body))
+}
+
+/// Middleware giving the bare `413` and `408` responses of the limit layers
+/// an OpenAI-style error body.
+///
+/// Responses that already carry JSON, such as handler errors, are kept.
+pub async fn api_error_body(
+    axum::extract::State(limits): axum::extract::State<RequestLimits>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let uri = request.uri().clone();
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if is_json {
+        return response;
+    }
+
+    let (message, r#type, code) = match response.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => (
+            format!("Request body is larger than the {} byte limit", limits.max_body_bytes),
+            "invalid_request_error",
+            "request_too_large",
+        ),
+        StatusCode::REQUEST_TIMEOUT => {
+            let secs = limits.request_timeout.unwrap_or_default().as_secs_f64();
+            warn!(uri = %uri, timeout_secs = secs, "Request timed out");
+            (
+                format!("Request did not complete within {} seconds", secs),
+                "timeout_error",
+                "request_timeout",
+            )
+        }
+        _ => return response,
+    };
+    let error = ApiError {
+        error: ErrorDetails {
+            message,
+            r#type: r#type.to_string(),
+            param: None,
+            code: Some(code.to_string()),
+        },
+    };
+    (response.status(), ResponseJson(error)).into_response()
+}
+
+/// Most inputs one embedding request may carry by default.
+pub const DEFAULT_MAX_BATCH_INPUTS: usize = 100;
+
+/// Most tokens one input may have, counted by the model's tokenizer.
+pub const MAX_INPUT_TOKENS: usize = 8192;
+
+/// Longest input accepted by default, in characters: eight per token of
+/// [`MAX_INPUT_TOKENS`], so the token limit is what bounds ordinary text.
+pub const DEFAULT_MAX_INPUT_CHARS: usize = 8 * MAX_INPUT_TOKENS;
+
+/// Limits on the inputs of one embedding request, shared by the HTTP API and
+/// the MCP tools.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputLimits {
+    /// Most inputs in one batch
+    pub max_batch_inputs: usize,
+    /// Longest input, in characters
+    pub max_input_chars: usize,
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        Self {
+            max_batch_inputs: DEFAULT_MAX_BATCH_INPUTS,
+            max_input_chars: DEFAULT_MAX_INPUT_CHARS,
+        }
+    }
+}
+
+/// Why the inputs of an embedding request were rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum InputLimitError {
+    #[error("inputs must not be empty")]
+    NoInputs,
+    /// `index` is the first input past the limit.
+    #[error("Batch size too large: input {index} is over the maximum of {max} inputs")]
+    TooManyInputs { index: usize, max: usize },
+    #[error("Input {index} is empty")]
+    EmptyInput { index: usize },
+    #[error("Input {index} has {chars} characters, over the {max} character limit")]
+    TooManyChars { index: usize, chars: usize, max: usize },
+    #[error("Input {index} has {tokens} tokens, over the {max} token limit")]
+    TooManyTokens { index: usize, tokens: usize, max: usize },
+}
+
+impl InputLimits {
+    /// Limits from `server.max_batch_inputs` and `server.max_input_chars`,
+    /// where 0 keeps the built-in default.
+    pub fn new(max_batch_inputs: usize, max_input_chars: usize) -> Self {
+        let or_default = |value: usize, default: usize| if value == 0 { default } else { value };
+        Self {
+            max_batch_inputs: or_default(max_batch_inputs, DEFAULT_MAX_BATCH_INPUTS),
+            max_input_chars: or_default(max_input_chars, DEFAULT_MAX_INPUT_CHARS),
+        }
+    }
+
+    /// Reject a batch of `count` inputs that is empty or over `max_batch_inputs`.
+    pub fn check_count(&self, count: usize) -> Result<(), InputLimitError> {
+        match count {
+            0 => Err(InputLimitError::NoInputs),
+            count if count > self.max_batch_inputs => Err(InputLimitError::TooManyInputs {
+                index: self.max_batch_inputs,
+                max: self.max_batch_inputs,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject input `index`, `text` of `tokens` tokens, if it is empty, over
+    /// `max_input_chars` or over [`MAX_INPUT_TOKENS`].
+    pub fn check_text(&self, index: usize, text: &str, tokens: usize) -> Result<(), InputLimitError> {
+        if text.is_empty() {
+            return Err(InputLimitError::EmptyInput { index });
+        }
+        let chars = text.chars().count();
+        if chars > self.max_input_chars {
+            return Err(InputLimitError::TooManyChars { index, chars, max: self.max_input_chars });
+        }
+        if tokens > MAX_INPUT_TOKENS {
+            return Err(InputLimitError::TooManyTokens { index, tokens, max: MAX_INPUT_TOKENS });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::post;
+
+    async fn spawn_limited_server(limits: RequestLimits) -> String {
+        let app = Router::new()
+            .route("/echo", post(|body: String| async move { body }))
+            .route(
+                "/slow",
+                post(|| async {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    "done"
+                }),
+            );
+        let app = apply(app, limits);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_gets_413_api_error() {
+        let limits = RequestLimits { max_body_bytes: 16, request_timeout: None };
+        let base = spawn_limited_server(limits).await;
+        let client = reqwest::Client::new();
+
+        let response = client.post(format!("{}/echo", base)).body("small").send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), "small");
+
+        let response = client.post(format!("{}/echo", base)).body("x".repeat(64)).send().await.unwrap();
+        assert_eq!(response.status(), 413);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "request_too_large");
+        assert_eq!(body["error"]["type"], "invalid_request_error");
+        assert!(body["error"]["message"].as_str().unwrap().contains("16 byte"));
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_gets_408_api_error() {
+        let limits = RequestLimits {
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            request_timeout: Some(Duration::from_millis(50)),
+        };
+        let base = spawn_limited_server(limits).await;
+
+        let response = reqwest::Client::new().post(format!("{}/slow", base)).send().await.unwrap();
+        assert_eq!(response.status(), 408);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "request_timeout");
+        assert_eq!(body["error"]["type"], "timeout_error");
+    }
+
+    #[test]
+    fn test_check_count_names_first_input_past_limit() {
+        let limits = InputLimits::new(3, 0);
+        assert_eq!(limits.check_count(0), Err(InputLimitError::NoInputs));
+        assert_eq!(limits.check_count(3), Ok(()));
+        let err = limits.check_count(5).unwrap_err();
+        assert_eq!(err, InputLimitError::TooManyInputs { index: 3, max: 3 });
+        assert_eq!(err.to_string(), "Batch size too large: input 3 is over the maximum of 3 inputs");
+    }
+}
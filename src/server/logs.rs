@@ -1,10 +1,14 @@
 
 
-use tracing::info;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{info, warn};
 
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 use metrics::{counter, gauge};
 
+pub use crate::utils::logging::{DEFAULT_MAX_LOG_FILES, LogFile, RotatingFile};
+use crate::utils::logging::file_log_layer;
+
 /// Initialize structured logging and metrics collection.
 ///
 /// Sets up tracing subscriber with environment-based filtering and configures
@@ -13,6 +17,7 @@ use metrics::{counter, gauge};
 /// # Arguments
 ///
 /// * `stdio` - If true, logs to stderr (for MCP STDIO mode); otherwise stdout
+/// * `log_file` - Also write logs to this file, rotating it by size
 ///
 /// # Environment Variables
 ///
@@ -21,48 +26,50 @@ use metrics::{counter, gauge};
 /// # Examples
 ///
 /// ```no_run
-/// # use static_embedding_tool::logs::init_logging_and_metrics;
+/// # use static_embedding_tool::server::logs::init_logging_and_metrics;
 /// // Initialize for HTTP mode
-/// init_logging_and_metrics(false);
+/// init_logging_and_metrics(false, None);
 ///
 /// // Initialize for STDIO mode
-/// init_logging_and_metrics(true);
+/// init_logging_and_metrics(true, None);
 /// ```
-pub fn init_logging_and_metrics(stdio: bool) {
-    {
-        // Check if we are running in stdio mode
-        if stdio {
-            // Set up environment filter for log levels
-            let filter = EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| EnvFilter::new("static_embedding_tool=error,rmcp=error"));
-            // Initialize tracing subscriber with stderr output
-            let _ = tracing_subscriber::registry()
-                .with(filter)
-                .with(
-                    tracing_subscriber::fmt::layer()
-                        .with_target(true)
-                        .with_writer(std::io::stderr),
-                )
-                .try_init(); // Use try_init to avoid panic if already initialized
+pub fn init_logging_and_metrics(stdio: bool, log_file: Option<&LogFile>) {
+    // The CLI has already installed a subscriber from the `[logging]` config
+    if !tracing::dispatcher::has_been_set() {
+        // A log file that cannot be opened is reported once logging is up
+        let (file_layer, file_error) = match log_file.map(|config| file_log_layer(config, false)) {
+            Some(Ok(layer)) => (Some(layer), None),
+            Some(Err(e)) => (None, Some(e)),
+            None => (None, None),
+        };
+        // Check if we are running in stdio mode: stdout then carries the
+        // protocol, so logs go to stderr
+        let (default_filter, console) = if stdio {
+            ("static_embedding_tool=error,rmcp=error", BoxMakeWriter::new(std::io::stderr))
         } else {
-            // Set up environment filter for log levels
-            let filter = EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| EnvFilter::new("static_embedding_tool=trace,rmcp=warn"));
-            // Initialize tracing subscriber with stdout output
-            let _ = tracing_subscriber::registry()
-                .with(filter)
-                .with(
-                    tracing_subscriber::fmt::layer()
-                        .with_target(true)
-                        .with_writer(std::io::stdout),
-                )
-                .try_init(); // Use try_init to avoid panic if already initialized
+            ("static_embedding_tool=trace,rmcp=warn", BoxMakeWriter::new(std::io::stdout))
+        };
+        // Set up environment filter for log levels
+        let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter));
+        let _ = tracing_subscriber::registry()
+            .with(filter)
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_target(true)
+                    .with_writer(console),
+            )
+            .with(file_layer)
+            .try_init(); // Use try_init to avoid panic if already initialized
+        if let (Some(config), Some(e)) = (log_file, file_error) {
+            warn!(path = %config.path.display(), error = %e, "Failed to open log file; logging to the console only");
         }
         // Output debugging information
         info!("Logging and tracing initialized");
     }
 
     {
+        // Keep recorded metrics readable by GET /metrics and metrics://server
+        crate::server::metrics::install();
         // Initialize metrics with default values
         gauge!("embedtool.active_connections").set(0.0);
         counter!("embedtool.total_connections").absolute(0);
@@ -80,17 +87,68 @@ pub fn init_logging_and_metrics(stdio: bool) {
         counter!("embedtool.errors.model_distillation").absolute(0);
         counter!("embedtool.errors.model_not_found").absolute(0);
         counter!("embedtool.errors.invalid_input").absolute(0);
+        // Encode retries for backends with transient failures
+        counter!("embedtool.encode.retries").absolute(0);
+        counter!("embedtool.encode.retry_successes").absolute(0);
+        counter!("embedtool.encode.failures").absolute(0);
         // Tool method call counters
         counter!("embedtool.tools.embed").absolute(0);
         counter!("embedtool.tools.batch_embed").absolute(0);
+        counter!("embedtool.tools.similarity").absolute(0);
         counter!("embedtool.tools.list_models").absolute(0);
         counter!("embedtool.tools.model_info").absolute(0);
+        counter!("embedtool.tools.session_info").absolute(0);
         counter!("embedtool.tools.distill_model").absolute(0);
+        // MCP session activity
+        counter!("embedtool.mcp.session_requests").absolute(0);
+        counter!("embedtool.mcp.sessions_reaped").absolute(0);
+        gauge!("embedtool.mcp.active_sessions").set(0.0);
+        // Asynchronous embedding jobs
+        counter!("embedtool.jobs.created").absolute(0);
+        counter!("embedtool.jobs.completed").absolute(0);
+        counter!("embedtool.jobs.failed").absolute(0);
         // Output debugging information
         info!("Metrics collection initialized");
     }
 }
 
+/// Decides which successful requests are logged at INFO.
+///
+/// Sampling is deterministic: with a rate of `0.25`, every fourth request is
+/// logged, so the share of logged requests matches the rate even over short
+/// windows. Error responses bypass the sampler and are always logged.
+#[derive(Debug)]
+pub struct RequestSampler {
+    rate: f64,
+    seen: AtomicU64,
+}
+
+impl RequestSampler {
+    /// Create a sampler logging `rate` (clamped to 0.0–1.0) of requests.
+    pub fn new(rate: f64) -> Self {
+        Self {
+            rate: if rate.is_nan() { 1.0 } else { rate.clamp(0.0, 1.0) },
+            seen: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether the next successful request should be logged at INFO.
+    pub fn should_log(&self) -> bool {
+        if self.rate >= 1.0 {
+            return true;
+        }
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        // Log whenever the running total of `rate` crosses a whole number
+        ((n + 1.0) * self.rate).floor() > (n * self.rate).floor()
+    }
+}
+
+impl Default for RequestSampler {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,7 +158,7 @@ mod tests {
         // Test that the function can be called without panicking
         // We use try_init internally to avoid conflicts with other tests
         let result = std::panic::catch_unwind(|| {
-            init_logging_and_metrics(true);
+            init_logging_and_metrics(true, None);
         });
         assert!(result.is_ok(), "init_logging_and_metrics(true) should not panic");
     }
@@ -109,11 +167,23 @@ mod tests {
     fn test_init_logging_and_metrics_stdio_false() {
         // Test that the function can be called without panicking
         let result = std::panic::catch_unwind(|| {
-            init_logging_and_metrics(false);
+            init_logging_and_metrics(false, None);
         });
         assert!(result.is_ok(), "init_logging_and_metrics(false) should not panic");
     }
 
+    #[test]
+    fn test_request_sampler_rate() {
+        let count = |sampler: &RequestSampler| (0..1000).filter(|_| sampler.should_log()).count();
+        assert_eq!(count(&RequestSampler::default()), 1000);
+        assert_eq!(count(&RequestSampler::new(0.0)), 0);
+        assert_eq!(count(&RequestSampler::new(0.1)), 100);
+        assert_eq!(count(&RequestSampler::new(0.25)), 250);
+        // Out-of-range rates are clamped
+        assert_eq!(count(&RequestSampler::new(7.0)), 1000);
+        assert_eq!(count(&RequestSampler::new(-1.0)), 0);
+    }
+
     #[test]
     fn test_metrics_initialization() {
         // Test metrics initialization separately without calling init_logging_and_metrics
@@ -0,0 +1,267 @@
+//! In-process metrics registry, read back by `GET /metrics` and the
+//! `metrics://server` MCP resource.
+//!
+//! [`install`] registers a global [`metrics::Recorder`] that keeps every
+//! counter, gauge and histogram recorded with the `metrics` macros in memory.
+//! [`snapshot`] copies their current values, which render as Prometheus text
+//! exposition with [`MetricsSnapshot::to_prometheus`] or serialize as JSON.
+//! Histograms are kept as a count and a sum, enough for Prometheus summaries
+//! without quantiles.
+
+use metrics::{Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+/// Storage behind the global recorder.
+#[derive(Default)]
+struct Registry {
+    counters: Mutex<BTreeMap<Key, Arc<AtomicU64>>>,
+    /// Gauge values as `f64` bits, as `metrics` stores them
+    gauges: Mutex<BTreeMap<Key, Arc<AtomicU64>>>,
+    histograms: Mutex<BTreeMap<Key, Arc<Summary>>>,
+    descriptions: Mutex<BTreeMap<String, SharedString>>,
+}
+
+static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::default);
+
+/// Count and sum of the values recorded into a histogram.
+#[derive(Default)]
+struct Summary {
+    values: Mutex<(u64, f64)>,
+}
+
+impl HistogramFn for Summary {
+    fn record(&self, value: f64) {
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values.0 += 1;
+        values.1 += value;
+    }
+}
+
+/// Look up `key` in `map`, registering `T::default()` the first time.
+fn handle<T: Default>(map: &Mutex<BTreeMap<Key, Arc<T>>>, key: &Key) -> Arc<T> {
+    let mut map = map.lock().unwrap_or_else(|e| e.into_inner());
+    Arc::clone(map.entry(key.clone()).or_default())
+}
+
+/// The recorder [`install`] registers; all state lives in [`REGISTRY`].
+struct GlobalRecorder;
+
+impl GlobalRecorder {
+    fn describe(&self, key: KeyName, description: SharedString) {
+        let mut descriptions = REGISTRY.descriptions.lock().unwrap_or_else(|e| e.into_inner());
+        descriptions.insert(key.as_str().to_string(), description);
+    }
+}
+
+impl Recorder for GlobalRecorder {
+    fn describe_counter(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn describe_gauge(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn describe_histogram(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        Counter::from_arc(handle(&REGISTRY.counters, key))
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(handle(&REGISTRY.gauges, key))
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(handle(&REGISTRY.histograms, key))
+    }
+}
+
+/// Install the global recorder, so metrics recorded from now on can be read
+/// back. Calling it again has no effect.
+pub fn install() {
+    // Only fails when a recorder is already installed, e.g. on a second call
+    let _ = metrics::set_global_recorder(GlobalRecorder);
+}
+
+/// One metric value with its labels.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MetricValue {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+}
+
+/// Count and sum of a histogram's values, with its labels.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SummaryValue {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub count: u64,
+    pub sum: f64,
+}
+
+/// Values of every metric recorded so far, sorted by name and labels.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MetricsSnapshot {
+    pub counters: Vec<MetricValue>,
+    pub gauges: Vec<MetricValue>,
+    pub histograms: Vec<SummaryValue>,
+}
+
+fn labels(key: &Key) -> BTreeMap<String, String> {
+    key.labels()
+        .map(|label| (label.key().to_string(), label.value().to_string()))
+        .collect()
+}
+
+/// Copy the current value of every recorded metric.
+pub fn snapshot() -> MetricsSnapshot {
+    let values = |map: &Mutex<BTreeMap<Key, Arc<AtomicU64>>>, as_f64: fn(u64) -> f64| {
+        let map = map.lock().unwrap_or_else(|e| e.into_inner());
+        map.iter()
+            .map(|(key, value)| MetricValue {
+                name: key.name().to_string(),
+                labels: labels(key),
+                value: as_f64(value.load(Ordering::Relaxed)),
+            })
+            .collect()
+    };
+    let histograms = REGISTRY.histograms.lock().unwrap_or_else(|e| e.into_inner());
+    MetricsSnapshot {
+        counters: values(&REGISTRY.counters, |v| v as f64),
+        gauges: values(&REGISTRY.gauges, f64::from_bits),
+        histograms: histograms
+            .iter()
+            .map(|(key, summary)| {
+                let (count, sum) = *summary.values.lock().unwrap_or_else(|e| e.into_inner());
+                SummaryValue {
+                    name: key.name().to_string(),
+                    labels: labels(key),
+                    count,
+                    sum,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Prometheus metric names allow `[a-zA-Z0-9_:]`, so `embedtool.tools.embed`
+/// becomes `embedtool_tools_embed`.
+fn prometheus_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == ':' { c } else { '_' })
+        .collect()
+}
+
+/// Render labels as `{key="value",...}`, or nothing without labels.
+fn prometheus_labels(labels: &BTreeMap<String, String>) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            format!("{}=\"{}\"", prometheus_name(key), value)
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+impl MetricsSnapshot {
+    /// Render in the Prometheus text exposition format (version 0.0.4).
+    pub fn to_prometheus(&self) -> String {
+        let descriptions = REGISTRY.descriptions.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+        let header = |out: &mut String, last: &mut Option<String>, name: &str, kind: &str| {
+            if last.as_deref() == Some(name) {
+                return;
+            }
+            let exposed = prometheus_name(name);
+            if let Some(help) = descriptions.get(name) {
+                let _ = writeln!(out, "# HELP {} {}", exposed, help.replace('\n', " "));
+            }
+            let _ = writeln!(out, "# TYPE {} {}", exposed, kind);
+            *last = Some(name.to_string());
+        };
+
+        for (metrics, kind) in [(&self.counters, "counter"), (&self.gauges, "gauge")] {
+            let mut last = None;
+            for metric in metrics {
+                header(&mut out, &mut last, &metric.name, kind);
+                let _ = writeln!(
+                    out,
+                    "{}{} {}",
+                    prometheus_name(&metric.name),
+                    prometheus_labels(&metric.labels),
+                    metric.value
+                );
+            }
+        }
+        let mut last = None;
+        for summary in &self.histograms {
+            header(&mut out, &mut last, &summary.name, "summary");
+            let name = prometheus_name(&summary.name);
+            let labels = prometheus_labels(&summary.labels);
+            let _ = writeln!(out, "{}_sum{} {}", name, labels, summary.sum);
+            let _ = writeln!(out, "{}_count{} {}", name, labels, summary.count);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_reads_back_recorded_values() {
+        install();
+        install();
+        metrics::counter!("test.metrics.requests", "route" => "embed").increment(3);
+        metrics::counter!("test.metrics.requests", "route" => "embed").increment(2);
+        metrics::gauge!("test.metrics.connections").set(4.5);
+        metrics::histogram!("test.metrics.latency").record(0.25);
+        metrics::histogram!("test.metrics.latency").record(0.5);
+
+        let snapshot = snapshot();
+        let counter = snapshot.counters.iter().find(|m| m.name == "test.metrics.requests").unwrap();
+        assert_eq!(counter.value, 5.0);
+        assert_eq!(counter.labels["route"], "embed");
+        let gauge = snapshot.gauges.iter().find(|m| m.name == "test.metrics.connections").unwrap();
+        assert_eq!(gauge.value, 4.5);
+        let histogram = snapshot.histograms.iter().find(|m| m.name == "test.metrics.latency").unwrap();
+        assert_eq!((histogram.count, histogram.sum), (2, 0.75));
+    }
+
+    #[test]
+    fn test_to_prometheus() {
+        let labels = BTreeMap::from([("route".to_string(), "a \"b\"".to_string())]);
+        let snapshot = MetricsSnapshot {
+            counters: vec![
+                MetricValue { name: "embedtool.requests".into(), labels: BTreeMap::new(), value: 7.0 },
+                MetricValue { name: "embedtool.requests".into(), labels: labels.clone(), value: 2.0 },
+            ],
+            gauges: vec![MetricValue { name: "embedtool.active".into(), labels: BTreeMap::new(), value: 1.5 }],
+            histograms: vec![SummaryValue { name: "embedtool.latency".into(), labels, count: 2, sum: 0.75 }],
+        };
+        assert_eq!(
+            snapshot.to_prometheus(),
+            "# TYPE embedtool_requests counter\n\
+             embedtool_requests 7\n\
+             embedtool_requests{route=\"a \\\"b\\\"\"} 2\n\
+             # TYPE embedtool_active gauge\n\
+             embedtool_active 1.5\n\
+             # TYPE embedtool_latency summary\n\
+             embedtool_latency_sum{route=\"a \\\"b\\\"\"} 0.75\n\
+             embedtool_latency_count{route=\"a \\\"b\\\"\"} 2\n"
+        );
+    }
+}
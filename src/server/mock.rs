@@ -0,0 +1,154 @@
+//! Deterministic mock model for development and integration testing.
+//!
+//! [`MockModel`] stands in for a real Model2Vec model when none can be loaded,
+//! and lets tests (in this crate or downstream, via the `mock` feature) spin up
+//! a server with predictable embeddings:
+//!
+//! ```rust
+//! use static_embedding_tool::server::mock::MockModel;
+//! use static_embedding_tool::server::state::Model;
+//!
+//! let model = MockModel::new("mock".to_string(), 8);
+//! let first = model.encode(&["hello".to_string()]);
+//! let again = model.encode(&["hello".to_string()]);
+//! assert_eq!(first, again);
+//! assert_eq!(first[0].len(), 8);
+//! ```
+
+use std::time::Duration;
+
+use crate::server::state::Model;
+
+/// Mock model producing unit-length vectors derived from a hash of each input.
+///
+/// The same text always maps to the same vector, independent of its position
+/// in the batch, the process or the platform. Errors and latency can be forced
+/// to exercise failure and timeout handling.
+#[derive(Clone, Debug)]
+pub struct MockModel {
+    pub name: String,
+    pub dimensions: usize,
+    /// When set, every encode fails with this message
+    pub error: Option<String>,
+    /// Time each encode call blocks for before returning
+    pub latency: Duration,
+}
+
+impl MockModel {
+    pub fn new(name: String, dimensions: usize) -> Self {
+        Self {
+            name,
+            dimensions,
+            error: None,
+            latency: Duration::ZERO,
+        }
+    }
+
+    /// Make every encode fail with `message`.
+    ///
+    /// [`Model::try_encode`] returns the message as an error; [`Model::encode`],
+    /// which cannot report failures, panics with it.
+    pub fn with_error(mut self, message: impl Into<String>) -> Self {
+        self.error = Some(message.into());
+        self
+    }
+
+    /// Block each encode call for `latency`, as a slow backend would.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// The embedding this model returns for `text`.
+    pub fn embed(&self, text: &str) -> Vec<f32> {
+        // FNV-1a keeps the seed stable across Rust releases, unlike `DefaultHasher`
+        let seed = text.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        let mut embedding: Vec<f32> = (0..self.dimensions as u64)
+            .map(|j| {
+                // splitmix64 step, mapped onto [-1, 1)
+                let mut z = seed.wrapping_add((j + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                z ^= z >> 31;
+                (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+            })
+            .collect();
+
+        let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            embedding.iter_mut().for_each(|v| *v /= norm);
+        }
+        embedding
+    }
+}
+
+impl Model for MockModel {
+    fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
+        match self.try_encode(inputs) {
+            Ok(embeddings) => embeddings,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    fn try_encode(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        if !self.latency.is_zero() {
+            std::thread::sleep(self.latency);
+        }
+        match &self.error {
+            Some(message) => Err(message.clone()),
+            None => Ok(inputs.iter().map(|text| self.embed(text)).collect()),
+        }
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_model_is_deterministic_per_text() {
+        let model = MockModel::new("m".to_string(), 16);
+        let inputs = vec!["alpha".to_string(), "beta".to_string(), "alpha".to_string()];
+        let embeddings = model.encode(&inputs);
+
+        assert_eq!(embeddings[0], embeddings[2]);
+        assert_ne!(embeddings[0], embeddings[1]);
+        assert_eq!(embeddings[1], model.embed("beta"));
+        assert_eq!(embeddings[0], MockModel::new("other".to_string(), 16).embed("alpha"));
+
+        let norm = embeddings[0].iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert!(MockModel::new("m".to_string(), 0).embed("alpha").is_empty());
+    }
+
+    #[test]
+    fn test_mock_model_forced_error_and_latency() {
+        let model = MockModel::new("m".to_string(), 4).with_error("backend unavailable");
+        assert_eq!(
+            model.try_encode(&["x".to_string()]).unwrap_err(),
+            "backend unavailable"
+        );
+
+        let model = MockModel::new("m".to_string(), 4).with_latency(Duration::from_millis(20));
+        let start = std::time::Instant::now();
+        assert_eq!(model.try_encode(&["x".to_string()]).unwrap().len(), 1);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_mock_model_dimensions_without_encoding() {
+        // A failing model still reports its size, since no probe is encoded
+        let model = MockModel::new("m".to_string(), 12).with_error("backend unavailable");
+        assert_eq!(Model::dimensions(&model), 12);
+
+        let models = crate::server::state::ModelMap::default();
+        models.insert("m", std::sync::Arc::new(model));
+        assert_eq!(models.dimensions("m"), Some(12));
+    }
+}
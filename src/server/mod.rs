@@ -3,33 +3,259 @@
 
 
 pub mod api;
+pub mod cache;
+pub mod cors;
+pub mod deadline;
 pub mod errors;
 pub mod http;
+pub mod jobs;
+pub mod limits;
+pub mod metrics;
+pub mod mock;
+pub mod sessions;
+pub mod shutdown;
+pub mod socket;
 pub mod start;
 pub mod start_simple;
 pub mod state;
+pub mod tls;
 
 pub mod logs;
 
 use serde::{Deserialize, Serialize};
 
+use crate::embed::EncodeOptions;
+use crate::utils::EncodingFormat;
+
 // ============================================================================
 // Request/Response Structures (OpenAI-compatible)
 // ============================================================================
 
+/// The `input` of an embeddings request, in any of the forms OpenAI accepts.
+///
+/// Token-id inputs are decoded back to text with the model's tokenizer before
+/// encoding, so they are only supported by models that ship one.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    /// A single text
+    Text(String),
+    /// A batch of texts
+    TextBatch(Vec<String>),
+    /// A single pre-tokenized input
+    Tokens(Vec<u32>),
+    /// A batch of pre-tokenized inputs
+    TokenBatch(Vec<Vec<u32>>),
+}
+
+impl EmbeddingInput {
+    /// Number of inputs in the request.
+    pub fn len(&self) -> usize {
+        match self {
+            EmbeddingInput::Text(_) | EmbeddingInput::Tokens(_) => 1,
+            EmbeddingInput::TextBatch(texts) => texts.len(),
+            EmbeddingInput::TokenBatch(batch) => batch.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Exact token count for pre-tokenized input; `None` for text.
+    pub fn token_count(&self) -> Option<usize> {
+        match self {
+            EmbeddingInput::Text(_) | EmbeddingInput::TextBatch(_) => None,
+            EmbeddingInput::Tokens(ids) => Some(ids.len()),
+            EmbeddingInput::TokenBatch(batch) => Some(batch.iter().map(Vec::len).sum()),
+        }
+    }
+
+    /// Resolve the input to texts, decoding token ids with `model`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the input is pre-tokenized and `model` cannot decode it.
+    pub fn into_texts(self, model: &dyn state::Model) -> Result<Vec<String>, String> {
+        let decode = |ids: &[u32]| {
+            model
+                .decode_tokens(ids)
+                .ok_or_else(|| "Token-id input is not supported by this model; send text instead".to_string())
+        };
+        match self {
+            EmbeddingInput::Text(text) => Ok(vec![text]),
+            EmbeddingInput::TextBatch(texts) => Ok(texts),
+            EmbeddingInput::Tokens(ids) => Ok(vec![decode(&ids)?]),
+            EmbeddingInput::TokenBatch(batch) => batch.iter().map(|ids| decode(ids)).collect(),
+        }
+    }
+}
+
+impl From<Vec<String>> for EmbeddingInput {
+    fn from(texts: Vec<String>) -> Self {
+        EmbeddingInput::TextBatch(texts)
+    }
+}
+
+impl FromIterator<String> for EmbeddingInput {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        EmbeddingInput::TextBatch(iter.into_iter().collect())
+    }
+}
+
+/// The `model` of an embeddings request: one model, or several to embed the
+/// same input with each.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ModelSelection {
+    /// A single model name
+    One(String),
+    /// Several model names; the response groups embeddings by model
+    Many(Vec<String>),
+}
+
+impl ModelSelection {
+    /// The model to use for a single-model response: the only name given, or the first.
+    pub fn primary(&self) -> Option<&str> {
+        match self {
+            ModelSelection::One(name) => Some(name),
+            ModelSelection::Many(names) => names.first().map(String::as_str),
+        }
+    }
+}
+
+impl From<&str> for ModelSelection {
+    fn from(name: &str) -> Self {
+        ModelSelection::One(name.to_string())
+    }
+}
+
+impl From<String> for ModelSelection {
+    fn from(name: String) -> Self {
+        ModelSelection::One(name)
+    }
+}
+
 /// Request structure for POST /v1/embeddings endpoint.
 #[derive(Deserialize)]
 pub struct EmbeddingRequest {
-    /// Input text(s) to generate embeddings for. Cannot be empty.
-    pub input: Vec<String>,
-    /// Model to use for embedding generation. If omitted, uses default model.
-    pub model: Option<String>,
-    /// Encoding format for embeddings. Only "float" is supported.
+    /// Input text(s) or token ids to generate embeddings for. Cannot be empty.
+    pub input: EmbeddingInput,
+    /// Model (or list of models) to use for embedding generation. If omitted,
+    /// uses default model.
+    pub model: Option<ModelSelection>,
+    /// Encoding format for embeddings: "float" (default) or "base64".
     pub encoding_format: Option<String>,
-    /// Target dimensions for output embeddings (not yet implemented).
+    /// Truncate embeddings to this many dimensions and re-normalize them to
+    /// unit length. Must not exceed the model's native dimension.
     pub dimensions: Option<usize>,
     /// User identifier for tracking and analytics.
     pub user: Option<String>,
+    /// Precision of returned values ("f32" or "f16"). Defaults to the server's
+    /// configured `output_dtype`.
+    pub output_dtype: Option<String>,
+    /// Rescale embeddings to unit length. Defaults to `true` when `dimensions`
+    /// is set, `false` otherwise.
+    pub normalize: Option<bool>,
+    /// Cut each input text to this many characters before encoding.
+    pub max_length: Option<usize>,
+}
+
+impl EmbeddingRequest {
+    /// How this request's embeddings are post-processed.
+    pub fn encode_options(&self) -> EncodeOptions {
+        EncodeOptions::from_request(self.dimensions, self.normalize, self.max_length)
+    }
+}
+
+/// Request structure for POST /v1/similarity endpoint.
+#[derive(Deserialize)]
+pub struct SimilarityRequest {
+    /// First text, or a batch of texts compared pairwise with `text_b`.
+    pub text_a: EmbeddingInput,
+    /// Second text, or a batch of the same length as `text_a`.
+    pub text_b: EmbeddingInput,
+    /// Model to embed both sides with. If omitted, uses default model.
+    pub model: Option<String>,
+}
+
+/// Response structure for POST /v1/similarity endpoint.
+#[derive(Serialize)]
+pub struct SimilarityResponse {
+    /// Object type identifier ("list").
+    pub object: String,
+    /// Cosine similarity of each pair, in input order.
+    pub data: Vec<SimilarityData>,
+    /// Model used for generating embeddings.
+    pub model: String,
+    /// Dimensions of the compared embeddings.
+    pub dimensions: usize,
+    /// Token usage statistics, summed over both sides.
+    pub usage: Usage,
+}
+
+/// Cosine similarity of one `text_a`/`text_b` pair.
+#[derive(Serialize)]
+pub struct SimilarityData {
+    /// Object type identifier ("similarity").
+    pub object: String,
+    /// Cosine similarity in [-1, 1]; 0.0 when either embedding is all zeros.
+    pub similarity: f32,
+    /// Index of this pair in the input arrays.
+    pub index: usize,
+}
+
+/// Request structure for POST /v1/rank endpoint.
+#[derive(Deserialize)]
+pub struct RankRequest {
+    /// Text the documents are ranked against.
+    pub query: String,
+    /// Candidate texts to rank. Cannot be empty.
+    pub documents: Vec<String>,
+    /// Model to embed the query and documents with. If omitted, uses default model.
+    pub model: Option<String>,
+    /// Return only the best `top_k` documents. If omitted, returns all of them.
+    pub top_k: Option<usize>,
+}
+
+/// Response structure for POST /v1/rank endpoint.
+#[derive(Serialize)]
+pub struct RankResponse {
+    /// Object type identifier ("list").
+    pub object: String,
+    /// Documents by descending similarity to the query.
+    pub data: Vec<RankData>,
+    /// Model used for generating embeddings.
+    pub model: String,
+    /// Token usage statistics, for the query and all documents.
+    pub usage: Usage,
+}
+
+/// One ranked document.
+#[derive(Serialize)]
+pub struct RankData {
+    /// Object type identifier ("rank").
+    pub object: String,
+    /// Index of the document in the request's `documents`.
+    pub index: usize,
+    /// Cosine similarity to the query; 0.0 when either embedding is all zeros.
+    pub score: f32,
+}
+
+/// Request structure for POST /v1/models/load endpoint.
+#[derive(Deserialize)]
+pub struct LoadModelRequest {
+    /// Name to serve the model under; replaces a loaded model of the same name.
+    pub name: String,
+    /// Directory containing the Model2Vec model files.
+    pub path: String,
+}
+
+/// Request structure for POST /v1/models/unload endpoint.
+#[derive(Deserialize)]
+pub struct UnloadModelRequest {
+    /// Name of the loaded model to remove.
+    pub name: String,
 }
 
 /// Query parameters for endpoints supporting model selection.
@@ -39,6 +265,28 @@ pub struct QueryParams {
     pub model: Option<String>,
 }
 
+/// Query parameters for GET /v1/tokenize.
+#[derive(Deserialize)]
+pub struct TokenizeParams {
+    /// Text to count the tokens of.
+    pub text: String,
+    /// Model whose tokenizer counts the text. If omitted, uses default model.
+    pub model: Option<String>,
+}
+
+/// Response structure for GET /v1/tokenize endpoint.
+#[derive(Serialize)]
+pub struct TokenizeResponse {
+    /// Object type identifier ("tokenize").
+    pub object: String,
+    /// Model whose tokenizer counted the text.
+    pub model: String,
+    /// Tokens the text counts as, in `usage` and against the input limit.
+    pub tokens: usize,
+    /// Most tokens one input may have.
+    pub max_tokens: usize,
+}
+
 /// Response structure for POST /v1/embeddings endpoint.
 #[derive(Serialize)]
 pub struct EmbeddingResponse {
@@ -48,17 +296,72 @@ pub struct EmbeddingResponse {
     pub data: Vec<EmbeddingData>,
     /// Model used for generating embeddings.
     pub model: String,
+    /// Length of each embedding, after any truncation to the requested `dimensions`.
+    pub dimensions: usize,
     /// Token usage statistics.
     pub usage: Usage,
 }
 
+/// Response for POST /v1/embeddings when `model` lists several models.
+#[derive(Serialize)]
+pub struct ByModelEmbeddingResponse {
+    /// Object type identifier ("list").
+    pub object: String,
+    /// Embeddings for each requested model, keyed by model name.
+    pub by_model: std::collections::BTreeMap<String, Vec<EmbeddingData>>,
+    /// Models used, in request order.
+    pub models: Vec<String>,
+    /// Token usage statistics, summed over all models.
+    pub usage: Usage,
+}
+
+/// An embedding as sent on the wire, per the request's `encoding_format`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum EmbeddingValue {
+    /// A JSON array of numbers (`"float"`)
+    Float(Vec<f32>),
+    /// Base64 of the little-endian `f32` bytes (`"base64"`)
+    Base64(String),
+}
+
+impl EmbeddingValue {
+    /// Encode `embedding` in `format`.
+    pub fn new(embedding: Vec<f32>, format: EncodingFormat) -> Self {
+        match format {
+            EncodingFormat::Float => EmbeddingValue::Float(embedding),
+            EncodingFormat::Base64 => EmbeddingValue::Base64(EncodingFormat::encode_base64(&embedding)),
+        }
+    }
+
+    /// The values, unless the embedding was base64-encoded.
+    pub fn as_floats(&self) -> Option<&[f32]> {
+        match self {
+            EmbeddingValue::Float(values) => Some(values),
+            EmbeddingValue::Base64(_) => None,
+        }
+    }
+}
+
+impl From<Vec<f32>> for EmbeddingValue {
+    fn from(embedding: Vec<f32>) -> Self {
+        EmbeddingValue::Float(embedding)
+    }
+}
+
+impl PartialEq<Vec<f32>> for EmbeddingValue {
+    fn eq(&self, other: &Vec<f32>) -> bool {
+        self.as_floats() == Some(other.as_slice())
+    }
+}
+
 /// Individual embedding result within EmbeddingResponse.
 #[derive(Serialize)]
 pub struct EmbeddingData {
     /// Object type identifier ("embedding").
     pub object: String,
-    /// Dense vector embedding.
-    pub embedding: Vec<f32>,
+    /// Dense vector embedding, as numbers or base64 per `encoding_format`.
+    pub embedding: EmbeddingValue,
     /// Index of this embedding in the input array.
     pub index: usize,
 }
@@ -94,6 +397,22 @@ pub struct ModelInfo {
     pub owned_by: String,
 }
 
+/// Response structure for GET /v1/models/{model} endpoint.
+#[derive(Serialize)]
+pub struct ModelDetail {
+    /// OpenAI-style model fields; `created` is when the model was loaded.
+    #[serde(flatten)]
+    pub info: ModelInfo,
+    /// Embedding dimensions.
+    pub dimensions: usize,
+    /// Local directory the model was loaded from, if it came from disk.
+    pub path: Option<String>,
+    /// Unix timestamp at which this instance was loaded or last reloaded.
+    pub loaded_at: u64,
+    /// Whether requests without a model use this one.
+    pub default: bool,
+}
+
 /// API error response structure (OpenAI-compatible).
 #[derive(Serialize, Debug)]
 pub struct ApiError {
@@ -102,7 +421,7 @@ pub struct ApiError {
 }
 
 /// Detailed error information.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct ErrorDetails {
     /// Human-readable error message.
     pub message: String,
@@ -124,40 +443,31 @@ mod tests {
     use std::sync::Arc;
     use axum::extract::{Json, Query, State};
     use crate::server::api::embeddings_handler;
+    use crate::server::mock::MockModel;
     use crate::server::state::AppState;
 
-    // Define a local mock model for testing with predictable outputs
-    struct LocalMockModel;
-
-    impl crate::server::state::Model for LocalMockModel {
-        fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
-            inputs.iter().map(|_| vec![0.1, 0.2, 0.3]).collect()
-        }
-    }
-
     fn create_test_app_state() -> Arc<AppState> {
         let mut models: HashMap<String, Arc<dyn crate::server::state::Model>> = HashMap::new();
         models.insert(
             "potion-32M".to_string(),
-            Arc::new(LocalMockModel),
+            Arc::new(MockModel::new("potion-32M".to_string(), 3)),
         );
 
-        Arc::new(AppState {
-            models,
-            default_model: "potion-32M".to_string(),
-            startup_time: std::time::SystemTime::now(),
-        })
+        Arc::new(AppState::from_models(models, "potion-32M"))
     }
 
     #[tokio::test]
     async fn test_embeddings_handler_happy_path() {
         let state = create_test_app_state();
         let request = EmbeddingRequest {
-            input: vec!["test text".to_string()],
+            input: vec!["test text".to_string()].into(),
             model: None,
             encoding_format: None,
             dimensions: None,
             user: None,
+            output_dtype: None,
+            normalize: None,
+            max_length: None,
         };
 
         let params = QueryParams { model: None };
@@ -168,12 +478,19 @@ mod tests {
         assert!(result.is_ok());
         let axum::response::Json(response) = result.unwrap();
         assert_eq!(response.data.len(), 1);
-        assert_eq!(response.data[0].embedding, vec![0.1, 0.2, 0.3]);
+        assert_eq!(
+            response.data[0].embedding,
+            MockModel::new("potion-32M".to_string(), 3).embed("test text")
+        );
         assert_eq!(response.model, "potion-32M");
     }
 }
 
-#[cfg(test)]
+/// Helpers for spinning up a test server, public behind the `mock` feature.
+///
+/// Pair [`test_utils::spawn_test_server_with_state`] with
+/// [`mock::MockModel`] for predictable embeddings without loading real models.
+#[cfg(any(test, feature = "mock"))]
 pub mod test_utils {
     use crate::server::state::AppState;
     use axum::{Router, routing::{get, post}};
@@ -184,16 +501,63 @@ pub mod test_utils {
     use tracing::{debug, info};
     use uuid::Uuid;
 
+    /// Write a tiny Model2Vec model to `dir`, loadable with `StaticModel::from_pretrained`.
+    ///
+    /// Its vocabulary is `hello` and `world` (plus `[UNK]`), with `dimensions`-wide
+    /// embeddings.
+    pub fn write_test_model(dir: &std::path::Path, dimensions: usize) {
+        std::fs::create_dir_all(dir).unwrap();
+        let tokenizer = serde_json::json!({
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [],
+            "normalizer": null,
+            "pre_tokenizer": {"type": "Whitespace"},
+            "post_processor": null,
+            "decoder": null,
+            "model": {
+                "type": "WordLevel",
+                "vocab": {"[UNK]": 0, "hello": 1, "world": 2},
+                "unk_token": "[UNK]"
+            }
+        });
+        std::fs::write(dir.join("tokenizer.json"), tokenizer.to_string()).unwrap();
+        std::fs::write(dir.join("config.json"), r#"{"normalize": true}"#).unwrap();
+
+        // safetensors: header length, JSON header, then the raw little-endian tensor
+        let values: Vec<f32> = (0..3 * dimensions).map(|i| (i % 7) as f32 + 1.0).collect();
+        let header = serde_json::json!({
+            "embeddings": {
+                "dtype": "F32",
+                "shape": [3, dimensions],
+                "data_offsets": [0, values.len() * 4]
+            }
+        })
+        .to_string();
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+        std::fs::write(dir.join("model.safetensors"), bytes).unwrap();
+    }
+
+    /// Serve the built-in models (or mocks when none load) on a random local port.
+    ///
+    /// Returns the server's base URL and the task serving it.
     pub async fn spawn_test_server() -> (String, JoinHandle<()>) {
+        let app_state = Arc::new(AppState::new().await.expect("Failed to create AppState"));
+        spawn_test_server_with_state(app_state).await
+    }
+
+    /// Serve `app_state` on a random local port, without the MCP endpoint.
+    pub async fn spawn_test_server_with_state(app_state: Arc<AppState>) -> (String, JoinHandle<()>) {
         let listener = TcpListener::bind("127.0.0.1:0")
             .await
             .expect("Failed to bind test listener");
         let addr = listener.local_addr().expect("Failed to get local addr");
         let addr_str = format!("http://{}", addr);
 
-        // Use a unique secure temp directory for test databases
-        let _tmp = tempfile::tempdir().expect("failed to create tempdir");
-        let app_state = Arc::new(AppState::new().await.expect("Failed to create AppState"));
+        app_state.apply_ready_policy().await;
 
         let trace_layer = TraceLayer::new_for_http()
             .make_span_with(|request: &axum::http::Request<_>| {
@@ -236,6 +600,7 @@ pub mod test_utils {
         let router = Router::new()
             .nest_service("/v1/mcp", Router::new()) // Skip MCP for tests
             .route("/health", get(crate::server::http::health))
+            .route("/ready", get(crate::server::http::ready))
             .route("/v1/embeddings", post(crate::server::embeddings_handler))
             .with_state(app_state)
             .layer(trace_layer);
@@ -0,0 +1,164 @@
+//! Per-session activity of MCP clients.
+//!
+//! Every MCP session gets a [`SessionMetrics`] counting its requests and
+//! remembering when it was last active. Sessions served over HTTP are tracked
+//! in a [`SessionRegistry`], and with `server.mcp_session_idle_timeout_secs`
+//! set, [`spawn_session_reaper`] periodically logs and drops the ones idle for
+//! longer. This makes clients that hold sessions open, or hammer a tool, easy
+//! to spot in the logs and the `embedtool.mcp.*` metrics.
+
+use metrics::{counter, gauge};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// Request count and last activity of one MCP session.
+///
+/// Cloning is cheap and every clone updates the same values.
+#[derive(Clone, Debug)]
+pub struct SessionMetrics {
+    /// When the session started
+    pub created_at: Instant,
+    /// Requests handled so far
+    pub requests: Arc<AtomicU64>,
+    /// When the last request arrived
+    pub last_activity: Arc<RwLock<Instant>>,
+}
+
+impl Default for SessionMetrics {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            created_at: now,
+            requests: Arc::new(AtomicU64::new(0)),
+            last_activity: Arc::new(RwLock::new(now)),
+        }
+    }
+}
+
+impl SessionMetrics {
+    /// Count a request and mark the session active now.
+    pub fn record_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        *self.last_activity.write().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+        counter!("embedtool.mcp.session_requests").increment(1);
+    }
+
+    /// Requests handled so far.
+    pub fn request_count(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Time since the last request, or since the session started.
+    pub fn idle_for(&self) -> Duration {
+        self.last_activity.read().unwrap_or_else(|e| e.into_inner()).elapsed()
+    }
+}
+
+/// Live MCP sessions by connection id.
+///
+/// Cloning is cheap and every clone sees the same sessions.
+#[derive(Clone, Default)]
+pub struct SessionRegistry {
+    sessions: Arc<RwLock<HashMap<String, SessionMetrics>>>,
+}
+
+impl SessionRegistry {
+    /// Track the session `connection_id`.
+    pub fn register(&self, connection_id: &str, metrics: SessionMetrics) {
+        let mut sessions = self.sessions.write().unwrap_or_else(|e| e.into_inner());
+        sessions.insert(connection_id.to_string(), metrics);
+        gauge!("embedtool.mcp.active_sessions").set(sessions.len() as f64);
+    }
+
+    /// Number of tracked sessions.
+    pub fn len(&self) -> usize {
+        self.sessions.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every session idle for longer than `idle_timeout`, logging each,
+    /// and return their connection ids.
+    pub fn reap_idle(&self, idle_timeout: Duration) -> Vec<String> {
+        let mut sessions = self.sessions.write().unwrap_or_else(|e| e.into_inner());
+        let mut reaped = Vec::new();
+        sessions.retain(|connection_id, metrics| {
+            let idle = metrics.idle_for();
+            if idle <= idle_timeout {
+                return true;
+            }
+            info!(
+                connection_id = %connection_id,
+                requests = metrics.request_count(),
+                idle_secs = idle.as_secs(),
+                session_secs = metrics.created_at.elapsed().as_secs(),
+                "Dropping idle MCP session"
+            );
+            reaped.push(connection_id.clone());
+            false
+        });
+        counter!("embedtool.mcp.sessions_reaped").increment(reaped.len() as u64);
+        gauge!("embedtool.mcp.active_sessions").set(sessions.len() as f64);
+        reaped
+    }
+}
+
+/// Check `registry` for idle sessions in the background, dropping those idle
+/// for longer than `idle_timeout`.
+///
+/// Checks run at least once a minute, so a session outlives the timeout by at
+/// most that long.
+pub fn spawn_session_reaper(registry: SessionRegistry, idle_timeout: Duration) -> JoinHandle<()> {
+    let period = idle_timeout.clamp(Duration::from_millis(10), Duration::from_secs(60));
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            registry.reap_idle(idle_timeout);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_metrics_record_requests() {
+        let metrics = SessionMetrics::default();
+        let clone = metrics.clone();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(metrics.idle_for() >= Duration::from_millis(20));
+
+        clone.record_request();
+        clone.record_request();
+        assert_eq!(metrics.request_count(), 2);
+        assert!(metrics.idle_for() < Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_reaper_drops_only_idle_sessions() {
+        let registry = SessionRegistry::default();
+        let (idle, busy) = (SessionMetrics::default(), SessionMetrics::default());
+        registry.register("idle", idle);
+        registry.register("busy", busy.clone());
+        assert_eq!(registry.len(), 2);
+
+        let reaper = spawn_session_reaper(registry.clone(), Duration::from_millis(50));
+        for _ in 0..10 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            busy.record_request();
+        }
+        reaper.abort();
+
+        assert_eq!(registry.len(), 1);
+        assert!(registry.reap_idle(Duration::ZERO).contains(&"busy".to_string()));
+        assert!(registry.is_empty());
+    }
+}
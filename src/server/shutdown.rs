@@ -0,0 +1,175 @@
+//! Graceful shutdown of the HTTP servers.
+//!
+//! A [`Shutdown`] is shared by the API server and the dedicated MCP server.
+//! Once it is triggered, by SIGTERM or Ctrl+C, both stop accepting new
+//! connections and let the requests already in flight finish. Draining is
+//! bounded by `server.shutdown_timeout_secs`: requests still running after
+//! that are dropped and the process exits anyway. A second Ctrl+C skips the
+//! drain and quits immediately.
+
+use std::future::Future;
+use std::time::Duration;
+use tokio::signal;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Default time in-flight requests get to finish on shutdown, in seconds.
+pub const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+
+/// Shutdown trigger shared by every server of the process.
+///
+/// Cloning is cheap and every clone sees the same trigger.
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    requested: watch::Receiver<bool>,
+    timeout: Duration,
+}
+
+impl Shutdown {
+    /// A shutdown triggered by sending `true` on the returned sender, draining
+    /// for at most `timeout`.
+    pub fn new(timeout: Duration) -> (watch::Sender<bool>, Self) {
+        let (trigger, requested) = watch::channel(false);
+        (trigger, Self { requested, timeout })
+    }
+
+    /// A shutdown triggered by SIGTERM or Ctrl+C, draining for at most `timeout`.
+    pub fn on_signal(timeout: Duration) -> Self {
+        let (trigger, shutdown) = Self::new(timeout);
+        tokio::spawn(async move {
+            wait_for_signal().await;
+            info!(
+                timeout_secs = timeout.as_secs_f64(),
+                "Shutting down: no longer accepting connections, draining in-flight requests. Press Ctrl+C again to force quit."
+            );
+            let _ = trigger.send(true);
+
+            // The drain is already under way; a second Ctrl+C abandons it
+            if signal::ctrl_c().await.is_ok() {
+                warn!("Received second Ctrl+C signal. Force quitting immediately.");
+                std::process::exit(1);
+            }
+        });
+        shutdown
+    }
+
+    /// Resolve once shutdown has been triggered.
+    pub fn requested(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut requested = self.requested.clone();
+        async move {
+            // A dropped trigger can never fire, so wait forever
+            if requested.wait_for(|requested| *requested).await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// Run `server`, which stops once [`Self::requested`] resolves, giving it
+    /// at most the drain timeout to finish after shutdown is triggered.
+    pub async fn drain<F, T>(&self, server: F) -> Option<T>
+    where
+        F: Future<Output = T>,
+    {
+        let deadline = async {
+            self.requested().await;
+            tokio::time::sleep(self.timeout).await;
+        };
+        tokio::select! {
+            result = server => Some(result),
+            _ = deadline => {
+                warn!(
+                    timeout_secs = self.timeout.as_secs_f64(),
+                    "Requests still in flight after the shutdown timeout; dropping them"
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Wait for SIGTERM, sent by `server stop` and service managers, or Ctrl+C.
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal as unix_signal};
+        match unix_signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = signal::ctrl_c() => info!("Received Ctrl+C signal"),
+                    _ = sigterm.recv() => info!("Received SIGTERM signal"),
+                }
+                return;
+            }
+            Err(e) => warn!(error = %e, "Cannot listen for SIGTERM; only Ctrl+C stops the server gracefully"),
+        }
+    }
+    if signal::ctrl_c().await.is_ok() {
+        info!("Received Ctrl+C signal");
+    } else {
+        std::future::pending::<()>().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, routing::get};
+    use std::sync::Arc;
+    use tokio::sync::Notify;
+
+    /// Serves `/slow`, which notifies the returned `Notify` once it is running
+    /// and answers after `delay`.
+    async fn spawn_slow_server(
+        shutdown: Shutdown,
+        delay: Duration,
+    ) -> (String, Arc<Notify>, tokio::task::JoinHandle<Option<()>>) {
+        let started = Arc::new(Notify::new());
+        let notify = Arc::clone(&started);
+        let app = Router::new().route(
+            "/slow",
+            get(move || async move {
+                notify.notify_one();
+                tokio::time::sleep(delay).await;
+                "done"
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let server = axum::serve(listener, app).with_graceful_shutdown(shutdown.requested());
+            shutdown.drain(async { server.await.unwrap() }).await
+        });
+        (format!("http://{}", addr), started, handle)
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_request_finishes_after_shutdown() {
+        let (trigger, shutdown) = Shutdown::new(Duration::from_secs(5));
+        let (base, started, server) = spawn_slow_server(shutdown, Duration::from_millis(300)).await;
+
+        let request = tokio::spawn(reqwest::get(format!("{}/slow", base)));
+        started.notified().await;
+        trigger.send(true).unwrap();
+
+        let response = request.await.unwrap().unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.text().await.unwrap(), "done");
+        assert_eq!(server.await.unwrap(), Some(()));
+
+        // The listener is closed once the drain is over
+        assert!(reqwest::get(format!("{}/slow", base)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_drain_gives_up_after_timeout() {
+        let (trigger, shutdown) = Shutdown::new(Duration::from_millis(100));
+        let (base, started, server) = spawn_slow_server(shutdown, Duration::from_secs(30)).await;
+
+        let _request = tokio::spawn(reqwest::get(format!("{}/slow", base)));
+        started.notified().await;
+        trigger.send(true).unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(5), server).await.unwrap();
+        assert_eq!(result.unwrap(), None);
+    }
+}
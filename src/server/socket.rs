@@ -0,0 +1,112 @@
+//! Serving the HTTP API on a Unix domain socket.
+//!
+//! `server start --socket-path` (or `server.socket_path`) binds the socket
+//! instead of a TCP port and serves the same router on it. The socket file is
+//! created with `server.socket_mode` permissions (0600 by default, so only the
+//! owner can connect) and removed again when the server shuts down. A socket
+//! file left behind by a server that did not shut down cleanly is replaced;
+//! one that a running server still answers on is not.
+
+#[cfg(unix)]
+use anyhow::{Result as AnyhowResult, anyhow};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Removes the socket file when dropped, i.e. when the server stops.
+#[derive(Debug)]
+pub struct SocketFile(PathBuf);
+
+impl SocketFile {
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        match std::fs::remove_file(&self.0) {
+            Ok(()) => debug!(path = %self.0.display(), "Removed socket file"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!(path = %self.0.display(), error = %e, "Failed to remove socket file"),
+        }
+    }
+}
+
+/// Bind a Unix socket at `path` with permissions `mode`.
+///
+/// # Errors
+///
+/// Returns an error if another server is listening on `path`, `path` exists
+/// and is not a socket, or the socket cannot be bound or given `mode`.
+#[cfg(unix)]
+pub fn bind_unix_socket(path: &Path, mode: u32) -> AnyhowResult<(tokio::net::UnixListener, SocketFile)> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(anyhow!("{} exists and is not a socket", path.display()));
+        }
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(anyhow!("Another server is already listening on {}", path.display()));
+        }
+        debug!(path = %path.display(), "Removing stale socket file");
+        std::fs::remove_file(path)
+            .map_err(|e| anyhow!("Failed to remove stale socket {}: {}", path.display(), e))?;
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)
+        .map_err(|e| anyhow!("Failed to bind to {}: {}", path.display(), e))?;
+    let socket = SocketFile(path.to_path_buf());
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+        .map_err(|e| anyhow!("Failed to set permissions {:o} on {}: {}", mode, path.display(), e))?;
+    Ok((listener, socket))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[tokio::test]
+    async fn test_bind_unix_socket_sets_mode_and_cleans_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("embed.sock");
+
+        let (listener, socket) = bind_unix_socket(&path, 0o600).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(socket.path(), path);
+
+        // A live server's socket is not taken over
+        let error = bind_unix_socket(&path, 0o600).unwrap_err().to_string();
+        assert!(error.contains("already listening"), "{}", error);
+
+        drop(listener);
+        drop(socket);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_bind_unix_socket_replaces_stale_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("embed.sock");
+
+        // A socket file nobody listens on any more, as after a crash
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+        let (_listener, _socket) = bind_unix_socket(&path, 0o660).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o660);
+    }
+
+    #[test]
+    fn test_bind_unix_socket_refuses_regular_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("not-a-socket");
+        std::fs::write(&path, "data").unwrap();
+
+        let error = bind_unix_socket(&path, 0o600).unwrap_err().to_string();
+        assert!(error.contains("not a socket"), "{}", error);
+        assert!(path.exists());
+    }
+}
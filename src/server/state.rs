@@ -7,7 +7,8 @@
 //!
 //! Models are loaded concurrently on server startup using `tokio::task::spawn_blocking`
 //! to prevent blocking the async runtime. Failed model loads are logged but don't
-//! prevent the server from starting with successfully loaded models.
+//! prevent the server from starting with successfully loaded models, unless
+//! [`ModelRequest::strict`] is set, in which case any failure aborts startup.
 //!
 //! ## Default Models
 //!
@@ -16,10 +17,21 @@
 //! 2. `potion-8M` (faster, smaller model)
 //! 3. First available model
 //!
+//! ## Readiness
+//!
+//! Loading a model and being able to serve it quickly are not the same thing: the
+//! first encode on a cold start can be slow while the weights are paged in. The
+//! [`ReadyPolicy`] decides which of the two `/ready` waits for, and the outcome
+//! of the startup warm-up is kept as a [`WarmupStatus`]. A server with no models
+//! loaded is never ready.
+//!
 //! ## Thread Safety
 //!
 //! All models are wrapped in `Arc<dyn Model>` for safe sharing across request handlers.
-//! The entire `AppState` implements `Clone` for efficient sharing via Axum's State extractor.
+//! The set of models lives in a [`ModelMap`] shared by the HTTP API and MCP tools, so a
+//! model can be swapped at runtime; requests already holding the old instance finish
+//! with it. The entire `AppState` implements `Clone` for efficient sharing via Axum's
+//! State extractor.
 //!
 //! ## Examples
 //!
@@ -39,64 +51,156 @@ use anyhow::anyhow;
 use futures::future::join_all;
 use model2vec_rs::model::StaticModel;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::SystemTime;
+use tokenizers::Tokenizer;
 use tokio::task;
 use tracing::{info, warn};
 
-/// Load models from the user's model registry.
-/// Returns a map of model names to loaded models.
-fn load_models_from_registry() -> Result<HashMap<String, StaticModel>, anyhow::Error> {
-    let registry_path = get_registry_path()?;
-    if !registry_path.exists() {
-        info!("No model registry found, no custom models to load");
-        return Ok(HashMap::new());
+use super::cache::EmbeddingCache;
+use super::errors::AppError;
+use super::jobs::JobStore;
+use super::limits::InputLimits;
+use crate::utils::OutputDtype;
+
+/// Built-in models and the HuggingFace repositories they are loaded from.
+const BUILTIN_MODELS: &[(&str, &str)] = &[
+    ("potion-8M", "minishlab/potion-base-8M"),
+    ("potion-32M", "minishlab/potion-base-32M"),
+];
+
+/// Background task loading one model.
+type ModelLoadHandle = task::JoinHandle<Result<Arc<dyn Model>, anyhow::Error>>;
+
+/// Load a Model2Vec model from a local directory or HuggingFace repository.
+///
+/// The model's tokenizer is loaded alongside it so that pre-tokenized input can
+/// be decoded; if it cannot be found the model still loads, without token-id support.
+fn load_model(source: &str) -> Result<Arc<dyn Model>, anyhow::Error> {
+    let model = StaticModel::from_pretrained(source, None, None, None)?;
+    match load_tokenizer(source) {
+        Ok(tokenizer) => Ok(Arc::new(TokenizedModel::new(model, tokenizer))),
+        Err(e) => {
+            warn!("Tokenizer for '{}' unavailable, token-id input disabled: {}", source, e);
+            Ok(Arc::new(model))
+        }
     }
+}
 
-    let registry_content = std::fs::read_to_string(&registry_path)?;
-    let registry: serde_json::Value = serde_json::from_str(&registry_content)?;
+fn load_tokenizer(source: &str) -> Result<Tokenizer, anyhow::Error> {
+    let local = Path::new(source);
+    let path = if local.exists() {
+        local.join("tokenizer.json")
+    } else {
+        // Already in the hub cache once the model itself has loaded
+        hf_hub::api::sync::Api::new()?
+            .model(source.to_string())
+            .get("tokenizer.json")?
+    };
+    Tokenizer::from_file(&path).map_err(|e| anyhow!("{}", e))
+}
 
-    let empty_map = serde_json::Map::new();
-    let models_value = registry
-        .get("models")
-        .and_then(|v| v.as_object())
-        .unwrap_or(&empty_map);
-    let mut models = HashMap::new();
-
-    for (name, model_info) in models_value {
-        if let Some(path_str) = model_info.get("path").and_then(|v| v.as_str()) {
-            let model_path = PathBuf::from(path_str);
-            if model_path.exists() {
-                match StaticModel::from_pretrained(&model_path, None, None, None) {
-                    Ok(model) => {
-                        info!(
-                            "✓ Loaded registered model '{}' from {}",
-                            name,
-                            model_path.display()
-                        );
-                        models.insert(name.clone(), model);
-                    }
-                    Err(e) => {
-                        warn!(
-                            "✗ Failed to load registered model '{}' from {}: {}",
-                            name,
-                            model_path.display(),
-                            e
-                        );
+/// Result of loading models at startup.
+#[derive(Default)]
+struct LoadOutcome {
+    models: HashMap<String, Arc<dyn Model>>,
+    /// Where each loaded model came from
+    sources: HashMap<String, String>,
+    /// Models that could not be loaded, with the reason
+    failures: Vec<(String, String)>,
+}
+
+/// Load `(name, source)` pairs concurrently, off the async runtime.
+async fn load_models(sources: Vec<(String, String)>, outcome: &mut LoadOutcome) {
+    let (names, handles): (Vec<(String, String)>, Vec<ModelLoadHandle>) = sources
+        .into_iter()
+        .map(|(name, source)| {
+            let task_source = source.clone();
+            ((name, source), task::spawn_blocking(move || load_model(&task_source)))
+        })
+        .unzip();
+
+    for ((name, source), result) in names.into_iter().zip(join_all(handles).await) {
+        match result {
+            Ok(Ok(model)) => {
+                info!("✓ Loaded model '{}'", name);
+                outcome.sources.insert(name.clone(), source);
+                outcome.models.insert(name, model);
+            }
+            Ok(Err(e)) => outcome.failures.push((name, e.to_string())),
+            Err(e) => outcome.failures.push((name, format!("loading task failed: {}", e))),
+        }
+    }
+}
+
+/// Sources of every registered model, plus the built-in models not overridden
+/// by the registry. Registered models whose files are gone are reported as failures.
+fn all_model_sources(outcome: &mut LoadOutcome) -> Vec<(String, String)> {
+    let mut sources = Vec::new();
+    match read_registry() {
+        Ok(registry) => {
+            let empty_map = serde_json::Map::new();
+            let models_value = registry
+                .get("models")
+                .and_then(|v| v.as_object())
+                .unwrap_or(&empty_map);
+            for (name, model_info) in models_value {
+                if let Some(path_str) = model_info.get("path").and_then(|v| v.as_str()) {
+                    if Path::new(path_str).exists() {
+                        sources.push((name.clone(), path_str.to_string()));
+                    } else {
+                        outcome
+                            .failures
+                            .push((name.clone(), format!("registered path does not exist: {}", path_str)));
                     }
                 }
+            }
+            if sources.is_empty() {
+                info!("No registered models found, loading built-in models only");
             } else {
-                warn!(
-                    "✗ Registered model '{}' path does not exist: {}",
-                    name,
-                    model_path.display()
-                );
+                info!("Loading {} models from registry", sources.len());
             }
         }
+        Err(e) => outcome.failures.push(("registry".to_string(), e.to_string())),
     }
 
-    Ok(models)
+    for (name, repo) in BUILTIN_MODELS {
+        if !sources.iter().any(|(registered, _)| registered == name) {
+            sources.push((name.to_string(), repo.to_string()));
+        }
+    }
+    sources
+}
+
+/// The user's model registry, or an empty one if there is none yet.
+fn read_registry() -> Result<serde_json::Value, anyhow::Error> {
+    let registry_path = get_registry_path()?;
+    if !registry_path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(&registry_path)?)?)
+}
+
+/// Where the files for model `name` live: its registry path, or the HuggingFace
+/// repository of a built-in model.
+fn model_source(name: &str) -> Result<Option<String>, anyhow::Error> {
+    if let Some(path) = read_registry()?
+        .get("models")
+        .and_then(|models| models.get(name))
+        .and_then(|model| model.get("path"))
+        .and_then(|path| path.as_str())
+    {
+        return Ok(Some(path.to_string()));
+    }
+
+    Ok(BUILTIN_MODELS
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, repo)| repo.to_string()))
 }
 
 fn get_registry_path() -> Result<PathBuf, anyhow::Error> {
@@ -122,6 +226,44 @@ pub trait Model: Send + Sync {
     ///
     /// Vector of embeddings, one per input text
     fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>>;
+
+    /// Encode input texts, reporting failures instead of panicking.
+    ///
+    /// Backends that can fail transiently (remote or runtime-backed models)
+    /// override this; failed calls may be retried per `encode_retries`. The
+    /// default wraps [`Model::encode`], which cannot fail.
+    fn try_encode(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        Ok(self.encode(inputs))
+    }
+
+    /// Decode pre-tokenized input back to text.
+    ///
+    /// Returns `None` when the model has no tokenizer to decode with.
+    fn decode_tokens(&self, _ids: &[u32]) -> Option<String> {
+        None
+    }
+
+    /// Embedding size of this model.
+    ///
+    /// The default encodes a short probe; models that know their size override
+    /// it. [`ModelMap`] caches the result for each loaded instance, so the
+    /// probe runs at most once per instance.
+    fn dimensions(&self) -> usize {
+        self.encode(&[DIMENSIONS_PROBE.to_string()]).first().map_or(0, Vec::len)
+    }
+
+    /// Number of tokens `text` is counted as in `usage`.
+    ///
+    /// Models with a tokenizer count the tokens it produces. The default, for
+    /// models without one, estimates one token per four bytes.
+    fn count_tokens(&self, text: &str) -> usize {
+        estimate_tokens(text)
+    }
+}
+
+/// Rough token count for models without a tokenizer: one per four bytes.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
 }
 
 // Implement the trait for StaticModel
@@ -133,48 +275,572 @@ impl Model for StaticModel {
     }
 }
 
-/// Mock model implementation for development and testing when real models are unavailable
+/// A Model2Vec model paired with its tokenizer, which `StaticModel` keeps private.
+pub struct TokenizedModel {
+    model: StaticModel,
+    tokenizer: Tokenizer,
+    /// Embedding size, measured once when the model is wrapped
+    dimensions: usize,
+}
+
+impl TokenizedModel {
+    pub fn new(model: StaticModel, tokenizer: Tokenizer) -> Self {
+        // `StaticModel` keeps its embedding matrix private, so measure one output
+        let dimensions = model.encode_single(DIMENSIONS_PROBE).len();
+        Self { model, tokenizer, dimensions }
+    }
+}
+
+impl Model for TokenizedModel {
+    fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
+        self.model.encode(inputs)
+    }
+
+    fn decode_tokens(&self, ids: &[u32]) -> Option<String> {
+        self.tokenizer.decode(ids, true).ok()
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        // Special tokens are not embedded, so they are not counted either
+        self.tokenizer
+            .encode(text, false)
+            .map_or_else(|_| estimate_tokens(text), |encoding| encoding.len())
+    }
+}
+
+/// Mock model used for development and testing when real models are unavailable
+pub use crate::server::mock::MockModel;
+
+/// A model whose encodes go through the shared [`EmbeddingCache`].
+struct CachedModel {
+    model: Arc<dyn Model>,
+    instance: u64,
+    cache: Arc<EmbeddingCache>,
+}
+
+impl Model for CachedModel {
+    fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
+        self.cache
+            .encode(self.instance, inputs, |texts| Ok(self.model.encode(texts)))
+            .unwrap_or_default()
+    }
+
+    fn try_encode(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        self.cache
+            .encode(self.instance, inputs, |texts| self.model.try_encode(texts))
+    }
+
+    fn decode_tokens(&self, ids: &[u32]) -> Option<String> {
+        self.model.decode_tokens(ids)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.model.dimensions()
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        self.model.count_tokens(text)
+    }
+}
+
+/// Source of [`ModelEntry::instance`] ids.
+static NEXT_INSTANCE: AtomicU64 = AtomicU64::new(0);
+
+/// A loaded model and what is known about it.
 #[derive(Clone)]
-pub struct MockModel {
-    pub name: String,
+struct ModelEntry {
+    model: Arc<dyn Model>,
+    /// Directory or HuggingFace repository the model was loaded from, when known
+    source: Option<String>,
+    loaded_at: SystemTime,
+    /// Embedding size, probed on first use; shared by clones of the entry
+    dimensions: Arc<OnceLock<usize>>,
+    /// Identifies this instance in the embedding cache
+    instance: u64,
+}
+
+impl ModelEntry {
+    fn new(model: Arc<dyn Model>, source: Option<String>) -> Self {
+        Self {
+            model,
+            source,
+            loaded_at: SystemTime::now(),
+            dimensions: Arc::new(OnceLock::new()),
+            instance: NEXT_INSTANCE.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+/// Metadata about one loaded model, as reported by [`ModelMap::details`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelDetails {
+    /// Embedding size
     pub dimensions: usize,
+    /// Local directory the model was loaded from; `None` for HuggingFace
+    /// repositories and models registered at runtime
+    pub path: Option<PathBuf>,
+    /// When this instance was loaded or last swapped in
+    pub loaded_at: SystemTime,
+}
+
+/// Loaded models keyed by name, shared between request handlers and swappable at runtime.
+///
+/// Cloning is cheap and every clone sees the same models and embedding cache.
+#[derive(Clone, Default)]
+pub struct ModelMap {
+    entries: Arc<RwLock<HashMap<String, ModelEntry>>>,
+    cache: Arc<EmbeddingCache>,
+}
+
+impl ModelMap {
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, ModelEntry>> {
+        self.entries.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, ModelEntry>> {
+        self.entries.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The model registered as `name`, answering from the embedding cache when
+    /// it is enabled.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Model>> {
+        let models = self.read();
+        let entry = models.get(name)?;
+        if !self.cache.is_enabled() {
+            return Some(Arc::clone(&entry.model));
+        }
+        Some(Arc::new(CachedModel {
+            model: Arc::clone(&entry.model),
+            instance: entry.instance,
+            cache: Arc::clone(&self.cache),
+        }))
+    }
+
+    /// Embedding cache shared by every model in the map.
+    pub fn cache(&self) -> &EmbeddingCache {
+        &self.cache
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.read().contains_key(name)
+    }
+
+    /// Names of all loaded models.
+    pub fn names(&self) -> Vec<String> {
+        self.read().keys().cloned().collect()
+    }
+
+    /// A point-in-time copy of every model.
+    pub fn snapshot(&self) -> Vec<(String, Arc<dyn Model>)> {
+        self.read()
+            .iter()
+            .map(|(name, entry)| (name.clone(), Arc::clone(&entry.model)))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Register `model` as `name`, returning the instance it replaced.
+    ///
+    /// A replacement keeps the source recorded for the instance it replaces.
+    pub fn insert(&self, name: impl Into<String>, model: Arc<dyn Model>) -> Option<Arc<dyn Model>> {
+        let mut models = self.write();
+        let name = name.into();
+        let source = models.get(&name).and_then(|entry| entry.source.clone());
+        models
+            .insert(name, ModelEntry::new(model, source))
+            .map(|entry| entry.model)
+    }
+
+    /// Remove model `name`, returning the instance that was registered.
+    pub fn remove(&self, name: &str) -> Option<Arc<dyn Model>> {
+        self.write().remove(name).map(|entry| entry.model)
+    }
+
+    /// Build a replacement for model `name` with `load`, check it with a probe
+    /// encode, then swap it in atomically.
+    ///
+    /// Returns the dimensions of the new instance; on failure the current
+    /// instance, if any, stays active.
+    pub async fn swap_in<F>(&self, name: &str, load: F) -> Result<usize, AppError>
+    where
+        F: FnOnce() -> Result<Arc<dyn Model>, String> + Send + 'static,
+    {
+        let load_error = |e: String| AppError::ModelLoad(name.to_string(), e);
+        let (model, dimensions) = task::spawn_blocking(move || {
+            let model = load()?;
+            let dimensions = model
+                .encode(&[WARMUP_PROBE.to_string()])
+                .first()
+                .map(|e| e.len())
+                .unwrap_or(0);
+            if dimensions == 0 {
+                return Err("probe encode returned no embedding".to_string());
+            }
+            Ok((model, dimensions))
+        })
+        .await
+        .map_err(|e| load_error(e.to_string()))?
+        .map_err(load_error)?;
+
+        self.insert(name, model);
+        self.set_dimensions(name, dimensions);
+        Ok(dimensions)
+    }
+
+    /// Load the Model2Vec model in directory `path` and register it as `name`,
+    /// replacing any model already registered under that name.
+    ///
+    /// Returns the dimensions of the loaded model.
+    ///
+    /// # Errors
+    ///
+    /// - [`AppError::InvalidInput`] if `path` does not exist
+    /// - [`AppError::ModelLoad`] if the files cannot be loaded or fail the probe encode
+    pub async fn load_from_path(&self, name: &str, path: &str) -> Result<usize, AppError> {
+        if !Path::new(path).exists() {
+            return Err(AppError::InvalidInput(format!("Model path does not exist: {}", path)));
+        }
+        let source = path.to_string();
+        let dimensions = self
+            .swap_in(name, move || load_model(&source).map_err(|e| e.to_string()))
+            .await?;
+        self.set_source(name, path);
+        info!("✓ Loaded model '{}' from {} ({} dimensions)", name, path, dimensions);
+        Ok(dimensions)
+    }
+
+    /// Record where model `name` was loaded from.
+    pub fn set_source(&self, name: &str, source: impl Into<String>) {
+        if let Some(entry) = self.write().get_mut(name) {
+            entry.source = Some(source.into());
+        }
+    }
+
+    /// Embedding size of model `name`.
+    ///
+    /// The first call for an instance asks [`Model::dimensions`]; later calls,
+    /// and calls after [`ModelMap::set_dimensions`], return the cached size.
+    pub fn dimensions(&self, name: &str) -> Option<usize> {
+        let (model, dimensions) = {
+            let models = self.read();
+            let entry = models.get(name)?;
+            (Arc::clone(&entry.model), Arc::clone(&entry.dimensions))
+        };
+        // Ask outside the lock so a slow probe does not block other requests
+        Some(*dimensions.get_or_init(|| model.dimensions()))
+    }
+
+    /// Cache the embedding size of model `name`, when already known.
+    pub fn set_dimensions(&self, name: &str, dimensions: usize) {
+        if let Some(entry) = self.read().get(name) {
+            let _ = entry.dimensions.set(dimensions);
+        }
+    }
+
+    /// Metadata for model `name`, probing its dimensions if not yet known.
+    pub fn details(&self, name: &str) -> Option<ModelDetails> {
+        let dimensions = self.dimensions(name)?;
+        let models = self.read();
+        let entry = models.get(name)?;
+        Some(ModelDetails {
+            dimensions,
+            path: entry
+                .source
+                .as_deref()
+                .map(PathBuf::from)
+                .filter(|path| path.exists()),
+            loaded_at: entry.loaded_at,
+        })
+    }
 }
 
-impl MockModel {
-    pub fn new(name: String, dimensions: usize) -> Self {
-        Self { name, dimensions }
+impl From<HashMap<String, Arc<dyn Model>>> for ModelMap {
+    fn from(models: HashMap<String, Arc<dyn Model>>) -> Self {
+        let entries = models
+            .into_iter()
+            .map(|(name, model)| (name, ModelEntry::new(model, None)))
+            .collect();
+        Self {
+            entries: Arc::new(RwLock::new(entries)),
+            cache: Arc::default(),
+        }
     }
 }
 
-impl Model for MockModel {
-    fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
-        // Return mock embeddings: fixed-size vectors with simple patterns
-        inputs.iter().enumerate().map(|(i, _)| {
-            (0..self.dimensions).map(|j| {
-                // Simple pattern: mix of sine waves and index-based values
-                let base = (i as f32 * 0.1 + j as f32 * 0.01).sin();
-                let variation = (i as f32 + j as f32) * 0.001;
-                (base + variation).clamp(-1.0, 1.0)
-            }).collect()
-        }).collect()
+/// Text encoded to discover a model's embedding size.
+const DIMENSIONS_PROBE: &str = "dimensions";
+
+/// Text encoded by every model during warm-up.
+const WARMUP_PROBE: &str = "warmup";
+
+/// Progress of the startup warm-up encode, reported by `/health` and `/ready`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum WarmupStatus {
+    /// Warm-up has not finished yet
+    #[default]
+    Pending,
+    /// The `loaded` policy does not warm up
+    Skipped,
+    /// Every loaded model encoded the probe
+    Completed,
+    /// Warm-up failed with this error
+    Failed(String),
+}
+
+impl WarmupStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarmupStatus::Pending => "pending",
+            WarmupStatus::Skipped => "skipped",
+            WarmupStatus::Completed => "completed",
+            WarmupStatus::Failed(_) => "failed",
+        }
+    }
+}
+
+/// Startup readiness policy, selected with `server.ready_policy`.
+///
+/// - `loaded`: ready as soon as the model objects are constructed. Traffic is
+///   accepted sooner, but the first requests may pay for the cold start.
+/// - `warmed`: ready only after a probe encode succeeds on every loaded model.
+///   Startup takes longer, but a ready server is known to be able to encode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadyPolicy {
+    Loaded,
+    #[default]
+    Warmed,
+}
+
+impl ReadyPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadyPolicy::Loaded => "loaded",
+            ReadyPolicy::Warmed => "warmed",
+        }
     }
 }
 
+impl fmt::Display for ReadyPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ReadyPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "loaded" => Ok(ReadyPolicy::Loaded),
+            "warmed" => Ok(ReadyPolicy::Warmed),
+            other => Err(anyhow!(
+                "Invalid ready policy '{}'. Use: loaded, warmed",
+                other
+            )),
+        }
+    }
+}
+
+/// Which models the server loads at startup.
+#[derive(Clone, Debug, Default)]
+pub struct ModelRequest {
+    /// Models to load by name; `None` loads every registered and built-in model
+    pub names: Option<Vec<String>>,
+    /// Preferred default model, used when it loads
+    pub default_model: Option<String>,
+    /// Refuse to start unless every requested model loads
+    pub strict: bool,
+}
+
 /// Shared application state containing loaded models.
 ///
 /// This structure is cloned cheaply (via Arc) and passed to all request handlers
 /// through Axum's State extractor.
 #[derive(Clone)]
 pub struct AppState {
-    /// Map of model names to model instances
-    pub models: HashMap<String, Arc<dyn Model>>,
+    /// Loaded models, shared with the MCP service
+    pub models: ModelMap,
     /// Name of the default model used when no model is specified
     pub default_model: String,
     /// Server startup timestamp for uptime calculations
     pub startup_time: SystemTime,
+    /// Policy deciding when `ready` is flipped
+    pub ready_policy: ReadyPolicy,
+    /// Whether the server is ready to accept traffic
+    pub ready: Arc<AtomicBool>,
+    /// Outcome of the startup warm-up
+    pub warmup: Arc<RwLock<WarmupStatus>>,
+    /// Embedding precision used when a request does not choose one
+    pub default_output_dtype: OutputDtype,
+    /// How many times a failed encode is retried before the request fails
+    pub encode_retries: u32,
+    /// Limits on the inputs of one embedding request, for the HTTP API and MCP tools
+    pub input_limits: InputLimits,
+    /// Asynchronous embedding jobs, kept until a while after they finish
+    pub jobs: JobStore,
 }
 
 impl AppState {
+    /// Create an AppState from already loaded models.
+    ///
+    /// The state starts out not ready; call [`AppState::apply_ready_policy`]
+    /// (or [`AppState::mark_ready`]) once it is being served.
+    pub fn from_models(
+        models: HashMap<String, Arc<dyn Model>>,
+        default_model: impl Into<String>,
+    ) -> Self {
+        Self {
+            models: models.into(),
+            default_model: default_model.into(),
+            startup_time: SystemTime::now(),
+            ready_policy: ReadyPolicy::default(),
+            ready: Arc::new(AtomicBool::new(false)),
+            warmup: Arc::new(RwLock::new(WarmupStatus::default())),
+            default_output_dtype: OutputDtype::default(),
+            encode_retries: 0,
+            input_limits: InputLimits::default(),
+            jobs: JobStore::default(),
+        }
+    }
+
+    /// Whether the server currently reports itself as ready: readiness has been
+    /// flipped and at least one model is loaded.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire) && !self.models.is_empty()
+    }
+
+    /// Current state of the startup warm-up.
+    pub fn warmup_status(&self) -> WarmupStatus {
+        self.warmup.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn set_warmup_status(&self, status: WarmupStatus) {
+        *self.warmup.write().unwrap_or_else(|e| e.into_inner()) = status;
+    }
+
+    /// Mark the server as ready to accept traffic.
+    pub fn mark_ready(&self) {
+        self.ready.store(true, Ordering::Release);
+    }
+
+    /// Encode a probe string with every loaded model.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no model is loaded, or any model panics or returns no
+    /// embedding for the probe.
+    pub async fn warm_up(&self) -> Result<(), anyhow::Error> {
+        if self.models.is_empty() {
+            return Err(anyhow!("No models are loaded"));
+        }
+        for (name, model) in self.models.snapshot() {
+            let embeddings =
+                task::spawn_blocking(move || model.encode(&[WARMUP_PROBE.to_string()]))
+                    .await
+                    .map_err(|e| anyhow!("Warm-up of model '{}' failed: {}", name, e))?;
+            if embeddings.first().is_none_or(|e| e.is_empty()) {
+                return Err(anyhow!(
+                    "Warm-up of model '{}' returned no embedding",
+                    name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Flip readiness according to `ready_policy`.
+    ///
+    /// With [`ReadyPolicy::Warmed`] a failed warm-up leaves the server not ready.
+    pub async fn apply_ready_policy(&self) {
+        match self.ready_policy {
+            ReadyPolicy::Loaded => {
+                self.set_warmup_status(WarmupStatus::Skipped);
+                self.mark_ready();
+            }
+            ReadyPolicy::Warmed => match self.warm_up().await {
+                Ok(()) => {
+                    info!("Warm-up completed for {} models", self.models.len());
+                    self.set_warmup_status(WarmupStatus::Completed);
+                    self.mark_ready();
+                }
+                Err(e) => {
+                    warn!("✗ {}; server will not report ready", e);
+                    self.set_warmup_status(WarmupStatus::Failed(e.to_string()));
+                }
+            },
+        }
+    }
+
+    /// Reload model `name` from its registry path or built-in source and swap it in.
+    ///
+    /// Returns the dimensions of the new instance. If loading fails the current
+    /// instance stays active.
+    ///
+    /// # Errors
+    ///
+    /// - [`AppError::ModelNotFound`] if `name` is not loaded
+    /// - [`AppError::ModelLoad`] if the files cannot be loaded or fail the probe encode
+    pub async fn reload_model(&self, name: &str) -> Result<usize, AppError> {
+        if !self.models.contains(name) {
+            return Err(AppError::ModelNotFound(name.to_string()));
+        }
+        let source = model_source(name)
+            .map_err(|e| AppError::ModelLoad(name.to_string(), e.to_string()))?
+            .ok_or_else(|| {
+                AppError::ModelLoad(name.to_string(), "no registry entry or built-in source".to_string())
+            })?;
+
+        self.swap_model(name, move || load_model(&source).map_err(|e| e.to_string()))
+        .await
+    }
+
+    /// Build a replacement for model `name` with `load`, check it with a probe
+    /// encode, then swap it in atomically.
+    ///
+    /// Returns the dimensions of the new instance; on failure the current
+    /// instance stays active.
+    pub async fn swap_model<F>(&self, name: &str, load: F) -> Result<usize, AppError>
+    where
+        F: FnOnce() -> Result<Arc<dyn Model>, String> + Send + 'static,
+    {
+        let dimensions = self.models.swap_in(name, load).await?;
+        info!("✓ Reloaded model '{}' ({} dimensions)", name, dimensions);
+        Ok(dimensions)
+    }
+
+    /// Remove model `name` from the live set.
+    ///
+    /// Requests already holding the model finish with it.
+    ///
+    /// # Errors
+    ///
+    /// - [`AppError::ModelNotFound`] if `name` is not loaded
+    /// - [`AppError::InvalidInput`] if `name` is the default model
+    pub fn unload_model(&self, name: &str) -> Result<(), AppError> {
+        if name == self.default_model {
+            return Err(AppError::InvalidInput(format!(
+                "'{}' is the default model and cannot be unloaded",
+                name
+            )));
+        }
+        self.models
+            .remove(name)
+            .ok_or_else(|| AppError::ModelNotFound(name.to_string()))?;
+        info!("✓ Unloaded model '{}'", name);
+        Ok(())
+    }
+
     /// Create a new AppState with models loaded from registry and default sources.
     ///
     /// Loading order:
@@ -203,83 +869,64 @@ impl AppState {
     /// # }
     /// ```
     pub async fn new() -> Result<Self, anyhow::Error> {
+        Self::with_models(&ModelRequest::default()).await
+    }
+
+    /// Load the models selected by `request`.
+    ///
+    /// Failed loads are logged and the server starts with the models that did
+    /// load, unless `request.strict` is set.
+    ///
+    /// # Errors
+    ///
+    /// With `request.strict`, fails listing every model that could not be
+    /// loaded and why.
+    pub async fn with_models(request: &ModelRequest) -> Result<Self, anyhow::Error> {
         info!("Loading Model2Vec models...");
 
-        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
-        let mut loaded_count = 0;
-
-        // First, load models from registry
-        match load_models_from_registry() {
-            Ok(registry_models) => {
-                let registry_count = registry_models.len();
-                for (name, model) in registry_models {
-                    models.insert(name.clone(), Arc::new(model));
-                    loaded_count += 1;
-                }
-                if registry_count > 0 {
-                    info!("Loaded {} models from registry", registry_count);
+        let mut outcome = LoadOutcome::default();
+        let sources = match &request.names {
+            Some(names) => {
+                let mut sources = Vec::new();
+                for name in names {
+                    match model_source(name) {
+                        Ok(Some(source)) => sources.push((name.clone(), source)),
+                        Ok(None) => outcome
+                            .failures
+                            .push((name.clone(), "not a registered or built-in model".to_string())),
+                        Err(e) => outcome.failures.push((name.clone(), e.to_string())),
+                    }
                 }
+                sources
             }
-            Err(e) => {
-                warn!("Failed to load models from registry: {}", e);
-            }
-        }
+            None => all_model_sources(&mut outcome),
+        };
+        let requested = sources.len() + outcome.failures.len();
+        load_models(sources, &mut outcome).await;
 
-        // Define built-in models to load if not already available
-        let builtin_models = vec![
-            (
-                "potion-8M".to_string(),
-                "minishlab/potion-base-8M".to_string(),
-            ),
-            (
-                "potion-32M".to_string(),
-                "minishlab/potion-base-32M".to_string(),
-            ),
-        ];
-
-        // Load built-in models that aren't already loaded
-        let mut handles: Vec<task::JoinHandle<Result<(String, StaticModel), anyhow::Error>>> =
-            vec![];
-
-        for (name, path) in builtin_models {
-            if !models.contains_key(&name) {
-                let name_clone = name.clone();
-                let path_clone = path.clone();
-                let name_clone_err = name_clone.clone();
-                let handle = task::spawn_blocking(move || {
-                    StaticModel::from_pretrained(&path_clone, None, None, None)
-                        .map(|model| (name_clone, model))
-                        .map_err(|e| {
-                            anyhow!(format!("Failed to load model {}: {}", name_clone_err, e))
-                        })
-                });
-                handles.push(handle);
+        let LoadOutcome { mut models, sources, failures } = outcome;
+        if !failures.is_empty() {
+            if request.strict {
+                let details: Vec<String> = failures
+                    .iter()
+                    .map(|(name, reason)| format!("  - {}: {}", name, reason))
+                    .collect();
+                return Err(anyhow!(
+                    "{} of {} requested models failed to load (strict models enabled):\n{}",
+                    failures.len(),
+                    requested,
+                    details.join("\n")
+                ));
             }
-        }
-
-        if !handles.is_empty() {
-            let results = join_all(handles).await;
-
-            for result in results {
-                match result {
-                    Ok(Ok((name, model))) => {
-                        info!("✓ Loaded built-in {} model", name);
-                        models.insert(name, Arc::new(model));
-                        loaded_count += 1;
-                    }
-                    Ok(Err(e)) => {
-                        warn!("✗ {}", e);
-                    }
-                    Err(e) => {
-                        warn!("✗ Failed to join model loading task: {}", e);
-                    }
-                }
+            for (name, reason) in &failures {
+                warn!("✗ Failed to load model '{}': {}", name, reason);
             }
         }
 
         // If no models loaded, create mock models for development/testing
         if models.is_empty() {
             warn!("No models could be loaded from registry or built-in sources. Creating mock models for development/testing.");
+            // Only the built-in names: a mistyped model must not answer with fake vectors
             models.insert(
                 "potion-8M".to_string(),
                 Arc::new(MockModel::new("potion-8M".to_string(), 8)),
@@ -288,27 +935,29 @@ impl AppState {
                 "potion-32M".to_string(),
                 Arc::new(MockModel::new("potion-32M".to_string(), 32)),
             );
-            loaded_count = 2;
         }
 
-        let default_model = if models.contains_key("potion-32M") {
-            "potion-32M".to_string()
-        } else if models.contains_key("potion-8M") {
-            "potion-8M".to_string()
-        } else {
-            models.keys().next().unwrap().clone()
+        let default_model = match &request.default_model {
+            Some(name) if models.contains_key(name) => name.clone(),
+            _ if models.contains_key("potion-32M") => "potion-32M".to_string(),
+            _ if models.contains_key("potion-8M") => "potion-8M".to_string(),
+            _ => {
+                let mut names: Vec<&String> = models.keys().collect();
+                names.sort();
+                names[0].clone()
+            }
         };
 
         info!(
             "Loaded {} models total, default: {}",
-            loaded_count, default_model
+            models.len(), default_model
         );
 
-        Ok(AppState {
-            models,
-            default_model,
-            startup_time: SystemTime::now(),
-        })
+        let state = AppState::from_models(models, default_model);
+        for (name, source) in sources {
+            state.models.set_source(&name, source);
+        }
+        Ok(state)
     }
 }
 
@@ -317,6 +966,129 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
 
+    #[tokio::test]
+    async fn test_with_models_strict_lists_failed_models() {
+        let request = ModelRequest {
+            names: Some(vec!["no-such-model".to_string(), "also-missing".to_string()]),
+            default_model: None,
+            strict: true,
+        };
+        let message = AppState::with_models(&request).await.err().unwrap().to_string();
+        assert!(message.contains("2 of 2 requested models failed to load"), "{}", message);
+        assert!(message.contains("  - no-such-model: not a registered or built-in model"));
+        assert!(message.contains("  - also-missing: not a registered or built-in model"));
+
+        // Without strict mode the server still starts, but never under a name
+        // that failed to load
+        let request = ModelRequest { strict: false, ..request };
+        let state = AppState::with_models(&request).await.unwrap();
+        assert!(!state.models.contains("no-such-model"));
+        assert!(!state.models.contains("also-missing"));
+        assert!(state.models.contains("potion-32M"));
+    }
+
+    #[tokio::test]
+    async fn test_swap_model_keeps_old_instance_on_failure() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("m".to_string(), Arc::new(MockModel::new("m".to_string(), 4)));
+        let state = AppState::from_models(models, "m");
+        let dims = |state: &AppState| state.models.get("m").unwrap().encode(&["x".to_string()])[0].len();
+
+        let err = state
+            .swap_model("m", || Err("corrupt safetensors".to_string()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::ModelLoad(_, _)));
+        assert_eq!(dims(&state), 4);
+
+        // A model that cannot encode is rejected too
+        let err = state
+            .swap_model("m", || Ok(Arc::new(MockModel::new("m".to_string(), 0)) as Arc<dyn Model>))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("probe encode"));
+        assert_eq!(dims(&state), 4);
+
+        let new_dims = state
+            .swap_model("m", || Ok(Arc::new(MockModel::new("m".to_string(), 16)) as Arc<dyn Model>))
+            .await
+            .unwrap();
+        assert_eq!(new_dims, 16);
+        assert_eq!(dims(&state), 16);
+    }
+
+    #[test]
+    fn test_model_map_caches_dimensions_per_instance() {
+        use std::sync::atomic::AtomicUsize;
+
+        struct CountingModel(Arc<AtomicUsize>, usize);
+        impl Model for CountingModel {
+            fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                inputs.iter().map(|_| vec![0.5; self.1]).collect()
+            }
+        }
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let models = ModelMap::default();
+        models.insert("m", Arc::new(CountingModel(calls.clone(), 3)));
+        assert_eq!(models.dimensions("m"), Some(3));
+        assert_eq!(models.dimensions("m"), Some(3));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(models.dimensions("missing"), None);
+
+        // A swapped-in instance is probed afresh and keeps the recorded source
+        let tmp = tempfile::TempDir::new().unwrap();
+        models.set_source("m", tmp.path().to_str().unwrap());
+        models.insert("m", Arc::new(CountingModel(calls.clone(), 5)));
+        let details = models.details("m").unwrap();
+        assert_eq!(details.dimensions, 5);
+        assert_eq!(details.path.as_deref(), Some(tmp.path()));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Known sizes are not probed, and HuggingFace sources have no local path
+        models.insert("hub", Arc::new(CountingModel(calls.clone(), 7)));
+        models.set_source("hub", "minishlab/potion-base-8M");
+        models.set_dimensions("hub", 7);
+        assert_eq!(models.details("hub").unwrap().path, None);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_load_tokenizer_from_local_dir_decodes_ids() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let tokenizer = serde_json::json!({
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [],
+            "normalizer": null,
+            "pre_tokenizer": {"type": "Whitespace"},
+            "post_processor": null,
+            "decoder": null,
+            "model": {
+                "type": "WordLevel",
+                "vocab": {"[UNK]": 0, "hello": 1, "world": 2},
+                "unk_token": "[UNK]"
+            }
+        });
+        std::fs::write(tmp.path().join("tokenizer.json"), tokenizer.to_string()).unwrap();
+
+        let tokenizer = load_tokenizer(tmp.path().to_str().unwrap()).unwrap();
+        assert_eq!(tokenizer.decode(&[1, 2], true).unwrap(), "hello world");
+
+        // A directory without a tokenizer is an error, not a panic
+        let empty = tempfile::TempDir::new().unwrap();
+        assert!(load_tokenizer(empty.path().to_str().unwrap()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reload_model_unknown_name() {
+        let state = AppState::from_models(HashMap::new(), "none");
+        let err = state.reload_model("does-not-exist").await.unwrap_err();
+        assert!(matches!(err, AppError::ModelNotFound(_)));
+    }
+
     #[test]
     fn test_app_state_creation() {
         let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
@@ -328,9 +1100,8 @@ mod tests {
         let startup_time = SystemTime::now();
 
         let state = AppState {
-            models,
-            default_model: "test-model".to_string(),
             startup_time,
+            ..AppState::from_models(models, "test-model")
         };
 
         assert_eq!(state.models.len(), 1);
@@ -343,7 +1114,7 @@ mod tests {
         // Test the default model selection logic (extracted from AppState::new())
 
         // Test case 1: potion-32M is available
-        let model_names = vec!["potion-8M", "potion-32M"];
+        let model_names = ["potion-8M", "potion-32M"];
 
         let default_model = if model_names.contains(&"potion-32M") {
             "potion-32M".to_string()
@@ -354,7 +1125,7 @@ mod tests {
         assert_eq!(default_model, "potion-32M");
 
         // Test case 2: potion-32M not available, should pick first available
-        let model_names2 = vec!["custom-model"];
+        let model_names2 = ["custom-model"];
 
         let default_model2 = if model_names2.contains(&"potion-32M") {
             "potion-32M".to_string()
@@ -371,9 +1142,8 @@ mod tests {
         let startup_time = SystemTime::now();
 
         let state = AppState {
-            models: models.clone(),
-            default_model: "test-model".to_string(),
             startup_time,
+            ..AppState::from_models(models.clone(), "test-model")
         };
 
         let cloned_state = state.clone();
@@ -405,9 +1175,8 @@ mod tests {
         let startup_time = SystemTime::now();
 
         let state = AppState {
-            models,
-            default_model: "potion-32M".to_string(),
             startup_time,
+            ..AppState::from_models(models, "potion-32M")
         };
 
         // Test that we can access all public fields
@@ -419,8 +1188,7 @@ mod tests {
     #[test]
     fn test_model_loading_configuration() {
         // Test the model loading configuration used in AppState::new()
-        let model_loads = vec![
-            (
+        let model_loads = [(
                 "potion-8M".to_string(),
                 "minishlab/potion-base-8M".to_string(),
             ),
@@ -431,8 +1199,7 @@ mod tests {
             (
                 "code-distilled".to_string(),
                 "./code-model-distilled".to_string(),
-            ),
-        ];
+            )];
 
         // Verify the expected models are configured
         assert_eq!(model_loads.len(), 3);
@@ -455,4 +1222,64 @@ mod tests {
             .unwrap();
         assert_eq!(code_distilled.1, "./code-model-distilled");
     }
+
+    #[test]
+    fn test_ready_policy_parse() {
+        assert_eq!("loaded".parse::<ReadyPolicy>().unwrap(), ReadyPolicy::Loaded);
+        assert_eq!(" Warmed ".parse::<ReadyPolicy>().unwrap(), ReadyPolicy::Warmed);
+        assert!("eventually".parse::<ReadyPolicy>().is_err());
+        assert_eq!(ReadyPolicy::default(), ReadyPolicy::Warmed);
+        assert_eq!(ReadyPolicy::Loaded.to_string(), "loaded");
+    }
+
+    #[tokio::test]
+    async fn test_apply_ready_policy_loaded_and_warmed() {
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert(
+            "test-model".to_string(),
+            Arc::new(MockModel::new("test-model".to_string(), 8)),
+        );
+
+        for policy in [ReadyPolicy::Loaded, ReadyPolicy::Warmed] {
+            let state = AppState {
+                ready_policy: policy,
+                ..AppState::from_models(models.clone(), "test-model")
+            };
+            assert!(!state.is_ready());
+            assert_eq!(state.warmup_status(), WarmupStatus::Pending);
+            state.apply_ready_policy().await;
+            assert!(state.is_ready(), "{} policy should become ready", policy);
+            let expected = match policy {
+                ReadyPolicy::Loaded => WarmupStatus::Skipped,
+                ReadyPolicy::Warmed => WarmupStatus::Completed,
+            };
+            assert_eq!(state.warmup_status(), expected);
+        }
+
+        // Without models the server never reports ready
+        let state = AppState::from_models(HashMap::new(), "none");
+        state.mark_ready();
+        assert!(!state.is_ready());
+        state.apply_ready_policy().await;
+        assert_eq!(state.warmup_status(), WarmupStatus::Failed("No models are loaded".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_failure_keeps_not_ready() {
+        struct EmptyModel;
+        impl Model for EmptyModel {
+            fn encode(&self, _inputs: &[String]) -> Vec<Vec<f32>> {
+                Vec::new()
+            }
+        }
+
+        let mut models: HashMap<String, Arc<dyn Model>> = HashMap::new();
+        models.insert("empty".to_string(), Arc::new(EmptyModel));
+        let state = AppState::from_models(models, "empty");
+
+        assert!(state.warm_up().await.is_err());
+        state.apply_ready_policy().await;
+        assert!(!state.is_ready());
+        assert_eq!(state.warmup_status().as_str(), "failed");
+    }
 }
@@ -7,16 +7,25 @@
 //!
 //! - **embed**: Generate embeddings for a single text input
 //! - **batch_embed**: Process multiple texts in parallel
+//! - **similarity**: Rank candidate texts by cosine similarity to a query
 //! - **list_models**: Query available embedding models
+//! - **session_info**: Report this session's request count and idle time
 //! - **load_model**: Dynamically load a model into memory
 //!
+//! ## Resources
+//!
+//! Each session also serves the resources of [`ResourceRegistry`], such as
+//! `models://list` and `metrics://server`, read from the shared server state.
+//!
 //! ## Connection Management
 //!
 //! Each MCP client session maintains:
 //! - Unique connection ID
 //! - Session start time
-//! - Request metrics (count, last access)
-//! - Lock-based state for thread safety
+//! - Request metrics (count, last access) in a shared [`SessionMetrics`]
+//!
+//! Sessions served over HTTP are registered in a [`SessionRegistry`], so the
+//! server can drop those left idle.
 //!
 //! ## Examples
 //!
@@ -37,25 +46,360 @@
 
 use rmcp::{
     ErrorData as McpError,
-    model::{CallToolResult, Content, Tool, ListToolsResult},
+    model::{
+        CallToolResult, Content, Implementation, ListResourcesResult, ListToolsResult,
+        PaginatedRequestParam, ReadResourceRequestParam, ReadResourceResult, ServerCapabilities,
+        ServerInfo, Tool, ToolAnnotations,
+    },
     handler::server::ServerHandler,
     service::RequestContext,
     RoleServer,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 
 use tracing::{debug, error, info, warn};
 use metrics::counter;
-use crate::server::state::Model;
-use crate::utils;
+use crate::embed::EncodeOptions;
+use crate::server::EmbeddingValue;
+use crate::server::errors::AppError;
+use crate::server::limits::{InputLimitError, InputLimits};
+use crate::resources::ResourceRegistry;
+use crate::server::sessions::{SessionMetrics, SessionRegistry};
+use crate::server::state::{AppState, Model, ModelMap};
+use crate::utils::{self, EncodingFormat, UniqueTexts};
 
 // Global metrics
 static EMBEDDING_COUNTER: AtomicU64 = AtomicU64::new(0);
 
+/// Parse a tool's `encoding_format`, defaulting to float.
+fn parse_encoding_format(requested: Option<&str>) -> Result<EncodingFormat, McpError> {
+    requested.map_or(Ok(EncodingFormat::Float), |format| {
+        format
+            .parse()
+            .map_err(|e: anyhow::Error| McpError::invalid_params(e.to_string(), None))
+    })
+}
+
+/// `invalid_params` for inputs rejected by the limits shared with the HTTP API.
+fn input_limit_error(e: InputLimitError) -> McpError {
+    counter!("embedtool.errors.invalid_input").increment(1);
+    McpError::invalid_params(e.to_string(), None)
+}
+
+/// Encode `inputs` with `model`, splitting batches of more than 32 texts into
+/// chunks encoded in parallel on the blocking pool.
+///
+/// Repeated texts are encoded once and copied back to each of their positions.
+async fn encode_chunked(model_instance: &Arc<dyn Model>, inputs: &[String]) -> Result<Vec<Vec<f32>>, McpError> {
+    let unique = UniqueTexts::new(inputs);
+    let encoded = encode_unique_chunked(model_instance, &unique.texts).await?;
+    unique
+        .fan_out(encoded)
+        .map_err(|e| McpError::internal_error(e.to_string(), None))
+}
+
+async fn encode_unique_chunked(model_instance: &Arc<dyn Model>, inputs: &[String]) -> Result<Vec<Vec<f32>>, McpError> {
+    if inputs.len() <= 32 {
+        // Small batch: encode directly
+        return Ok(model_instance.encode(inputs));
+    }
+
+    // Large batch: split into chunks of 32 and process in parallel
+    use futures::future::join_all;
+    use tokio::task::spawn_blocking;
+
+    let chunk_size = 32;
+    let mut chunk_futures = Vec::new();
+    for chunk in inputs.chunks(chunk_size) {
+        let chunk_vec: Vec<String> = chunk.to_vec();
+        let model_clone = model_instance.clone();
+        chunk_futures.push(spawn_blocking(move || model_clone.encode(&chunk_vec)));
+    }
+
+    let mut all_embeddings = Vec::with_capacity(inputs.len());
+    for result in join_all(chunk_futures).await {
+        match result {
+            Ok(embeddings) => all_embeddings.extend(embeddings),
+            Err(e) => {
+                error!("Spawn blocking failed during batch embed: {}", e);
+                return Err(McpError::internal_error(
+                    "Embedding generation failed".to_string(),
+                    None
+                ));
+            }
+        }
+    }
+    Ok(all_embeddings)
+}
+
+/// Most tools returned by one `list_tools` call.
+const TOOLS_PAGE_SIZE: usize = 20;
+
+/// Every tool the service offers.
+///
+/// `list_tools` advertises exactly these and `call_tool` dispatches on them
+/// with an exhaustive match, so a tool cannot be listed without a handler or
+/// handled without being listed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolName {
+    Embed,
+    BatchEmbed,
+    Similarity,
+    ListModels,
+    SessionInfo,
+    ModelInfo,
+    DistillModel,
+    LoadModel,
+}
+
+impl ToolName {
+    /// All tools, in the order `list_tools` returns them.
+    pub const ALL: [ToolName; 8] = [
+        ToolName::Embed,
+        ToolName::BatchEmbed,
+        ToolName::Similarity,
+        ToolName::ListModels,
+        ToolName::SessionInfo,
+        ToolName::ModelInfo,
+        ToolName::DistillModel,
+        ToolName::LoadModel,
+    ];
+
+    /// Name clients call the tool by.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ToolName::Embed => "embed",
+            ToolName::BatchEmbed => "batch_embed",
+            ToolName::Similarity => "similarity",
+            ToolName::ListModels => "list_models",
+            ToolName::SessionInfo => "session_info",
+            ToolName::ModelInfo => "model_info",
+            ToolName::DistillModel => "distill_model",
+            ToolName::LoadModel => "load_model",
+        }
+    }
+
+    /// The tool called `name`, if there is one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|tool| tool.as_str() == name)
+    }
+
+    /// The tool as advertised by `list_tools`.
+    fn definition(self) -> Tool {
+        match self {
+            ToolName::Embed => Tool {
+                name: self.as_str().into(),
+                description: Some(r#"
+                Generate embeddings for a single text input using Model2Vec.
+
+                This function generates vector embeddings for the provided text using the specified
+                Model2Vec model. The embeddings can be used for semantic search, similarity comparison,
+                clustering, and other machine learning tasks.
+
+                Available models include:
+                - potion-8M: Lightweight model with 8M parameters
+                - potion-32M: Balanced model with 32M parameters (default)
+                - code-distilled: Specialized model for code embeddings
+                "#.into()),
+                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(EmbedParams)).unwrap()).unwrap()),
+                output_schema: None,
+                annotations: Some(read_only_annotations("Embed Text")),
+                icons: None,
+                title: Some("Embed Text".into()),
+                meta: None,
+            },
+            ToolName::BatchEmbed => Tool {
+                name: self.as_str().into(),
+                description: Some(r#"
+                Generate embeddings for multiple text inputs in batch using Model2Vec.
+
+                This function generates vector embeddings for an array of text inputs using the
+                specified Model2Vec model. This is more efficient than calling embed multiple times
+                for processing multiple texts.
+
+                The batch processing maintains the order of inputs, so the returned embeddings array
+                corresponds to the input array by index.
+
+                Examples:
+                - batch_embed(["Hello world", "Goodbye world"])  # Uses default potion-32M model
+                - batch_embed(["Hello", "World"], Some("potion-8M"))  # Uses specific model
+                - batch_embed(["def hello():", "class World:"], Some("code-distilled"))  # Code embeddings
+                "#.into()),
+                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(BatchEmbedParams)).unwrap()).unwrap()),
+                output_schema: None,
+                annotations: Some(read_only_annotations("Embed Texts in Batch")),
+                icons: None,
+                title: Some("Embed Texts in Batch".into()),
+                meta: None,
+            },
+            ToolName::Similarity => Tool {
+                name: self.as_str().into(),
+                description: Some(r#"
+                Rank candidate texts by semantic similarity to a query using Model2Vec.
+
+                This function embeds the query and every candidate with the specified Model2Vec
+                model and scores each candidate by cosine similarity to the query. Candidates are
+                returned best match first with their score and their index in the input array,
+                which makes it suitable for reranking search results.
+
+                Examples:
+                - similarity("cat", ["kitten", "car", "tiger"])  # Rank all candidates
+                - similarity("cat", ["kitten", "car", "tiger"], top_k=Some(2))  # Two best matches
+                "#.into()),
+                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(SimilarityParams)).unwrap()).unwrap()),
+                output_schema: None,
+                annotations: Some(read_only_annotations("Rank by Similarity")),
+                icons: None,
+                title: Some("Rank by Similarity".into()),
+                meta: None,
+            },
+            ToolName::ListModels => Tool {
+                name: self.as_str().into(),
+                description: Some(r#"
+                List available embedding models.
+
+                This function returns information about all available Model2Vec models that can be
+                used for generating embeddings. Each model has different characteristics in terms
+                of size, performance, and specialization.
+
+                The response includes model names, dimensions, and other metadata to help you choose
+                the right model for your use case.
+                "#.into()),
+                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(ModelListParams)).unwrap()).unwrap()),
+                output_schema: None,
+                annotations: Some(read_only_annotations("List Models")),
+                icons: None,
+                title: Some("List Models".into()),
+                meta: None,
+            },
+            ToolName::SessionInfo => Tool {
+                name: self.as_str().into(),
+                description: Some(r#"
+                Report information about the current MCP session.
+
+                This function returns the session's connection ID, how long it has been open, how
+                many requests it has made and how long it has been idle. Use it to check which
+                session a client is on when debugging.
+                "#.into()),
+                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(SessionInfoParams)).unwrap()).unwrap()),
+                output_schema: None,
+                annotations: Some(read_only_annotations("Session Info")),
+                icons: None,
+                title: Some("Session Info".into()),
+                meta: None,
+            },
+            ToolName::ModelInfo => Tool {
+                name: self.as_str().into(),
+                description: Some(r#"
+                Get detailed information about a specific embedding model.
+
+                This function returns detailed information about a specific Model2Vec model, including
+                its dimensions, capabilities, and current status.
+
+                Examples:
+                - model_info("potion-32M")  # Get info about the default model
+                - model_info("code-distilled")  # Get info about the code model
+                "#.into()),
+                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(ModelInfoParams)).unwrap()).unwrap()),
+                output_schema: None,
+                annotations: Some(read_only_annotations("Model Info")),
+                icons: None,
+                title: Some("Model Info".into()),
+                meta: None,
+            },
+            ToolName::DistillModel => Tool {
+                name: self.as_str().into(),
+                description: Some(r#"
+    /// Distills a pre-trained model into a more efficient Model2Vec model.
+    /// 
+    /// This process:
+    ///   1. Downloads the source model (if needed)
+    ///   2. Applies PCA to reduce dimensions (automatically adjusted based on model name if not provided)
+    ///   3. Loads the new model under `output_name`, ready for `embed` and the HTTP API
+    /// 
+    /// Examples:
+    /// - distill_model("minishlab/potion-base-8M", "my-mini-model")  # Auto-sets 8 dims
+    /// - distill_model("minishlab/potion-base-32M", "my-model")      # Auto-sets 32 dims
+    /// - distill_model("microsoft/codebert-base", "code-32", Some(32))  # Custom dimensions
+                "#.into()),
+                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(ModelDistillParams)).unwrap()).unwrap()),
+                output_schema: None,
+                annotations: Some(
+                    ToolAnnotations::with_title("Distill Model")
+                        .read_only(false)
+                        .destructive(true)
+                        .idempotent(false)
+                        .open_world(true),
+                ),
+                icons: None,
+                title: Some("Distill Model".into()),
+                meta: None,
+            },
+            ToolName::LoadModel => Tool {
+                name: self.as_str().into(),
+                description: Some(r#"
+                Load a Model2Vec model from a local directory under a name.
+
+                The model is served right away by this session, every other MCP session and the
+                HTTP API. A model already loaded under the same name is replaced.
+
+                Examples:
+                - load_model("my-model", "/models/my-model")  # Serve a distilled model
+                "#.into()),
+                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(LoadModelParams)).unwrap()).unwrap()),
+                output_schema: None,
+                annotations: Some(
+                    ToolAnnotations::with_title("Load Model")
+                        .read_only(false)
+                        .destructive(true)
+                        .idempotent(true)
+                        .open_world(false),
+                ),
+                icons: None,
+                title: Some("Load Model".into()),
+                meta: None,
+            },
+        }
+    }
+}
+
+/// Tool arguments as `T`, or an invalid-params error naming what is wrong.
+fn parse_params<T: serde::de::DeserializeOwned>(args: serde_json::Value) -> Result<T, McpError> {
+    serde_json::from_value(args).map_err(|e| McpError::invalid_params(e.to_string(), None))
+}
+
+/// Annotations for a tool that only reads: it changes nothing on the server
+/// and works offline, so clients may call it without confirmation.
+fn read_only_annotations(title: &str) -> ToolAnnotations {
+    ToolAnnotations::with_title(title)
+        .read_only(true)
+        .destructive(false)
+        .idempotent(true)
+        .open_world(false)
+}
+
+/// Return the page of `tools` starting at `cursor`, at most `page_size` long.
+///
+/// Cursors are the offset of the page's first tool, as handed out in
+/// `next_cursor`; no cursor starts at the first tool.
+fn paginate_tools(tools: Vec<Tool>, cursor: Option<&str>, page_size: usize) -> Result<ListToolsResult, McpError> {
+    let start = match cursor {
+        None => 0,
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .ok()
+            .filter(|&start| start <= tools.len())
+            .ok_or_else(|| McpError::invalid_params(format!("Invalid cursor: {}", cursor), None))?,
+    };
+    let end = start.saturating_add(page_size).min(tools.len());
+    let next_cursor = (end < tools.len()).then(|| end.to_string());
+    let tools = tools.into_iter().skip(start).take(end - start).collect();
+    Ok(ListToolsResult { tools, next_cursor, meta: None })
+}
+
 /// Parameters for the embed tool.
 #[derive(Serialize, Deserialize, schemars::JsonSchema)]
 pub struct EmbedParams {
@@ -63,9 +407,13 @@ pub struct EmbedParams {
     pub input: String,
     #[schemars(description = "Model to use for embedding (optional, defaults to potion-32M)")]
     pub model: Option<String>,
-    #[schemars(description = "Target dimensions for output embeddings (optional, not yet implemented)")]
+    #[schemars(description = "Truncate embeddings to this many dimensions and re-normalize (optional, at most the model's native size)")]
     pub dimensions: Option<usize>,
-    #[schemars(description = "Encoding format for embeddings (optional, defaults to float)")]
+    #[schemars(description = "Rescale embeddings to unit length (optional, defaults to true when dimensions is set)")]
+    pub normalize: Option<bool>,
+    #[schemars(description = "Cut each input to this many characters before encoding (optional)")]
+    pub max_length: Option<usize>,
+    #[schemars(description = "Encoding format for embeddings: float or base64 (optional, defaults to float)")]
     pub encoding_format: Option<String>,
     #[schemars(description = "User identifier for tracking and analytics (optional)")]
     pub user: Option<String>,
@@ -77,17 +425,44 @@ pub struct BatchEmbedParams {
     pub inputs: Vec<String>,
     #[schemars(description = "Model to use for embedding (optional, defaults to potion-32M)")]
     pub model: Option<String>,
-    #[schemars(description = "Target dimensions for output embeddings (optional, not yet implemented)")]
+    #[schemars(description = "Truncate embeddings to this many dimensions and re-normalize (optional, at most the model's native size)")]
     pub dimensions: Option<usize>,
-    #[schemars(description = "Encoding format for embeddings (optional, defaults to float)")]
+    #[schemars(description = "Rescale embeddings to unit length (optional, defaults to true when dimensions is set)")]
+    pub normalize: Option<bool>,
+    #[schemars(description = "Cut each input to this many characters before encoding (optional)")]
+    pub max_length: Option<usize>,
+    #[schemars(description = "Encoding format for embeddings: float or base64 (optional, defaults to float)")]
     pub encoding_format: Option<String>,
     #[schemars(description = "User identifier for tracking and analytics (optional)")]
     pub user: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SimilarityParams {
+    #[schemars(description = "Text to compare the candidates against")]
+    pub query: String,
+    #[schemars(description = "Array of candidate texts to rank by similarity to the query")]
+    pub candidates: Vec<String>,
+    #[schemars(description = "Model to use for embedding (optional, defaults to potion-32M)")]
+    pub model: Option<String>,
+    #[schemars(description = "Return only this many best matches (optional, defaults to all candidates)")]
+    pub top_k: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, schemars::JsonSchema)]
+pub struct LoadModelParams {
+    #[schemars(description = "Name to serve the model under")]
+    pub name: String,
+    #[schemars(description = "Local directory holding the model files")]
+    pub path: String,
+}
+
 #[derive(Serialize, Deserialize, schemars::JsonSchema)]
 pub struct ModelListParams {}
 
+#[derive(Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SessionInfoParams {}
+
 #[derive(Serialize, Deserialize, schemars::JsonSchema)]
 pub struct ModelInfoParams {
     #[schemars(description = "Name of the model to get information about")]
@@ -109,26 +484,52 @@ pub struct EmbeddingService {
     /// Connection ID for tracking this client session
     pub connection_id: String,
     /// Available models (shared from AppState)
-    pub models: HashMap<String, Arc<dyn Model>>,
+    pub models: ModelMap,
     /// Timestamp when this service was created
     pub created_at: std::time::Instant,
-
+    /// Resources served to MCP clients, reading live values from the server state
+    pub resources: Arc<ResourceRegistry>,
+    /// Request count and last activity of this session
+    pub session: SessionMetrics,
+    /// Limits on tool inputs, the same as the HTTP API's
+    pub input_limits: InputLimits,
 }
 
 impl EmbeddingService {
     /// Create a new EmbeddingService instance with models
-    pub fn new(connection_id: String, models: HashMap<String, Arc<dyn Model>>) -> Self {
+    pub fn new(connection_id: String, models: impl Into<ModelMap>) -> Self {
+        let mut state = AppState::from_models(std::collections::HashMap::new(), "potion-32M");
+        state.models = models.into();
+        Self::with_state(connection_id, Arc::new(state))
+    }
+
+    /// Create a new EmbeddingService instance sharing the server's state, so
+    /// resources report the settings and models the HTTP API serves
+    pub fn with_state(connection_id: String, state: Arc<AppState>) -> Self {
         info!(connection_id = %connection_id, "Creating new embedding service session");
+        let session = SessionMetrics::default();
         Self {
             connection_id,
-            models,
-            created_at: Instant::now(),
+            models: state.models.clone(),
+            created_at: session.created_at,
+            input_limits: state.input_limits,
+            resources: Arc::new(ResourceRegistry::new(state)),
+            session,
         }
     }
 
+    /// Track this session in `sessions`, so it is dropped once left idle
+    pub fn registered_in(self, sessions: &SessionRegistry) -> Self {
+        sessions.register(&self.connection_id, self.session.clone());
+        self
+    }
+
     /// Generate embeddings for a single text input
     pub async fn embed(&self, params: EmbedParams) -> Result<CallToolResult, McpError> {
-        let EmbedParams { input, model, .. } = params;
+        let EmbedParams { input, model, dimensions, normalize, max_length, encoding_format, .. } = params;
+        let encoding = parse_encoding_format(encoding_format.as_deref())?;
+        let options = EncodeOptions::from_request(dimensions, normalize, max_length);
+        let input = options.truncate_input(&input).to_string();
         let start_time = Instant::now();
 
         counter!("embedtool.tools.embed").increment(1);
@@ -155,19 +556,24 @@ impl EmbeddingService {
                 McpError::internal_error(
                     format!("Model '{}' not found. Available models: {:?}",
                            model_name,
-                           self.models.keys().collect::<Vec<_>>()),
+                           self.models.names()),
                     None
                 )
             })?;
 
-        let embeddings = model_instance.encode(std::slice::from_ref(&input));
+        let prompt_tokens = model_instance.count_tokens(&input);
+        self.input_limits.check_text(0, &input, prompt_tokens).map_err(input_limit_error)?;
+
+        let mut embeddings = model_instance.encode(std::slice::from_ref(&input));
+        options
+            .apply(&mut embeddings)
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
         if let Some(embedding) = embeddings.first() {
             let duration = start_time.elapsed();
             let dimensions = embedding.len();
-            let prompt_tokens = input.len().div_ceil(4);
 
             let response = serde_json::json!({
-                "embedding": embedding,
+                "embedding": EmbeddingValue::new(embedding.clone(), encoding),
                 "model": model_name,
                 "dimensions": dimensions,
                 "usage": {
@@ -197,7 +603,11 @@ impl EmbeddingService {
 
     /// Generate embeddings for multiple text inputs in batch
     pub async fn batch_embed(&self, params: BatchEmbedParams) -> Result<CallToolResult, McpError> {
-        let BatchEmbedParams { inputs, model, .. } = params;
+        let BatchEmbedParams { inputs, model, dimensions, normalize, max_length, encoding_format, .. } = params;
+        self.input_limits.check_count(inputs.len()).map_err(input_limit_error)?;
+        let encoding = parse_encoding_format(encoding_format.as_deref())?;
+        let options = EncodeOptions::from_request(dimensions, normalize, max_length);
+        let inputs: Vec<String> = inputs.iter().map(|text| options.truncate_input(text).to_string()).collect();
         let start_time = Instant::now();
         
         counter!("embedtool.tools.batch_embed").increment(1);
@@ -224,54 +634,32 @@ impl EmbeddingService {
                 McpError::internal_error(
                     format!("Model '{}' not found. Available models: {:?}", 
                            model_name, 
-                            self.models.keys().collect::<Vec<_>>()),
+                            self.models.names()),
                     None
                 )
             })?;
 
-        // Generate embeddings with optional parallel chunking for large batches
-        let batch_embeddings: Vec<Vec<f32>> = if inputs.len() <= 32 {
-            // Small batch: encode directly
-            model_instance.encode(&inputs)
-        } else {
-            // Large batch: split into chunks of 32 and process in parallel
-            use futures::future::join_all;
-            use tokio::task::spawn_blocking;
-
-            let chunk_size = 32;
-            let chunks: Vec<_> = inputs.chunks(chunk_size).collect();
-            let mut chunk_futures = Vec::new();
-
-            for chunk in chunks {
-                let chunk_vec: Vec<String> = chunk.to_vec();
-                let model_clone = model_instance.clone();
-                chunk_futures.push(spawn_blocking(move || model_clone.encode(&chunk_vec)));
-            }
+        // Counted once, for both the input limit and `usage`
+        let text_tokens: Vec<usize> = inputs.iter().map(|text| model_instance.count_tokens(text)).collect();
+        for (index, (text, &tokens)) in inputs.iter().zip(&text_tokens).enumerate() {
+            self.input_limits.check_text(index, text, tokens).map_err(input_limit_error)?;
+        }
 
-            let results = join_all(chunk_futures).await;
-            let mut all_embeddings = Vec::new();
-
-            for result in results {
-                match result {
-                    Ok(embeddings) => all_embeddings.extend(embeddings),
-                    Err(e) => {
-                        error!("Spawn blocking failed during batch embed: {}", e);
-                        return Err(McpError::internal_error(
-                            "Embedding generation failed".to_string(),
-                            None
-                        ));
-                    }
-                }
-            }
-            all_embeddings
-        };
+        let mut batch_embeddings = encode_chunked(&model_instance, &inputs).await?;
+
+        options
+            .apply(&mut batch_embeddings)
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
 
         let duration = start_time.elapsed();
         let dimensions = batch_embeddings.first().map(|e| e.len()).unwrap_or(0);
-        let prompt_tokens: usize = inputs.iter().map(|s| s.len().div_ceil(4)).sum();
+        let prompt_tokens: usize = text_tokens.iter().sum();
 
         let response = serde_json::json!({
-            "embeddings": batch_embeddings,
+            "embeddings": batch_embeddings
+                .iter()
+                .map(|embedding| EmbeddingValue::new(embedding.clone(), encoding))
+                .collect::<Vec<_>>(),
             "model": model_name,
             "dimensions": dimensions,
             "usage": {
@@ -298,6 +686,111 @@ impl EmbeddingService {
         Ok(CallToolResult::success(vec![Content::text(json_response)]))
     }
 
+    /// Rank candidate texts by cosine similarity to a query
+    pub async fn similarity(&self, params: SimilarityParams) -> Result<CallToolResult, McpError> {
+        let SimilarityParams { query, candidates, model, top_k } = params;
+        if candidates.is_empty() {
+            counter!("embedtool.errors.invalid_input").increment(1);
+            return Err(McpError::invalid_params(
+                "candidates must contain at least one text".to_string(),
+                None,
+            ));
+        }
+        self.input_limits.check_count(candidates.len()).map_err(input_limit_error)?;
+        let start_time = Instant::now();
+
+        counter!("embedtool.tools.similarity").increment(1);
+
+        debug!(
+            connection_id = %self.connection_id,
+            model = model.as_deref().unwrap_or("potion-32M"),
+            candidate_count = candidates.len(),
+            "Ranking candidates by similarity"
+        );
+
+        let model_name = model.unwrap_or_else(|| "potion-32M".to_string());
+
+        let model_instance = self.models.get(&model_name)
+            .ok_or_else(|| {
+                error!(
+                    connection_id = %self.connection_id,
+                    model = %model_name,
+                    "Model not found or not loaded"
+                );
+                counter!("embedtool.errors.model_not_found").increment(1);
+                McpError::internal_error(
+                    format!("Model '{}' not found. Available models: {:?}",
+                           model_name,
+                           self.models.names()),
+                    None
+                )
+            })?;
+
+        // Checked as the HTTP rank endpoint checks its query and documents,
+        // each indexed on its own; the counts are reused for `usage`
+        let query_tokens = model_instance.count_tokens(&query);
+        self.input_limits.check_text(0, &query, query_tokens).map_err(input_limit_error)?;
+        let candidate_tokens: Vec<usize> = candidates.iter().map(|text| model_instance.count_tokens(text)).collect();
+        for (index, (text, &tokens)) in candidates.iter().zip(&candidate_tokens).enumerate() {
+            self.input_limits.check_text(index, text, tokens).map_err(input_limit_error)?;
+        }
+
+        // The query goes first, so one batch embeds everything
+        let mut texts = Vec::with_capacity(candidates.len() + 1);
+        texts.push(query);
+        texts.extend(candidates);
+        let embeddings = encode_chunked(&model_instance, &texts).await?;
+        let Some((query_embedding, candidate_embeddings)) = embeddings.split_first() else {
+            return Err(McpError::internal_error("No embedding generated".to_string(), None));
+        };
+
+        let mut ranked: Vec<(usize, f32)> = candidate_embeddings
+            .iter()
+            .enumerate()
+            .map(|(index, embedding)| (index, utils::cosine_similarity(query_embedding, embedding)))
+            .collect();
+        // Stable sort, so equal scores stay in candidate order
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        if let Some(top_k) = top_k {
+            ranked.truncate(top_k);
+        }
+
+        let duration = start_time.elapsed();
+        let prompt_tokens: usize = query_tokens + candidate_tokens.iter().sum::<usize>();
+        let candidate_count = candidate_embeddings.len();
+
+        let response = serde_json::json!({
+            "results": ranked
+                .iter()
+                .map(|&(index, score)| serde_json::json!({
+                    "index": index,
+                    "score": score,
+                    "text": texts[index + 1],
+                }))
+                .collect::<Vec<_>>(),
+            "model": model_name,
+            "usage": {
+                "prompt_tokens": prompt_tokens,
+                "total_tokens": prompt_tokens
+            },
+            "processing_time_ms": duration.as_millis(),
+            "candidate_count": candidate_count
+        });
+
+        info!(
+            connection_id = %self.connection_id,
+            model = %model_name,
+            candidate_count = candidate_count,
+            duration_ms = duration.as_millis(),
+            "Successfully ranked candidates by similarity"
+        );
+
+        let json_response = serde_json::to_string_pretty(&response)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json_response)]))
+    }
+
     /// List available embedding models
     pub async fn list_models(&self, _params: ModelListParams) -> Result<CallToolResult, McpError> {
         let start_time = Instant::now();
@@ -310,9 +803,11 @@ impl EmbeddingService {
         );
 
         let mut models_info = Vec::new();
-        for (name, model) in &self.models {
-            let embeddings = model.encode(&["test".to_string()]);
-            let dimensions = embeddings.first().map(|e| e.len()).unwrap_or(0);
+        for name in self.models.names() {
+            let Some(dimensions) = self.models.dimensions(&name) else {
+                // Removed since the names were read
+                continue;
+            };
 
             models_info.push(serde_json::json!({
                 "name": name,
@@ -342,6 +837,28 @@ impl EmbeddingService {
         )]))
     }
 
+    /// Report this session's request count and activity
+    pub async fn session_info(&self, _params: SessionInfoParams) -> Result<CallToolResult, McpError> {
+        counter!("embedtool.tools.session_info").increment(1);
+
+        let result = serde_json::json!({
+            "connection_id": self.connection_id,
+            "session_duration_secs": self.session.created_at.elapsed().as_secs(),
+            "request_count": self.session.request_count(),
+            "idle_secs": self.session.idle_for().as_secs(),
+        });
+
+        debug!(
+            connection_id = %self.connection_id,
+            request_count = self.session.request_count(),
+            "Reported session information"
+        );
+
+        Ok(CallToolResult::success(vec![Content::text(
+            result.to_string(),
+        )]))
+    }
+
     /// Get detailed information about a specific model
     pub async fn model_info(&self, params: ModelInfoParams) -> Result<CallToolResult, McpError> {
         let ModelInfoParams { model: model_name } = params;
@@ -355,7 +872,7 @@ impl EmbeddingService {
             "Getting model information"
         );
 
-        let model_instance = self.models.get(&model_name)
+        let dimensions = self.models.dimensions(&model_name)
             .ok_or_else(|| {
                 error!(
                     connection_id = %self.connection_id,
@@ -366,14 +883,11 @@ impl EmbeddingService {
                 McpError::internal_error(
                     format!("Model '{}' not found. Available models: {:?}", 
                            model_name, 
-                            self.models.keys().collect::<Vec<_>>()),
+                            self.models.names()),
                     None
                 )
             })?;
 
-        let embeddings = model_instance.encode(&["test".to_string()]);
-        let dimensions = embeddings.first().map(|e| e.len()).unwrap_or(0);
-
         let duration = start_time.elapsed();
         
         let result = serde_json::json!({
@@ -448,32 +962,34 @@ impl EmbeddingService {
                     "Successfully distilled model"
                 );
 
-                match model2vec_rs::model::StaticModel::from_pretrained(&output_path, None, None, None) {
-                    Ok(_model) => {
-                        // Note: In a real implementation, we'd need to add this to the shared AppState
-                        // For now, we'll just log that it was created
-                        info!(
-                            connection_id = %self.connection_id,
-                            model_name = %output_name,
-                            "Model distilled successfully but not added to service (would need AppState access)"
-                        );
-                    }
-                    Err(e) => {
-                        warn!(
-                            connection_id = %self.connection_id,
-                            model_name = %output_name,
-                            error = %e,
-                            "Model distilled successfully but failed to load into service"
-                        );
-                    }
-                }
+                // Shared with the HTTP API, so the new model is served there too
+                let loaded_dims = self.models.load_from_path(&output_name, &output_path).await.map_err(|e| {
+                    warn!(
+                        connection_id = %self.connection_id,
+                        model_name = %output_name,
+                        error = %e,
+                        "Model distilled successfully but failed to load into service"
+                    );
+                    counter!("embedtool.errors.distill_model").increment(1);
+                    McpError::internal_error(
+                        format!("Model distilled to {} but could not be loaded: {}", output_path, e),
+                        None,
+                    )
+                })?;
+                info!(
+                    connection_id = %self.connection_id,
+                    model_name = %output_name,
+                    dimensions = loaded_dims,
+                    "Distilled model loaded"
+                );
 
                 let result = serde_json::json!({
                     "message": "Model distillation completed successfully",
                     "input_model": input_model,
                     "output_name": output_name,
                     "output_path": output_path,
-                    "dimensions": dims,
+                    "dimensions": loaded_dims,
+                    "loaded": true,
                     "processing_time_ms": duration.as_millis()
                 });
 
@@ -504,21 +1020,50 @@ impl EmbeddingService {
         }
     }
 
+    /// Load a model from a local directory, as the `load_model` tool
+    pub async fn load_model_tool(&self, params: LoadModelParams) -> Result<CallToolResult, McpError> {
+        let LoadModelParams { name, path } = params;
+        counter!("embedtool.tools.load_model").increment(1);
+        let dimensions = self.models.load_from_path(&name, &path).await.map_err(|e| {
+            counter!("embedtool.errors.load_model").increment(1);
+            match e {
+                AppError::InvalidInput(message) => McpError::invalid_params(message, None),
+                e => McpError::internal_error(e.to_string(), None),
+            }
+        })?;
+        info!(
+            connection_id = %self.connection_id,
+            model_name = %name,
+            model_path = %path,
+            dimensions = dimensions,
+            "Model loaded"
+        );
+
+        let result = serde_json::json!({
+            "name": name,
+            "path": path,
+            "dimensions": dimensions,
+            "loaded": true
+        });
+        Ok(CallToolResult::success(vec![Content::text(result.to_string())]))
+    }
+
     /// Check if a model can be loaded (for compatibility - models are now managed by AppState)
     pub async fn load_model(&self, name: &str, path: &str) -> Result<(), Box<dyn std::error::Error>> {
         info!(
             connection_id = %self.connection_id,
             model_name = %name,
             model_path = %path,
-            "Checking if model can be loaded (models managed by AppState)"
+            "Loading model"
         );
 
-        // In the current architecture, models are loaded in AppState
-        // This method is kept for API compatibility but doesn't actually load models
+        // Shared with the HTTP API, so the model is served there too
+        let dimensions = self.models.load_from_path(name, path).await?;
         info!(
             connection_id = %self.connection_id,
             model_name = %name,
-            "Model loading check completed (use AppState for actual loading)"
+            dimensions = dimensions,
+            "Model loaded"
         );
 
         Ok(())
@@ -527,152 +1072,56 @@ impl EmbeddingService {
 
 
 impl ServerHandler for EmbeddingService {
-    async fn list_tools(&self, _pagination: Option<rmcp::model::PaginatedRequestParam>, _context: RequestContext<RoleServer>) -> Result<ListToolsResult, McpError> {
-        let tools = vec![
-            Tool {
-                name: "embed".into(),
-                description: Some(r#"
-                Generate embeddings for a single text input using Model2Vec.
-
-                This function generates vector embeddings for the provided text using the specified
-                Model2Vec model. The embeddings can be used for semantic search, similarity comparison,
-                clustering, and other machine learning tasks.
-
-                Available models include:
-                - potion-8M: Lightweight model with 8M parameters
-                - potion-32M: Balanced model with 32M parameters (default)
-                - code-distilled: Specialized model for code embeddings
-                "#.into()),
-                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(EmbedParams)).unwrap()).unwrap()),
-                output_schema: None,
-                annotations: None,
-                icons: None,
-                title: None,
-                meta: None,
-            },
-            Tool {
-                name: "batch_embed".into(),
-                description: Some(r#"
-                Generate embeddings for multiple text inputs in batch using Model2Vec.
-
-                This function generates vector embeddings for an array of text inputs using the
-                specified Model2Vec model. This is more efficient than calling embed multiple times
-                for processing multiple texts.
-
-                The batch processing maintains the order of inputs, so the returned embeddings array
-                corresponds to the input array by index.
-
-                Examples:
-                - batch_embed(["Hello world", "Goodbye world"])  # Uses default potion-32M model
-                - batch_embed(["Hello", "World"], Some("potion-8M"))  # Uses specific model
-                - batch_embed(["def hello():", "class World:"], Some("code-distilled"))  # Code embeddings
-                "#.into()),
-                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(BatchEmbedParams)).unwrap()).unwrap()),
-                output_schema: None,
-                annotations: None,
-                icons: None,
-                title: None,
-                meta: None,
-            },
-            Tool {
-                name: "list_models".into(),
-                description: Some(r#"
-                List available embedding models.
-
-                This function returns information about all available Model2Vec models that can be
-                used for generating embeddings. Each model has different characteristics in terms
-                of size, performance, and specialization.
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_resources()
+                .enable_tools()
+                .build(),
+            server_info: Implementation::from_build_env(),
+            ..Default::default()
+        }
+    }
 
-                The response includes model names, dimensions, and other metadata to help you choose
-                the right model for your use case.
-                "#.into()),
-                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(ModelListParams)).unwrap()).unwrap()),
-                output_schema: None,
-                annotations: None,
-                icons: None,
-                title: None,
-                meta: None,
-            },
-            Tool {
-                name: "model_info".into(),
-                description: Some(r#"
-                Get detailed information about a specific embedding model.
+    async fn list_resources(&self, _pagination: Option<PaginatedRequestParam>, _context: RequestContext<RoleServer>) -> Result<ListResourcesResult, McpError> {
+        self.session.record_request();
+        Ok(ListResourcesResult {
+            resources: self.resources.list_resources()?,
+            next_cursor: None,
+            meta: None,
+        })
+    }
 
-                This function returns detailed information about a specific Model2Vec model, including
-                its dimensions, capabilities, and current status.
+    async fn read_resource(&self, request: ReadResourceRequestParam, _context: RequestContext<RoleServer>) -> Result<ReadResourceResult, McpError> {
+        self.session.record_request();
+        Ok(self.resources.read_resource(&request.uri)?)
+    }
 
-                Examples:
-                - model_info("potion-32M")  # Get info about the default model
-                - model_info("code-distilled")  # Get info about the code model
-                "#.into()),
-                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(ModelInfoParams)).unwrap()).unwrap()),
-                output_schema: None,
-                annotations: None,
-                icons: None,
-                title: None,
-                meta: None,
-            },
-            Tool {
-                name: "distill_model".into(),
-                description: Some(r#"
-    /// Distills a pre-trained model into a more efficient Model2Vec model.
-    /// 
-    /// This process:
-    ///   1. Downloads the source model (if needed)
-    ///   2. Applies PCA to reduce dimensions (automatically adjusted based on model name if not provided)
-    ///   3. Registers the new model in the local registry
-    /// 
-    /// Examples:
-    /// - distill_model("minishlab/potion-base-8M", "my-mini-model")  # Auto-sets 8 dims
-    /// - distill_model("minishlab/potion-base-32M", "my-model")      # Auto-sets 32 dims
-    /// - distill_model("microsoft/codebert-base", "code-32", Some(32))  # Custom dimensions
-                "#.into()),
-                input_schema: Arc::new(serde_json::from_value(serde_json::to_value(schemars::schema_for!(ModelDistillParams)).unwrap()).unwrap()),
-                output_schema: None,
-                annotations: None,
-                icons: None,
-                title: None,
-                meta: None,
-            },
-        ];
+    async fn list_tools(&self, pagination: Option<PaginatedRequestParam>, _context: RequestContext<RoleServer>) -> Result<ListToolsResult, McpError> {
+        self.session.record_request();
+        let tools = ToolName::ALL.into_iter().map(ToolName::definition).collect();
 
-        Ok(ListToolsResult { tools, next_cursor: None, meta: None })
+        paginate_tools(tools, pagination.and_then(|p| p.cursor).as_deref(), TOOLS_PAGE_SIZE)
     }
 
     async fn call_tool(&self, request: rmcp::model::CallToolRequestParam, _context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        self.session.record_request();
         let args = request.arguments
             .ok_or_else(|| McpError::invalid_params("Missing arguments", None))?;
 
-        match &request.name as &str {
-            "embed" => {
-                let params: EmbedParams = serde_json::from_value(serde_json::Value::Object(args))
-                    .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
-                self.embed(params).await
-            }
-            "batch_embed" => {
-                let params: BatchEmbedParams = serde_json::from_value(serde_json::Value::Object(args))
-                    .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
-                self.batch_embed(params).await
-            }
-            "list_models" => {
-                let params: ModelListParams = serde_json::from_value(serde_json::Value::Object(args))
-                    .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
-                self.list_models(params).await
-            }
-            "model_info" => {
-                let params: ModelInfoParams = serde_json::from_value(serde_json::Value::Object(args))
-                    .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
-                self.model_info(params).await
-            }
-            "distill_model" => {
-                let params: ModelDistillParams = serde_json::from_value(serde_json::Value::Object(args))
-                    .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
-                self.distill_model(params).await
-            }
-            _ => Err(McpError::invalid_params(
-                format!("Unknown tool: {}", request.name),
-                None,
-            )),
+        let Some(tool) = ToolName::from_name(&request.name) else {
+            return Err(McpError::invalid_params(format!("Unknown tool: {}", request.name), None));
+        };
+        let args = serde_json::Value::Object(args);
+        match tool {
+            ToolName::Embed => self.embed(parse_params(args)?).await,
+            ToolName::BatchEmbed => self.batch_embed(parse_params(args)?).await,
+            ToolName::Similarity => self.similarity(parse_params(args)?).await,
+            ToolName::ListModels => self.list_models(parse_params(args)?).await,
+            ToolName::SessionInfo => self.session_info(parse_params(args)?).await,
+            ToolName::ModelInfo => self.model_info(parse_params(args)?).await,
+            ToolName::DistillModel => self.distill_model(parse_params(args)?).await,
+            ToolName::LoadModel => self.load_model_tool(parse_params(args)?).await,
         }
     }
 }
@@ -707,6 +1156,8 @@ mod tests {
             input: "Hello world".to_string(),
             model: Some("potion-32M".to_string()),
             dimensions: None,
+            normalize: None,
+            max_length: None,
             encoding_format: None,
             user: None,
         };
@@ -728,6 +1179,8 @@ mod tests {
             inputs: vec!["Hello".to_string(), "world".to_string()],
             model: None,
             dimensions: None,
+            normalize: None,
+            max_length: None,
             encoding_format: None,
             user: None,
         };
@@ -840,6 +1293,8 @@ mod tests {
             input: "test".to_string(),
             model: None,
             dimensions: None,
+            normalize: None,
+            max_length: None,
             encoding_format: None,
             user: None,
         };
@@ -854,6 +1309,8 @@ mod tests {
             inputs: vec![],
             model: None,
             dimensions: None,
+            normalize: None,
+            max_length: None,
             encoding_format: None,
             user: None,
         };
@@ -903,10 +1360,500 @@ mod tests {
 
     #[tokio::test]
     async fn test_load_model_nonexistent_path() {
-        // Attempt to load a model from a non-existent path should return ok (dummy implementation)
         let models = HashMap::new();
         let service = EmbeddingService::new("test-load".to_string(), models);
-        let result = service.load_model("missing", "/path/that/does/not/exist").await;
-        assert!(result.is_ok()); // Now returns ok since it's just a compatibility stub
+        let err = service.load_model("missing", "/path/that/does/not/exist").await.unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+        assert!(!service.models.contains("missing"));
+    }
+
+    #[tokio::test]
+    async fn test_embed_tools_encoding_format() {
+        use base64::Engine;
+        use crate::server::mock::MockModel;
+
+        let mut models: HashMap<String, Arc<dyn crate::server::state::Model>> = HashMap::new();
+        models.insert("potion-32M".to_string(), Arc::new(MockModel::new("potion-32M".to_string(), 4)));
+        let service = EmbeddingService::new("test-encoding".to_string(), models);
+        let expected = MockModel::new("potion-32M".to_string(), 4).embed("hello");
+        let decode = |value: &serde_json::Value| -> Vec<f32> {
+            base64::engine::general_purpose::STANDARD
+                .decode(value.as_str().unwrap())
+                .unwrap()
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect()
+        };
+        let body = |result: CallToolResult| -> serde_json::Value {
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap()
+        };
+        let embed_params = |encoding_format: Option<&str>| EmbedParams {
+            input: "hello".to_string(),
+            model: None,
+            dimensions: None,
+            normalize: None,
+            max_length: None,
+            encoding_format: encoding_format.map(str::to_string),
+            user: None,
+        };
+
+        let float = body(service.embed(embed_params(Some("float"))).await.unwrap());
+        let floats: Vec<f32> = serde_json::from_value(float["embedding"].clone()).unwrap();
+        assert_eq!(floats, expected);
+        let base64 = body(service.embed(embed_params(Some("base64"))).await.unwrap());
+        assert_eq!(decode(&base64["embedding"]), expected);
+        assert_eq!(base64["dimensions"], 4);
+
+        let batch = body(
+            service
+                .batch_embed(BatchEmbedParams {
+                    inputs: vec!["hello".to_string(), "world".to_string()],
+                    model: None,
+                    dimensions: None,
+                    normalize: None,
+                    max_length: None,
+                    encoding_format: Some("base64".to_string()),
+                    user: None,
+                })
+                .await
+                .unwrap(),
+        );
+        assert_eq!(decode(&batch["embeddings"][0]), expected);
+        assert_eq!(decode(&batch["embeddings"][1]), MockModel::new("m".to_string(), 4).embed("world"));
+
+        let err = service.embed(embed_params(Some("binary"))).await.unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("binary"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_embed_encodes_repeated_inputs_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Default)]
+        struct CountingModel(AtomicUsize);
+        impl crate::server::state::Model for CountingModel {
+            fn encode(&self, inputs: &[String]) -> Vec<Vec<f32>> {
+                self.0.fetch_add(inputs.len(), Ordering::SeqCst);
+                inputs.iter().map(|text| vec![text.len() as f32, 1.0]).collect()
+            }
+        }
+
+        let model = Arc::new(CountingModel::default());
+        let mut models: HashMap<String, Arc<dyn crate::server::state::Model>> = HashMap::new();
+        models.insert("potion-32M".to_string(), model.clone());
+        let service = EmbeddingService::new("test-dedupe".to_string(), models);
+
+        let mut inputs = vec!["templated text".to_string(); 100];
+        inputs[50] = "odd one".to_string();
+        let result = service
+            .batch_embed(BatchEmbedParams {
+                inputs: inputs.clone(),
+                model: None,
+                dimensions: None,
+                normalize: None,
+                max_length: None,
+                encoding_format: None,
+                user: None,
+            })
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+
+        assert_eq!(model.0.load(Ordering::SeqCst), 2);
+        let embeddings = body["embeddings"].as_array().unwrap();
+        assert_eq!(embeddings.len(), 100);
+        assert_eq!(embeddings[49], serde_json::json!([14.0, 1.0]));
+        assert_eq!(embeddings[50], serde_json::json!([7.0, 1.0]));
+        let expected_tokens: usize = inputs.iter().map(|s| crate::server::state::estimate_tokens(s)).sum();
+        assert_eq!(body["usage"]["prompt_tokens"], expected_tokens);
+    }
+
+    #[tokio::test]
+    async fn test_embed_tools_reject_inputs_out_of_bounds() {
+        use crate::server::limits::{DEFAULT_MAX_BATCH_INPUTS, DEFAULT_MAX_INPUT_CHARS};
+        use crate::server::mock::MockModel;
+
+        let mut models: HashMap<String, Arc<dyn crate::server::state::Model>> = HashMap::new();
+        models.insert("potion-32M".to_string(), Arc::new(MockModel::new("potion-32M".to_string(), 4)));
+        let mut service = EmbeddingService::new("test-input-limits".to_string(), models);
+        let batch_params = |inputs: Vec<String>| BatchEmbedParams {
+            inputs,
+            model: None,
+            dimensions: None,
+            normalize: None,
+            max_length: None,
+            encoding_format: None,
+            user: None,
+        };
+        let text = |len: usize| "a".repeat(len);
+
+        // The same limits and messages as the HTTP API
+        let cases = [
+            (vec![], "inputs must not be empty".to_string()),
+            (
+                vec![text(4); DEFAULT_MAX_BATCH_INPUTS + 1],
+                "Batch size too large: input 100 is over the maximum of 100 inputs".to_string(),
+            ),
+            (vec![text(4), String::new()], "Input 1 is empty".to_string()),
+            (
+                vec![text(4), text(DEFAULT_MAX_INPUT_CHARS + 1)],
+                format!("Input 1 has {} characters, over the {} character limit", DEFAULT_MAX_INPUT_CHARS + 1, DEFAULT_MAX_INPUT_CHARS),
+            ),
+        ];
+        for (inputs, message) in cases {
+            let err = service.batch_embed(batch_params(inputs)).await.unwrap_err();
+            assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+            assert_eq!(err.message, message);
+        }
+        assert!(service.batch_embed(batch_params(vec![text(4); DEFAULT_MAX_BATCH_INPUTS])).await.is_ok());
+
+        // Lowered limits reject the third input, by index
+        service.input_limits = InputLimits::new(2, 8);
+        let err = service.batch_embed(batch_params(vec![text(4); 3])).await.unwrap_err();
+        assert_eq!(err.message, "Batch size too large: input 2 is over the maximum of 2 inputs");
+        service.input_limits = InputLimits::new(3, 8);
+        let err = service.batch_embed(batch_params(vec![text(4), text(8), text(9)])).await.unwrap_err();
+        assert_eq!(err.message, "Input 2 has 9 characters, over the 8 character limit");
+
+        let err = service
+            .embed(EmbedParams {
+                input: text(9),
+                model: None,
+                dimensions: None,
+                normalize: None,
+                max_length: None,
+                encoding_format: None,
+                user: None,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert_eq!(err.message, "Input 0 has 9 characters, over the 8 character limit");
+    }
+
+    #[tokio::test]
+    async fn test_embed_tools_dimensions() {
+        use crate::server::mock::MockModel;
+
+        let mut models: HashMap<String, Arc<dyn crate::server::state::Model>> = HashMap::new();
+        models.insert("potion-32M".to_string(), Arc::new(MockModel::new("potion-32M".to_string(), 6)));
+        let service = EmbeddingService::new("test-dimensions".to_string(), models);
+        let body = |result: CallToolResult| -> serde_json::Value {
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap()
+        };
+        let embed_params = |dimensions: Option<usize>| EmbedParams {
+            input: "hello".to_string(),
+            model: None,
+            dimensions,
+            normalize: None,
+            max_length: None,
+            encoding_format: None,
+            user: None,
+        };
+
+        let full = body(service.embed(embed_params(None)).await.unwrap());
+        let truncated = body(service.embed(embed_params(Some(2))).await.unwrap());
+        assert_eq!(truncated["dimensions"], 2);
+        assert_eq!(truncated["embedding"].as_array().unwrap().len(), 2);
+        assert_eq!(truncated["usage"], full["usage"]);
+        let norm: f64 = truncated["embedding"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_f64().unwrap().powi(2))
+            .sum();
+        assert!((norm - 1.0).abs() < 1e-5);
+
+        let batch = body(
+            service
+                .batch_embed(BatchEmbedParams {
+                    inputs: vec!["a".to_string(), "b".to_string()],
+                    model: None,
+                    dimensions: Some(3),
+                    normalize: None,
+                    max_length: None,
+                    encoding_format: None,
+                    user: None,
+                })
+                .await
+                .unwrap(),
+        );
+        assert_eq!(batch["dimensions"], 3);
+        assert_eq!(batch["embeddings"][1].as_array().unwrap().len(), 3);
+
+        let err = service.embed(embed_params(Some(7))).await.unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("native dimension is 6"));
+
+        // The same options as the HTTP API: a prefix without normalization, inputs cut to max_length
+        let batch = body(
+            service
+                .batch_embed(BatchEmbedParams {
+                    inputs: vec!["hello".to_string(), "hello world".to_string()],
+                    model: None,
+                    dimensions: Some(2),
+                    normalize: Some(false),
+                    max_length: Some(5),
+                    encoding_format: None,
+                    user: None,
+                })
+                .await
+                .unwrap(),
+        );
+        assert_eq!(batch["embeddings"][0], serde_json::json!(full["embedding"].as_array().unwrap()[..2]));
+        assert_eq!(batch["embeddings"][1], batch["embeddings"][0]);
+    }
+
+    #[tokio::test]
+    async fn test_similarity_ranks_candidates() {
+        use crate::server::mock::MockModel;
+
+        let mock = MockModel::new("potion-32M".to_string(), 8);
+        let mut models: HashMap<String, Arc<dyn crate::server::state::Model>> = HashMap::new();
+        models.insert("potion-32M".to_string(), Arc::new(mock.clone()));
+        let service = EmbeddingService::new("test-similarity".to_string(), models);
+        // More than one chunk of 32, with an exact match of the query in the middle
+        let mut candidates: Vec<String> = (0..40).map(|i| format!("candidate {}", i)).collect();
+        candidates[35] = "query".to_string();
+        let params = |candidates: Vec<String>, top_k: Option<usize>| SimilarityParams {
+            query: "query".to_string(),
+            candidates,
+            model: None,
+            top_k,
+        };
+
+        let result = service.similarity(params(candidates.clone(), Some(3))).await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        let results = body["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(body["candidate_count"], 40);
+        assert_eq!(results[0]["index"], 35);
+        assert_eq!(results[0]["text"], "query");
+        assert!((results[0]["score"].as_f64().unwrap() - 1.0).abs() < 1e-5);
+        let scores: Vec<f64> = results.iter().map(|r| r["score"].as_f64().unwrap()).collect();
+        assert!(scores.windows(2).all(|w| w[0] >= w[1]));
+        let index = results[1]["index"].as_u64().unwrap() as usize;
+        let expected = crate::utils::cosine_similarity(&mock.embed("query"), &mock.embed(&candidates[index]));
+        assert!((scores[1] - expected as f64).abs() < 1e-6);
+
+        let err = service.similarity(params(vec![], None)).await.unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("candidates"));
+    }
+
+    #[tokio::test]
+    async fn test_similarity_applies_input_limits() {
+        use crate::server::mock::MockModel;
+
+        let mut models: HashMap<String, Arc<dyn crate::server::state::Model>> = HashMap::new();
+        models.insert("potion-32M".to_string(), Arc::new(MockModel::new("potion-32M".to_string(), 4)));
+        let mut service = EmbeddingService::new("test-similarity-limits".to_string(), models);
+        service.input_limits = InputLimits::new(2, 8);
+        let similarity = |query: &str, candidates: &[&str]| {
+            service.similarity(SimilarityParams {
+                query: query.to_string(),
+                candidates: candidates.iter().map(|text| text.to_string()).collect(),
+                model: None,
+                top_k: None,
+            })
+        };
+
+        // The same limits and messages as the HTTP rank endpoint
+        let err = similarity("query", &["a", "b", "c"]).await.unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert_eq!(err.message, "Batch size too large: input 2 is over the maximum of 2 inputs");
+        let err = similarity("query", &["short", "too long!"]).await.unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert_eq!(err.message, "Input 1 has 9 characters, over the 8 character limit");
+        let err = similarity("long query", &["short"]).await.unwrap_err();
+        assert_eq!(err.message, "Input 0 has 10 characters, over the 8 character limit");
+        let err = similarity("query", &[""]).await.unwrap_err();
+        assert_eq!(err.message, "Input 0 is empty");
+        assert!(similarity("query", &["short", "exactly8"]).await.is_ok());
+    }
+
+    #[test]
+    fn test_tool_names_round_trip() {
+        for tool in ToolName::ALL {
+            assert_eq!(ToolName::from_name(tool.as_str()), Some(tool));
+            assert_eq!(tool.definition().name, tool.as_str());
+        }
+        assert_eq!(ToolName::from_name("Embed"), None);
+    }
+
+    #[tokio::test]
+    async fn test_load_model_tool() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        crate::server::test_utils::write_test_model(tmp.path(), 5);
+        let service = EmbeddingService::new("test-load-tool".to_string(), HashMap::new());
+        let path = tmp.path().to_str().unwrap().to_string();
+
+        let result = service
+            .load_model_tool(LoadModelParams { name: "fresh".to_string(), path: path.clone() })
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(body["dimensions"], 5);
+        assert_eq!(service.models.dimensions("fresh"), Some(5));
+
+        let err = service
+            .load_model_tool(LoadModelParams { name: "gone".to_string(), path: format!("{}/missing", path) })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_paginate_tools() {
+        let tool = |name: &'static str| Tool {
+            name: name.into(),
+            description: None,
+            input_schema: Arc::new(serde_json::Map::new()),
+            output_schema: None,
+            annotations: None,
+            icons: None,
+            title: None,
+            meta: None,
+        };
+        let tools = || vec![tool("a"), tool("b"), tool("c")];
+        let names = |page: &ListToolsResult| page.tools.iter().map(|t| t.name.to_string()).collect::<Vec<_>>();
+
+        let first = paginate_tools(tools(), None, 2).unwrap();
+        assert_eq!(names(&first), ["a", "b"]);
+        assert_eq!(first.next_cursor.as_deref(), Some("2"));
+        let last = paginate_tools(tools(), first.next_cursor.as_deref(), 2).unwrap();
+        assert_eq!(names(&last), ["c"]);
+        assert_eq!(last.next_cursor, None);
+
+        for cursor in ["4", "-1", "next"] {
+            let err = paginate_tools(tools(), Some(cursor), 2).unwrap_err();
+            assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_tools_over_mcp() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let (client, server) = tokio::io::duplex(1 << 16);
+        let (server_read, server_write) = tokio::io::split(server);
+        let service = EmbeddingService::new("test-list-tools".to_string(), HashMap::new());
+        tokio::spawn(async move {
+            if let Ok(running) = rmcp::serve_server(service, (server_read, server_write)).await {
+                let _ = running.waiting().await;
+            }
+        });
+        let (client_read, mut client_write) = tokio::io::split(client);
+        let mut lines = BufReader::new(client_read).lines();
+        let mut send = async |message: serde_json::Value| {
+            client_write.write_all(format!("{}\n", message).as_bytes()).await.unwrap();
+            if message.get("id").is_none() {
+                return serde_json::Value::Null;
+            }
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap()
+        };
+
+        let init = send(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": { "name": "test", "version": "0" }
+            }
+        }))
+        .await;
+        assert!(init["result"]["capabilities"]["tools"].is_object());
+        send(serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await;
+
+        let all = send(serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" })).await;
+        let tools = all["result"]["tools"].as_array().unwrap();
+        assert!(all["result"].get("nextCursor").is_none());
+        let tool = |name: &str| tools.iter().find(|t| t["name"] == name).unwrap().clone();
+        assert_eq!(tool("embed")["title"], "Embed Text");
+        assert_eq!(tool("embed")["annotations"]["readOnlyHint"], true);
+        assert_eq!(tool("model_info")["annotations"]["readOnlyHint"], true);
+        assert_eq!(tool("distill_model")["annotations"]["readOnlyHint"], false);
+        assert_eq!(tool("distill_model")["annotations"]["destructiveHint"], true);
+        assert_eq!(tool("load_model")["annotations"]["readOnlyHint"], false);
+
+        // Every advertised tool is dispatched: empty arguments get past the
+        // tool lookup and fail, if at all, on the tool's own parameters
+        let names: Vec<&str> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ToolName::ALL.map(ToolName::as_str));
+        for (id, name) in (100..).zip(names.iter().chain(&["no_such_tool"])) {
+            let called = send(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "tools/call",
+                "params": { "name": name, "arguments": {} }
+            }))
+            .await;
+            let unknown = called["error"]["message"].as_str().is_some_and(|m| m.contains("Unknown tool"));
+            assert_eq!(unknown, *name == "no_such_tool", "{}: {}", name, called);
+        }
+
+        let rest = send(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "tools/list",
+            "params": { "cursor": "2" }
+        }))
+        .await;
+        let rest = rest["result"]["tools"].as_array().unwrap();
+        assert_eq!(rest.len(), tools.len() - 2);
+        assert_eq!(rest[0]["name"], tools[2]["name"]);
+
+        let invalid = send(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "tools/list",
+            "params": { "cursor": "bogus" }
+        }))
+        .await;
+        assert_eq!(invalid["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    async fn test_session_info_reports_session_activity() {
+        let sessions = SessionRegistry::default();
+        let service = EmbeddingService::new("test-session".to_string(), HashMap::new()).registered_in(&sessions);
+        assert_eq!(sessions.len(), 1);
+
+        // Clones handed out per request share the session's counters
+        service.clone().session.record_request();
+        service.session.record_request();
+        let result = service.session_info(SessionInfoParams {}).await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(body["connection_id"], "test-session");
+        assert_eq!(body["request_count"], 2);
+        assert_eq!(body["idle_secs"], 0);
+        assert!(body["session_duration_secs"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_load_model_serves_immediately() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        crate::server::test_utils::write_test_model(tmp.path(), 5);
+        let service = EmbeddingService::new("test-load".to_string(), HashMap::new());
+
+        service.load_model("fresh", tmp.path().to_str().unwrap()).await.unwrap();
+        assert_eq!(service.models.dimensions("fresh"), Some(5));
+        let result = service
+            .embed(EmbedParams {
+                input: "hello".to_string(),
+                model: Some("fresh".to_string()),
+                dimensions: None,
+                normalize: None,
+                max_length: None,
+                encoding_format: None,
+                user: None,
+            })
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(body["dimensions"], 5);
     }
 }
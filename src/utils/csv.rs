@@ -0,0 +1,170 @@
+//! Minimal streaming reader for CSV files, and quoting for fields written
+//! back out.
+//!
+//! Reads RFC 4180 records one at a time: fields are separated by commas,
+//! quoted fields may contain commas, doubled quotes and line breaks, and both
+//! LF and CRLF line endings are accepted. Blank lines are skipped. A record's
+//! row is the line it starts on, counting from 1, so errors point at the line
+//! to fix even when earlier records span several lines.
+
+use anyhow::{Result, anyhow};
+use std::borrow::Cow;
+use std::io::BufRead;
+
+/// Quote `field` for a CSV record when it holds a comma, quote or line break,
+/// doubling any quotes inside; other fields are returned as they are.
+pub fn quote_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Reads CSV records from a buffered reader.
+pub struct CsvReader<R> {
+    reader: R,
+    /// Lines read so far
+    lines: usize,
+    /// Row the last record started on
+    row: usize,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, lines: 0, row: 0 }
+    }
+
+    /// Row the last record returned started on.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Read the next record, or `None` at the end of the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the row if the input cannot be read, a quoted
+    /// field is not closed, or a quote appears where a field cannot have one.
+    pub fn next_record(&mut self) -> Result<Option<Vec<String>>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.lines += 1;
+            if !line.trim_end_matches(['\r', '\n']).is_empty() {
+                break;
+            }
+        }
+
+        self.row = self.lines;
+        let row = self.row;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut quoted = false;
+        loop {
+            let mut chars = line.chars().peekable();
+            while let Some(c) = chars.next() {
+                if in_quotes {
+                    match c {
+                        '"' if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        '"' => in_quotes = false,
+                        _ => field.push(c),
+                    }
+                    continue;
+                }
+                match c {
+                    ',' => {
+                        fields.push(std::mem::take(&mut field));
+                        quoted = false;
+                    }
+                    // Line endings outside quotes end the record
+                    '\n' => {}
+                    '\r' if matches!(chars.peek(), Some('\n') | None) => {}
+                    '"' if field.is_empty() && !quoted => {
+                        in_quotes = true;
+                        quoted = true;
+                    }
+                    '"' => return Err(anyhow!("row {}: unexpected quote in field {}", row, fields.len() + 1)),
+                    _ if quoted => {
+                        return Err(anyhow!(
+                            "row {}: unexpected text after the closing quote of field {}",
+                            row,
+                            fields.len() + 1
+                        ));
+                    }
+                    _ => field.push(c),
+                }
+            }
+            if !in_quotes {
+                break;
+            }
+            // A quoted field continues on the next line
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(anyhow!("row {}: quoted field {} is never closed", row, fields.len() + 1));
+            }
+            self.lines += 1;
+        }
+        fields.push(field);
+        Ok(Some(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(input: &str) -> Vec<Vec<String>> {
+        let mut reader = CsvReader::new(input.as_bytes());
+        std::iter::from_fn(|| reader.next_record().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_reads_plain_and_quoted_fields() {
+        let input = "id,text\r\n1,plain\n2,\"with, comma\"\n\n3,\"say \"\"hi\"\"\"\n4,\"two\nlines\"\n5,\n";
+        assert_eq!(
+            records(input),
+            vec![
+                vec!["id", "text"],
+                vec!["1", "plain"],
+                vec!["2", "with, comma"],
+                vec!["3", "say \"hi\""],
+                vec!["4", "two\nlines"],
+                vec!["5", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quote_field_round_trips() {
+        assert_eq!(quote_field("plain"), "plain");
+        let fields = ["with, comma", "say \"hi\"", "two\nlines"];
+        let line: Vec<_> = fields.iter().map(|f| quote_field(f)).collect();
+        assert_eq!(records(&line.join(",")), vec![fields.to_vec()]);
+    }
+
+    #[test]
+    fn test_reports_row_of_malformed_record() {
+        let mut reader = CsvReader::new("text\nok\n\"multi\nline\"\nbad \"quote\"\n".as_bytes());
+        for _ in 0..3 {
+            reader.next_record().unwrap();
+        }
+        let error = reader.next_record().unwrap_err().to_string();
+        assert!(error.starts_with("row 5:"), "{}", error);
+
+        let mut reader = CsvReader::new("text\n\"open\nstill open".as_bytes());
+        reader.next_record().unwrap();
+        let error = reader.next_record().unwrap_err().to_string();
+        assert!(error.starts_with("row 2:") && error.contains("never closed"), "{}", error);
+
+        let error = CsvReader::new("\"closed\"x\n".as_bytes()).next_record().unwrap_err().to_string();
+        assert!(error.contains("after the closing quote"), "{}", error);
+    }
+}
@@ -0,0 +1,306 @@
+//! Log output shared by the CLI and the server.
+//!
+//! [`init_logging`] installs the process-wide subscriber from the `[logging]`
+//! config: console output on stderr, optionally teed to a [`LogFile`] rotated
+//! by size, as text or as JSON lines. A JSON line looks like:
+//!
+//! ```json
+//! {"timestamp":"2025-01-01T12:00:00.000000Z","level":"INFO","target":"static_embedding_tool::server::start","message":"Server stopped","fields":{"port":8084}}
+//! ```
+
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber, warn};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, Layer, layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Rotated log files kept when `logging.max_files` is not set.
+pub const DEFAULT_MAX_LOG_FILES: u32 = 5;
+
+/// A log file written alongside console output, from the `[logging]` config.
+#[derive(Clone, Debug)]
+pub struct LogFile {
+    /// File logs are appended to
+    pub path: PathBuf,
+    /// Rotate before the file grows past this many bytes; never rotate when `None`
+    pub max_file_size: Option<u64>,
+    /// Rotated files kept as `<file>.1` (newest) to `<file>.N`; defaults to
+    /// [`DEFAULT_MAX_LOG_FILES`]
+    pub max_files: Option<u32>,
+}
+
+/// Appends to a [`LogFile`], rotating it by size.
+///
+/// On rotation `<file>` becomes `<file>.1`, `<file>.1` becomes `<file>.2` and so
+/// on; the oldest file beyond `max_files` is deleted.
+pub struct RotatingFile {
+    config: LogFile,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Open `config.path` for appending, creating it and its directory if needed.
+    pub fn open(config: LogFile) -> io::Result<Self> {
+        if let Some(dir) = config.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&config.path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { config, file, size })
+    }
+
+    fn rotated_path(path: &Path, n: u32) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let path = &self.config.path;
+        let keep = self.config.max_files.unwrap_or(DEFAULT_MAX_LOG_FILES).max(1);
+        for n in (1..keep).rev() {
+            match fs::rename(Self::rotated_path(path, n), Self::rotated_path(path, n + 1)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        fs::rename(path, Self::rotated_path(path, 1))?;
+        self.file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(max_size) = self.config.max_file_size
+            && self.size > 0
+            && self.size + buf.len() as u64 > max_size
+        {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Formats each event as one JSON object per line.
+///
+/// The message and any other fields of the event are kept apart, and the
+/// names of the spans the event happened in are listed from the outermost.
+pub struct JsonLines;
+
+impl<S, N> FormatEvent<S, N> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let metadata = event.metadata();
+
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".into(),
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true).into(),
+        );
+        line.insert("level".into(), metadata.level().as_str().into());
+        line.insert("target".into(), metadata.target().into());
+        if let Some(message) = fields.0.remove("message") {
+            line.insert("message".into(), message);
+        }
+        if !fields.0.is_empty() {
+            line.insert("fields".into(), Value::Object(fields.0));
+        }
+        if let Some(scope) = ctx.event_scope() {
+            let spans: Vec<Value> = scope.from_root().map(|span| span.name().into()).collect();
+            line.insert("spans".into(), spans.into());
+        }
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// Collects the fields of an event as JSON values.
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().into(), format!("{:?}", value).into());
+    }
+}
+
+/// Log layer writing to `writer`, as JSON lines when `json` is set.
+pub fn log_layer<S, W>(writer: W, json: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_target(true).with_writer(writer);
+    if json {
+        layer.with_ansi(false).event_format(JsonLines).boxed()
+    } else {
+        layer.boxed()
+    }
+}
+
+/// Log layer appending to the rotating log `file`, without terminal colors.
+pub fn file_log_layer<S>(file: &LogFile, json: bool) -> io::Result<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let writer = Mutex::new(RotatingFile::open(file.clone())?);
+    Ok(if json {
+        log_layer(writer, true)
+    } else {
+        tracing_subscriber::fmt::layer()
+            .with_target(true)
+            .with_ansi(false)
+            .with_writer(writer)
+            .boxed()
+    })
+}
+
+/// How [`init_logging`] sets up log output.
+#[derive(Clone, Debug)]
+pub struct LogSettings {
+    /// Filter directive such as `info` or `static_embedding_tool=debug`;
+    /// `RUST_LOG` takes precedence when set
+    pub level: String,
+    /// Write JSON lines instead of text
+    pub json: bool,
+    /// Also write logs to this file
+    pub file: Option<LogFile>,
+    /// Write logs to stderr
+    pub console: bool,
+}
+
+/// Install the process-wide subscriber: logs on stderr unless `console` is
+/// off, teed to the log file when one is set.
+///
+/// A log file that cannot be opened is reported and skipped. Does nothing if
+/// a subscriber is already installed; returns whether this call installed one.
+pub fn init_logging(settings: &LogSettings) -> bool {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(&settings.level))
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+    let (file_layer, file_error) = match settings.file.as_ref().map(|file| file_log_layer(file, settings.json)) {
+        Some(Ok(layer)) => (Some(layer), None),
+        Some(Err(e)) => (None, Some(e)),
+        None => (None, None),
+    };
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(settings.console.then(|| log_layer(std::io::stderr, settings.json)))
+        .with(file_layer)
+        .try_init()
+        .is_ok();
+    if let (Some(file), Some(e)) = (&settings.file, file_error) {
+        warn!(path = %file.path.display(), error = %e, "Failed to open log file; logging to the console only");
+    }
+    installed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::{info, info_span};
+
+    fn log_file(dir: &Path, max_file_size: Option<u64>, max_files: Option<u32>) -> LogFile {
+        LogFile {
+            path: dir.join("logs").join("server.log"),
+            max_file_size,
+            max_files,
+        }
+    }
+
+    #[test]
+    fn test_rotating_file_rotates_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = log_file(dir.path(), Some(10), Some(2));
+        let path = config.path.clone();
+        let mut writer = RotatingFile::open(config).unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        // Each line pushes the file past 10 bytes, so every line rotates; only
+        // the two newest rotated files are kept
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(fs::read_to_string(RotatingFile::rotated_path(&path, 1)).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(RotatingFile::rotated_path(&path, 2)).unwrap(), "second\n");
+        assert!(!RotatingFile::rotated_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn test_rotating_file_appends_without_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = log_file(dir.path(), None, None);
+        let path = config.path.clone();
+
+        RotatingFile::open(config.clone()).unwrap().write_all(b"one\n").unwrap();
+        // Reopening appends to, rather than truncates, an existing file
+        RotatingFile::open(config).unwrap().write_all(b"two\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+        assert!(!RotatingFile::rotated_path(&path, 1).exists());
+    }
+
+    #[test]
+    fn test_file_log_layer_writes_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = log_file(dir.path(), None, None);
+        let subscriber = tracing_subscriber::registry().with(file_log_layer(&config, true).unwrap());
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = info_span!("request").entered();
+            info!(port = 8084u64, ready = true, "Server started");
+            info!(path = ?config.path, "Second line");
+        });
+
+        let content = fs::read_to_string(&config.path).unwrap();
+        let lines: Vec<Value> = content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["message"], "Server started");
+        assert_eq!(lines[0]["fields"]["port"], 8084);
+        assert_eq!(lines[0]["fields"]["ready"], true);
+        assert_eq!(lines[0]["spans"], serde_json::json!(["request"]));
+        assert!(lines[0]["timestamp"].as_str().unwrap().ends_with('Z'));
+        assert!(lines[1]["fields"]["path"].as_str().unwrap().contains("server.log"));
+    }
+}
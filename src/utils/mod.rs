@@ -3,6 +3,12 @@ use rand;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::fs;
+use std::str::FromStr;
+
+pub mod csv;
+#[cfg(any(feature = "cli", feature = "mcp"))]
+pub mod logging;
+pub mod npy;
 
 /// Generate a unique connection ID
 pub fn generate_connection_id() -> String {
@@ -37,10 +43,94 @@ pub fn format_duration(duration: std::time::Duration) -> String {
     }
 }
 
+/// Numeric precision of emitted embeddings.
+///
+/// `f16` halves the size of npy files and brings JSON values down to what a
+/// half-precision float can represent: about 3 significant decimal digits,
+/// with a relative error below 0.1% for the unit-scale values embeddings use.
+/// That is usually invisible to cosine-similarity ranking, but tiny components
+/// (below ~6e-8) flush to zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputDtype {
+    #[default]
+    F32,
+    F16,
+}
+
+impl OutputDtype {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputDtype::F32 => "f32",
+            OutputDtype::F16 => "f16",
+        }
+    }
+
+    /// Round a value to this precision, returning it widened back to `f32`.
+    pub fn round(&self, value: f32) -> f32 {
+        match self {
+            OutputDtype::F32 => value,
+            OutputDtype::F16 => half::f16::from_f32(value).to_f32(),
+        }
+    }
+
+    /// Round every component of an embedding in place.
+    pub fn round_embedding(&self, embedding: &mut [f32]) {
+        if *self != OutputDtype::F32 {
+            for value in embedding {
+                *value = self.round(*value);
+            }
+        }
+    }
+}
+
+impl FromStr for OutputDtype {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "f32" | "float32" => Ok(OutputDtype::F32),
+            "f16" | "float16" => Ok(OutputDtype::F16),
+            other => Err(anyhow!("Invalid output dtype '{}'. Use: f32, f16", other)),
+        }
+    }
+}
+
+/// Wire encoding of embeddings in API responses (OpenAI's `encoding_format`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EncodingFormat {
+    /// A JSON array of numbers
+    #[default]
+    Float,
+    /// Base64 of the little-endian `f32` bytes, as OpenAI returns it
+    Base64,
+}
+
+impl EncodingFormat {
+    /// Base64-encode an embedding's little-endian `f32` bytes.
+    pub fn encode_base64(embedding: &[f32]) -> String {
+        use base64::Engine;
+        let bytes: Vec<u8> = embedding.iter().flat_map(|v| v.to_le_bytes()).collect();
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+}
+
+impl FromStr for EncodingFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "float" => Ok(EncodingFormat::Float),
+            "base64" => Ok(EncodingFormat::Base64),
+            other => Err(anyhow!("Invalid encoding_format '{}'. Use: float, base64", other)),
+        }
+    }
+}
+
 /// Distill a model using Model2Vec and PCA
 ///
 /// This function distills a model by reducing its dimensions using PCA.
-/// The distilled model is saved to the specified output directory.
+/// The distilled model is saved to the specified output directory. Output of
+/// the `model2vec` subprocess is forwarded to stderr as it is produced.
 ///
 /// # Arguments
 /// * `model_name` - The name of the model to distill
@@ -96,21 +186,17 @@ pub async fn distill(
 
     println!("Distilling model '{}' with {} PCA dimensions...", model_name, pca_dims);
 
-    let output_result = Command::new("model2vec")
-        .args(["distill", model_name, &pca_dims.to_string()])
-        .output();
+    let mut command = Command::new("model2vec");
+    command.args(["distill", model_name, &pca_dims.to_string()]);
+    let output_result = tokio::task::spawn_blocking(move || run_streaming(&mut command))
+        .await
+        .map_err(|e| anyhow!("model2vec task failed: {}", e))?;
 
     match output_result {
-        Ok(output) if output.status.success() => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if !stdout.trim().is_empty() {
-                println!("model2vec output: {}", stdout.trim());
-            }
-        }
-        Ok(output) => {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("model2vec distillation failed (exit {}): {}", 
-                output.status.code().unwrap_or(-1), stderr.trim()));
+        Ok((status, _)) if status.success() => {}
+        Ok((status, stderr_tail)) => {
+            return Err(anyhow!("model2vec distillation failed (exit {}): {}",
+                status.code().unwrap_or(-1), stderr_tail.join("\n").trim()));
         }
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
             eprintln!("⚠️  model2vec binary not found – skipping actual distillation in test mode.");
@@ -124,6 +210,128 @@ pub async fn distill(
     Ok(final_output.to_string_lossy().to_string())
 }
 
+/// Lines of subprocess stderr kept for the error message when it fails.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Run `command`, forwarding its stdout and stderr to our stderr line by line
+/// as they arrive.
+///
+/// Returns the exit status and the last [`STDERR_TAIL_LINES`] lines of stderr.
+fn run_streaming(command: &mut Command) -> std::io::Result<(std::process::ExitStatus, Vec<String>)> {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    let mut child = command.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+
+    // Drain both pipes concurrently so neither can fill up and stall the child
+    let stdout_thread = std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            eprintln!("  model2vec: {}", line);
+        }
+    });
+    let mut tail = std::collections::VecDeque::with_capacity(STDERR_TAIL_LINES);
+    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+        eprintln!("  model2vec: {}", line);
+        if tail.len() == STDERR_TAIL_LINES {
+            tail.pop_front();
+        }
+        tail.push_back(line);
+    }
+    let _ = stdout_thread.join();
+
+    Ok((child.wait()?, tail.into()))
+}
+
+/// Check that the model at `path` loads and produces usable embeddings.
+///
+/// Runs a probe encode and returns the embedding dimensions.
+///
+/// # Errors
+///
+/// Returns an error if the model cannot be loaded, or the probe embedding is
+/// empty or contains non-finite values.
+pub fn verify_model(path: &Path) -> Result<usize> {
+    use model2vec_rs::model::StaticModel;
+
+    let model = StaticModel::from_pretrained(path, None, None, None)
+        .map_err(|e| anyhow!("Model at {} could not be loaded: {}", path.display(), e))?;
+    let embedding = model.encode_single("verification probe");
+    if embedding.is_empty() {
+        return Err(anyhow!("Model at {} produced an empty embedding", path.display()));
+    }
+    if embedding.iter().any(|v| !v.is_finite()) {
+        return Err(anyhow!("Model at {} produced non-finite values", path.display()));
+    }
+    Ok(embedding.len())
+}
+
+/// Cosine similarity of two vectors; 0.0 when either has zero norm or the
+/// lengths differ.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Texts of a batch with repeats removed, so each distinct text is encoded once.
+pub struct UniqueTexts {
+    /// Distinct texts in order of first appearance
+    pub texts: Vec<String>,
+    /// For each original text, the index of its copy in `texts`
+    pub positions: Vec<usize>,
+}
+
+impl UniqueTexts {
+    pub fn new(texts: &[String]) -> Self {
+        let mut seen: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        let mut unique = Vec::new();
+        let positions = texts
+            .iter()
+            .map(|text| {
+                *seen.entry(text.as_str()).or_insert_with(|| {
+                    unique.push(text.clone());
+                    unique.len() - 1
+                })
+            })
+            .collect();
+        Self { texts: unique, positions }
+    }
+
+    /// Whether every text is distinct, so embeddings need no fanning out.
+    pub fn is_all_unique(&self) -> bool {
+        self.texts.len() == self.positions.len()
+    }
+
+    /// Embeddings of the distinct texts copied back to the original positions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is not one embedding per distinct text.
+    pub fn fan_out(&self, embeddings: Vec<Vec<f32>>) -> Result<Vec<Vec<f32>>> {
+        if embeddings.len() != self.texts.len() {
+            return Err(anyhow!(
+                "Model returned {} embeddings for {} inputs",
+                embeddings.len(),
+                self.texts.len()
+            ));
+        }
+        if self.is_all_unique() {
+            return Ok(embeddings);
+        }
+        Ok(self.positions.iter().map(|&i| embeddings[i].clone()).collect())
+    }
+}
+
 pub fn calculate_total(numbers: &[i32]) -> i32 {
     numbers.iter().sum()
 }
@@ -132,6 +340,56 @@ pub fn calculate_total(numbers: &[i32]) -> i32 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_verify_model_rejects_broken_output() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        // What a subprocess that "succeeded" without writing a real model leaves behind
+        fs::write(tmp.path().join("config.json"), "{}").unwrap();
+        fs::write(tmp.path().join("model.safetensors"), "dummy content").unwrap();
+        let err = verify_model(tmp.path()).unwrap_err();
+        assert!(err.to_string().contains("could not be loaded"));
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]).abs() < 1e-6);
+        assert!((cosine_similarity(&[1.0, 1.0], &[-1.0, -1.0]) + 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_unique_texts_fan_out() {
+        let texts: Vec<String> = ["a", "b", "a", "c", "b"].iter().map(|s| s.to_string()).collect();
+        let unique = UniqueTexts::new(&texts);
+        assert_eq!(unique.texts, vec!["a", "b", "c"]);
+        assert_eq!(unique.positions, vec![0, 1, 0, 2, 1]);
+        assert!(!unique.is_all_unique());
+
+        let fanned = unique.fan_out(vec![vec![1.0], vec![2.0], vec![3.0]]).unwrap();
+        assert_eq!(fanned, vec![vec![1.0], vec![2.0], vec![1.0], vec![3.0], vec![2.0]]);
+        assert!(unique.fan_out(vec![vec![1.0]]).is_err());
+
+        assert!(UniqueTexts::new(&texts[..2]).is_all_unique());
+    }
+
+    #[test]
+    fn test_output_dtype_parse_and_round() {
+        assert_eq!("f16".parse::<OutputDtype>().unwrap(), OutputDtype::F16);
+        assert_eq!("Float32".parse::<OutputDtype>().unwrap(), OutputDtype::F32);
+        assert!("f64".parse::<OutputDtype>().is_err());
+
+        assert_eq!(OutputDtype::F32.round(0.1234567), 0.1234567);
+        let rounded = OutputDtype::F16.round(0.1234567);
+        assert_ne!(rounded, 0.1234567);
+        assert!((rounded - 0.1234567).abs() < 1e-4);
+
+        let mut embedding = vec![0.1234567, -0.7654321];
+        OutputDtype::F16.round_embedding(&mut embedding);
+        assert_eq!(embedding[1], OutputDtype::F16.round(-0.7654321));
+    }
+
     #[test]
     fn test_empty_list() {
         assert_eq!(calculate_total(&[]), 0);
@@ -234,7 +492,7 @@ mod tests {
             .join(".static-embedding-tool/models")
             .join("test-model");
 
-        let default_path = None;
+        let default_path: Option<PathBuf> = std::hint::black_box(None);
         let computed_default = default_path.unwrap_or_else(|| {
             let home = env::var("HOME")
                 .or_else(|_| env::var("USERPROFILE"))
@@ -247,6 +505,21 @@ mod tests {
         assert_eq!(computed_default, expected_default);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_run_streaming_captures_status_and_stderr_tail() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo progress; for i in $(seq 1 25); do echo err$i >&2; done; exit 3"]);
+        let (status, tail) = run_streaming(&mut command).unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(tail.len(), STDERR_TAIL_LINES);
+        assert_eq!(tail.first().map(String::as_str), Some("err6"));
+        assert_eq!(tail.last().map(String::as_str), Some("err25"));
+
+        let error = run_streaming(&mut Command::new("definitely-not-a-real-binary")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    }
+
     #[tokio::test]
     async fn test_distill_with_custom_path() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,175 @@
+//! Minimal writer for NumPy `.npy` files.
+//!
+//! Embeddings are written as a C-ordered 2-D array of shape `(rows, dims)` using
+//! format version 1.0, which `numpy.load` reads directly:
+//!
+//! ```python
+//! import numpy as np
+//! embeddings = np.load("embeddings.npy")  # shape (rows, dims)
+//! ```
+
+use anyhow::{Result, anyhow};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::OutputDtype;
+
+/// Magic string opening every `.npy` file.
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Header alignment required by the format (magic, version, length and dict).
+const HEADER_ALIGNMENT: usize = 64;
+
+/// Write `rows` to `path` as a `(rows, dims)` array of `dtype`.
+///
+/// # Errors
+///
+/// Returns an error if the rows have different lengths or the file cannot be written.
+pub fn write_npy(path: &Path, rows: &[Vec<f32>], dtype: OutputDtype) -> Result<()> {
+    let dims = rows.first().map(|r| r.len()).unwrap_or(0);
+    write_npy_with_dims(path, rows, dims, dtype)
+}
+
+/// Write `rows` to `path` as a `(rows, dims)` array of `dtype`, so an empty
+/// batch still records the model's dimensions as `(0, dims)`.
+///
+/// # Errors
+///
+/// Returns an error if a row is not `dims` long or the file cannot be written.
+pub fn write_npy_with_dims(path: &Path, rows: &[Vec<f32>], dims: usize, dtype: OutputDtype) -> Result<()> {
+    if let Some((index, row)) = rows.iter().enumerate().find(|(_, r)| r.len() != dims) {
+        return Err(anyhow!(
+            "Cannot write ragged embeddings to npy: row {} has {} dimensions, expected {}",
+            index,
+            row.len(),
+            dims
+        ));
+    }
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&header(dtype, rows.len(), dims))?;
+    for value in rows.iter().flatten() {
+        match dtype {
+            OutputDtype::F32 => writer.write_all(&value.to_le_bytes())?,
+            OutputDtype::F16 => writer.write_all(&half::f16::from_f32(*value).to_le_bytes())?,
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Build the version 1.0 header: magic, version, header length and the
+/// space-padded dict describing dtype and shape.
+fn header(dtype: OutputDtype, rows: usize, dims: usize) -> Vec<u8> {
+    let descr = match dtype {
+        OutputDtype::F32 => "<f4",
+        OutputDtype::F16 => "<f2",
+    };
+    let mut dict = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+        descr, rows, dims
+    );
+    // magic (6) + version (2) + header length (2) + dict + trailing newline
+    let unpadded = MAGIC.len() + 4 + dict.len() + 1;
+    let padding = (HEADER_ALIGNMENT - unpadded % HEADER_ALIGNMENT) % HEADER_ALIGNMENT;
+    dict.push_str(&" ".repeat(padding));
+    dict.push('\n');
+
+    let mut header = Vec::with_capacity(unpadded + padding);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&[1, 0]);
+    header.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    header.extend_from_slice(dict.as_bytes());
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Parse a file produced by `write_npy`, returning the header dict and data bytes.
+    fn read_npy(path: &Path) -> (String, Vec<u8>) {
+        let bytes = std::fs::read(path).unwrap();
+        assert_eq!(&bytes[..6], MAGIC);
+        assert_eq!(&bytes[6..8], &[1, 0]);
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % HEADER_ALIGNMENT, 0);
+        let dict = String::from_utf8(bytes[10..10 + header_len].to_vec()).unwrap();
+        (dict, bytes[10 + header_len..].to_vec())
+    }
+
+    #[test]
+    fn test_write_npy_f32_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("embeddings.npy");
+        let rows = vec![vec![0.1, -0.5], vec![0.25, 3.0], vec![-1.0, 0.0]];
+
+        write_npy(&path, &rows, OutputDtype::F32).unwrap();
+
+        let (dict, data) = read_npy(&path);
+        assert_eq!(
+            dict.trim_end(),
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (3, 2), }"
+        );
+        let decoded: Vec<f32> = data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(decoded, rows.concat());
+
+        // An empty batch is still a valid (0, 0) array
+        write_npy(&path, &[], OutputDtype::F32).unwrap();
+        let (dict, data) = read_npy(&path);
+        assert!(dict.contains("'shape': (0, 0)"));
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn test_write_npy_f16_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("embeddings.npy");
+        let rows = vec![vec![0.1, -0.5, 1.0], vec![0.333, 2.0, -0.0001]];
+
+        write_npy(&path, &rows, OutputDtype::F16).unwrap();
+
+        let (dict, data) = read_npy(&path);
+        assert!(dict.contains("'descr': '<f2'"));
+        assert!(dict.contains("'shape': (2, 3)"));
+        assert!(dict.ends_with('\n'));
+        assert_eq!(data.len(), 2 * 3 * 2);
+
+        let decoded: Vec<f32> = data
+            .chunks_exact(2)
+            .map(|b| half::f16::from_le_bytes([b[0], b[1]]).to_f32())
+            .collect();
+        for (decoded, original) in decoded.iter().zip(rows.iter().flatten()) {
+            assert_eq!(*decoded, OutputDtype::F16.round(*original));
+            assert!((decoded - original).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_write_npy_with_dims_empty_batch() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("empty.npy");
+
+        write_npy_with_dims(&path, &[], 256, OutputDtype::F32).unwrap();
+        let (dict, data) = read_npy(&path);
+        assert!(dict.contains("'shape': (0, 256)"));
+        assert!(data.is_empty());
+
+        let err = write_npy_with_dims(&path, &[vec![1.0]], 2, OutputDtype::F32).unwrap_err();
+        assert!(err.to_string().contains("row 0 has 1 dimensions, expected 2"));
+    }
+
+    #[test]
+    fn test_write_npy_rejects_ragged_rows() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("ragged.npy");
+        let err = write_npy(&path, &[vec![1.0, 2.0], vec![1.0]], OutputDtype::F32).unwrap_err();
+        assert!(err.to_string().contains("row 1 has 1 dimensions"));
+        assert!(!path.exists());
+    }
+}
@@ -18,7 +18,7 @@ fn parse_server_start_args() {
     let cli = Cli::try_parse_from(args).unwrap();
     match cli.command {
         Commands::Server { action: ServerAction::Start(start) } => {
-            assert_eq!(start.port, 7070);
+            assert_eq!(start.port, Some(7070));
             assert_eq!(start.bind, "127.0.0.1");
         }
         _ => panic!("expected server start"),
@@ -1,16 +1,23 @@
-use static_embedding_tool::resources;
+use static_embedding_tool::resources::ResourceRegistry;
+use static_embedding_tool::server::state::AppState;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn registry() -> ResourceRegistry {
+    ResourceRegistry::new(Arc::new(AppState::from_models(HashMap::new(), "potion-32M")))
+}
 
 #[test]
 fn list_resources_contains_instructions() {
-    let list = resources::list_resources();
+    let list = registry().list_resources().unwrap();
     assert!(!list.is_empty());
     assert!(list.iter().any(|r| r.raw.uri == "embedtool://instructions"));
 }
 
 #[test]
 fn read_instructions_resource() {
-    let result = resources::read_resource("embedtool://instructions");
-    assert!(result.is_some());
+    let result = registry().read_resource("embedtool://instructions");
+    assert!(result.is_ok());
     let result = result.unwrap();
     assert_eq!(result.contents.len(), 1);
 }
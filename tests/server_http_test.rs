@@ -1,8 +1,13 @@
 // Integration test for server/http.rs
+use axum::extract::State;
 use static_embedding_tool::server::http;
+use static_embedding_tool::server::state::AppState;
+use std::sync::Arc;
 
 #[tokio::test]
 async fn health_returns_ok() {
-    let status = http::health().await;
+    let state = Arc::new(AppState::from_models(Default::default(), "potion-32M"));
+    let (status, body) = http::health(State(state)).await;
     assert_eq!(status, axum::http::StatusCode::OK);
+    assert_eq!(body["status"], "ok");
 }